@@ -0,0 +1,69 @@
+//! Parse throughput on a large synthetic diff.
+//!
+//! Run with `cargo bench --bench diff_parse`.
+
+use std::hint::black_box;
+use std::ops::ControlFlow;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use git_reabsorb::patch::{parse, parse_streaming};
+
+/// A diff of `files` files with `hunks_per_file` eight-line hunks each.
+fn synthetic_diff(files: usize, hunks_per_file: usize) -> String {
+    let mut diff = String::new();
+    for file in 0..files {
+        let path = format!("src/module_{}/file_{}.rs", file % 37, file);
+        diff.push_str(&format!(
+            "diff --git a/{path} b/{path}\nindex 1234567..89abcde 100644\n--- a/{path}\n+++ b/{path}\n"
+        ));
+        for hunk in 0..hunks_per_file {
+            let start = hunk * 40 + 1;
+            diff.push_str(&format!(
+                "@@ -{start},8 +{start},8 @@ fn item_{hunk}() {{\n"
+            ));
+            for line in 0..3 {
+                diff.push_str(&format!(
+                    "     let value_{line} = compute({line}, {hunk});\n"
+                ));
+            }
+            diff.push_str(&format!("-    old_call({hunk}, \"{path}\");\n"));
+            diff.push_str(&format!(
+                "+    new_call({hunk}, \"{path}\", Default::default());\n"
+            ));
+            for line in 3..6 {
+                diff.push_str(&format!(
+                    "     let value_{line} = compute({line}, {hunk});\n"
+                ));
+            }
+        }
+    }
+    diff
+}
+
+fn bench_parse(c: &mut Criterion) {
+    // About 184,000 lines
+    let diff = synthetic_diff(1000, 20);
+    let commits = vec!["0123456789abcdef".to_string()];
+
+    let mut group = c.benchmark_group("diff_parse");
+    group.throughput(Throughput::Bytes(diff.len() as u64));
+    group.sample_size(20);
+    group.bench_function("parse", |b| {
+        b.iter(|| parse(black_box(&diff), &commits, 0).unwrap())
+    });
+    group.bench_function("parse_streaming", |b| {
+        b.iter(|| {
+            let mut hunks = 0usize;
+            parse_streaming(black_box(diff.as_bytes()), &commits, 0, |hunk| {
+                hunks += black_box(hunk).lines.len();
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+            hunks
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse);
+criterion_main!(benches);
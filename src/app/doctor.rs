@@ -0,0 +1,238 @@
+//! Environment checks for `git reabsorb doctor`.
+
+use std::process::Command;
+
+use crate::color::{paint, Style};
+use crate::git::{git_version, GitOps};
+use crate::llm::LlmConfig;
+use crate::plan_store::list_saved_plans;
+
+use super::{open_repository, AppError};
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    /// Not broken, but likely to surprise (e.g. a leftover plan).
+    Warn,
+    Fail,
+}
+
+/// One line of the doctor checklist.
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// How to fix a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check, print the checklist, and fail if any check failed.
+pub fn run(
+    llm_config: &LlmConfig,
+    ref_prefix: &str,
+    check_llm: bool,
+    color: bool,
+) -> Result<(), AppError> {
+    let checks = run_checks(llm_config, ref_prefix, check_llm);
+    print!("{}", format_checks(&checks, color));
+
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        return Err(AppError::User(format!("{} check(s) failed", failed)));
+    }
+    Ok(())
+}
+
+/// Collect the checklist without printing it.
+pub fn run_checks(llm_config: &LlmConfig, ref_prefix: &str, check_llm: bool) -> Vec<Check> {
+    let mut checks = vec![check_git()];
+
+    match open_repository() {
+        Ok(git) => {
+            checks.push(Check::ok("repository", "inside a git work tree"));
+            checks.push(check_saved_plans());
+            checks.push(check_pre_reabsorb_refs(&git, ref_prefix));
+        }
+        Err(err) => checks.push(Check::fail(
+            "repository",
+            err.to_string(),
+            "cd into the repository you want to reorganize",
+        )),
+    }
+
+    checks.push(check_llm_binary(llm_config));
+    if check_llm {
+        checks.push(check_llm_responds(llm_config));
+    }
+
+    checks
+}
+
+fn check_git() -> Check {
+    match git_version() {
+        Ok(version) => Check::ok("git", version),
+        Err(err) => Check::fail(
+            "git",
+            err.to_string(),
+            "install git and make sure it is on PATH",
+        ),
+    }
+}
+
+fn check_saved_plans() -> Check {
+    let plans = list_saved_plans();
+    if plans.is_empty() {
+        return Check::ok("saved plans", "none");
+    }
+    let paths: Vec<_> = plans.iter().map(|p| p.display().to_string()).collect();
+    Check::warn(
+        "saved plans",
+        paths.join(", "),
+        "finish with 'git reabsorb apply' or delete the stale plan file",
+    )
+}
+
+fn check_pre_reabsorb_refs(git: &impl GitOps, ref_prefix: &str) -> Check {
+    let pattern = format!("{}/pre-reabsorb/", ref_prefix);
+    match git.run_git_output(&["for-each-ref", "--format=%(refname)", &pattern]) {
+        Ok(output) if output.trim().is_empty() => Check::ok("pre-reabsorb refs", "none"),
+        Ok(output) => {
+            let refs: Vec<_> = output.lines().collect();
+            Check::warn(
+                "pre-reabsorb refs",
+                refs.join(", "),
+                "undo with 'git reabsorb reset', or drop with 'git update-ref -d <ref>' once you're happy",
+            )
+        }
+        Err(err) => Check::fail(
+            "pre-reabsorb refs",
+            err.to_string(),
+            "check that the repository is readable",
+        ),
+    }
+}
+
+/// Check that the configured provider's CLI can be started.
+pub fn check_llm_binary(llm_config: &LlmConfig) -> Check {
+    let binary = llm_config.binary();
+    let hint = format!(
+        "install the {:?} CLI or point {} at it",
+        llm_config.provider,
+        llm_config.binary_env()
+    );
+    match Command::new(&binary).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout);
+            let version = version.lines().next().unwrap_or("").trim();
+            Check::ok("llm binary", format!("{} ({})", binary, version))
+        }
+        Ok(output) => Check::fail(
+            "llm binary",
+            format!("{} --version exited with {}", binary, output.status),
+            hint,
+        ),
+        Err(err) => Check::fail("llm binary", format!("{}: {}", binary, err), hint),
+    }
+}
+
+fn check_llm_responds(llm_config: &LlmConfig) -> Check {
+    let client = llm_config.create_client();
+    match client.complete("Reply with the single word: pong") {
+        Ok(_) => Check::ok("llm response", "provider answered a test prompt"),
+        Err(err) => Check::fail(
+            "llm response",
+            err.to_string(),
+            "check the model name and that the CLI is logged in",
+        ),
+    }
+}
+
+/// Render checks as a checklist, one per line with an indented hint.
+pub fn format_checks(checks: &[Check], color: bool) -> String {
+    let mut output = String::new();
+    for check in checks {
+        let (mark, style) = match check.status {
+            CheckStatus::Ok => ("✓", Style::Green),
+            CheckStatus::Warn => ("!", Style::Yellow),
+            CheckStatus::Fail => ("✗", Style::Red),
+        };
+        output.push_str(&format!(
+            "{} {}: {}\n",
+            paint(mark, style, color),
+            check.name,
+            check.detail
+        ));
+        if let Some(hint) = &check.hint {
+            output.push_str(&format!("    → {}\n", hint));
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_llm_binary_fails_with_hint() {
+        let config = LlmConfig::new().with_claude_bin("/nonexistent/claude-for-doctor-test");
+        let check = check_llm_binary(&config);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check
+            .hint
+            .as_deref()
+            .unwrap()
+            .contains("GIT_REABSORB_CLAUDE_BIN"));
+    }
+
+    #[test]
+    fn format_checks_marks_each_status() {
+        let checks = vec![
+            Check::ok("git", "git version 2.43.0"),
+            Check::warn("saved plans", "plan.json", "apply it"),
+            Check::fail("llm binary", "claude: not found", "install it"),
+        ];
+        let plain = format_checks(&checks, false);
+        assert_eq!(
+            plain,
+            "✓ git: git version 2.43.0\n\
+             ! saved plans: plan.json\n    → apply it\n\
+             ✗ llm binary: claude: not found\n    → install it\n"
+        );
+        assert!(format_checks(&checks, true).contains('\x1b'));
+    }
+}
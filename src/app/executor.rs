@@ -1,14 +1,20 @@
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
 
 use log::{debug, info, warn};
 
+use crate::audit::{AuditEvent, AuditSink};
 use crate::cancel;
-use crate::editor::{Editor, EditorError};
-use crate::git::{GitError, GitOps};
-use crate::models::{FileChange, Hunk, PlannedCommit};
+use crate::editor::{
+    commit_template_help, load_commit_template, with_commit_template, Editor, EditorError,
+};
+use crate::git::{CommitResult, GitError, GitOps};
+use crate::lint::{lint_message, LintConfig};
+use crate::models::{ChangeType, DiffLine, FileChange, Hunk, PlannedCommit};
 use crate::patch::PatchContext;
-use crate::plan_store::{PlanFileError, PlanStore, SavedPlan};
+use crate::plan_store::{PlanFileError, PlanStore, SavedPlan, SKIPPED_SHA};
 use crate::utils::short_sha;
 
 #[derive(Debug, thiserror::Error)]
@@ -21,6 +27,34 @@ pub enum ExecutionError {
     Plan(#[from] PlanFileError),
     #[error("Cancelled by user")]
     Cancelled,
+    #[error("Message for commit {index} fails lint: {issues}")]
+    Lint {
+        /// 1-based position of the commit in the plan
+        index: usize,
+        issues: String,
+    },
+    #[error(
+        "Commit {index} ({}) left {} different from the {expected}",
+        short_sha(.sha),
+        .path.display()
+    )]
+    TreeMismatch {
+        /// 1-based position of the commit in the plan
+        index: usize,
+        sha: String,
+        path: std::path::PathBuf,
+        /// What the file should match: "base", "original head" or "planned
+        /// intermediate state"
+        expected: &'static str,
+    },
+    #[error("Test command '{command}' failed ({status}) on commit {index} ({})", short_sha(.sha))]
+    TestFailed {
+        command: String,
+        /// 1-based position of the commit in the plan
+        index: usize,
+        sha: String,
+        status: String,
+    },
 }
 
 /// Applies planned commits by staging hunks, opening the editor, and committing.
@@ -28,6 +62,11 @@ pub struct PlanExecutor<'a, G: GitOps, E: Editor, P: PlanStore> {
     git: &'a G,
     editor: &'a E,
     plan_store: &'a P,
+    test_cmd: Option<String>,
+    audit: Option<Arc<dyn AuditSink>>,
+    index_free: bool,
+    lint: Option<LintConfig>,
+    verify_each: bool,
 }
 
 impl<'a, G: GitOps, E: Editor, P: PlanStore> PlanExecutor<'a, G, E, P> {
@@ -36,9 +75,57 @@ impl<'a, G: GitOps, E: Editor, P: PlanStore> PlanExecutor<'a, G, E, P> {
             git,
             editor,
             plan_store,
+            test_cmd: None,
+            audit: None,
+            index_free: false,
+            lint: None,
+            verify_each: false,
         }
     }
 
+    /// Run `cmd` through the shell after each created commit, like
+    /// `git rebase --exec`, and stop at the first commit where it fails.
+    ///
+    /// The work tree still holds the final state while commits are built up
+    /// in the index, so the command runs in a temporary worktree checked out
+    /// at the new commit.
+    pub fn with_test_cmd(mut self, cmd: Option<String>) -> Self {
+        self.test_cmd = cmd.filter(|c| !c.trim().is_empty());
+        self
+    }
+
+    /// Stage into a temporary index and create commits with `commit-tree`,
+    /// so the repository's index (and anything the user staged) is never
+    /// touched. Hooks don't run in this mode. Expects HEAD to already be at
+    /// the commit to build on, as `reset_to` would leave it.
+    pub fn with_index_free(mut self, index_free: bool) -> Self {
+        self.index_free = index_free;
+        self
+    }
+
+    /// Check each message against `config` before committing. A failing
+    /// message goes back to the editor with the problems listed, or stops
+    /// execution under `--no-editor`.
+    pub fn with_lint(mut self, config: Option<LintConfig>) -> Self {
+        self.lint = config;
+        self
+    }
+
+    /// Check each created commit's tree, stopping at the first commit that
+    /// leaves a file where it should not be. A file whose changes all landed
+    /// must match the original head, and one with none yet must match the
+    /// base; files that are part way through are not checked.
+    pub fn with_verify_each(mut self, verify_each: bool) -> Self {
+        self.verify_each = verify_each;
+        self
+    }
+
+    /// Record each created commit to `sink`.
+    pub fn with_audit(mut self, sink: Option<Arc<dyn AuditSink>>) -> Self {
+        self.audit = sink;
+        self
+    }
+
     pub fn execute(
         &self,
         hunks: &[Hunk],
@@ -52,12 +139,28 @@ impl<'a, G: GitOps, E: Editor, P: PlanStore> PlanExecutor<'a, G, E, P> {
         let start_index = plan.next_commit_index;
 
         let patch_context = PatchContext::new(file_changes);
+        let commit_template = if no_editor {
+            None
+        } else {
+            self.load_commit_template()
+        };
+
+        let _private_index = if self.index_free {
+            Some(PrivateIndex::seeded_from_head(self.git)?)
+        } else {
+            None
+        };
 
         // Track which hunks have been applied (for line number adjustment)
         let mut applied_hunks_per_file: HashMap<std::path::PathBuf, Vec<Hunk>> = HashMap::new();
 
-        // Track whether extra changes have been applied (only apply once)
-        let mut extra_changes_applied = start_index > 0;
+        let mut tree_check = self
+            .verify_each
+            .then(|| TreeCheck::new(hunks, planned_commits, file_changes));
+
+        // Hunkless changes not routed to a commit (plans saved before routing)
+        // go with the first commit; track whether that has happened
+        let mut unrouted_changes_applied = start_index > 0;
 
         // Reconstruct applied hunks from previous commits (for resumed execution)
         for commit in planned_commits.iter().take(start_index) {
@@ -86,13 +189,30 @@ impl<'a, G: GitOps, E: Editor, P: PlanStore> PlanExecutor<'a, G, E, P> {
                 .filter_map(|change| change.resolve(hunks))
                 .collect();
 
+            // Settle the message before staging anything, so an editor
+            // failure leaves the index as the previous commit left it and
+            // resuming starts this commit fresh
             let help_text = generate_commit_help(&commit_hunk_refs);
             let template = planned.description.to_string();
             let message = if no_editor {
                 template
             } else {
-                self.editor.edit(&template, &help_text)?
+                match &commit_template {
+                    Some(commit_template) => {
+                        let help = commit_template_help(commit_template);
+                        let help = if help.is_empty() {
+                            help_text.clone()
+                        } else {
+                            format!("{}\n\n{}", help, help_text)
+                        };
+                        self.editor
+                            .edit(&with_commit_template(&template, commit_template), &help)?
+                    }
+                    None => self.editor.edit(&template, &help_text)?,
+                }
             };
+            let message =
+                self.lint_until_clean(message, &help_text, &commit_hunk_refs, no_editor, i + 1)?;
 
             // Adjust hunk line numbers based on what's been applied to each file.
             // Note: Patch header generation (new/modified/deleted) is handled by
@@ -100,56 +220,303 @@ impl<'a, G: GitOps, E: Editor, P: PlanStore> PlanExecutor<'a, G, E, P> {
             let adjusted_hunks =
                 adjust_hunks_for_current_index(&commit_hunk_refs, &applied_hunks_per_file);
 
-            let has_pending_extra_changes = !extra_changes_applied && !file_changes.is_empty();
-            if adjusted_hunks.is_empty() && !has_pending_extra_changes {
+            // Binary and mode-only changes have no hunks, so stage them with
+            // the commit they were routed to
+            let hunkless_changes: Vec<&FileChange> = file_changes
+                .iter()
+                .filter(|fc| fc.is_hunkless())
+                .filter(|fc| match fc.commit_index {
+                    Some(index) => index.min(total - 1) == i,
+                    None => !unrouted_changes_applied,
+                })
+                .collect();
+            unrouted_changes_applied = true;
+
+            if adjusted_hunks.is_empty() && hunkless_changes.is_empty() {
                 debug!("Skipped (all changes already applied)");
-                plan.mark_commit_created("SKIPPED".to_string());
+                plan.mark_commit_created(SKIPPED_SHA.to_string());
                 self.plan_store.save(plan)?;
                 continue;
             }
 
-            let adjusted_refs: Vec<&Hunk> = adjusted_hunks.iter().collect();
+            let adjusted_refs =
+                stage_deletions(self.git, &patch_context, adjusted_hunks.iter().collect())?;
 
             if !adjusted_refs.is_empty() {
                 self.git
                     .apply_hunks_to_index(&adjusted_refs, &patch_context)?;
             }
 
-            if !extra_changes_applied {
-                let binary_changes: Vec<_> =
-                    file_changes.iter().filter(|fc| fc.is_binary).collect();
-                if !binary_changes.is_empty() {
-                    debug!("Applying {} binary files...", binary_changes.len());
-                    self.git.apply_binary_files(&binary_changes)?;
-                }
-                let mode_only_changes: Vec<_> = file_changes
-                    .iter()
-                    .filter(|fc| !fc.has_content_hunks && !fc.is_binary)
-                    .collect();
-                if !mode_only_changes.is_empty() {
-                    debug!("Applying {} mode-only changes...", mode_only_changes.len());
-                    apply_mode_only_patches(self.git, &mode_only_changes)?;
-                }
-                extra_changes_applied = true;
+            let (binary_changes, mode_only_changes): (Vec<&FileChange>, Vec<&FileChange>) =
+                hunkless_changes.into_iter().partition(|fc| fc.is_binary);
+            if !binary_changes.is_empty() {
+                debug!("Applying {} binary files...", binary_changes.len());
+                self.git.apply_binary_files(&binary_changes)?;
+            }
+            if !mode_only_changes.is_empty() {
+                debug!("Applying {} mode-only changes...", mode_only_changes.len());
+                apply_mode_only_changes(self.git, &mode_only_changes)?;
             }
 
-            let new_sha = self.git.commit(&message, no_verify)?;
-            info!("Created {}", short_sha(&new_sha));
+            let result = if self.index_free {
+                self.commit_index_free(&message)?
+            } else {
+                self.git.commit(&message, no_verify, false)?
+            };
 
             // Track these hunks as applied for line number adjustment in subsequent commits
-            for hunk in commit_hunk_refs {
+            for &hunk in &commit_hunk_refs {
                 applied_hunks_per_file
                     .entry(hunk.file_path.clone())
                     .or_default()
                     .push(hunk.clone());
             }
 
-            plan.mark_commit_created(new_sha);
+            // The changes cancelled out (or were already in HEAD), so git
+            // made no commit and HEAD hasn't moved
+            if !result.created {
+                info!("Skipped commit {}: it changes nothing", i + 1);
+                plan.mark_commit_created(SKIPPED_SHA.to_string());
+                self.plan_store.save(plan)?;
+                continue;
+            }
+            let new_sha = result.sha;
+            info!("Created {}", self.git.abbrev(&new_sha));
+
+            plan.mark_commit_created(new_sha.clone());
             self.plan_store.save(plan)?;
+            if let Some(sink) = &self.audit {
+                let source_shas: BTreeSet<_> = commit_hunk_refs
+                    .iter()
+                    .flat_map(|h| h.likely_source_commits.iter().cloned())
+                    .collect();
+                sink.record(&AuditEvent::CommitCreated {
+                    index: i + 1,
+                    source_shas: source_shas.into_iter().collect(),
+                    sha: new_sha.clone(),
+                    hunks: commit_hunk_refs.len(),
+                });
+            }
+
+            // The commit is recorded first, so fixing it up and resuming
+            // continues with the next one
+            if let Some(check) = &mut tree_check {
+                self.verify_commit_tree(check, plan, i, &new_sha)?;
+            }
+            if let Some(cmd) = &self.test_cmd {
+                self.run_test_cmd(cmd, i + 1, &new_sha)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-open the editor until `message` passes lint. Emptying the message
+    /// aborts, as it does on the first edit.
+    fn lint_until_clean(
+        &self,
+        mut message: String,
+        help_text: &str,
+        hunks: &[&Hunk],
+        no_editor: bool,
+        index: usize,
+    ) -> Result<String, ExecutionError> {
+        let Some(config) = &self.lint else {
+            return Ok(message);
+        };
+        loop {
+            let issues = lint_message(&message, hunks, config);
+            if issues.is_empty() {
+                return Ok(message);
+            }
+            let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+            if no_editor {
+                return Err(ExecutionError::Lint {
+                    index,
+                    issues: issues.join("; "),
+                });
+            }
+            warn!("Commit {} message: {}", index, issues.join("; "));
+            let help = format!("Lint: {}\n\n{}", issues.join("\nLint: "), help_text);
+            message = self.editor.edit(&message, &help)?;
         }
+    }
 
+    /// Compare the files `sha` should have finished or not yet touched
+    /// against the original head or the base, and the ones it is part way
+    /// through against the base with the hunks applied so far.
+    fn verify_commit_tree(
+        &self,
+        check: &mut TreeCheck,
+        plan: &SavedPlan,
+        index: usize,
+        sha: &str,
+    ) -> Result<(), ExecutionError> {
+        debug!("Verifying the tree of commit {}...", index + 1);
+        for (path, &(first, last)) in &check.spans {
+            let (want, expected) = if last <= index {
+                let head =
+                    TreeCheck::blob(&mut check.head_blobs, self.git, &plan.original_head, path)?;
+                (head.clone(), "original head")
+            } else if first > index {
+                let base = TreeCheck::blob(&mut check.base_blobs, self.git, &plan.base_sha, path)?;
+                (base.clone(), "base")
+            } else {
+                let base = TreeCheck::blob(&mut check.base_blobs, self.git, &plan.base_sha, path)?;
+                let applied: Vec<&Hunk> = check
+                    .file_hunks
+                    .get(path)
+                    .map_or(&[][..], Vec::as_slice)
+                    .iter()
+                    .filter(|(i, _)| *i <= index)
+                    .map(|(_, hunk)| hunk)
+                    .collect();
+                match apply_to_content(base.as_deref().unwrap_or_default(), &applied) {
+                    Some(content) => (Some(content), "planned intermediate state"),
+                    None => {
+                        debug!(
+                            "Can't predict {} after commit {}; not checking it",
+                            path.display(),
+                            index + 1
+                        );
+                        continue;
+                    }
+                }
+            };
+            if self.git.read_blob(sha, path)? != want {
+                return Err(ExecutionError::TreeMismatch {
+                    index: index + 1,
+                    sha: sha.to_string(),
+                    path: path.clone(),
+                    expected,
+                });
+            }
+        }
         Ok(())
     }
+
+    /// Commit the private index's tree on HEAD and move HEAD to it. Like
+    /// `GitOps::commit`, makes no commit when the tree matches HEAD's.
+    fn commit_index_free(&self, message: &str) -> Result<CommitResult, ExecutionError> {
+        let parent = self.git.get_head()?;
+        let tree = self.git.write_tree()?;
+        if tree == self.git.tree_of(&parent)? {
+            return Ok(CommitResult {
+                sha: parent,
+                created: false,
+                empty: true,
+            });
+        }
+        let sha = self.git.commit_tree(&tree, &parent, message, None)?;
+        self.git.update_head(&sha)?;
+        Ok(CommitResult {
+            sha,
+            created: true,
+            empty: false,
+        })
+    }
+
+    fn run_test_cmd(&self, cmd: &str, index: usize, sha: &str) -> Result<(), ExecutionError> {
+        info!("Running '{}' on {}...", cmd, self.git.abbrev(sha));
+        let temp_dir = tempfile::tempdir().map_err(GitError::ExecutionFailed)?;
+        let checkout = temp_dir.path().join("checkout");
+        let checkout_str = checkout.to_string_lossy();
+        self.git
+            .run_git_output(&["worktree", "add", "--detach", "--quiet", &checkout_str, sha])?;
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .current_dir(&checkout)
+            .status();
+        if let Err(e) = self
+            .git
+            .run_git_output(&["worktree", "remove", "--force", &checkout_str])
+        {
+            warn!(
+                "Could not remove temporary worktree {}: {}",
+                checkout_str, e
+            );
+        }
+        let status = status.map_err(GitError::ExecutionFailed)?;
+        if status.success() {
+            return Ok(());
+        }
+        Err(ExecutionError::TestFailed {
+            command: cmd.to_string(),
+            index,
+            sha: sha.to_string(),
+            status: status.to_string(),
+        })
+    }
+
+    /// Load the file named by `commit.template`, if configured.
+    fn load_commit_template(&self) -> Option<String> {
+        let path = match self.git.get_config_path("commit.template") {
+            Ok(Some(path)) => path,
+            Ok(None) => return None,
+            Err(e) => {
+                debug!("Could not read commit.template: {}", e);
+                return None;
+            }
+        };
+
+        match load_commit_template(Path::new(&path)) {
+            Ok(template) => template,
+            Err(e) => {
+                warn!("Ignoring commit.template {}: {}", path, e);
+                None
+            }
+        }
+    }
+}
+
+/// A temporary index file that git operations use while this is alive.
+struct PrivateIndex<'g, G: GitOps> {
+    git: &'g G,
+    _dir: tempfile::TempDir,
+}
+
+impl<'g, G: GitOps> PrivateIndex<'g, G> {
+    fn seeded_from_head(git: &'g G) -> Result<Self, ExecutionError> {
+        let dir = tempfile::tempdir().map_err(GitError::ExecutionFailed)?;
+        git.use_index_file(Some(&dir.path().join("index")));
+        let index = Self { git, _dir: dir };
+        git.read_tree("HEAD")?;
+        Ok(index)
+    }
+}
+
+impl<G: GitOps> Drop for PrivateIndex<'_, G> {
+    fn drop(&mut self) {
+        self.git.use_index_file(None);
+    }
+}
+
+/// Lint the planned messages of `planned` (numbered from `first_index`) as
+/// `--no-editor` would commit them, failing at the first that breaks a
+/// rule. Run before HEAD moves, since nothing can fix them mid-apply.
+pub fn lint_planned_messages(
+    planned: &[PlannedCommit],
+    hunks: &[Hunk],
+    config: &LintConfig,
+    first_index: usize,
+) -> Result<(), ExecutionError> {
+    for (offset, commit) in planned.iter().enumerate() {
+        let commit_hunks: Vec<&Hunk> = commit
+            .changes
+            .iter()
+            .filter_map(|change| change.resolve(hunks))
+            .collect();
+        let issues = lint_message(&commit.description.to_string(), &commit_hunks, config);
+        if !issues.is_empty() {
+            let issues: Vec<String> = issues.iter().map(|i| i.to_string()).collect();
+            return Err(ExecutionError::Lint {
+                index: first_index + offset + 1,
+                issues: issues.join("; "),
+            });
+        }
+    }
+    Ok(())
 }
 
 fn generate_commit_help(hunks: &[&Hunk]) -> String {
@@ -181,6 +548,126 @@ fn generate_commit_help(hunks: &[&Hunk]) -> String {
     lines.join("\n")
 }
 
+/// What `--verify-each` checks each new commit's files against. Base and
+/// head blobs are read once per run, when first needed.
+struct TreeCheck {
+    spans: HashMap<std::path::PathBuf, (usize, usize)>,
+    /// Each file's hunks in the order the plan applies them, with the
+    /// position of the commit that applies each
+    file_hunks: HashMap<std::path::PathBuf, Vec<(usize, Hunk)>>,
+    base_blobs: HashMap<std::path::PathBuf, Option<Vec<u8>>>,
+    head_blobs: HashMap<std::path::PathBuf, Option<Vec<u8>>>,
+}
+
+impl TreeCheck {
+    fn new(hunks: &[Hunk], planned_commits: &[PlannedCommit], file_changes: &[FileChange]) -> Self {
+        let mut file_hunks: HashMap<std::path::PathBuf, Vec<(usize, Hunk)>> = HashMap::new();
+        for (i, planned) in planned_commits.iter().enumerate() {
+            for hunk in planned.changes.iter().filter_map(|c| c.resolve(hunks)) {
+                file_hunks
+                    .entry(hunk.file_path.clone())
+                    .or_default()
+                    .push((i, hunk.clone()));
+            }
+        }
+        Self {
+            spans: file_commit_spans(hunks, planned_commits, file_changes),
+            file_hunks,
+            base_blobs: HashMap::new(),
+            head_blobs: HashMap::new(),
+        }
+    }
+
+    fn blob<'c, G: GitOps>(
+        cache: &'c mut HashMap<std::path::PathBuf, Option<Vec<u8>>>,
+        git: &G,
+        sha: &str,
+        path: &Path,
+    ) -> Result<&'c Option<Vec<u8>>, GitError> {
+        if !cache.contains_key(path) {
+            cache.insert(path.to_path_buf(), git.read_blob(sha, path)?);
+        }
+        Ok(&cache[path])
+    }
+}
+
+/// `content` with `hunks` applied in order, the way the executor stages
+/// them: each hunk's start moves by what the earlier hunks above it added or
+/// removed. `None` when the content isn't text or a hunk doesn't fit.
+fn apply_to_content(content: &[u8], hunks: &[&Hunk]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(content).ok()?;
+    let mut ends_with_newline = text.is_empty() || text.ends_with('\n');
+    let mut lines: Vec<&str> = text.split_terminator('\n').collect();
+    let mut applied: Vec<&Hunk> = Vec::new();
+    for &hunk in hunks {
+        let shift: i64 = applied
+            .iter()
+            .filter(|earlier| earlier.old_start < hunk.old_start)
+            .map(|earlier| i64::from(earlier.new_count) - i64::from(earlier.old_count))
+            .sum();
+        let start = i64::from(hunk.old_start) + shift;
+        // An empty old side starts after its line, a non-empty one on it
+        let at = usize::try_from(if hunk.old_count == 0 {
+            start
+        } else {
+            start - 1
+        })
+        .ok()?;
+        let old: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter_map(|line| match line {
+                DiffLine::Context(text) | DiffLine::Removed(text) => Some(text.as_str()),
+                DiffLine::Added(_) => None,
+            })
+            .collect();
+        if lines.get(at..at + old.len())? != old.as_slice() {
+            return None;
+        }
+        let new = hunk.lines.iter().filter_map(|line| match line {
+            DiffLine::Context(text) | DiffLine::Added(text) => Some(text.as_str()),
+            DiffLine::Removed(_) => None,
+        });
+        lines.splice(at..at + old.len(), new);
+        if hunk.new_missing_newline_at_eof {
+            ends_with_newline = false;
+        } else if hunk.old_missing_newline_at_eof {
+            ends_with_newline = true;
+        }
+        applied.push(hunk);
+    }
+    let mut result = lines.join("\n");
+    if ends_with_newline && !lines.is_empty() {
+        result.push('\n');
+    }
+    Some(result.into_bytes())
+}
+
+/// For each file the plan changes, the 0-based positions of the first and
+/// last commits that change it. Hunkless changes count where they are staged.
+fn file_commit_spans(
+    hunks: &[Hunk],
+    planned_commits: &[PlannedCommit],
+    file_changes: &[FileChange],
+) -> HashMap<std::path::PathBuf, (usize, usize)> {
+    let mut spans: HashMap<std::path::PathBuf, (usize, usize)> = HashMap::new();
+    let mut record = |path: &Path, index: usize| {
+        let span = spans.entry(path.to_path_buf()).or_insert((index, index));
+        span.0 = span.0.min(index);
+        span.1 = span.1.max(index);
+    };
+    for (i, planned) in planned_commits.iter().enumerate() {
+        for hunk in planned.changes.iter().filter_map(|c| c.resolve(hunks)) {
+            record(&hunk.file_path, i);
+        }
+    }
+    let last = planned_commits.len().saturating_sub(1);
+    for fc in file_changes.iter().filter(|fc| fc.is_hunkless()) {
+        record(&fc.file_path, fc.commit_index.map_or(0, |i| i.min(last)));
+    }
+    spans
+}
+
 /// Adjust hunk line numbers based on previously applied hunks.
 ///
 /// When hunks are applied sequentially, later hunks need their line numbers
@@ -232,37 +719,298 @@ fn adjust_hunks_for_current_index(
     adjusted
 }
 
-fn apply_mode_only_patches<G: GitOps>(
+/// Stage whole-file deletions explicitly and return the hunks still to be applied.
+///
+/// Files whose hunks resolve to a deletion are removed from the index with
+/// `GitOps::stage_deletion` rather than by applying a deletion patch.
+fn stage_deletions<'h, G: GitOps>(
+    git: &G,
+    patch_context: &PatchContext,
+    hunks: Vec<&'h Hunk>,
+) -> Result<Vec<&'h Hunk>, GitError> {
+    let mut hunks_by_file: HashMap<&Path, Vec<&Hunk>> = HashMap::new();
+    for hunk in &hunks {
+        hunks_by_file
+            .entry(hunk.file_path.as_path())
+            .or_default()
+            .push(hunk);
+    }
+
+    let mut deleted: HashSet<&Path> = HashSet::new();
+    for (file_path, file_hunks) in hunks_by_file {
+        let file_in_index = git.file_in_index(file_path)?;
+        let change_type =
+            patch_context.determine_change_type(file_path, file_in_index, &file_hunks);
+        if change_type == ChangeType::Deleted {
+            debug!("Staging deletion of {}", file_path.display());
+            git.stage_deletion(file_path)?;
+            deleted.insert(file_path);
+        }
+    }
+
+    Ok(hunks
+        .into_iter()
+        .filter(|h| !deleted.contains(h.file_path.as_path()))
+        .collect())
+}
+
+/// Stage pure mode changes (e.g. `chmod +x`) that have no content hunks.
+fn apply_mode_only_changes<G: GitOps>(
     git: &G,
     file_changes: &[&FileChange],
 ) -> Result<(), ExecutionError> {
-    use crate::git::GitError;
-    use std::io::Write;
-
     for fc in file_changes {
         let (Some(old), Some(new)) = (&fc.old_mode, &fc.new_mode) else {
             continue;
         };
+        if old == new {
+            continue;
+        }
+        debug!("Setting mode {} on {}", new, fc.file_path.display());
+        git.set_file_mode(&fc.file_path, new)?;
+    }
+
+    Ok(())
+}
 
-        let path_str = fc.file_path.to_string_lossy();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::SystemEditor;
+    use crate::models::{CommitDescription, PlannedCommitId, Strategy};
+    use crate::test_utils::{
+        make_hunk_full, make_hunk_in_file, make_source_commit, MemoryPlanStore, MockGit,
+    };
+
+    fn setup() -> (MockGit, Vec<Hunk>, Vec<PlannedCommit>, SavedPlan) {
+        let git = MockGit::new().with_commit(make_source_commit("base", "Initial"), vec![]);
+        let hunks = vec![make_hunk_in_file(0, "a.rs"), make_hunk_in_file(1, "b.rs")];
+        let planned: Vec<PlannedCommit> = hunks
+            .iter()
+            .map(|h| {
+                PlannedCommit::from_hunk_ids(
+                    PlannedCommitId(h.id.0),
+                    CommitDescription::short_only(format!("Change {}", h.file_path.display())),
+                    vec![h.id],
+                )
+            })
+            .collect();
+        let plan = SavedPlan::new(
+            Strategy::Preserve,
+            "base".into(),
+            "head".into(),
+            &planned,
+            &hunks,
+            &HashMap::new(),
+            &[],
+        );
+        (git, hunks, planned, plan)
+    }
 
-        // Generate a mode-only patch
-        let patch = format!(
-            "diff --git a/{path} b/{path}\nold mode {old}\nnew mode {new}\n",
-            path = path_str,
-            old = old,
-            new = new
+    #[test]
+    fn apply_conflict_keeps_progress() {
+        let (git, hunks, planned, mut plan) = setup();
+        let store = MemoryPlanStore::new();
+        git.fail_call(
+            "apply_hunks_to_index",
+            2,
+            GitError::ApplyFailed {
+                file: "b.rs".to_string(),
+                detail: "patch does not apply".to_string(),
+            },
         );
 
-        // Write to temp file and apply
-        let mut temp_file = tempfile::NamedTempFile::new().map_err(GitError::ExecutionFailed)?;
-        temp_file
-            .write_all(patch.as_bytes())
-            .map_err(GitError::ExecutionFailed)?;
-        temp_file.flush().map_err(GitError::ExecutionFailed)?;
+        let result = PlanExecutor::new(&git, &SystemEditor::new(), &store).execute(
+            &hunks,
+            &planned,
+            &[],
+            false,
+            true,
+            &mut plan,
+        );
 
-        git.run_git_output(&["apply", "--cached", temp_file.path().to_str().unwrap()])?;
+        assert!(matches!(
+            result,
+            Err(ExecutionError::Git(GitError::ApplyFailed { ref file, .. })) if file == "b.rs"
+        ));
+        let saved = store.plan().unwrap();
+        assert_eq!(saved.next_commit_index, 1);
+        assert_eq!(
+            saved.commits[0].created_sha.as_deref(),
+            Some(git.head().as_str())
+        );
+        assert_eq!(git.message_of(&git.head()).as_deref(), Some("Change a.rs"));
     }
 
-    Ok(())
+    #[test]
+    fn index_free_commits_without_the_repository_index() {
+        let (git, hunks, planned, mut plan) = setup();
+        let store = MemoryPlanStore::new();
+
+        PlanExecutor::new(&git, &SystemEditor::new(), &store)
+            .with_index_free(true)
+            .execute(&hunks, &planned, &[], false, true, &mut plan)
+            .unwrap();
+
+        let calls = git.calls();
+        assert!(!calls.iter().any(|c| c == "commit"));
+        assert_eq!(calls.iter().filter(|c| *c == "commit_tree").count(), 2);
+        // Pointed at the private index, then back
+        assert_eq!(calls.iter().filter(|c| *c == "use_index_file").count(), 2);
+        let created = git.read_commits("base", "HEAD").unwrap();
+        let subjects: Vec<&str> = created.iter().map(|c| c.message.short.as_str()).collect();
+        assert_eq!(subjects, vec!["Change a.rs", "Change b.rs"]);
+    }
+
+    #[test]
+    fn index_free_makes_no_commit_when_the_tree_is_unchanged() {
+        let (git, ..) = setup();
+        let store = MemoryPlanStore::new();
+        let editor = SystemEditor::new();
+        let executor = PlanExecutor::new(&git, &editor, &store).with_index_free(true);
+
+        let result = executor.commit_index_free("Nothing").unwrap();
+
+        assert!(!result.created && result.empty);
+        assert_eq!(result.sha, "base");
+        assert_eq!(git.head(), "base");
+        assert!(!git.calls().iter().any(|c| c == "commit_tree"));
+    }
+
+    /// Hands out queued messages and records the help text of each edit.
+    struct QueueEditor {
+        replies: std::cell::RefCell<Vec<&'static str>>,
+        helps: std::cell::RefCell<Vec<String>>,
+    }
+
+    impl Editor for QueueEditor {
+        fn edit(&self, _initial: &str, comment_help: &str) -> Result<String, EditorError> {
+            self.helps.borrow_mut().push(comment_help.to_string());
+            Ok(self.replies.borrow_mut().remove(0).to_string())
+        }
+    }
+
+    #[test]
+    fn lint_reopens_the_editor_until_the_message_passes() {
+        let (git, hunks, planned, mut plan) = setup();
+        let store = MemoryPlanStore::new();
+        let editor = QueueEditor {
+            replies: vec!["Fix a.rs ", "Fix a.rs", "Fix b.rs"].into(),
+            helps: Vec::new().into(),
+        };
+
+        PlanExecutor::new(&git, &editor, &store)
+            .with_lint(Some(LintConfig::default()))
+            .execute(&hunks, &planned, &[], false, false, &mut plan)
+            .unwrap();
+
+        let helps = editor.helps.borrow();
+        assert_eq!(helps.len(), 3);
+        assert!(helps[1].starts_with("Lint: line 1 has trailing whitespace"));
+        let created = git.read_commits("base", "HEAD").unwrap();
+        let subjects: Vec<&str> = created.iter().map(|c| c.message.short.as_str()).collect();
+        assert_eq!(subjects, vec!["Fix a.rs", "Fix b.rs"]);
+    }
+
+    /// Fails the `fail_on`th edit (1-based) and accepts the rest unchanged.
+    struct FailingEditor {
+        fail_on: usize,
+        calls: std::cell::Cell<usize>,
+    }
+
+    impl Editor for FailingEditor {
+        fn edit(&self, initial: &str, _comment_help: &str) -> Result<String, EditorError> {
+            self.calls.set(self.calls.get() + 1);
+            if self.calls.get() == self.fail_on {
+                return Err(EditorError::EditorFailed("editor crashed".to_string()));
+            }
+            Ok(initial.to_string())
+        }
+    }
+
+    #[test]
+    fn editor_failure_stages_nothing_and_resume_starts_the_commit_fresh() {
+        let (git, hunks, planned, mut plan) = setup();
+        let store = MemoryPlanStore::new();
+        let editor = FailingEditor {
+            fail_on: 2,
+            calls: 0.into(),
+        };
+        let executor = PlanExecutor::new(&git, &editor, &store);
+
+        let result = executor.execute(&hunks, &planned, &[], false, false, &mut plan);
+        assert!(matches!(
+            result,
+            Err(ExecutionError::Editor(EditorError::EditorFailed(_)))
+        ));
+        assert_eq!(plan.next_commit_index, 1);
+        let staged = |git: &MockGit| {
+            git.calls()
+                .iter()
+                .filter(|c| *c == "apply_hunks_to_index")
+                .count()
+        };
+        assert_eq!(staged(&git), 1);
+
+        executor
+            .execute(&hunks, &planned, &[], false, false, &mut plan)
+            .unwrap();
+        assert_eq!(staged(&git), 2);
+        let created = git.read_commits("base", "HEAD").unwrap();
+        let subjects: Vec<&str> = created.iter().map(|c| c.message.short.as_str()).collect();
+        assert_eq!(subjects, vec!["Change a.rs", "Change b.rs"]);
+    }
+
+    #[test]
+    fn lint_failure_without_editor_stops_before_committing() {
+        let (git, hunks, planned, mut plan) = setup();
+        let store = MemoryPlanStore::new();
+        let config = LintConfig {
+            max_subject_length: 5,
+            ..LintConfig::default()
+        };
+
+        let result = PlanExecutor::new(&git, &SystemEditor::new(), &store)
+            .with_lint(Some(config))
+            .execute(&hunks, &planned, &[], false, true, &mut plan);
+
+        assert!(matches!(
+            result,
+            Err(ExecutionError::Lint { index: 1, ref issues }) if issues.contains("max 5")
+        ));
+        assert!(!git.calls().iter().any(|c| c == "commit"));
+    }
+
+    #[test]
+    fn apply_to_content_shifts_later_hunks() {
+        let context = |s: &str| DiffLine::Context(s.to_string());
+        // Adds a line after "a", then changes "c" (line 3 of the base)
+        let mut first = make_hunk_full(
+            0,
+            "f.txt",
+            vec![context("a"), DiffLine::Added("a2".to_string())],
+            vec![],
+        );
+        (first.old_count, first.new_count) = (1, 2);
+        let mut second = make_hunk_full(
+            1,
+            "f.txt",
+            vec![
+                DiffLine::Removed("c".to_string()),
+                DiffLine::Added("C".to_string()),
+            ],
+            vec![],
+        );
+        second.old_start = 3;
+
+        assert_eq!(
+            apply_to_content(b"a\nb\nc\n", &[&first, &second]).unwrap(),
+            b"a\na2\nb\nC\n"
+        );
+        assert_eq!(
+            apply_to_content(b"a\nb\nc\n", &[&second]).unwrap(),
+            b"a\nb\nC\n"
+        );
+        assert_eq!(apply_to_content(b"x\nb\nc\n", &[&first]), None);
+    }
 }
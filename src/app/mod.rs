@@ -1,39 +1,84 @@
+pub mod doctor;
 mod executor;
 mod planner;
+mod reorder;
 
-use log::{error, info, warn};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::assessment::{self, AssessmentEngine, CriterionId};
+use log::{debug, error, info, warn};
+
+use crate::assessment::{self, AssessmentCheckpoint, AssessmentEngine, CriterionId, VerdictBands};
+use crate::audit::{audited, AuditEvent, AuditSink, AuditedLlmClient};
 use crate::cancel;
 use crate::cli::{
-    ApplyArgs, AssessArgs, Command, CommitRange, CompareArgs, OutputFormat, PlanArgs, RewordArgs,
+    AmendArgs, ApplyArgs, AssessArgs, CheckArgs, Command, CommitRange, CompareArgs, ExecutionArgs,
+    FoldArgs, ListArgs, OutputFormat, PlanArgs, RangeOrigin, ReflogArgs, RewordArgs, StatsFormat,
 };
 use crate::editor::{Editor, EditorError};
 use crate::features::Feature;
-use crate::git::{GitError, GitOps};
-use crate::llm::{LlmConfig, ToolCapability};
-use crate::models::{PlannedCommit, Strategy};
+use crate::git::{Git, GitError, GitOps, EMPTY_TREE};
+use crate::llm::{LlmClient, LlmConfig, ToolCapability};
+use crate::models::{DiffLine, Hunk, PlannedCommit, SourceCommit, Strategy};
 use crate::patch::ParseError;
-use crate::plan_store::{PlanFileError, PlanStore, SavedPlan};
+use crate::plan_store::{PlanFileError, PlanStore, SavedCommit, SavedPlan};
+use crate::reorganize::llm::DEFAULT_MAX_HUNKS;
 use crate::reorganize::{
-    Absorb, ApplyResult, GroupByFile, HierarchicalReorganizer, LlmReorganizer, PreserveOriginal,
-    ReorganizeError, Reorganizer, Squash,
+    hint_in_line, repair_plan, Absorb, ApplyResult, CleanPreserve, CommentHint, Deadline,
+    GroupByFile, HierarchicalConfig, HierarchicalReorganizer, LlmReorganizer, PreserveOriginal,
+    ReorganizeError, Reorganizer, RewordOnly, RuleBased, Squash, DEFAULT_HINT_MARKER, RULES_FILE,
 };
-use crate::utils::short_sha;
+use crate::repo_config::{repo_config_path, RepoConfig};
+use crate::stats::RunStats;
+use crate::utils::{format_timestamp, short_sha};
+use crate::validation::{fix_creation_order, validate_plan};
 
 pub use executor::{ExecutionError, PlanExecutor};
 pub use planner::{PlanDraft, Planner};
 
+/// How many past pre-reabsorb states to keep for `reflog`.
+pub const MAX_PRE_REABSORB_HISTORY: usize = 10;
+
+/// Ranges above this many commits get a warning before LLM planning starts.
+const LARGE_LLM_RANGE: usize = 50;
+
+/// Suffixes `create_backup` tries before giving up on a backup name.
+const MAX_BACKUP_SUFFIX: usize = 100;
+
+/// Stands in for the head SHA of an `assess --working` report.
+const WORKING_TREE_LABEL: &str = "worktree";
+
 /// Factory for instantiating reorganizers from CLI strategy argument.
 #[derive(Clone, Default)]
 pub struct StrategyFactory {
     llm_config: LlmConfig,
+    llm_chunk_size: Option<usize>,
+    llm_max_hunks: usize,
+    llm_compact_diff: bool,
+    time_budget: Option<Duration>,
+    min_confidence: Option<f32>,
+    hierarchical_config: HierarchicalConfig,
+    keep_empty: bool,
+    hint_marker: String,
+    rules_file: PathBuf,
+    audit: Option<Arc<dyn AuditSink>>,
 }
 
 impl StrategyFactory {
     pub fn new() -> Self {
         Self {
             llm_config: LlmConfig::default(),
+            llm_chunk_size: None,
+            llm_max_hunks: DEFAULT_MAX_HUNKS,
+            llm_compact_diff: false,
+            time_budget: None,
+            min_confidence: None,
+            hierarchical_config: HierarchicalConfig::default(),
+            keep_empty: false,
+            hint_marker: DEFAULT_HINT_MARKER.to_string(),
+            rules_file: PathBuf::from(RULES_FILE),
+            audit: None,
         }
     }
 
@@ -42,32 +87,216 @@ impl StrategyFactory {
         self
     }
 
+    /// Chunk the llm strategy's hunks into prompts of at most this many hunks.
+    pub fn with_llm_chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        self.llm_chunk_size = chunk_size;
+        self
+    }
+
+    /// Most hunks the llm strategy plans in one prompt (0 for no limit).
+    pub fn with_llm_max_hunks(mut self, max_hunks: usize) -> Self {
+        self.llm_max_hunks = max_hunks;
+        self
+    }
+
+    /// Show hunks to the llm strategy as word diffs.
+    pub fn with_llm_compact_diff(mut self, compact_diff: bool) -> Self {
+        self.llm_compact_diff = compact_diff;
+        self
+    }
+
+    /// Limit how long LLM strategies keep calling the LLM before falling back
+    /// to heuristics.
+    pub fn with_time_budget(mut self, budget: Option<Duration>) -> Self {
+        self.time_budget = budget;
+        self
+    }
+
+    /// Minimum confidence for the llm strategy's hunk placements.
+    pub fn with_min_confidence(mut self, min_confidence: Option<f32>) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    pub fn with_hierarchical_config(mut self, config: HierarchicalConfig) -> Self {
+        self.hierarchical_config = config;
+        self
+    }
+
+    /// Keep whitespace-only commits in the clean-preserve strategy instead of
+    /// folding them into the commit before.
+    pub fn with_keep_empty(mut self, keep_empty: bool) -> Self {
+        self.keep_empty = keep_empty;
+        self
+    }
+
+    /// Marker the comment-hint strategy looks for in added comments.
+    pub fn with_hint_marker(mut self, marker: impl Into<String>) -> Self {
+        self.hint_marker = marker.into();
+        self
+    }
+
+    /// Read the rules strategy's buckets from `path` (see [`repo_config_path`]).
+    pub fn with_rules_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.rules_file = path.into();
+        self
+    }
+
+    /// Record the LLM calls of created strategies to `sink`.
+    pub fn with_audit(mut self, sink: Option<Arc<dyn AuditSink>>) -> Self {
+        self.audit = sink;
+        self
+    }
+
+    /// The budget clock starts here, so it also covers any `fix_plan` rounds.
     pub fn create(&self, strategy: Strategy) -> Box<dyn Reorganizer> {
+        let deadline = Deadline::after(self.time_budget);
         match strategy {
             Strategy::Preserve => Box::new(PreserveOriginal),
             Strategy::ByFile => Box::new(GroupByFile),
             Strategy::Squash => Box::new(Squash),
             Strategy::Llm => {
-                let config = self.config_with_file_io_tools();
-                Box::new(LlmReorganizer::new(config.create_boxed_client()))
+                let config = self.config_with_tools();
+                Box::new(
+                    LlmReorganizer::new(self.boxed_client(&config))
+                        .with_chunk_size(self.llm_chunk_size)
+                        .with_max_hunks(self.llm_max_hunks)
+                        .with_compact_diff(self.llm_compact_diff)
+                        .with_min_confidence(self.min_confidence)
+                        .with_redaction(self.llm_config.redaction.clone())
+                        .with_deadline(deadline),
+                )
             }
             Strategy::Hierarchical => {
-                let config = self.config_with_file_io_tools();
-                let client = config.create_client();
-                Box::new(HierarchicalReorganizer::new(Some(client)))
+                // Heuristic-only mode never talks to the LLM, so don't build clients
+                let hierarchical = &self.hierarchical_config;
+                let config = self.config_with_tools();
+                let client_for = |model: Option<&String>| {
+                    let config = match model {
+                        Some(model) => config.clone().with_model(model),
+                        None => config.clone(),
+                    };
+                    audited(config.create_client(), self.audit.as_ref()) as _
+                };
+                let (client, analyze_client, plan_client) = if hierarchical.use_llm {
+                    (
+                        Some(client_for(None)),
+                        hierarchical
+                            .analyze_model
+                            .as_ref()
+                            .map(|m| client_for(Some(m))),
+                        hierarchical
+                            .plan_model
+                            .as_ref()
+                            .map(|m| client_for(Some(m))),
+                    )
+                } else {
+                    (None, None, None)
+                };
+                Box::new(
+                    HierarchicalReorganizer::new(client)
+                        .with_analyze_client(analyze_client)
+                        .with_plan_client(plan_client)
+                        .with_config(hierarchical.clone())
+                        .with_redaction(self.llm_config.redaction.clone())
+                        .with_deadline(deadline),
+                )
             }
             Strategy::Absorb => Box::new(Absorb),
+            Strategy::Rules => Box::new(RuleBased::from_file(self.rules_file.clone())),
+            Strategy::Reword => Box::new(
+                RewordOnly::new(self.boxed_client(&self.llm_config))
+                    .with_redaction(self.llm_config.redaction.clone()),
+            ),
+            Strategy::CleanPreserve => Box::new(
+                CleanPreserve::new()
+                    .with_client(Some(self.boxed_client(&self.llm_config)))
+                    .with_keep_empty(self.keep_empty)
+                    .with_redaction(self.llm_config.redaction.clone()),
+            ),
+            Strategy::CommentHint => {
+                Box::new(CommentHint::new().with_marker(self.hint_marker.clone()))
+            }
         }
     }
 
-    /// Returns config with FileIo capability if FileBasedLlmIo feature is enabled.
-    fn config_with_file_io_tools(&self) -> LlmConfig {
-        if Feature::FileBasedLlmIo.is_enabled() {
-            self.llm_config
-                .clone()
-                .with_capabilities(vec![ToolCapability::FileIo])
-        } else {
+    fn boxed_client(&self, config: &LlmConfig) -> Box<dyn LlmClient> {
+        match &self.audit {
+            Some(sink) => Box::new(AuditedLlmClient::new(config.create_client(), sink.clone())),
+            None => config.create_boxed_client(),
+        }
+    }
+
+    /// Whether planning with `strategy`, as configured here, calls the LLM.
+    /// Agrees with the reorganizer `create` builds, without building clients.
+    pub fn uses_llm(&self, strategy: Strategy) -> bool {
+        match strategy {
+            Strategy::Hierarchical => self.hierarchical_config.use_llm,
+            other => other.requires_llm(),
+        }
+    }
+
+    /// Fail fast when `strategy` plans with an LLM whose CLI can't be
+    /// started, before any git work. A fallback chain is left to find a
+    /// working provider on its own.
+    pub fn ensure_llm_available(&self, strategy: Strategy) -> Result<(), AppError> {
+        if !self.llm_config.fallback.is_empty() || !self.uses_llm(strategy) {
+            return Ok(());
+        }
+        let check = doctor::check_llm_binary(&self.llm_config);
+        if check.status != doctor::CheckStatus::Fail {
+            // A typo'd model otherwise only fails once planning calls the LLM
+            match self.llm_config.model_is_listed() {
+                Ok(true) => {}
+                Ok(false) => warn!(
+                    "{} doesn't list the model '{}'; see 'git reabsorb models'",
+                    self.llm_config.provider,
+                    self.llm_config.model.as_deref().unwrap_or_default()
+                ),
+                Err(err) => debug!("Could not list models: {}", err),
+            }
+            return Ok(());
+        }
+        Err(AppError::User(format!(
+            "The {:?} strategy needs an LLM, but {}. Fix: {}",
+            strategy,
+            check.detail,
+            check.hint.unwrap_or_default()
+        )))
+    }
+
+    /// Refuse `--llm-redact` setups that can't keep redacted files from
+    /// `strategy`'s LLM.
+    pub fn check_redaction(&self, strategy: Strategy) -> Result<(), AppError> {
+        let redaction = &self.llm_config.redaction;
+        if !redaction.is_active() || !self.uses_llm(strategy) {
+            return Ok(());
+        }
+        if redaction.hides_paths() && strategy == Strategy::Hierarchical {
+            return Err(AppError::User(
+                "--llm-redact-paths is not supported by the hierarchical strategy, whose \
+                 later phases name every file; use --strategy llm"
+                    .to_string(),
+            ));
+        }
+        check_redaction_tools(&self.llm_config)
+    }
+
+    /// Returns config with the tool capabilities granted by enabled features:
+    /// FileIo for FileBasedLlmIo and RepoRead for LlmRepoRead.
+    fn config_with_tools(&self) -> LlmConfig {
+        let capabilities: Vec<ToolCapability> = [
+            (Feature::FileBasedLlmIo, ToolCapability::FileIo),
+            (Feature::LlmRepoRead, ToolCapability::RepoRead),
+        ]
+        .into_iter()
+        .filter(|(feature, _)| feature.is_enabled())
+        .map(|(_, capability)| capability)
+        .collect();
+        if capabilities.is_empty() {
             self.llm_config.clone()
+        } else {
+            self.llm_config.clone().with_capabilities(capabilities)
         }
     }
 }
@@ -94,6 +323,20 @@ pub enum AppError {
     User(String),
 }
 
+/// Open the repository containing the current directory, translating the
+/// usual first-run failures into user-facing errors.
+pub fn open_repository() -> Result<Git, AppError> {
+    Git::with_repo_root().map_err(|err| match err {
+        GitError::NotARepository => {
+            AppError::User("not a git repository; run inside a repo".to_string())
+        }
+        GitError::GitNotFound => {
+            AppError::User("git not found; install git and make sure it is on PATH".to_string())
+        }
+        other => AppError::Git(other),
+    })
+}
+
 pub struct App<G: GitOps, E: Editor, P: PlanStore> {
     git: G,
     editor: E,
@@ -102,6 +345,13 @@ pub struct App<G: GitOps, E: Editor, P: PlanStore> {
     llm_config: LlmConfig,
     namespace: String,
     pre_reabsorb_ref: String,
+    history_ref: String,
+    ref_prefix: String,
+    color: bool,
+    /// Where audit events go: `audit_log` and the `stats` counter
+    audit: Option<Arc<dyn AuditSink>>,
+    audit_log: Option<Arc<dyn AuditSink>>,
+    stats: Option<(Arc<RunStats>, StatsFormat)>,
 }
 
 impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
@@ -113,7 +363,10 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         llm_config: LlmConfig,
         namespace: String,
     ) -> Self {
-        let pre_reabsorb_ref = crate::git::pre_reabsorb_ref_for(&namespace);
+        let pre_reabsorb_ref =
+            crate::git::pre_reabsorb_ref_for(crate::git::DEFAULT_REF_PREFIX, &namespace);
+        let history_ref =
+            crate::git::pre_reabsorb_history_ref_for(crate::git::DEFAULT_REF_PREFIX, &namespace);
         Self {
             git,
             editor,
@@ -122,19 +375,290 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             llm_config,
             namespace,
             pre_reabsorb_ref,
+            history_ref,
+            ref_prefix: crate::git::DEFAULT_REF_PREFIX.to_string(),
+            color: false,
+            audit: None,
+            audit_log: None,
+            stats: None,
         }
     }
 
+    /// Keep the pre-reabsorb refs under `prefix` instead of [`crate::git::DEFAULT_REF_PREFIX`].
+    pub fn with_ref_prefix(mut self, prefix: &str) -> Self {
+        self.pre_reabsorb_ref = crate::git::pre_reabsorb_ref_for(prefix, &self.namespace);
+        self.history_ref = crate::git::pre_reabsorb_history_ref_for(prefix, &self.namespace);
+        self.ref_prefix = prefix.to_string();
+        self
+    }
+
+    /// Colorize human-readable report output.
+    pub fn with_color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Record what the run does to `sink`, including the LLM calls of the
+    /// strategies it creates.
+    pub fn with_audit(mut self, sink: Option<Arc<dyn AuditSink>>) -> Self {
+        self.audit_log = sink;
+        self.connect_audit()
+    }
+
+    /// Print a summary of the run in `format` after planning or applying:
+    /// time taken, LLM calls, commits created and the integrity check.
+    pub fn with_stats(mut self, format: Option<StatsFormat>) -> Self {
+        self.stats = format.map(|format| (Arc::new(RunStats::new()), format));
+        self.connect_audit()
+    }
+
+    /// Send audit events to both the audit log and the stats counter.
+    fn connect_audit(mut self) -> Self {
+        let stats = self
+            .stats
+            .as_ref()
+            .map(|(stats, _)| stats.clone() as Arc<dyn AuditSink>);
+        let sink = match (self.audit_log.clone(), stats) {
+            (Some(log), Some(stats)) => Some(Arc::new(vec![log, stats]) as Arc<dyn AuditSink>),
+            (log, stats) => log.or(stats),
+        };
+        self.strategies = self.strategies.with_audit(sink.clone());
+        self.audit = sink;
+        self
+    }
+
+    fn print_stats(&self) {
+        let Some((stats, format)) = &self.stats else {
+            return;
+        };
+        let summary = stats.summary();
+        match format {
+            StatsFormat::Json => match serde_json::to_string(&summary) {
+                Ok(json) => println!("{}", json),
+                Err(err) => warn!("Could not serialize stats: {}", err),
+            },
+            StatsFormat::Pretty => println!("{}", summary),
+        }
+    }
+
+    fn audit(&self, event: AuditEvent) {
+        if let Some(sink) = &self.audit {
+            sink.record(&event);
+        }
+    }
+
+    /// Run `command`. The binary runs `doctor` itself before opening a
+    /// repository, since it has to work without one.
     pub fn run(&mut self, command: Command) -> Result<(), AppError> {
         match command {
             Command::Reset => self.handle_reset(),
-            Command::Apply(opts) => self.handle_apply(opts),
-            Command::Plan(opts) => self.handle_plan(opts),
+            Command::Apply(opts) => {
+                let result = self.handle_apply(opts);
+                self.print_stats();
+                result
+            }
+            Command::Check(opts) => self.handle_check(opts),
+            Command::Repair => self.handle_repair(),
+            Command::Reorder => self.handle_reorder(),
+            Command::Plan(opts) => {
+                let result = self.handle_plan(opts);
+                self.print_stats();
+                result
+            }
             Command::Status => self.handle_status(),
             Command::Assess(opts) => self.handle_assess(opts),
             Command::Compare(opts) => self.handle_compare(opts),
             Command::Reword(opts) => self.handle_reword(opts),
+            Command::Reflog(opts) => self.handle_reflog(opts),
+            Command::Doctor(opts) => doctor::run(
+                &self.llm_config,
+                &self.ref_prefix,
+                opts.check_llm,
+                self.color,
+            ),
+            Command::Amend(opts) => self.handle_amend(opts),
+            Command::Fold(opts) => self.handle_fold(opts),
+            Command::List(opts) => self.handle_list(opts),
+            Command::Models => list_models(&self.llm_config),
+        }
+    }
+
+    /// Plan and apply in one go (what running without a subcommand does).
+    pub fn run_default(
+        &mut self,
+        plan_args: PlanArgs,
+        execution: ExecutionArgs,
+    ) -> Result<(), AppError> {
+        // git-absorb rewrites history itself, so there is no plan to save
+        if plan_args.strategy == Strategy::Absorb {
+            return self.run_absorb(plan_args.dry_run);
+        }
+
+        let dry_run = plan_args.dry_run;
+        let result = self
+            .handle_plan(PlanArgs {
+                save_plan: true,
+                ..plan_args
+            })
+            .and_then(|()| {
+                if dry_run {
+                    return Ok(());
+                }
+                self.handle_apply(ApplyArgs {
+                    resume: false,
+                    force: false,
+                    from: None,
+                    execution,
+                })
+            });
+        self.print_stats();
+        result
+    }
+
+    fn run_absorb(&mut self, dry_run: bool) -> Result<(), AppError> {
+        self.check_no_operation_in_progress()?;
+        let reorganizer = self.strategies.create(Strategy::Absorb);
+        // Checks that the feature is enabled
+        reorganizer.plan(&[], &[])?;
+        if dry_run {
+            info!("Would run git-absorb on the staged changes.");
+            return Ok(());
+        }
+        reorganizer.apply(&self.git, &[])?;
+        Ok(())
+    }
+
+    fn handle_amend(&self, opts: AmendArgs) -> Result<(), AppError> {
+        let head = self.git.read_commit("HEAD")?;
+        let message = match opts.message {
+            Some(message) => message,
+            None => self.editor.edit(
+                &head.message.long,
+                "Only the message is amended; staged changes stay staged.\n\
+                 Lines starting with '#' ignored. Empty message aborts.",
+            )?,
+        };
+        if message.trim().is_empty() {
+            return Err(AppError::User(
+                "Empty commit message; not amending".to_string(),
+            ));
+        }
+
+        let new_sha = self.git.amend_message(&message, opts.no_verify)?;
+        info!(
+            "Amended {} -> {}",
+            self.git.abbrev(&head.sha),
+            self.git.abbrev(&new_sha)
+        );
+        Ok(())
+    }
+
+    /// Replace `base..head` and the base commit itself with one commit that
+    /// has head's tree and base's message, on top of base's parent. The index
+    /// and work tree already match head, so only HEAD moves.
+    fn handle_fold(&mut self, opts: FoldArgs) -> Result<(), AppError> {
+        // Folding is destructive, so never guess how much of the branch to squash
+        if opts.range.is_none() && opts.base.is_none() {
+            return Err(AppError::User(
+                "Fold needs a range to fold, e.g. 'git-reabsorb fold HEAD~3..HEAD', or --base <branch>"
+                    .to_string(),
+            ));
+        }
+        self.check_no_operation_in_progress()?;
+        let range = CommitRange::resolve(opts.range.as_ref(), opts.base.as_deref(), &self.git)?;
+        let (base, head) = (range.base.as_str(), range.head());
+        if head != self.git.get_head()? {
+            return Err(AppError::User(format!(
+                "Fold rewrites the current branch, but {} is not HEAD. Check it out first.",
+                self.git.abbrev(head)
+            )));
+        }
+        self.ensure_range_not_empty(base, head)?;
+
+        let target = self.git.read_commit(base)?;
+        let Some(parent) = self.git.parents_of(&target.sha)?.into_iter().next() else {
+            return Err(AppError::User(format!(
+                "{} is a root commit; there is nothing to fold it onto",
+                self.git.abbrev(base)
+            )));
+        };
+
+        let pushed = self.git.commits_on_remote(&parent, head)?;
+        if !pushed.is_empty() && !opts.allow_pushed {
+            self.warn_pushed(&pushed);
+            return Err(AppError::User(
+                "Refusing to rewrite commits that are already on a remote. Re-run with --allow-pushed to proceed."
+                    .to_string(),
+            ));
         }
+
+        let commits = self.git.read_commits(base, head)?;
+        info!(
+            "Folding {} commit(s) into {} {}",
+            commits.len(),
+            self.git.abbrev(base),
+            target.message.short
+        );
+        for commit in &commits {
+            info!(
+                "  {} {}",
+                self.git.abbrev(&commit.sha),
+                commit.message.short
+            );
+        }
+        if opts.dry_run {
+            return Ok(());
+        }
+
+        self.git.save_pre_reabsorb_head(&self.pre_reabsorb_ref)?;
+        self.git
+            .record_pre_reabsorb_history(&self.history_ref, MAX_PRE_REABSORB_HISTORY)?;
+        info!("Saved pre-reabsorb state to {}", self.pre_reabsorb_ref);
+
+        let tree = format!("{}^{{tree}}", head);
+        let folded = self
+            .git
+            .commit_tree(&tree, &parent, &target.message.long, Some(&target))?;
+        self.git.update_head(&folded)?;
+        info!(
+            "Folded into {}. Run 'git reabsorb reset' to undo.",
+            self.git.abbrev(&folded)
+        );
+        Ok(())
+    }
+
+    fn handle_list(&self, opts: ListArgs) -> Result<(), AppError> {
+        let paths = if opts.archived {
+            crate::plan_store::list_archived_plans()
+        } else {
+            crate::plan_store::list_saved_plans()
+        };
+        if paths.is_empty() {
+            println!(
+                "No {} plans.",
+                if opts.archived { "archived" } else { "saved" }
+            );
+            return Ok(());
+        }
+        for path in paths {
+            let summary = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<SavedPlan>(&json).map_err(|e| e.to_string())
+                });
+            match summary {
+                Ok(plan) => println!(
+                    "{}  {:?}, {} commit(s), {}..{}",
+                    path.display(),
+                    plan.strategy,
+                    plan.commits.len(),
+                    short_sha(&plan.base_sha),
+                    short_sha(&plan.original_head)
+                ),
+                Err(e) => println!("{}  (unreadable: {})", path.display(), e),
+            }
+        }
+        Ok(())
     }
 
     fn handle_reset(&mut self) -> Result<(), AppError> {
@@ -147,8 +671,8 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         let pre_reabsorb_head = self.git.get_pre_reabsorb_head(&self.pre_reabsorb_ref)?;
         info!(
             "Resetting from {} to pre-reabsorb state {}",
-            short_sha(&self.git.get_head()?),
-            short_sha(&pre_reabsorb_head)
+            self.git.abbrev(&self.git.get_head()?),
+            self.git.abbrev(&pre_reabsorb_head)
         );
 
         self.git.reset_hard(&pre_reabsorb_head)?;
@@ -163,8 +687,259 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         Ok(())
     }
 
+    fn handle_check(&self, opts: CheckArgs) -> Result<(), AppError> {
+        let plan = self.plan_store.load()?;
+        let validation = validate_plan(&plan.to_planned_commits(), &plan.working_tree_hunks);
+
+        match opts.format {
+            OutputFormat::Json => println!("{}", validation.to_json()),
+            _ if validation.is_valid() => println!("Plan is valid."),
+            _ => {
+                println!("{}", validation.summary());
+                for issue in &validation.issues {
+                    println!("  {}", issue);
+                }
+            }
+        }
+
+        if validation.is_valid() {
+            Ok(())
+        } else {
+            Err(AppError::User(
+                "Plan failed validation; run 'git reabsorb repair' to fix it.".to_string(),
+            ))
+        }
+    }
+
+    fn handle_repair(&mut self) -> Result<(), AppError> {
+        let mut plan = self.plan_store.load()?;
+        if plan.next_commit_index > 0 {
+            return Err(AppError::User(
+                "Plan is partially applied; only unapplied plans can be repaired.".to_string(),
+            ));
+        }
+
+        let hunks = plan.get_working_tree_hunks();
+        let commits = plan.to_planned_commits();
+        let validation = validate_plan(&commits, &hunks);
+        if validation.is_valid() {
+            info!("Plan is valid. Nothing to repair.");
+            return Ok(());
+        }
+        for issue in &validation.issues {
+            info!("  {}", issue);
+        }
+
+        // Strategies whose fix_plan would replan from scratch get the generic
+        // repair instead, so hand edits survive
+        let reorganizer = self.strategies.create(plan.strategy);
+        let mut repaired = if reorganizer.fixes_in_place() {
+            let source_commits = self.git.read_commits(&plan.base_sha, &plan.original_head)?;
+            fix_creation_order(
+                reorganizer.fix_plan(commits, &validation, &source_commits, &hunks)?,
+                &hunks,
+            )
+        } else {
+            info!(
+                "Using generic repair for the {} strategy",
+                reorganizer.name()
+            );
+            repair_plan(commits, &hunks)
+        };
+        repaired.retain(|c| !c.changes.is_empty());
+
+        let remaining = validate_plan(&repaired, &hunks);
+        plan.commits = repaired.iter().map(SavedCommit::from).collect();
+        self.plan_store.save(&plan)?;
+
+        if remaining.is_valid() {
+            info!(
+                "Repaired {} issue(s); saved {} commits.",
+                validation.issues.len(),
+                plan.commits.len()
+            );
+        } else {
+            warn!(
+                "Saved the repaired plan, but {} issue(s) remain:",
+                remaining.issues.len()
+            );
+            for issue in &remaining.issues {
+                warn!("  {}", issue);
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_reorder(&mut self) -> Result<(), AppError> {
+        let mut plan = self.plan_store.load()?;
+        if plan.next_commit_index > 0 {
+            return Err(AppError::User(
+                "Plan is partially applied; only unapplied plans can be reordered.".to_string(),
+            ));
+        }
+
+        let edited = match self
+            .editor
+            .edit(&reorder::render_todo(&plan.commits), reorder::TODO_HELP)
+        {
+            Err(EditorError::EmptyMessage) => {
+                return Err(AppError::User(
+                    "Nothing left in the todo list; plan left unchanged".to_string(),
+                ))
+            }
+            result => result?,
+        };
+        let groups = reorder::parse_todo(&edited, plan.commits.len()).map_err(AppError::User)?;
+        let folded = plan.commits.len() - groups.len();
+
+        let mut reordered = plan.clone();
+        reordered.commits = reorder::reorder_commits(plan.commits, &groups);
+        reorder::remap_file_changes(&mut reordered.file_changes, &groups);
+        let validation = validate_plan(
+            &reordered.to_planned_commits(),
+            &reordered.get_working_tree_hunks(),
+        );
+        let violations = validation.creation_order_issues();
+        if !violations.is_empty() {
+            for issue in &violations {
+                warn!("  {}", issue);
+            }
+            return Err(AppError::User(format!(
+                "The new order modifies {} file(s) before the commit that creates them; plan left unchanged",
+                violations.len()
+            )));
+        }
+
+        plan.commits = reordered.commits;
+        plan.file_changes = reordered.file_changes;
+        self.plan_store.save(&plan)?;
+        info!("Saved {} commits in the new order.", plan.commits.len());
+        if folded > 0 {
+            info!("Folded {} commit(s) into the commits above them.", folded);
+        }
+        Ok(())
+    }
+
+    /// Count the commits in `base..head`, refusing an empty range. It is empty
+    /// when head is base or one of its ancestors, which usually means `--base`
+    /// names the branch tip rather than its fork point.
+    fn ensure_range_not_empty(&self, base: &str, head: &str) -> Result<usize, AppError> {
+        let count = self.git.count_commits(base, head)?;
+        if count > 0 {
+            return Ok(count);
+        }
+        Err(AppError::User(format!(
+            "No commits in range {}..{}: {} already contains {}. Check the base: it should be the commit this branch forked from (e.g. --base main), not the branch tip.",
+            self.git.abbrev(base),
+            self.git.abbrev(head),
+            self.git.abbrev(base),
+            self.git.abbrev(head)
+        )))
+    }
+
+    /// `.reabsorb.toml` from the top of the work tree.
+    fn repo_config(&self) -> Result<RepoConfig, AppError> {
+        let path = repo_config_path(&self.git)?;
+        RepoConfig::load(&path).map_err(|e| AppError::User(e.to_string()))
+    }
+
+    /// An unfinished merge or rebase leaves conflict markers and a
+    /// half-built index that planning and applying would fold into the new
+    /// commits, so refuse before touching anything.
+    fn check_no_operation_in_progress(&self) -> Result<(), AppError> {
+        if self.git.operation_in_progress()? {
+            return Err(AppError::User(
+                "repository is in the middle of a merge/rebase; finish or abort it first"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// A hard reset would throw away uncommitted changes, so refuse while
+    /// there are any.
+    fn check_clean_work_tree(&self) -> Result<(), AppError> {
+        if !self.git.get_working_tree_diff()?.trim().is_empty() {
+            return Err(AppError::User(
+                "working tree has uncommitted changes; commit or stash them first".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Resuming builds on HEAD, so HEAD must still contain the last commit the
+    /// plan created (or the base, if none were created yet).
+    fn check_resume_base(&self, plan: &SavedPlan, force: bool) -> Result<(), AppError> {
+        let last_created = plan.created_before(plan.next_commit_index);
+        if last_created == EMPTY_TREE {
+            // Nothing created yet on a root plan, so HEAD may be unborn
+            return Ok(());
+        }
+        let head = self.git.get_head()?;
+        if self.git.is_ancestor(last_created, &head)? {
+            return Ok(());
+        }
+
+        let message = format!(
+            "HEAD ({}) does not contain {} from the partially-applied plan, so resuming would build on the wrong base",
+            self.git.abbrev(&head),
+            self.git.abbrev(last_created)
+        );
+        if force {
+            warn!("{}; continuing because of --force", message);
+            return Ok(());
+        }
+        Err(AppError::User(format!(
+            "{}. Check out {} to resume, run 'git reabsorb reset' to start over, or pass --force.",
+            message,
+            self.git.abbrev(last_created)
+        )))
+    }
+
+    /// Undo the commits created from `from` (1-based) on and move HEAD back to
+    /// the one before, so resuming recreates them.
+    /// Rewind `plan` in memory so commit `from` (1-based) is next, returning
+    /// the commit to move HEAD back to. HEAD and the saved plan stay put until
+    /// every other check has passed.
+    fn rewind_plan(
+        &self,
+        plan: &mut SavedPlan,
+        from: usize,
+        opts: &ApplyArgs,
+    ) -> Result<String, AppError> {
+        if from == 0 || from > plan.next_commit_index {
+            return Err(AppError::User(format!(
+                "--from must be between 1 and {}, the commits created so far",
+                plan.next_commit_index
+            )));
+        }
+
+        plan.rewind_to(from - 1);
+        self.check_resume_base(plan, opts.force)?;
+        Ok(plan.created_before(from - 1).to_string())
+    }
+
     fn handle_apply(&mut self, opts: ApplyArgs) -> Result<(), AppError> {
+        self.check_no_operation_in_progress()?;
         let mut plan = self.plan_store.load()?;
+        self.ensure_range_not_empty(&plan.base_sha, &plan.original_head)?;
+        if opts.execution.index_free && plan.base_sha == EMPTY_TREE {
+            // Index-free commits are made on HEAD, which a root plan leaves unborn
+            return Err(AppError::User(
+                "--index-free can't apply a plan made with --root".to_string(),
+            ));
+        }
+
+        let pushed = self
+            .git
+            .commits_on_remote(&plan.base_sha, &plan.original_head)?;
+        if !pushed.is_empty() && !opts.execution.allow_pushed {
+            self.warn_pushed(&pushed);
+            return Err(AppError::User(
+                "Refusing to rewrite commits that are already on a remote. Re-run with --allow-pushed to proceed."
+                    .to_string(),
+            ));
+        }
 
         // Let the strategy handle apply if it wants to (e.g., absorb calls git-absorb directly)
         let reorganizer = self.strategies.create(plan.strategy);
@@ -175,14 +950,63 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             return Ok(());
         }
 
+        let rewind = match opts.from {
+            Some(from) => Some((from, self.rewind_plan(&mut plan, from, &opts)?)),
+            None => None,
+        };
+
         let already_created = plan.next_commit_index;
 
+        // Guard against runaway plans (e.g. an LLM splitting into hundreds of commits)
+        let remaining = plan.commits.len().saturating_sub(plan.next_commit_index);
+        if remaining > opts.execution.max_commits && !opts.execution.yes {
+            warn!(
+                "Plan would create {} commits, above the limit of {}",
+                remaining, opts.execution.max_commits
+            );
+            return Err(AppError::User(format!(
+                "Refusing to create {} commits (limit {}). Re-run with --yes or --max-commits {} to proceed.",
+                remaining, opts.execution.max_commits, remaining
+            )));
+        }
+
+        if opts.execution.verify_each && plan.stripped_hint_marker.is_some() {
+            return Err(AppError::User(
+                "--verify-each can't check a plan made with --strip-hints: its commits leave out the hint lines"
+                    .to_string(),
+            ));
+        }
+
+        // Read before touching HEAD, so a broken config stops nothing half
+        // way. Repo defaults share .reabsorb.toml with the rules strategy
+        let repo_config = self.repo_config()?;
+        let lint = (!opts.execution.no_lint).then_some(repo_config.lint);
+        let no_verify = opts.execution.skip_hooks(repo_config.no_verify);
+        if no_verify {
+            info!("Skipping commit hooks (--no-verify)");
+        }
+
+        let hunks = plan.get_working_tree_hunks();
+        let file_changes = plan.get_file_changes();
+        let planned_commits = plan.to_planned_commits();
+        if let (Some(config), true) = (&lint, opts.execution.no_editor) {
+            // Without an editor a failing message can't be fixed, so find it
+            // before HEAD moves
+            executor::lint_planned_messages(
+                &planned_commits[plan.next_commit_index..],
+                &hunks,
+                config,
+                plan.next_commit_index,
+            )?;
+        }
+
         if opts.resume {
             if plan.is_complete() {
                 info!("Plan is already complete. Nothing to resume.");
                 self.plan_store.delete()?;
                 return Ok(());
             }
+            self.check_resume_base(&plan, opts.force)?;
             info!(
                 "Resuming plan: {}/{} commits already created",
                 plan.next_commit_index,
@@ -205,7 +1029,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             if self.git.has_pre_reabsorb_head(&self.pre_reabsorb_ref) {
                 warn!(
                     "Pre-reabsorb state exists ({}). Use 'git reabsorb reset' or it will be overwritten.",
-                    short_sha(&self.git.get_pre_reabsorb_head(&self.pre_reabsorb_ref)?)
+                    self.git.abbrev(&self.git.get_pre_reabsorb_head(&self.pre_reabsorb_ref)?)
                 );
             }
 
@@ -214,22 +1038,60 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             if current_head != plan.original_head {
                 warn!(
                     "HEAD ({}) differs from plan's original HEAD ({})",
-                    short_sha(&current_head),
-                    short_sha(&plan.original_head)
+                    self.git.abbrev(&current_head),
+                    self.git.abbrev(&plan.original_head)
                 );
             }
 
             // Save pre-reabsorb state and reset to base
             self.git.save_pre_reabsorb_head(&self.pre_reabsorb_ref)?;
+            self.git
+                .record_pre_reabsorb_history(&self.history_ref, MAX_PRE_REABSORB_HISTORY)?;
             info!("Saved pre-reabsorb state to {}", self.pre_reabsorb_ref);
+            if let Some(template) = &opts.execution.backup_branch {
+                let name = create_backup(template, |name| {
+                    self.git.create_branch_at(name, &current_head)
+                })?;
+                info!(
+                    "Backed up {} to branch {}",
+                    self.git.abbrev(&current_head),
+                    name
+                );
+            }
+            if let Some(template) = &opts.execution.backup_tag {
+                let name =
+                    create_backup(template, |name| self.git.create_tag(name, &current_head))?;
+                info!(
+                    "Backed up {} to tag {}",
+                    self.git.abbrev(&current_head),
+                    name
+                );
+            }
 
-            info!("Resetting to {}...", short_sha(&plan.base_sha));
-            self.git.reset_to(&plan.base_sha)?;
+            info!("Resetting to {}...", self.git.abbrev(&plan.base_sha));
+            if opts.execution.index_free {
+                self.git.update_head(&plan.base_sha)?;
+            } else {
+                self.git.reset_to(&plan.base_sha)?;
+            }
+        }
+
+        if let Some((from, target)) = rewind {
+            info!(
+                "Redoing from commit {}: resetting to {}...",
+                from,
+                self.git.abbrev(&target)
+            );
+            // Keep the work tree: it holds the final state that binary changes
+            // are staged from
+            if opts.execution.index_free {
+                self.git.update_head(&target)?;
+            } else {
+                self.git.reset_to(&target)?;
+            }
+            self.plan_store.save(&plan)?;
         }
 
-        let hunks = plan.get_working_tree_hunks();
-        let file_changes = plan.get_file_changes();
-        let planned_commits = plan.to_planned_commits();
         print_planned_commits(
             &planned_commits[plan.next_commit_index..],
             plan.next_commit_index,
@@ -237,12 +1099,17 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
 
         cancel::register_handler();
 
-        let executor = PlanExecutor::new(&self.git, &self.editor, &self.plan_store);
+        let executor = PlanExecutor::new(&self.git, &self.editor, &self.plan_store)
+            .with_test_cmd(opts.execution.test_cmd.clone())
+            .with_index_free(opts.execution.index_free)
+            .with_lint(lint)
+            .with_verify_each(opts.execution.verify_each)
+            .with_audit(self.audit.clone());
         if let Err(err) = executor.execute(
             &hunks,
             &planned_commits,
             &file_changes,
-            opts.execution.no_verify,
+            no_verify,
             opts.execution.no_editor,
             &mut plan,
         ) {
@@ -256,12 +1123,45 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             }
 
             error!("Commit creation failed: {}", err);
+            match &err {
+                ExecutionError::Git(GitError::ApplyFailed { file, .. }) => info!(
+                    "The planned changes to '{}' no longer apply to the index; \
+                     check for edits made since planning, or run 'git reabsorb reset' to start over.",
+                    file
+                ),
+                ExecutionError::Git(GitError::MergeConflict(_)) => {
+                    info!("Resolve the unmerged paths (git status) before resuming.")
+                }
+                ExecutionError::TreeMismatch { .. } => info!(
+                    "The mismatched commit is HEAD; inspect it with 'git show', \
+                     or run 'git reabsorb reset' to start over."
+                ),
+                ExecutionError::TestFailed { .. } => info!(
+                    "The failing commit is HEAD (the command ran in a temporary checkout of it). \
+                     Fix it in a new commit, or amend it and resume with --force."
+                ),
+                _ => {}
+            }
             info!("Progress saved. Use 'git reabsorb apply --resume' to continue.");
             return Err(AppError::Execution(err));
         }
 
-        self.verify_final_state(&plan.original_head)?;
-        self.plan_store.delete()?;
+        let integrity = self.verify_final_state(
+            &plan.base_sha,
+            &plan.original_head,
+            plan.stripped_hint_marker.as_deref(),
+        );
+        self.audit(AuditEvent::IntegrityChecked {
+            ok: integrity.is_ok(),
+            detail: integrity.as_ref().err().map(|e| e.to_string()),
+        });
+        integrity?;
+        if opts.execution.keep_plan {
+            let path = self.plan_store.archive(&plan)?;
+            info!("Plan archived to {}", path.display());
+        } else {
+            self.plan_store.delete()?;
+        }
         info!(
             "Done! Created {} commits.",
             plan.next_commit_index.saturating_sub(already_created)
@@ -272,6 +1172,18 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
     }
 
     fn handle_plan(&mut self, opts: PlanArgs) -> Result<(), AppError> {
+        self.check_no_operation_in_progress()?;
+        if opts.strategy == Strategy::Absorb && opts.save_plan {
+            return Err(AppError::User(
+                "The absorb strategy runs git-absorb directly and has no plan to save. \
+                 Run 'git reabsorb --strategy absorb' instead."
+                    .to_string(),
+            ));
+        }
+
+        self.strategies.ensure_llm_available(opts.strategy)?;
+        self.strategies.check_redaction(opts.strategy)?;
+
         if self.plan_store.exists() {
             let plan_path = crate::plan_store::plan_file_path(&self.namespace);
             warn!(
@@ -280,14 +1192,58 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             );
         }
 
-        let range = CommitRange::resolve(opts.range.as_ref(), opts.base.as_deref(), &self.git)?;
+        let (range, origin) = if opts.root {
+            (CommitRange::root(&self.git)?, RangeOrigin::Root)
+        } else {
+            CommitRange::resolve_with_origin(opts.range.as_ref(), opts.base.as_deref(), &self.git)?
+        };
         info!(
-            "Planning {}..{}",
-            short_sha(&range.base),
-            short_sha(range.head())
+            "Planning {}..{} ({})",
+            self.git.abbrev(&range.base),
+            self.git.abbrev(range.head()),
+            origin
         );
+        self.audit(AuditEvent::RangeResolved {
+            base: range.base.clone(),
+            head: range.head().to_string(),
+        });
+        let count = self.ensure_range_not_empty(&range.base, range.head())?;
+        info!("Reorganizing {} commits", count);
+        if self.strategies.uses_llm(opts.strategy) && count > LARGE_LLM_RANGE {
+            warn!(
+                "{} commits is a large range for the {:?} strategy; expect slow, costly LLM calls. Consider planning a smaller range.",
+                count, opts.strategy
+            );
+        }
 
-        let planner = Planner::new(&self.git, self.strategies.clone());
+        // Reabsorbing replays base..head onto base, which only makes sense if
+        // head descends from base
+        if !self.git.is_ancestor(&range.base, range.head())? {
+            return Err(AppError::User(format!(
+                "{} is not an ancestor of {}; plan from their merge-base instead (git merge-base {} {})",
+                self.git.abbrev(&range.base),
+                self.git.abbrev(range.head()),
+                range.base,
+                range.head()
+            )));
+        }
+
+        let pushed = self.git.commits_on_remote(&range.base, range.head())?;
+        if !pushed.is_empty() {
+            self.warn_pushed(&pushed);
+            warn!("Applying this plan will require --allow-pushed.");
+        }
+
+        let strategies = self
+            .strategies
+            .clone()
+            .with_keep_empty(opts.keep_empty)
+            .with_hint_marker(opts.hint_marker.clone());
+        let planner = Planner::new(&self.git, strategies)
+            .with_author_filter(opts.author.clone())
+            .with_ignore_whitespace(opts.ignore_whitespace)
+            .with_strip_hints(opts.strip_hints.then(|| opts.hint_marker.clone()))
+            .with_fold_whitespace_commits(opts.fold_whitespace_commits);
         let source_commits = planner.read_source_commits(&range.base, range.head())?;
         info!("Found {} commits", source_commits.len());
 
@@ -309,6 +1265,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         if mode_count > 0 {
             info!("Found {} mode changes", mode_count);
         }
+        planner.warn_unrepresented_commits(&source_commits, &hunks, &file_changes)?;
 
         let plan = planner.draft_plan(
             opts.strategy,
@@ -318,7 +1275,49 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             &file_changes,
         )?;
         info!("Strategy: {:?}", plan.strategy);
-        print_planned_commits(&plan.planned_commits, 0);
+        self.audit(AuditEvent::StrategyChosen {
+            strategy: plan.strategy,
+        });
+        // Reworded commits are matched to sources by id, which the author
+        // filter and pruning renumber, so fall back to the plain listing there
+        if opts.explain {
+            print_explained_commits(&plan.planned_commits, &plan.hunks);
+        } else if plan.strategy == Strategy::Reword && opts.author.is_none() && plan.pruned == 0 {
+            print_reworded_commits(&plan.planned_commits, &source_commits);
+        } else {
+            print_planned_commits(&plan.planned_commits, 0);
+        }
+
+        // Strategies validate (or not) internally; check the final plan the same way for all
+        let validation = validate_plan(&plan.planned_commits, &plan.hunks);
+        if !validation.is_valid() {
+            warn!("Plan has validation issues: {}", validation.summary());
+            for issue in &validation.issues {
+                warn!("  {}", issue);
+                self.audit(AuditEvent::ValidationIssue {
+                    issue: issue.to_string(),
+                });
+            }
+            if opts.strict {
+                return Err(AppError::User(
+                    "Plan failed validation (--strict); not saving it.".to_string(),
+                ));
+            }
+        }
+
+        let mut saved_plan = SavedPlan::new(
+            plan.strategy,
+            range.base.clone(),
+            range.head().to_string(),
+            &plan.planned_commits,
+            &plan.hunks,
+            &plan.file_to_commits,
+            &plan.file_changes,
+        );
+        saved_plan.stripped_hint_marker = plan.stripped_hint_marker.clone();
+        if opts.dump_plan {
+            println!("{}", saved_plan.to_json()?);
+        }
 
         // Dry run: just show the plan, no disk writes
         if opts.dry_run {
@@ -327,15 +1326,6 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
 
         // Save plan to disk
         if opts.save_plan {
-            let saved_plan = SavedPlan::new(
-                plan.strategy,
-                range.base.clone(),
-                range.head().to_string(),
-                &plan.planned_commits,
-                &plan.hunks,
-                &plan.file_to_commits,
-                &plan.file_changes,
-            );
             self.plan_store.save(&saved_plan)?;
             info!(
                 "Plan saved to {}",
@@ -347,16 +1337,30 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         Ok(())
     }
 
-    fn verify_final_state(&self, expected_head: &str) -> Result<(), AppError> {
+    /// Check HEAD is built on `base` with `expected_head`'s tree, apart from
+    /// the hint lines for `stripped_marker`.
+    fn verify_final_state(
+        &self,
+        base: &str,
+        expected_head: &str,
+        stripped_marker: Option<&str>,
+    ) -> Result<(), AppError> {
         let current_head = self.git.get_head()?;
+        if !self.git.is_ancestor(base, &current_head)? {
+            return Err(AppError::Integrity(format!(
+                "HEAD {} is not built on the plan's base {}",
+                self.git.abbrev(&current_head),
+                self.git.abbrev(base)
+            )));
+        }
         let diff = self.git.diff_trees(expected_head, &current_head)?;
-        if diff.trim().is_empty() {
+        if diff.trim().is_empty() || only_removes_hints(&diff, stripped_marker) {
             Ok(())
         } else {
             Err(AppError::Integrity(format!(
                 "HEAD {} differs from expected {}",
-                short_sha(&current_head),
-                short_sha(expected_head)
+                self.git.abbrev(&current_head),
+                self.git.abbrev(expected_head)
             )))
         }
     }
@@ -374,7 +1378,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
 
         info!(
             "Reset to pre-reabsorb state ({})",
-            short_sha(&pre_reabsorb_head)
+            self.git.abbrev(&pre_reabsorb_head)
         );
         Ok(())
     }
@@ -384,7 +1388,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
 
         // Current git state
         let head = self.git.get_head()?;
-        info!("Current HEAD: {}", short_sha(&head));
+        info!("Current HEAD: {}", self.git.abbrev(&head));
 
         if let Ok(branch) = self.git.current_branch_name() {
             info!("Current branch: {}", branch);
@@ -397,7 +1401,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             info!(
                 "Pre-reabsorb ref: {} -> {}",
                 self.pre_reabsorb_ref,
-                short_sha(&pre)
+                self.git.abbrev(&pre)
             );
         } else {
             info!("No pre-reabsorb state saved");
@@ -412,20 +1416,34 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
 
         let plan = self.plan_store.load()?;
         info!("Strategy: {:?}", plan.strategy);
-        info!("Base SHA: {}", short_sha(&plan.base_sha));
-        info!("Original HEAD: {}", short_sha(&plan.original_head));
+        info!("Base SHA: {}", self.git.abbrev(&plan.base_sha));
+        info!("Original HEAD: {}", self.git.abbrev(&plan.original_head));
         info!(
             "Progress: {}/{} commits",
             plan.next_commit_index,
             plan.commits.len()
         );
 
+        // Source commits the plan was drafted from
+        if let Ok(source_commits) = self.git.read_commits(&plan.base_sha, &plan.original_head) {
+            info!("--- Source Commits ---");
+            for commit in &source_commits {
+                info!(
+                    "  {} {} ({}, {})",
+                    self.git.abbrev(&commit.sha),
+                    commit.message.short,
+                    commit.author_name,
+                    format_timestamp(commit.author_date)
+                );
+            }
+        }
+
         // Show commits
         info!("--- Planned Commits ---");
         for (i, commit) in plan.commits.iter().enumerate() {
             let status = if i < plan.next_commit_index {
                 if let Some(sha) = &commit.created_sha {
-                    format!("[DONE: {}]", short_sha(sha))
+                    format!("[DONE: {}]", self.git.abbrev(sha))
                 } else {
                     "[DONE]".to_string()
                 }
@@ -487,49 +1505,126 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         Ok(())
     }
 
-    fn handle_assess(&mut self, opts: AssessArgs) -> Result<(), AppError> {
-        // Resolve commit range
-        let range = CommitRange::resolve(opts.range.as_ref(), opts.base.as_deref(), &self.git)?;
+    fn handle_reflog(&mut self, opts: ReflogArgs) -> Result<(), AppError> {
+        let entries = self.git.list_pre_reabsorb_history(&self.history_ref)?;
+        if entries.is_empty() {
+            return Err(AppError::User(
+                "No pre-reabsorb history found for this branch.".to_string(),
+            ));
+        }
+
+        let Some(index) = opts.reset else {
+            info!("Pre-reabsorb history (newest first):");
+            for (i, entry) in entries.iter().enumerate() {
+                info!(
+                    "  reabsorb@{{{}}}  {}  {}",
+                    i,
+                    self.git.abbrev(&entry.sha),
+                    format_timestamp(entry.saved_at / 1000)
+                );
+            }
+            info!("To restore one: git reabsorb reflog --reset <INDEX>");
+            return Ok(());
+        };
+
+        let entry = entries.get(index).ok_or_else(|| {
+            AppError::User(format!(
+                "No history entry {} (have {} entries)",
+                index,
+                entries.len()
+            ))
+        })?;
+        self.check_no_operation_in_progress()?;
+        self.check_clean_work_tree()?;
+
+        // Keep the current HEAD, so jumping back is itself undoable
+        self.git.save_pre_reabsorb_head(&self.pre_reabsorb_ref)?;
+        self.git
+            .record_pre_reabsorb_history(&self.history_ref, MAX_PRE_REABSORB_HISTORY)?;
+        info!("Saved pre-reabsorb state to {}", self.pre_reabsorb_ref);
 
         info!(
-            "Assessing commits {}..{}",
-            short_sha(&range.base),
-            short_sha(range.head())
+            "Resetting from {} to reabsorb@{{{}}} ({})",
+            self.git.abbrev(&self.git.get_head()?),
+            index,
+            self.git.abbrev(&entry.sha)
         );
+        self.git.reset_hard(&entry.sha)?;
+        info!("Successfully reset to reabsorb@{{{}}}.", index);
 
-        // Read commits
-        let commits = self.git.read_commits(&range.base, range.head())?;
+        Ok(())
+    }
+
+    fn handle_assess(&mut self, opts: AssessArgs) -> Result<(), AppError> {
+        if opts.working {
+            return self.assess_working_tree(opts);
+        }
+        let (base, head, commits) = match &opts.commits {
+            Some(revs) => self.read_commit_set(revs)?,
+            None => {
+                let range =
+                    CommitRange::resolve(opts.range.as_ref(), opts.base.as_deref(), &self.git)?;
+                info!(
+                    "Assessing commits {}..{}",
+                    self.git.abbrev(&range.base),
+                    self.git.abbrev(range.head())
+                );
+                self.ensure_range_not_empty(&range.base, range.head())?;
+                let commits = self.git.read_commits(&range.base, range.head())?;
+                (range.base.clone(), range.head().to_string(), commits)
+            }
+        };
         if commits.is_empty() {
             return Err(AppError::User("No commits found in range".to_string()));
         }
 
         info!("Found {} commits to assess", commits.len());
 
-        // Parse criteria from args or use all
-        let criterion_ids = match &opts.criteria {
-            Some(names) => {
-                let mut ids = Vec::new();
-                for name in names {
-                    let id: CriterionId = name.parse().map_err(AppError::User)?;
-                    ids.push(id);
-                }
-                ids
-            }
-            None => CriterionId::all().to_vec(),
+        if opts.criteria.is_some() && opts.preset.is_some() {
+            warn!("--criteria overrides --preset");
+        }
+        let criterion_ids = opts.criterion_ids().map_err(AppError::User)?;
+
+        if opts.dry_run {
+            return self.preview_assessment(&commits, &criterion_ids);
+        }
+
+        let checkpoint_path = assessment::checkpoint::default_checkpoint_path();
+        let completed = if opts.resume {
+            self.load_assessment_checkpoint(&checkpoint_path, &criterion_ids)?
+        } else {
+            Vec::new()
         };
 
-        // Create assessment engine with parallelism
-        let client = self.llm_config.create_client();
-        let engine = AssessmentEngine::new(client, &criterion_ids).with_parallelism(opts.parallel);
+        let engine = AssessmentEngine::new(self.assessment_client()?, &criterion_ids)
+            .with_redaction(self.llm_config.redaction.clone())
+            .with_verdict_bands(verdict_bands(&self.repo_config()?)?)
+            .with_parallelism(opts.parallel)
+            .with_progress(!matches!(opts.format, OutputFormat::Json))
+            .with_checkpoint(Some(checkpoint_path.clone()))
+            .with_completed(completed);
 
         // Run assessment
-        let result = engine.assess_range(&self.git, &range.base, range.head(), &commits)?;
+        let result = engine.assess_range(&self.git, &base, &head, &commits)?;
+        if let Err(e) = AssessmentCheckpoint::clear(&checkpoint_path) {
+            warn!("Could not remove assessment checkpoint: {}", e);
+        }
+        // The baseline is a separate run, so it must not overwrite or reuse
+        // the checkpoint
+        let engine = engine.with_checkpoint(None).with_completed(Vec::new());
 
         // Handle comparison if requested
-        if let Some(compare_path) = &opts.compare {
-            let previous = assessment::load_assessment(compare_path)
-                .map_err(|e| AppError::User(format!("Failed to load comparison: {}", e)))?;
-
+        let previous = if let Some(compare_range) = &opts.compare_range {
+            Some(self.assess_baseline(&engine, compare_range)?)
+        } else if let Some(compare_path) = &opts.compare {
+            Some(
+                assessment::load_assessment(compare_path)
+                    .map_err(|e| AppError::User(format!("Failed to load comparison: {}", e)))?,
+            )
+        } else {
+            None
+        };
+        if let Some(previous) = previous {
             let comparison = assessment::compare_assessments(previous, result.clone());
             let output =
                 assessment::report::format_comparison(&comparison, convert_format(opts.format));
@@ -540,6 +1635,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
                 &result,
                 convert_format(opts.format),
                 opts.full,
+                self.color,
             );
             println!("{}", output);
         }
@@ -554,6 +1650,181 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         Ok(())
     }
 
+    /// The LLM client for assessments; file-based I/O needs the LLM to be
+    /// able to read the diff files.
+    fn assessment_client(&self) -> Result<Arc<dyn LlmClient>, AppError> {
+        check_redaction_tools(&self.llm_config)?;
+        let config = if Feature::FileBasedLlmIo.is_enabled() {
+            self.llm_config
+                .clone()
+                .with_capabilities(vec![ToolCapability::FileIo])
+        } else {
+            self.llm_config.clone()
+        };
+        Ok(audited(config.create_client(), self.audit.as_ref()))
+    }
+
+    /// `assess --working`: assess the uncommitted diff against HEAD as if it
+    /// were about to be committed.
+    fn assess_working_tree(&self, opts: AssessArgs) -> Result<(), AppError> {
+        let diff = self.git.get_working_tree_diff()?;
+        if diff.trim().is_empty() {
+            return Err(AppError::User(
+                "No uncommitted changes to assess".to_string(),
+            ));
+        }
+
+        if opts.criteria.is_some() && opts.preset.is_some() {
+            warn!("--criteria overrides --preset");
+        }
+        let mut criterion_ids = opts.criterion_ids().map_err(AppError::User)?;
+        if opts.message.is_none() && criterion_ids.contains(&CriterionId::MessageQuality) {
+            info!(
+                "No --message given; skipping {}",
+                CriterionId::MessageQuality
+            );
+            criterion_ids.retain(|id| *id != CriterionId::MessageQuality);
+        }
+        if criterion_ids.is_empty() {
+            return Err(AppError::User(
+                "Nothing to assess: pass --message to assess message quality".to_string(),
+            ));
+        }
+
+        info!("Assessing uncommitted changes");
+        let engine = AssessmentEngine::new(self.assessment_client()?, &criterion_ids)
+            .with_redaction(self.llm_config.redaction.clone())
+            .with_verdict_bands(verdict_bands(&self.repo_config()?)?);
+        let message = opts.message.as_deref().unwrap_or("Uncommitted changes");
+        let commit = engine.assess_diff(message, &diff)?;
+        let head = self.git.get_head()?;
+        let result = engine.summarize(&head, WORKING_TREE_LABEL, vec![commit]);
+
+        let output = assessment::report::format_assessment(
+            &result,
+            convert_format(opts.format),
+            opts.full,
+            self.color,
+        );
+        println!("{}", output);
+
+        if let Some(save_path) = opts.save {
+            let path = assessment::save_assessment(&result, save_path.as_deref())
+                .map_err(|e| AppError::User(format!("Failed to save assessment: {}", e)))?;
+            info!("Assessment saved to: {}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Assessments saved by an earlier run, for `assess --resume`.
+    fn load_assessment_checkpoint(
+        &self,
+        path: &Path,
+        criterion_ids: &[CriterionId],
+    ) -> Result<Vec<assessment::CommitAssessment>, AppError> {
+        let checkpoint = AssessmentCheckpoint::load(path)
+            .map_err(|e| AppError::User(format!("Failed to load assessment checkpoint: {}", e)))?
+            .ok_or_else(|| {
+                AppError::User(
+                    "No assessment checkpoint to resume. Run 'git reabsorb assess' without --resume."
+                        .to_string(),
+                )
+            })?;
+        if checkpoint.criteria != criterion_ids {
+            let names: Vec<String> = checkpoint.criteria.iter().map(|c| c.to_string()).collect();
+            return Err(AppError::User(format!(
+                "The checkpoint was assessed against different criteria; resume with --criteria {}",
+                names.join(",")
+            )));
+        }
+        Ok(checkpoint.commit_assessments)
+    }
+
+    /// Assess `range` as the "before" side of `assess --compare-range`.
+    fn assess_baseline(
+        &self,
+        engine: &AssessmentEngine,
+        range: &CommitRange,
+    ) -> Result<assessment::RangeAssessment, AppError> {
+        let range = CommitRange::resolve(Some(range), None, &self.git)?;
+        let count = self.ensure_range_not_empty(&range.base, range.head())?;
+        warn!(
+            "Also assessing {}..{} ({} commits) to compare against; this is a second full assessment",
+            self.git.abbrev(&range.base),
+            self.git.abbrev(range.head()),
+            count
+        );
+        let commits = self.git.read_commits(&range.base, range.head())?;
+        Ok(engine.assess_range(&self.git, &range.base, range.head(), &commits)?)
+    }
+
+    /// Print what `assess` would send to the LLM: one call per commit, with
+    /// every criterion in the same prompt.
+    fn preview_assessment(
+        &self,
+        commits: &[SourceCommit],
+        criterion_ids: &[CriterionId],
+    ) -> Result<(), AppError> {
+        let names: Vec<String> = criterion_ids.iter().map(|c| c.to_string()).collect();
+        println!(
+            "Would assess {} commit(s) against {} criteria ({})",
+            commits.len(),
+            criterion_ids.len(),
+            names.join(", ")
+        );
+        println!(
+            "LLM calls: {} (one per commit, covering all criteria; failed replies are retried)",
+            commits.len()
+        );
+        for commit in commits {
+            let hunks = self.git.read_hunks(&commit.sha, 0)?;
+            let (added, removed) = hunks.iter().flat_map(|h| &h.lines).fold(
+                (0, 0),
+                |(added, removed), line| match line {
+                    DiffLine::Added(_) => (added + 1, removed),
+                    DiffLine::Removed(_) => (added, removed + 1),
+                    DiffLine::Context(_) => (added, removed),
+                },
+            );
+            println!(
+                "  {} {} ({} hunk(s), +{} -{})",
+                self.git.abbrev(&commit.sha),
+                commit.message.short,
+                hunks.len(),
+                added,
+                removed
+            );
+        }
+        Ok(())
+    }
+
+    /// Read an explicit set of commits as a pseudo-range, oldest first.
+    ///
+    /// The reported base is the oldest commit's parent (or the commit itself
+    /// for a root commit) and the head is the newest commit.
+    fn read_commit_set(
+        &self,
+        revs: &[String],
+    ) -> Result<(String, String, Vec<SourceCommit>), AppError> {
+        let mut commits = revs
+            .iter()
+            .map(|rev| self.git.read_commit(rev))
+            .collect::<Result<Vec<_>, _>>()?;
+        commits.sort_by_key(|c| c.commit_date);
+        commits.dedup_by(|a, b| a.sha == b.sha);
+
+        let (Some(oldest), Some(newest)) = (commits.first(), commits.last()) else {
+            return Err(AppError::User("No commits given to --commits".to_string()));
+        };
+        let base = self
+            .git
+            .resolve_ref(&format!("{}^", oldest.sha))
+            .unwrap_or_else(|_| oldest.sha.clone());
+        let head = newest.sha.clone();
+        info!("Assessing {} selected commits", commits.len());
+        Ok((base, head, commits))
+    }
+
     fn handle_compare(&self, opts: CompareArgs) -> Result<(), AppError> {
         let before = assessment::load_assessment(&opts.before)
             .map_err(|e| AppError::User(format!("Failed to load 'before' assessment: {}", e)))?;
@@ -566,6 +1837,19 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
             assessment::report::format_comparison(&comparison, convert_format(opts.format));
         println!("{}", output);
 
+        if opts.fail_on_regression {
+            let regressed: Vec<String> = comparison
+                .regressed()
+                .map(|c| c.criterion_id.to_string())
+                .collect();
+            if !regressed.is_empty() {
+                return Err(AppError::User(format!(
+                    "Regressed criteria: {}",
+                    regressed.join(", ")
+                )));
+            }
+        }
+
         Ok(())
     }
 
@@ -601,8 +1885,10 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         info!("Found {} commits to analyze", commits.len());
 
         // Create assessment engine
-        let client = self.llm_config.create_client();
-        let engine = AssessmentEngine::new(client.clone(), &criteria);
+        check_redaction_tools(&self.llm_config)?;
+        let client = audited(self.llm_config.create_client(), self.audit.as_ref());
+        let engine = AssessmentEngine::new(client.clone(), &criteria)
+            .with_redaction(self.llm_config.redaction.clone());
 
         // Assess commits
         let assessment_result =
@@ -618,7 +1904,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
         {
             info!(
                 "  {} {}: {:.1}%",
-                short_sha(&commit.sha),
+                self.git.abbrev(&commit.sha),
                 commit.message.short,
                 ca.overall_score * 100.0
             );
@@ -630,14 +1916,14 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
                 .map(|hunks| {
                     hunks
                         .iter()
-                        .map(|h| h.to_patch())
+                        .map(|h| self.llm_config.redaction.hunk_patch(h))
                         .collect::<Vec<_>>()
                         .join("\n")
                 })
                 .unwrap_or_default();
 
             // Build prompt and get improved message
-            let prompt = build_reword_prompt(commit, ca, &diff_content);
+            let prompt = build_reword_prompt(commit, Some(ca), &diff_content);
 
             match client.complete(&prompt) {
                 Ok(response) => {
@@ -653,7 +1939,11 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
                     }
                 }
                 Err(e) => {
-                    warn!("Failed to reword commit {}: {}", short_sha(&commit.sha), e);
+                    warn!(
+                        "Failed to reword commit {}: {}",
+                        self.git.abbrev(&commit.sha),
+                        e
+                    );
                 }
             }
         }
@@ -666,7 +1956,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
 
         info!("\n=== Proposed Rewrites ===\n");
         for (sha, old_short, new_short, new_long) in &rewrites {
-            info!("Commit {}", short_sha(sha));
+            info!("Commit {}", self.git.abbrev(sha));
             info!("  Before: {}", old_short);
             info!("  After:  {}", new_short);
             if !new_long.is_empty() {
@@ -686,7 +1976,7 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
                 "Note: Rewriting commits requires an interactive rebase.\n\
                    You can manually apply these changes with:\n\
                    git rebase -i {}",
-                short_sha(&range.base)
+                self.git.abbrev(&range.base)
             );
         } else {
             // For now, just show the instructions - actual git rebase is complex
@@ -694,12 +1984,129 @@ impl<G: GitOps, E: Editor, P: PlanStore> App<G, E, P> {
                 "To apply these changes, run:\n\
                    git rebase -i {}\n\n\
                    Then change 'pick' to 'reword' for each commit you want to update.",
-                short_sha(&range.base)
+                self.git.abbrev(&range.base)
             );
         }
 
         Ok(())
     }
+
+    fn warn_pushed(&self, pushed: &[String]) {
+        warn!(
+            "{} commit(s) in range are already on a remote-tracking branch; rewriting them will diverge from what others have pulled:",
+            pushed.len()
+        );
+        for sha in pushed {
+            warn!("  {}", short_sha(sha));
+        }
+
+        // Newest first, so the first pushed commit covers the rest
+        let upstream = self.git.upstream_of("HEAD").ok().flatten();
+        if let (Some(upstream), Some(newest)) = (upstream, pushed.first()) {
+            if self.git.is_ancestor(newest, &upstream).unwrap_or(false) {
+                warn!(
+                    "They are on {}, this branch's upstream; publishing the result will need a force push.",
+                    upstream
+                );
+            }
+        }
+    }
+}
+
+/// Whether every change in `diff` removes a hint line for `marker`.
+fn only_removes_hints(diff: &str, marker: Option<&str>) -> bool {
+    let Some(marker) = marker else {
+        return false;
+    };
+    let Ok(patch) = crate::patch::parse(diff, &[], 0) else {
+        return false;
+    };
+    patch.file_changes.iter().all(|fc| !fc.is_hunkless())
+        && patch
+            .hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .all(|line| match line {
+                DiffLine::Removed(text) => hint_in_line(text, marker).is_some(),
+                DiffLine::Added(_) => false,
+                DiffLine::Context(_) => true,
+            })
+}
+
+/// Print the models the configured provider offers, one per line.
+pub fn list_models(llm_config: &LlmConfig) -> Result<(), AppError> {
+    match llm_config.list_models() {
+        Ok(Some(models)) => {
+            for model in models {
+                println!("{}", model);
+            }
+            Ok(())
+        }
+        Ok(None) => {
+            println!(
+                "The {} CLI can't list its models; --llm-model is passed to it unchecked.",
+                llm_config.provider
+            );
+            Ok(())
+        }
+        Err(err) => Err(AppError::User(err.to_string())),
+    }
+}
+
+/// The default verdict bands, moved or extended by `[verdict]` in
+/// .reabsorb.toml.
+fn verdict_bands(repo_config: &RepoConfig) -> Result<VerdictBands, AppError> {
+    VerdictBands::default()
+        .with_thresholds(&repo_config.verdict)
+        .map_err(|e| AppError::User(e.to_string()))
+}
+
+/// Create a backup ref with `create`, named after `template` with
+/// `{timestamp}` filled in. A taken name gets a `-2`, `-3`... suffix, so an
+/// earlier backup is never moved. Returns the name used.
+fn create_backup(
+    template: &str,
+    create: impl Fn(&str) -> Result<(), GitError>,
+) -> Result<String, AppError> {
+    let stamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let base = template.replace("{timestamp}", &stamp);
+    for suffix in 1..=MAX_BACKUP_SUFFIX {
+        let name = if suffix == 1 {
+            base.clone()
+        } else {
+            format!("{}-{}", base, suffix)
+        };
+        match create(&name) {
+            Ok(()) => return Ok(name),
+            Err(GitError::RefExists(_)) => continue,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Err(AppError::User(format!(
+        "Could not create a backup: every name from '{}' to '{}-{}' is taken",
+        base, base, MAX_BACKUP_SUFFIX
+    )))
+}
+
+/// Redaction only changes what prompts contain, so an LLM allowed to read
+/// files could still open the redacted ones.
+fn check_redaction_tools(llm_config: &LlmConfig) -> Result<(), AppError> {
+    if !llm_config.redaction.is_active() {
+        return Ok(());
+    }
+    for (feature, name) in [
+        (Feature::FileBasedLlmIo, "file-based-llm-io"),
+        (Feature::LlmRepoRead, "llm-repo-read"),
+    ] {
+        if feature.is_enabled() {
+            return Err(AppError::User(format!(
+                "--llm-redact can't hide files from an LLM that may read them; \
+                 disable the {} feature",
+                name
+            )));
+        }
+    }
+    Ok(())
 }
 
 fn print_planned_commits(commits: &[PlannedCommit], offset: usize) {
@@ -714,6 +2121,64 @@ fn print_planned_commits(commits: &[PlannedCommit], offset: usize) {
     }
 }
 
+/// Print old vs new messages for plans that keep the original commit boundaries.
+fn print_reworded_commits(commits: &[PlannedCommit], source_commits: &[SourceCommit]) {
+    info!("Planned {} commits:", commits.len());
+    let width = commits
+        .iter()
+        .filter_map(|c| source_commits.get(c.id.0))
+        .map(|s| s.message.short.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (i, commit) in commits.iter().enumerate() {
+        let old = source_commits
+            .get(commit.id.0)
+            .map(|s| s.message.short.as_str())
+            .unwrap_or("");
+        info!(
+            "  {}. {:<width$}  ->  {}",
+            i + 1,
+            old,
+            commit.description.short,
+            width = width
+        );
+    }
+}
+
+/// Print each planned commit with its files and the reason for the grouping.
+///
+/// Falls back to the first line of the message body when the strategy gave
+/// no rationale.
+fn print_explained_commits(commits: &[PlannedCommit], hunks: &[Hunk]) {
+    info!("Planned {} commits:", commits.len());
+    for (i, commit) in commits.iter().enumerate() {
+        info!("  {}. \"{}\"", i + 1, commit.description.short);
+
+        let why = commit.rationale.as_deref().or_else(|| {
+            commit
+                .description
+                .long
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty() && *line != commit.description.short)
+        });
+        if let Some(why) = why {
+            info!("     Why: {}", why);
+        }
+
+        let files: std::collections::BTreeSet<_> = commit
+            .changes
+            .iter()
+            .filter_map(|change| change.resolve(hunks))
+            .map(|h| h.file_path.display().to_string())
+            .collect();
+        info!(
+            "     Files: {}",
+            files.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+}
+
 fn convert_format(format: OutputFormat) -> assessment::report::OutputFormat {
     match format {
         OutputFormat::Pretty => assessment::report::OutputFormat::Pretty,
@@ -722,3 +2187,443 @@ fn convert_format(format: OutputFormat) -> assessment::report::OutputFormat {
         OutputFormat::Compact => assessment::report::OutputFormat::Compact,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use clap::ValueEnum;
+
+    use super::*;
+    use crate::editor::SystemEditor;
+    use crate::git::DEFAULT_BACKUP_NAME;
+    use crate::models::{CommitDescription, FileChange, PlannedCommitId};
+    use crate::test_utils::{make_hunk_in_file, make_source_commit, MemoryPlanStore, MockGit};
+
+    /// Returns the same edited text every time.
+    struct ScriptedEditor(&'static str);
+
+    impl Editor for ScriptedEditor {
+        fn edit(&self, _initial: &str, _comment_help: &str) -> Result<String, EditorError> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    fn app_with_plan(git: MockGit) -> App<MockGit, SystemEditor, MemoryPlanStore> {
+        let hunks = vec![make_hunk_in_file(0, "a.rs"), make_hunk_in_file(1, "b.rs")];
+        app_with_hunks(git, SystemEditor::new(), hunks)
+    }
+
+    /// An app whose saved plan has one commit per hunk, named after its file.
+    fn app_with_hunks<E: Editor>(
+        git: MockGit,
+        editor: E,
+        hunks: Vec<Hunk>,
+    ) -> App<MockGit, E, MemoryPlanStore> {
+        let planned: Vec<PlannedCommit> = hunks
+            .iter()
+            .map(|h| {
+                PlannedCommit::from_hunk_ids(
+                    PlannedCommitId(h.id.0),
+                    CommitDescription::short_only(format!("Change {}", h.file_path.display())),
+                    vec![h.id],
+                )
+            })
+            .collect();
+        let plan = SavedPlan::new(
+            Strategy::Preserve,
+            "base".into(),
+            "head".into(),
+            &planned,
+            &hunks,
+            &HashMap::new(),
+            &[],
+        );
+        App::new(
+            git,
+            editor,
+            MemoryPlanStore::with_plan(plan),
+            StrategyFactory::new(),
+            LlmConfig::new(),
+            "main".to_string(),
+        )
+    }
+
+    fn repo() -> MockGit {
+        MockGit::new()
+            .with_commit(make_source_commit("base", "Initial"), vec![])
+            .with_commit(make_source_commit("head", "Work"), vec![])
+    }
+
+    fn apply_args() -> ApplyArgs {
+        ApplyArgs {
+            resume: false,
+            force: false,
+            from: None,
+            execution: ExecutionArgs {
+                no_editor: true,
+                ..ExecutionArgs::default()
+            },
+        }
+    }
+
+    #[test]
+    fn empty_range_is_a_user_error() {
+        let app = app_with_plan(repo());
+
+        assert_eq!(app.ensure_range_not_empty("base", "head").unwrap(), 1);
+        for (base, head) in [("head", "head"), ("head", "base")] {
+            let err = app.ensure_range_not_empty(base, head).unwrap_err();
+            assert!(matches!(err, AppError::User(msg) if msg.contains("Check the base")));
+        }
+    }
+
+    fn plan_subjects<E: Editor>(app: &App<MockGit, E, MemoryPlanStore>) -> Vec<String> {
+        let plan = app.plan_store.plan().unwrap();
+        plan.commits
+            .iter()
+            .map(|c| c.description.short.clone())
+            .collect()
+    }
+
+    #[test]
+    fn reorder_saves_the_edited_order() {
+        let hunks = vec![
+            make_hunk_in_file(0, "a.rs"),
+            make_hunk_in_file(1, "b.rs"),
+            make_hunk_in_file(2, "c.rs"),
+        ];
+        let mut app = app_with_hunks(repo(), ScriptedEditor("pick 3\npick 2\nfixup 1"), hunks);
+
+        app.handle_reorder().unwrap();
+
+        assert_eq!(plan_subjects(&app), vec!["Change c.rs", "Change b.rs"]);
+        let plan = app.plan_store.plan().unwrap();
+        assert_eq!(plan.commits[1].changes.len(), 2);
+    }
+
+    #[test]
+    fn reorder_keeps_binary_files_with_their_commit() {
+        let hunks = vec![make_hunk_in_file(0, "a.rs"), make_hunk_in_file(1, "b.rs")];
+        let mut app = app_with_hunks(
+            repo(),
+            ScriptedEditor(
+                "pick 2
+pick 1",
+            ),
+            hunks,
+        );
+        let mut plan = app.plan_store.plan().unwrap();
+        let mut logo = FileChange::with_path("logo.png".into());
+        logo.is_binary = true;
+        logo.commit_index = Some(1);
+        plan.file_changes = vec![logo];
+        app.plan_store.save(&plan).unwrap();
+
+        app.handle_reorder().unwrap();
+
+        let plan = app.plan_store.plan().unwrap();
+        assert_eq!(plan.commits[0].description.short, "Change b.rs");
+        assert_eq!(plan.file_changes[0].commit_index, Some(0));
+    }
+
+    #[test]
+    fn reorder_refuses_to_modify_a_file_before_creating_it() {
+        let mut create = make_hunk_in_file(0, "new.rs");
+        create.old_start = 0;
+        create.old_count = 0;
+        let hunks = vec![create, make_hunk_in_file(1, "new.rs")];
+        let mut app = app_with_hunks(repo(), ScriptedEditor("pick 2\npick 1"), hunks);
+
+        let err = app.handle_reorder().unwrap_err();
+
+        assert!(
+            matches!(err, AppError::User(msg) if msg.contains("before the commit that creates"))
+        );
+        assert_eq!(plan_subjects(&app), vec!["Change new.rs", "Change new.rs"]);
+    }
+
+    #[test]
+    fn apply_refuses_pushed_commits() {
+        let mut app = app_with_plan(repo().with_pushed(&["head"]).with_upstream("HEAD", "main"));
+
+        let err = app.handle_apply(apply_args()).unwrap_err();
+
+        assert!(matches!(err, AppError::User(msg) if msg.contains("--allow-pushed")));
+        assert_eq!(app.git.head(), "head");
+        let calls = app.git.calls();
+        assert!(!calls.iter().any(|c| c == "reset_to"));
+        // The warning checks whether the pushed commits are on the upstream
+        assert!(calls.iter().any(|c| c == "is_ancestor"));
+    }
+
+    #[test]
+    fn apply_conflict_leaves_a_resumable_plan() {
+        let mut app = app_with_plan(repo());
+        app.git.fail_call(
+            "apply_hunks_to_index",
+            2,
+            GitError::ApplyFailed {
+                file: "b.rs".to_string(),
+                detail: "patch does not apply".to_string(),
+            },
+        );
+
+        let err = app.handle_apply(apply_args()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::Execution(ExecutionError::Git(GitError::ApplyFailed { .. }))
+        ));
+        assert_eq!(app.plan_store.plan().unwrap().next_commit_index, 1);
+        assert_eq!(
+            app.git
+                .get_pre_reabsorb_head(&app.pre_reabsorb_ref)
+                .unwrap(),
+            "head"
+        );
+        let created = app.git.read_commits("base", "HEAD").unwrap();
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].message.short, "Change a.rs");
+    }
+
+    #[test]
+    fn no_editor_lint_failure_stops_before_moving_head() {
+        let long_name = format!("{}.rs", "x".repeat(80));
+        let hunks = vec![
+            make_hunk_in_file(0, "a.rs"),
+            make_hunk_in_file(1, &long_name),
+        ];
+        let mut app = app_with_hunks(repo(), SystemEditor::new(), hunks);
+
+        let err = app.handle_apply(apply_args()).unwrap_err();
+
+        assert!(matches!(
+            err,
+            AppError::Execution(ExecutionError::Lint { index: 2, .. })
+        ));
+        assert_eq!(app.git.head(), "head");
+        let calls = app.git.calls();
+        assert!(!calls.iter().any(|c| c == "reset_to" || c == "commit"));
+        assert!(!app.git.has_pre_reabsorb_head(&app.pre_reabsorb_ref));
+    }
+
+    #[test]
+    fn apply_refuses_to_run_during_a_merge() {
+        let mut app = app_with_plan(repo().with_operation_in_progress());
+
+        let err = app.handle_apply(apply_args()).unwrap_err();
+
+        assert!(matches!(err, AppError::User(msg) if msg.contains("merge/rebase")));
+        assert_eq!(app.git.calls(), vec!["operation_in_progress"]);
+        assert!(app.plan_store.plan().is_some());
+    }
+
+    #[test]
+    fn backups_point_at_the_original_head_and_skip_taken_names() {
+        let mut app = app_with_plan(repo().with_ref("refs/heads/before", "base"));
+        let mut args = apply_args();
+        args.execution.backup_branch = Some("before".to_string());
+        args.execution.backup_tag = Some(DEFAULT_BACKUP_NAME.to_string());
+
+        app.handle_apply(args).unwrap();
+
+        assert_eq!(app.git.resolve_ref("refs/heads/before").unwrap(), "base");
+        assert_eq!(app.git.resolve_ref("refs/heads/before-2").unwrap(), "head");
+        let calls = app.git.calls();
+        assert_eq!(calls.iter().filter(|c| *c == "create_tag").count(), 1);
+        assert!(
+            calls.iter().position(|c| c == "create_tag")
+                < calls.iter().position(|c| c == "reset_to")
+        );
+    }
+
+    #[test]
+    fn keep_plan_archives_the_applied_plan() {
+        let mut app = app_with_plan(repo());
+        let mut args = apply_args();
+        args.execution.keep_plan = true;
+
+        app.handle_apply(args).unwrap();
+
+        assert!(app.plan_store.plan().is_none());
+        let archived = app.plan_store.archived();
+        assert_eq!(archived.len(), 1);
+        assert!(archived[0].is_complete());
+        assert!(archived[0].commits.iter().all(|c| c.created_sha.is_some()));
+    }
+
+    #[test]
+    fn hierarchical_strategy_refuses_to_hide_redacted_paths() {
+        let redaction = crate::llm::Redaction::new(&["*.env".to_string()]).unwrap();
+        let factory = |hide_paths: bool| {
+            StrategyFactory::new().with_llm_config(
+                LlmConfig::new().with_redaction(redaction.clone().with_hide_paths(hide_paths)),
+            )
+        };
+
+        assert!(factory(false)
+            .check_redaction(Strategy::Hierarchical)
+            .is_ok());
+        assert!(factory(true).check_redaction(Strategy::Llm).is_ok());
+        assert!(factory(true).check_redaction(Strategy::Preserve).is_ok());
+        let err = factory(true)
+            .check_redaction(Strategy::Hierarchical)
+            .unwrap_err();
+        assert!(err.to_string().contains("--llm-redact-paths"));
+    }
+
+    #[test]
+    fn missing_llm_binary_only_blocks_llm_strategies() {
+        let factory = StrategyFactory::new()
+            .with_llm_config(LlmConfig::new().with_claude_bin("/nonexistent/claude"));
+
+        for strategy in [
+            Strategy::Llm,
+            Strategy::Hierarchical,
+            Strategy::Reword,
+            Strategy::CleanPreserve,
+        ] {
+            let err = factory.ensure_llm_available(strategy).unwrap_err();
+            assert!(matches!(err, AppError::User(msg) if msg.contains("needs an LLM")));
+        }
+        for strategy in [Strategy::Preserve, Strategy::ByFile, Strategy::Squash] {
+            assert!(factory.ensure_llm_available(strategy).is_ok());
+        }
+        let heuristic_only = factory.with_hierarchical_config(HierarchicalConfig {
+            use_llm: false,
+            ..HierarchicalConfig::default()
+        });
+        assert!(heuristic_only
+            .ensure_llm_available(Strategy::Hierarchical)
+            .is_ok());
+    }
+
+    #[test]
+    fn uses_llm_agrees_with_the_built_reorganizer() {
+        let factory = StrategyFactory::new();
+        let heuristic_only = StrategyFactory::new().with_hierarchical_config(HierarchicalConfig {
+            use_llm: false,
+            ..HierarchicalConfig::default()
+        });
+        for factory in [factory, heuristic_only] {
+            for &strategy in Strategy::value_variants() {
+                assert_eq!(
+                    factory.uses_llm(strategy),
+                    factory.create(strategy).requires_llm(),
+                    "{:?}",
+                    strategy
+                );
+            }
+        }
+    }
+
+    fn fold_args(range: &str) -> FoldArgs {
+        FoldArgs {
+            range: Some(range.parse().unwrap()),
+            base: None,
+            dry_run: false,
+            allow_pushed: false,
+        }
+    }
+
+    #[test]
+    fn fold_squashes_the_range_into_its_base() {
+        let git = repo()
+            .with_commit(make_source_commit("c1", "Fix"), vec![])
+            .with_commit(make_source_commit("c2", "Fix again"), vec![]);
+        let mut app = app_with_plan(git);
+
+        app.handle_fold(fold_args("head..c2")).unwrap();
+
+        let folded = app.git.read_commits("base", "HEAD").unwrap();
+        assert_eq!(folded.len(), 1);
+        assert_eq!(folded[0].message.short, "Work");
+        assert_eq!(folded[0].author_ident(), "Test User <test@example.com>");
+        assert_eq!(folded[0].author_date, 1_700_000_000);
+        assert_eq!(
+            app.git
+                .get_pre_reabsorb_head(&app.pre_reabsorb_ref)
+                .unwrap(),
+            "c2"
+        );
+    }
+
+    #[test]
+    fn fold_refuses_root_commits_and_pushed_history() {
+        let mut app = app_with_plan(repo());
+        let err = app.handle_fold(fold_args("base..head")).unwrap_err();
+        assert!(matches!(err, AppError::User(msg) if msg.contains("root commit")));
+
+        let git = repo()
+            .with_commit(make_source_commit("c1", "Fix"), vec![])
+            .with_pushed(&["head"]);
+        let mut app = app_with_plan(git);
+        let err = app.handle_fold(fold_args("head..c1")).unwrap_err();
+        assert!(matches!(err, AppError::User(msg) if msg.contains("--allow-pushed")));
+        assert_eq!(app.git.head(), "c1");
+
+        let git = repo()
+            .with_commit(make_source_commit("c1", "Fix"), vec![])
+            .with_operation_in_progress();
+        let mut app = app_with_plan(git);
+        let err = app.handle_fold(fold_args("head..c1")).unwrap_err();
+        assert!(matches!(err, AppError::User(msg) if msg.contains("merge/rebase")));
+        assert_eq!(app.git.head(), "c1");
+    }
+
+    /// An app whose pre-reabsorb history holds `head`, with HEAD moved back
+    /// to `base`.
+    fn app_with_history(git: MockGit) -> App<MockGit, SystemEditor, MemoryPlanStore> {
+        let app = app_with_plan(git);
+        app.git
+            .record_pre_reabsorb_history(&app.history_ref, MAX_PRE_REABSORB_HISTORY)
+            .unwrap();
+        app.git.update_head("base").unwrap();
+        app
+    }
+
+    #[test]
+    fn reflog_reset_records_the_head_it_leaves() {
+        let mut app = app_with_history(repo());
+
+        app.handle_reflog(ReflogArgs { reset: Some(0) }).unwrap();
+
+        assert_eq!(app.git.head(), "head");
+        let history = app.git.list_pre_reabsorb_history(&app.history_ref).unwrap();
+        let shas: Vec<_> = history.iter().map(|e| e.sha.as_str()).collect();
+        assert_eq!(shas, vec!["base", "head"]);
+    }
+
+    #[test]
+    fn reflog_reset_refuses_to_discard_uncommitted_work() {
+        let mut app = app_with_history(repo().with_working_tree_diff("+edit\n"));
+        let err = app
+            .handle_reflog(ReflogArgs { reset: Some(0) })
+            .unwrap_err();
+        assert!(matches!(err, AppError::User(msg) if msg.contains("uncommitted")));
+        assert_eq!(app.git.head(), "base");
+
+        let mut app = app_with_history(repo().with_operation_in_progress());
+        let err = app
+            .handle_reflog(ReflogArgs { reset: Some(0) })
+            .unwrap_err();
+        assert!(matches!(err, AppError::User(msg) if msg.contains("merge/rebase")));
+        assert_eq!(app.git.head(), "base");
+    }
+
+    #[test]
+    fn fold_needs_an_explicit_range() {
+        let git = repo().with_commit(make_source_commit("c1", "Fix"), vec![]);
+        let mut app = app_with_plan(git);
+        let opts = FoldArgs {
+            range: None,
+            ..fold_args("head..c1")
+        };
+
+        let err = app.handle_fold(opts).unwrap_err();
+
+        assert!(matches!(err, AppError::User(msg) if msg.contains("needs a range")));
+        assert_eq!(app.git.head(), "c1");
+    }
+}
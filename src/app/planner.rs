@@ -1,12 +1,17 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use log::{debug, warn};
+use log::{debug, info, warn};
 
-use crate::git::{GitError, GitOps};
-use crate::models::{FileChange, Hunk, PlannedCommit, SourceCommit, Strategy};
+use crate::git::{FileStatus, GitError, GitOps};
+use crate::models::{
+    CommitDescription, DiffLine, FileChange, Hunk, HunkId, PlannedChange, PlannedCommit,
+    PlannedCommitId, SourceCommit, Strategy,
+};
 use crate::patch::{parse, ParseError, Patch};
-use crate::reorganize::ReorganizeError;
-use crate::validation::validate_plan;
+use crate::reorganize::{
+    fold_commits, strip_hints, Disposition, PreserveOriginal, ReorganizeError, Reorganizer,
+};
+use crate::validation::{fix_creation_order, validate_plan};
 
 use super::StrategyFactory;
 
@@ -15,6 +20,10 @@ pub struct Planner<'a, G: GitOps> {
     git: &'a G,
     strategies: StrategyFactory,
     max_fix_attempts: usize,
+    author_filter: Option<String>,
+    ignore_whitespace: bool,
+    strip_hints: Option<String>,
+    fold_whitespace_commits: bool,
 }
 
 impl<'a, G: GitOps> Planner<'a, G> {
@@ -23,6 +32,10 @@ impl<'a, G: GitOps> Planner<'a, G> {
             git,
             strategies,
             max_fix_attempts: 3,
+            author_filter: None,
+            ignore_whitespace: false,
+            strip_hints: None,
+            fold_whitespace_commits: false,
         }
     }
 
@@ -31,6 +44,35 @@ impl<'a, G: GitOps> Planner<'a, G> {
         self
     }
 
+    /// Only reorganize commits whose author matches `pattern`; changes from
+    /// other commits keep their original commit boundaries.
+    pub fn with_author_filter(mut self, pattern: Option<String>) -> Self {
+        self.author_filter = pattern;
+        self
+    }
+
+    /// Keep whitespace-only hunks away from the strategy and put them in a
+    /// single trailing commit, so they are still applied but never planned.
+    pub fn with_ignore_whitespace(mut self, ignore_whitespace: bool) -> Self {
+        self.ignore_whitespace = ignore_whitespace;
+        self
+    }
+
+    /// Leave lines holding a hint comment for `marker` out of the planned
+    /// hunks, once the strategy has seen them.
+    pub fn with_strip_hints(mut self, marker: Option<String>) -> Self {
+        self.strip_hints = marker;
+        self
+    }
+
+    /// Merge commits whose changes are all whitespace into a neighbour when
+    /// pruning the drafted plan. Has no effect with `with_ignore_whitespace`,
+    /// whose trailing commit is whitespace-only on purpose.
+    pub fn with_fold_whitespace_commits(mut self, fold: bool) -> Self {
+        self.fold_whitespace_commits = fold;
+        self
+    }
+
     pub fn read_source_commits(
         &self,
         base: &str,
@@ -45,8 +87,21 @@ impl<'a, G: GitOps> Planner<'a, G> {
     ) -> Result<HashMap<String, Vec<String>>, GitError> {
         let mut file_to_commits: HashMap<String, Vec<String>> = HashMap::new();
 
+        // One git process for the whole range rather than one per commit
+        let shas: Vec<&str> = source_commits.iter().map(|c| c.sha.as_str()).collect();
+        let mut statuses = self.git.get_file_statuses_for_commits(&shas)?;
+
         for commit in source_commits {
-            for file in self.git.get_files_changed_in_commit(&commit.sha)? {
+            let files = statuses.remove(&commit.sha).unwrap_or_default();
+            for (file, status) in files {
+                // The range diff has no rename detection, so a rename also
+                // touches its old path
+                if let FileStatus::Renamed { from } = status {
+                    file_to_commits
+                        .entry(from)
+                        .or_default()
+                        .push(commit.sha.clone());
+                }
                 file_to_commits
                     .entry(file)
                     .or_default()
@@ -86,6 +141,42 @@ impl<'a, G: GitOps> Planner<'a, G> {
         Ok((hunks, file_changes))
     }
 
+    /// Warn about source commits that touched files but have no hunk or file
+    /// change attributed to them: nothing of theirs is left in the range diff
+    /// (a pure rename that was undone, a change reverted later), so they
+    /// vanish from the plan. Returns their SHAs.
+    pub fn warn_unrepresented_commits(
+        &self,
+        source_commits: &[SourceCommit],
+        hunks: &[Hunk],
+        file_changes: &[FileChange],
+    ) -> Result<Vec<String>, GitError> {
+        let mut warned = Vec::new();
+        for commit in unrepresented_commits(source_commits, hunks, file_changes) {
+            let statuses = self.git.get_file_statuses_in_commit(&commit.sha)?;
+            if statuses.is_empty() {
+                // Genuinely empty commits have nothing to lose
+                continue;
+            }
+            let what = if statuses
+                .iter()
+                .all(|(_, status)| matches!(status, FileStatus::Renamed { .. }))
+            {
+                "was a pure rename".to_string()
+            } else {
+                format!("touched {} file(s)", statuses.len())
+            };
+            warn!(
+                "Commit {} (\"{}\") {} but none of its changes are left in the range diff; it will be dropped",
+                self.git.abbrev(&commit.sha),
+                commit.message.short,
+                what
+            );
+            warned.push(commit.sha.clone());
+        }
+        Ok(warned)
+    }
+
     pub fn draft_plan(
         &self,
         strategy: Strategy,
@@ -95,7 +186,39 @@ impl<'a, G: GitOps> Planner<'a, G> {
         file_changes: &[FileChange],
     ) -> Result<PlanDraft, ReorganizeError> {
         let reorganizer = self.strategies.create(strategy);
-        let mut planned_commits = reorganizer.plan(source_commits, hunks)?;
+        let (planned_hunks, whitespace_hunks): (Vec<Hunk>, Vec<Hunk>) = if self.ignore_whitespace {
+            hunks.iter().cloned().partition(|h| !h.is_whitespace_only())
+        } else {
+            (hunks.to_vec(), Vec::new())
+        };
+        let mut planned = if planned_hunks.is_empty() {
+            Vec::new()
+        } else {
+            self.plan_filtered(reorganizer.as_ref(), source_commits, &planned_hunks)?
+        };
+        if !whitespace_hunks.is_empty() {
+            debug!(
+                "Set aside {} whitespace-only hunks for a trailing commit",
+                whitespace_hunks.len()
+            );
+            planned.push(whitespace_commit(&planned, &whitespace_hunks));
+        }
+        let stripped;
+        let hunks = match &self.strip_hints {
+            Some(marker) => {
+                stripped = strip_hints(hunks, marker);
+                debug!(
+                    "Stripped '{}' hints; {} of {} hunks had nothing else",
+                    marker,
+                    hunks.len() - stripped.len(),
+                    hunks.len()
+                );
+                drop_missing_hunks(&mut planned, &stripped);
+                stripped.as_slice()
+            }
+            None => hunks,
+        };
+        let mut planned_commits = fix_creation_order(planned, hunks);
         let removed_empty = retain_non_empty(&mut planned_commits);
         if removed_empty > 0 {
             debug!("Dropped {} empty commits from plan", removed_empty);
@@ -122,22 +245,206 @@ impl<'a, G: GitOps> Planner<'a, G> {
                 validation.issues
             );
 
-            planned_commits =
-                reorganizer.fix_plan(planned_commits, &validation, source_commits, hunks)?;
+            planned_commits = fix_creation_order(
+                reorganizer.fix_plan(planned_commits, &validation, source_commits, hunks)?,
+                hunks,
+            );
             let removed = retain_non_empty(&mut planned_commits);
             if removed > 0 {
                 debug!("Dropped {} empty commits after fix", removed);
             }
         }
 
+        // Applying a modification to a file that doesn't exist yet leaves the
+        // index in a broken intermediate state, so refuse such plans outright.
+        let creation_issues: Vec<String> = validate_plan(&planned_commits, hunks)
+            .creation_order_issues()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        if !creation_issues.is_empty() {
+            return Err(ReorganizeError::InvalidPlan(creation_issues.join("; ")));
+        }
+
+        // Whatever the strategy, never create a commit that changes nothing
+        let pruned = prune_empty_commits(
+            &mut planned_commits,
+            hunks,
+            self.fold_whitespace_commits && !self.ignore_whitespace,
+        );
+        if pruned > 0 {
+            info!("Pruned {} empty or whitespace-only commits", pruned);
+        }
+
+        let file_changes = route_hunkless_changes(&planned_commits, hunks, file_changes);
+
         Ok(PlanDraft {
             strategy,
             planned_commits,
             hunks: hunks.to_vec(),
             file_to_commits: file_to_commits.clone(),
-            file_changes: file_changes.to_vec(),
+            file_changes,
+            stripped_hint_marker: self.strip_hints.clone(),
+            pruned,
         })
     }
+
+    /// Run the reorganizer, restricted to the author filter if one is set.
+    ///
+    /// Passthrough commits (original boundaries, for other authors' changes)
+    /// come first, followed by the reorganized commits.
+    fn plan_filtered(
+        &self,
+        reorganizer: &dyn Reorganizer,
+        source_commits: &[SourceCommit],
+        hunks: &[Hunk],
+    ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
+        let Some(pattern) = &self.author_filter else {
+            return reorganizer.plan(source_commits, hunks);
+        };
+
+        let (matching, selected, passthrough) = split_by_author(source_commits, hunks, pattern);
+        if matching.is_empty() {
+            return Err(ReorganizeError::Failed(format!(
+                "No commits by an author matching '{}'",
+                pattern
+            )));
+        }
+        debug!(
+            "Author filter '{}': reorganizing {} of {} commits ({} hunks kept as-is)",
+            pattern,
+            matching.len(),
+            source_commits.len(),
+            passthrough.len()
+        );
+
+        let mut planned = if passthrough.is_empty() {
+            Vec::new()
+        } else {
+            PreserveOriginal.plan(source_commits, &passthrough)?
+        };
+
+        if !selected.is_empty() {
+            // Passthrough ids are source commit indices, so shift the
+            // reorganized ids past them to keep every id unique
+            let offset = source_commits.len();
+            planned.extend(reorganizer.plan(&matching, &selected)?.into_iter().map(
+                |mut commit| {
+                    commit.id.0 += offset;
+                    for dep in &mut commit.depends_on {
+                        dep.0 += offset;
+                    }
+                    commit
+                },
+            ));
+        }
+
+        Ok(planned)
+    }
+}
+
+/// Source commits that no hunk or file change is attributed to.
+fn unrepresented_commits<'c>(
+    source_commits: &'c [SourceCommit],
+    hunks: &[Hunk],
+    file_changes: &[FileChange],
+) -> Vec<&'c SourceCommit> {
+    let attributed: HashSet<&str> = hunks
+        .iter()
+        .flat_map(|h| &h.likely_source_commits)
+        .chain(file_changes.iter().flat_map(|fc| &fc.likely_source_commits))
+        .map(String::as_str)
+        .collect();
+    source_commits
+        .iter()
+        .filter(|c| !attributed.contains(c.sha.as_str()))
+        .collect()
+}
+
+/// Split commits and hunks by an author pattern.
+///
+/// Returns the matching commits, the hunks attributed only to those commits,
+/// and the remaining (passthrough) hunks. A hunk also touched by another
+/// author's commit, or with no known source, is passed through untouched.
+fn split_by_author(
+    source_commits: &[SourceCommit],
+    hunks: &[Hunk],
+    pattern: &str,
+) -> (Vec<SourceCommit>, Vec<Hunk>, Vec<Hunk>) {
+    let matching: Vec<SourceCommit> = source_commits
+        .iter()
+        .filter(|c| c.matches_author(pattern))
+        .cloned()
+        .collect();
+    let matching_shas: HashSet<&str> = matching.iter().map(|c| c.sha.as_str()).collect();
+
+    let (selected, passthrough) = hunks.iter().cloned().partition(|hunk| {
+        !hunk.likely_source_commits.is_empty()
+            && hunk
+                .likely_source_commits
+                .iter()
+                .all(|sha| matching_shas.contains(sha.as_str()))
+    });
+
+    (matching, selected, passthrough)
+}
+
+/// The trailing commit holding hunks set aside by `--ignore-whitespace`.
+fn whitespace_commit(planned: &[PlannedCommit], hunks: &[Hunk]) -> PlannedCommit {
+    let id = planned.iter().map(|c| c.id.0 + 1).max().unwrap_or(0);
+    PlannedCommit::from_hunk_ids(
+        PlannedCommitId(id),
+        CommitDescription::new(
+            "Whitespace changes",
+            "Whitespace changes\n\nWhitespace-only hunks set aside by --ignore-whitespace.",
+        ),
+        hunks.iter().map(|h| h.id).collect(),
+    )
+}
+
+/// Assign each binary or mode-only file change to the first planned commit
+/// holding a hunk from one of the same source commits, falling back to the
+/// first commit.
+fn route_hunkless_changes(
+    planned: &[PlannedCommit],
+    hunks: &[Hunk],
+    file_changes: &[FileChange],
+) -> Vec<FileChange> {
+    let commit_sources: Vec<HashSet<&str>> = planned
+        .iter()
+        .map(|commit| {
+            commit
+                .changes
+                .iter()
+                .filter_map(|change| change.resolve(hunks))
+                .flat_map(|hunk| hunk.likely_source_commits.iter().map(String::as_str))
+                .collect()
+        })
+        .collect();
+
+    file_changes
+        .iter()
+        .cloned()
+        .map(|mut fc| {
+            if fc.is_hunkless() && !planned.is_empty() {
+                let index = commit_sources
+                    .iter()
+                    .position(|sources| {
+                        fc.likely_source_commits
+                            .iter()
+                            .any(|sha| sources.contains(sha.as_str()))
+                    })
+                    .unwrap_or(0);
+                debug!(
+                    "Staging {} with planned commit {}",
+                    fc.file_path.display(),
+                    index + 1
+                );
+                fc.commit_index = Some(index);
+            }
+            fc
+        })
+        .collect()
 }
 
 pub struct PlanDraft {
@@ -146,18 +453,85 @@ pub struct PlanDraft {
     pub hunks: Vec<Hunk>,
     pub file_to_commits: HashMap<String, Vec<String>>,
     pub file_changes: Vec<FileChange>,
+    /// Marker whose hint lines were left out of `hunks`
+    pub stripped_hint_marker: Option<String>,
+    /// Commits removed by the final prune, which renumbers the rest
+    pub pruned: usize,
+}
+
+/// Remove references to hunks that are no longer in `hunks`.
+fn drop_missing_hunks(planned_commits: &mut [PlannedCommit], hunks: &[Hunk]) {
+    let ids: HashSet<HunkId> = hunks.iter().map(|h| h.id).collect();
+    for commit in planned_commits {
+        commit.changes.retain(|change| match change {
+            PlannedChange::ExistingHunk(id) => ids.contains(id),
+            PlannedChange::NewHunk(_) => true,
+        });
+    }
 }
 
 fn retain_non_empty(planned_commits: &mut Vec<PlannedCommit>) -> usize {
     let before = planned_commits.len();
-    planned_commits.retain(|c| !c.changes.is_empty());
+    *planned_commits = fold_commits(std::mem::take(planned_commits), |commit| {
+        if commit.changes.is_empty() {
+            Disposition::Drop
+        } else {
+            Disposition::Keep
+        }
+    });
+    before - planned_commits.len()
+}
+
+/// Drop commits whose changes add or remove nothing, and with
+/// `fold_whitespace` merge whitespace-only commits into the commit before
+/// them (the one after, for the first). The rest are renumbered in order.
+/// Returns how many commits went.
+fn prune_empty_commits(
+    planned_commits: &mut Vec<PlannedCommit>,
+    hunks: &[Hunk],
+    fold_whitespace: bool,
+) -> usize {
+    let before = planned_commits.len();
+    let mut kept = fold_commits(std::mem::take(planned_commits), |commit| {
+        let resolved: Vec<&Hunk> = commit
+            .changes
+            .iter()
+            .filter_map(|change| change.resolve(hunks))
+            .collect();
+        let changes = resolved
+            .iter()
+            .any(|h| h.lines.iter().any(|l| !matches!(l, DiffLine::Context(_))));
+        if !changes {
+            Disposition::Drop
+        } else if fold_whitespace && resolved.iter().all(|h| h.is_whitespace_only()) {
+            Disposition::Fold
+        } else {
+            Disposition::Keep
+        }
+    });
+
+    let renumbered: HashMap<PlannedCommitId, PlannedCommitId> = kept
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| (commit.id, PlannedCommitId(i)))
+        .collect();
+    for (i, commit) in kept.iter_mut().enumerate() {
+        commit.id = PlannedCommitId(i);
+        commit.depends_on = commit
+            .depends_on
+            .iter()
+            .filter_map(|dep| renumbered.get(dep).copied())
+            .collect();
+    }
+
+    *planned_commits = kept;
     before - planned_commits.len()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{CommitDescription, HunkId, PlannedCommit, PlannedCommitId};
+    use crate::test_utils::{make_hunk_full, make_hunk_with_source, make_source_commit};
 
     #[test]
     fn drops_empty_commits() {
@@ -180,4 +554,182 @@ mod tests {
         assert_eq!(planned.len(), 1);
         assert_eq!(planned[0].description.short, "keep");
     }
+
+    #[test]
+    fn prunes_empty_commits_and_folds_whitespace_ones() {
+        let mut whitespace = make_hunk_with_source(1, "b.rs", vec![]);
+        whitespace.lines = vec![
+            DiffLine::Removed("fn b() {}".to_string()),
+            DiffLine::Added("fn b() {} ".to_string()),
+        ];
+        let mut context_only = make_hunk_with_source(9, "c.rs", vec![]);
+        context_only.lines = vec![DiffLine::Context("fn c() {}".to_string())];
+        let hunks = vec![
+            make_hunk_with_source(0, "a.rs", vec![]),
+            whitespace,
+            make_hunk_with_source(2, "d.rs", vec![]),
+        ];
+        let commit = |id: usize, hunk_ids: &[usize]| {
+            PlannedCommit::from_hunk_ids(
+                PlannedCommitId(id),
+                CommitDescription::short_only(format!("Commit {}", id)),
+                hunk_ids.iter().copied().map(HunkId).collect(),
+            )
+        };
+        let mut last = commit(3, &[2]);
+        last.depends_on = vec![PlannedCommitId(2), PlannedCommitId(0)];
+        let plan = vec![
+            commit(0, &[0]),
+            PlannedCommit::new(
+                PlannedCommitId(1),
+                CommitDescription::short_only("Nothing"),
+                vec![PlannedChange::NewHunk(context_only)],
+            ),
+            commit(2, &[1]),
+            last,
+        ];
+
+        let mut kept = plan.clone();
+        assert_eq!(prune_empty_commits(&mut kept, &hunks, false), 1);
+        let ids: Vec<usize> = kept.iter().map(|c| c.id.0).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+        assert_eq!(
+            kept[2].depends_on,
+            vec![PlannedCommitId(1), PlannedCommitId(0)]
+        );
+
+        let mut folded = plan;
+        assert_eq!(prune_empty_commits(&mut folded, &hunks, true), 2);
+        assert_eq!(folded.len(), 2);
+        assert_eq!(folded[0].changes.len(), 2);
+        assert_eq!(folded[1].description.short, "Commit 3");
+        // The dependency on the folded commit now points at its new home
+        assert_eq!(folded[1].depends_on, vec![PlannedCommitId(0)]);
+    }
+
+    #[test]
+    fn file_to_commits_map_asks_git_once_for_the_whole_range() {
+        use crate::test_utils::MockGit;
+
+        let commits: Vec<SourceCommit> = (0..5)
+            .map(|i| make_source_commit(&format!("c{}", i), "Work"))
+            .collect();
+        let git = commits.iter().fold(MockGit::new(), |git, commit| {
+            git.with_file_statuses(
+                &commit.sha,
+                vec![("shared.txt".to_string(), FileStatus::Modified)],
+            )
+        });
+        let planner = Planner::new(&git, StrategyFactory::new());
+
+        let map = planner.build_file_to_commits_map(&commits).unwrap();
+
+        assert_eq!(map["shared.txt"].len(), 5);
+        assert_eq!(git.calls(), vec!["get_file_statuses_for_commits"]);
+    }
+
+    #[test]
+    fn splits_hunks_by_author() {
+        let commits = vec![
+            SourceCommit::new("aaa", "mine", "mine").with_author("Ada", "ada@example.com"),
+            SourceCommit::new("bbb", "theirs", "theirs").with_author("Bob", "bob@example.com"),
+        ];
+        let hunks = vec![
+            make_hunk_with_source(0, "mine.rs", vec!["aaa".to_string()]),
+            make_hunk_with_source(1, "theirs.rs", vec!["bbb".to_string()]),
+            make_hunk_with_source(2, "shared.rs", vec!["aaa".to_string(), "bbb".to_string()]),
+        ];
+
+        let (matching, selected, passthrough) = split_by_author(&commits, &hunks, "ada");
+
+        assert_eq!(matching.len(), 1);
+        assert_eq!(matching[0].sha, "aaa");
+        let ids = |hunks: &[Hunk]| hunks.iter().map(|h| h.id).collect::<Vec<_>>();
+        assert_eq!(ids(&selected), vec![HunkId(0)]);
+        assert_eq!(ids(&passthrough), vec![HunkId(1), HunkId(2)]);
+    }
+
+    #[test]
+    fn whitespace_hunks_go_to_a_trailing_commit() {
+        use crate::git::Git;
+
+        let git = Git::new();
+        let commits = vec![make_source_commit("aaa", "Add feature")];
+        let hunks = vec![
+            make_hunk_with_source(0, "feature.rs", vec!["aaa".to_string()]),
+            make_hunk_full(
+                1,
+                "fmt.rs",
+                vec![
+                    DiffLine::Removed("fn  main(){".to_string()),
+                    DiffLine::Added("fn main() {".to_string()),
+                ],
+                vec!["aaa".to_string()],
+            ),
+        ];
+
+        let draft = Planner::new(&git, StrategyFactory::new())
+            .with_ignore_whitespace(true)
+            .draft_plan(Strategy::Squash, &commits, &hunks, &HashMap::new(), &[])
+            .unwrap();
+
+        assert_eq!(draft.planned_commits.len(), 2);
+        let last = draft.planned_commits.last().unwrap();
+        assert_eq!(last.description.short, "Whitespace changes");
+        assert!(matches!(
+            last.changes.as_slice(),
+            [PlannedChange::ExistingHunk(HunkId(1))]
+        ));
+    }
+
+    #[test]
+    fn finds_commits_with_nothing_attributed() {
+        let commits = vec![
+            make_source_commit("aaa", "Add feature"),
+            make_source_commit("bbb", "Rename and rename back"),
+            make_source_commit("ccc", "Make script executable"),
+        ];
+        let hunks = vec![make_hunk_with_source(
+            0,
+            "feature.rs",
+            vec!["aaa".to_string()],
+        )];
+        let mut mode_change = FileChange::with_path("run.sh".into());
+        mode_change.likely_source_commits = vec!["ccc".to_string()];
+
+        let missing = unrepresented_commits(&commits, &hunks, &[mode_change]);
+        let shas: Vec<&str> = missing.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["bbb"]);
+    }
+
+    #[test]
+    fn test_hunkless_changes_follow_their_source_commit() {
+        let hunks = vec![
+            make_hunk_with_source(0, "a.rs", vec!["first".to_string()]),
+            make_hunk_with_source(1, "b.rs", vec!["second".to_string()]),
+        ];
+        let planned: Vec<PlannedCommit> = hunks
+            .iter()
+            .map(|h| {
+                PlannedCommit::from_hunk_ids(
+                    PlannedCommitId(h.id.0),
+                    CommitDescription::short_only("Commit"),
+                    vec![h.id],
+                )
+            })
+            .collect();
+
+        let mut binary = FileChange::with_path("logo.png".into());
+        binary.is_binary = true;
+        binary.likely_source_commits = vec!["second".to_string()];
+        let mut orphan = FileChange::with_path("run.sh".into());
+        orphan.likely_source_commits = vec!["elsewhere".to_string()];
+        let mut content = FileChange::with_path("a.rs".into());
+        content.has_content_hunks = true;
+
+        let routed = route_hunkless_changes(&planned, &hunks, &[binary, orphan, content]);
+        assert_eq!(routed[0].commit_index, Some(1));
+        assert_eq!(routed[1].commit_index, Some(0));
+        assert_eq!(routed[2].commit_index, None);
+    }
 }
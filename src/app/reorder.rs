@@ -0,0 +1,231 @@
+//! Reordering a saved plan's commits through a rebase-style todo list.
+
+use crate::models::FileChange;
+use crate::plan_store::SavedCommit;
+
+/// Guidance shown below the todo list in the editor.
+pub(super) const TODO_HELP: &str =
+    "Reorder the lines to change the order the commits are created in.\n\
+Change 'pick' to 'fixup' to fold that commit's changes into the commit on\n\
+the line above it, keeping that commit's message. Every commit must stay\n\
+listed: reordering never discards changes.\n\
+Lines starting with '#' are ignored. An empty list leaves the plan unchanged.";
+
+/// One `pick <n> <subject>` line per commit, numbered from 1.
+pub(super) fn render_todo(commits: &[SavedCommit]) -> String {
+    commits
+        .iter()
+        .enumerate()
+        .map(|(idx, commit)| format!("pick {} {}\n", idx + 1, commit.description.short))
+        .collect()
+}
+
+/// Parse an edited todo list into groups of 0-based commit indices, in their
+/// new order: each picked commit followed by the commits fixed up into it.
+pub(super) fn parse_todo(text: &str, count: usize) -> Result<Vec<Vec<usize>>, String> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut seen = vec![false; count];
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let action = words.next().unwrap_or_default();
+        let number = words
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|n| (1..=count).contains(n))
+            .ok_or_else(|| {
+                format!(
+                    "Bad todo line '{}': expected a commit number between 1 and {}",
+                    line, count
+                )
+            })?;
+        if std::mem::replace(&mut seen[number - 1], true) {
+            return Err(format!("Commit {} appears more than once", number));
+        }
+        match action {
+            "pick" | "p" => groups.push(vec![number - 1]),
+            "fixup" | "f" => match groups.last_mut() {
+                Some(group) => group.push(number - 1),
+                None => {
+                    return Err(format!(
+                        "Can't fix up commit {}: there is no commit above it",
+                        number
+                    ))
+                }
+            },
+            "drop" | "d" => {
+                return Err(format!(
+                    "Reorder can't drop commit {}: its changes would be lost. Use 'fixup' to fold it into the commit above",
+                    number
+                ))
+            }
+            other => {
+                return Err(format!(
+                    "Unknown action '{}' for commit {} (use pick or fixup)",
+                    other, number
+                ))
+            }
+        }
+    }
+    if groups.is_empty() {
+        return Err("Nothing left in the todo list; plan left unchanged".to_string());
+    }
+    if let Some(missing) = seen.iter().position(|&listed| !listed) {
+        return Err(format!(
+            "Commit {} is missing from the todo list; use 'fixup {}' to fold it into another commit",
+            missing + 1,
+            missing + 1
+        ));
+    }
+    Ok(groups)
+}
+
+/// Rebuild `commits` as `groups` lists them: each group's first commit, with
+/// the changes of the commits fixed up into it appended, so no hunk is lost.
+pub(super) fn reorder_commits(
+    commits: Vec<SavedCommit>,
+    groups: &[Vec<usize>],
+) -> Vec<SavedCommit> {
+    let mut slots: Vec<Option<SavedCommit>> = commits.into_iter().map(Some).collect();
+    groups
+        .iter()
+        .map(|group| {
+            let mut take = |idx: usize| slots[idx].take().expect("each commit is listed once");
+            let mut commit = take(group[0]);
+            for &idx in &group[1..] {
+                commit.changes.extend(take(idx).changes);
+            }
+            commit
+        })
+        .collect()
+}
+
+/// Point each routed hunkless change at the new position of the commit it
+/// was routed to, following the same folding as [`reorder_commits`].
+pub(super) fn remap_file_changes(file_changes: &mut [FileChange], groups: &[Vec<usize>]) {
+    let count: usize = groups.iter().map(Vec::len).sum();
+    if count == 0 {
+        return;
+    }
+    for fc in file_changes.iter_mut() {
+        if let Some(index) = fc.commit_index {
+            let index = index.min(count - 1);
+            fc.commit_index = groups.iter().position(|group| group.contains(&index));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CommitDescription, HunkId, PlannedChange};
+
+    fn commit(subject: &str, hunk: usize) -> SavedCommit {
+        SavedCommit {
+            description: CommitDescription::short_only(subject.to_string()),
+            changes: vec![PlannedChange::ExistingHunk(HunkId(hunk))],
+            created_sha: None,
+        }
+    }
+
+    fn subjects(commits: &[SavedCommit]) -> Vec<&str> {
+        commits
+            .iter()
+            .map(|c| c.description.short.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn todo_round_trips() {
+        let commits = vec![commit("Add feature", 0), commit("Add tests", 1)];
+        let todo = render_todo(&commits);
+
+        assert_eq!(todo, "pick 1 Add feature\npick 2 Add tests\n");
+        assert_eq!(parse_todo(&todo, 2).unwrap(), vec![vec![0], vec![1]]);
+    }
+
+    #[test]
+    fn parse_todo_reorders_and_fixes_up() {
+        let text = "# comment\npick 3 Third\n\np 1 First\nfixup 2 Second\n";
+
+        assert_eq!(parse_todo(text, 3).unwrap(), vec![vec![2], vec![0, 1]]);
+    }
+
+    #[test]
+    fn parse_todo_rejects_bad_lines() {
+        assert!(parse_todo("pick 4 Nope", 3)
+            .unwrap_err()
+            .contains("between 1 and 3"));
+        assert!(parse_todo("pick x", 3).is_err());
+        assert!(parse_todo("pick 1\npick 1", 3)
+            .unwrap_err()
+            .contains("more than once"));
+        assert!(parse_todo("squash 1", 3)
+            .unwrap_err()
+            .contains("Unknown action"));
+        assert!(parse_todo("# pick 1", 3)
+            .unwrap_err()
+            .contains("Nothing left"));
+    }
+
+    #[test]
+    fn parse_todo_never_discards_a_commit() {
+        assert!(parse_todo("pick 1\ndrop 2\npick 3", 3)
+            .unwrap_err()
+            .contains("can't drop commit 2"));
+        assert!(parse_todo("pick 1\npick 3", 3)
+            .unwrap_err()
+            .contains("Commit 2 is missing"));
+        assert!(parse_todo("fixup 2\npick 1", 2)
+            .unwrap_err()
+            .contains("no commit above it"));
+    }
+
+    #[test]
+    fn fixups_fold_into_the_commit_above() {
+        let commits = vec![
+            commit("First", 0),
+            commit("Second", 1),
+            commit("Third", 2),
+            commit("Fourth", 3),
+        ];
+
+        let reordered = reorder_commits(commits, &[vec![3, 0], vec![1, 2]]);
+
+        assert_eq!(subjects(&reordered), vec!["Fourth", "Second"]);
+        let ids: Vec<_> = reordered[1]
+            .changes
+            .iter()
+            .map(|c| match c {
+                PlannedChange::ExistingHunk(id) => id.0,
+                PlannedChange::NewHunk(h) => h.id.0,
+            })
+            .collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(reordered[0].changes.len(), 2);
+    }
+
+    #[test]
+    fn routed_file_changes_follow_their_commit() {
+        let routed = |index: Option<usize>| {
+            let mut fc = FileChange::with_path("logo.png".into());
+            fc.is_binary = true;
+            fc.commit_index = index;
+            fc
+        };
+        let mut changes = vec![
+            routed(Some(0)),
+            routed(Some(1)),
+            routed(Some(2)),
+            routed(None),
+        ];
+
+        // Third first, Second fixed up into First
+        remap_file_changes(&mut changes, &[vec![2], vec![0, 1]]);
+
+        let indices: Vec<_> = changes.iter().map(|fc| fc.commit_index).collect();
+        assert_eq!(indices, vec![Some(1), Some(1), Some(0), None]);
+    }
+}
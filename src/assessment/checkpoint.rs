@@ -0,0 +1,82 @@
+//! Per-commit checkpoints, so an interrupted `assess` can resume.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::assessment::criteria::CriterionId;
+use crate::assessment::types::CommitAssessment;
+
+const CHECKPOINT_FILE: &str = "assess-progress.json";
+
+/// Where `assess` checkpoints finished commits: in the state directory, next
+/// to saved plans.
+pub fn default_checkpoint_path() -> PathBuf {
+    crate::plan_store::state_root().join(CHECKPOINT_FILE)
+}
+
+/// Commits assessed so far, and the criteria they were scored against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssessmentCheckpoint {
+    pub criteria: Vec<CriterionId>,
+    pub commit_assessments: Vec<CommitAssessment>,
+}
+
+impl AssessmentCheckpoint {
+    /// Write the checkpoint, replacing any earlier one.
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        // Write then rename, so a crash mid-write keeps the previous checkpoint
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Load the checkpoint at `path`, if there is one.
+    pub fn load(path: &Path) -> Result<Option<Self>, io::Error> {
+        match fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map(Some)
+                .map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Delete the checkpoint at `path`, if there is one.
+    pub fn clear(path: &Path) -> Result<(), io::Error> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn save_load_and_clear_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested/assess-progress.json");
+        assert!(AssessmentCheckpoint::load(&path).unwrap().is_none());
+
+        let checkpoint = AssessmentCheckpoint {
+            criteria: vec![CriterionId::Atomicity],
+            commit_assessments: Vec::new(),
+        };
+        checkpoint.save(&path).unwrap();
+        let loaded = AssessmentCheckpoint::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.criteria, vec![CriterionId::Atomicity]);
+
+        AssessmentCheckpoint::clear(&path).unwrap();
+        AssessmentCheckpoint::clear(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
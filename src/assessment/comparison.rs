@@ -1,13 +1,16 @@
 //! Assessment comparison and persistence.
 
-use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::assessment::types::{AssessmentComparison, RangeAssessment};
+use crate::assessment::criteria::CriterionId;
+use crate::assessment::types::{AssessmentComparison, CriterionDelta, RangeAssessment, Verdict};
 
 const DEFAULT_ASSESSMENTS_DIR: &str = ".git/reabsorb/assessments";
 
+/// Mean score changes smaller than this are treated as noise.
+const SIGNIFICANT_DELTA: f32 = 0.1;
+
 /// Get the default assessment storage directory.
 pub fn default_assessments_dir() -> PathBuf {
     PathBuf::from(DEFAULT_ASSESSMENTS_DIR)
@@ -80,35 +83,64 @@ pub fn compare_assessments(
 ) -> AssessmentComparison {
     let overall_delta = after.overall_score - before.overall_score;
 
-    let mut criterion_deltas = HashMap::new();
-    for (criterion_id, after_agg) in &after.aggregate_scores {
-        if let Some(before_agg) = before.aggregate_scores.get(criterion_id) {
-            criterion_deltas.insert(*criterion_id, after_agg.mean_score - before_agg.mean_score);
-        }
-    }
+    // Walk criteria in their canonical order so output is stable
+    let criteria: Vec<CriterionDelta> = CriterionId::all()
+        .iter()
+        .filter_map(|id| {
+            let before_agg = before.aggregate_scores.get(id)?;
+            let after_agg = after.aggregate_scores.get(id)?;
+            let delta = after_agg.mean_score - before_agg.mean_score;
+            Some(CriterionDelta {
+                criterion_id: *id,
+                before: before_agg.mean_score,
+                after: after_agg.mean_score,
+                delta,
+                verdict: verdict_for(delta),
+            })
+        })
+        .collect();
 
-    let improvements = criterion_deltas
+    let improvements = criteria
         .iter()
-        .filter(|(_, delta)| **delta > 0.1)
-        .map(|(id, delta)| format!("{}: +{:.2}", id, delta))
+        .filter(|c| c.verdict == Verdict::Improved)
+        .map(|c| format!("{}: +{:.2}", c.criterion_id, c.delta))
         .collect();
 
-    let regressions = criterion_deltas
+    let regressions = criteria
         .iter()
-        .filter(|(_, delta)| **delta < -0.1)
-        .map(|(id, delta)| format!("{}: {:.2}", id, delta))
+        .filter(|c| c.verdict == Verdict::Regressed)
+        .map(|c| format!("{}: {:.2}", c.criterion_id, c.delta))
         .collect();
 
+    let verdict = if criteria.iter().any(|c| c.verdict == Verdict::Regressed) {
+        Verdict::Regressed
+    } else if criteria.iter().any(|c| c.verdict == Verdict::Improved) {
+        Verdict::Improved
+    } else {
+        Verdict::Unchanged
+    };
+
     AssessmentComparison {
         before,
         after,
         overall_delta,
-        criterion_deltas,
+        criteria,
+        verdict,
         improvements,
         regressions,
     }
 }
 
+fn verdict_for(delta: f32) -> Verdict {
+    if delta > SIGNIFICANT_DELTA {
+        Verdict::Improved
+    } else if delta < -SIGNIFICANT_DELTA {
+        Verdict::Regressed
+    } else {
+        Verdict::Unchanged
+    }
+}
+
 /// Delete a saved assessment.
 pub fn delete_assessment(path: &Path) -> Result<(), std::io::Error> {
     if path.exists() {
@@ -120,7 +152,7 @@ pub fn delete_assessment(path: &Path) -> Result<(), std::io::Error> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::assessment::types::RangeAssessment;
+    use crate::assessment::types::{AggregateScore, RangeAssessment};
     use std::collections::HashMap;
 
     fn make_assessment(overall: f32) -> RangeAssessment {
@@ -132,6 +164,7 @@ mod tests {
             aggregate_scores: HashMap::new(),
             overall_score: overall,
             range_observations: vec![],
+            verdict: None,
         }
     }
 
@@ -154,4 +187,59 @@ mod tests {
 
         assert!((comparison.overall_delta - (-0.3)).abs() < 0.001);
     }
+
+    #[test]
+    fn compare_reports_per_criterion_verdicts() {
+        let mut before = make_assessment(0.6);
+        let mut after = make_assessment(0.6);
+        for (id, b, a) in [
+            (CriterionId::Atomicity, 3.0, 4.0),
+            (CriterionId::MessageQuality, 4.0, 3.5),
+            (CriterionId::Reversibility, 3.0, 3.05),
+        ] {
+            before.aggregate_scores.insert(id, aggregate(id, b));
+            after.aggregate_scores.insert(id, aggregate(id, a));
+        }
+        // Only scored on one side, so not compared
+        after.aggregate_scores.insert(
+            CriterionId::LogicalCohesion,
+            aggregate(CriterionId::LogicalCohesion, 1.0),
+        );
+
+        let comparison = compare_assessments(before, after);
+
+        let verdicts: Vec<_> = comparison
+            .criteria
+            .iter()
+            .map(|c| (c.criterion_id, c.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                (CriterionId::Atomicity, Verdict::Improved),
+                (CriterionId::MessageQuality, Verdict::Regressed),
+                (CriterionId::Reversibility, Verdict::Unchanged),
+            ]
+        );
+        assert_eq!(comparison.verdict, Verdict::Regressed);
+        assert_eq!(comparison.regressed().count(), 1);
+        assert_eq!(comparison.regressions, vec!["message_quality: -0.50"]);
+    }
+
+    #[test]
+    fn compare_without_criteria_is_unchanged() {
+        let comparison = compare_assessments(make_assessment(0.5), make_assessment(0.5));
+        assert!(comparison.criteria.is_empty());
+        assert_eq!(comparison.verdict, Verdict::Unchanged);
+    }
+
+    fn aggregate(criterion_id: CriterionId, mean_score: f32) -> AggregateScore {
+        AggregateScore {
+            criterion_id,
+            mean_score,
+            min_score: mean_score,
+            max_score: mean_score,
+            std_deviation: 0.0,
+        }
+    }
 }
@@ -6,6 +6,8 @@ pub mod message;
 pub mod reversibility;
 pub mod scope;
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 use crate::assessment::types::{AssessmentLevel, CriterionScore};
@@ -107,6 +109,8 @@ pub struct RangeContext {
     pub position: usize,
     /// Files changed across the entire range.
     pub files_in_range: Vec<String>,
+    /// Files changed by each commit in the range, keyed by SHA.
+    pub files_by_commit: HashMap<String, Vec<String>>,
     /// Previous assessments in this run (for consistency).
     pub prior_assessments: Vec<CriterionScore>,
 }
@@ -117,6 +121,7 @@ impl RangeContext {
             commits,
             position,
             files_in_range: Vec::new(),
+            files_by_commit: HashMap::new(),
             prior_assessments: Vec::new(),
         }
     }
@@ -126,6 +131,11 @@ impl RangeContext {
         self
     }
 
+    pub fn with_commit_files(mut self, files_by_commit: HashMap<String, Vec<String>>) -> Self {
+        self.files_by_commit = files_by_commit;
+        self
+    }
+
     pub fn with_prior_assessments(mut self, assessments: Vec<CriterionScore>) -> Self {
         self.prior_assessments = assessments;
         self
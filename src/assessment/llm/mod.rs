@@ -11,9 +11,18 @@ use crate::assessment::criteria::{
     get_definition, AssessmentError, CriterionDefinition, CriterionId, RangeContext,
 };
 use crate::assessment::types::{CommitAssessment, CriterionScore};
+use crate::llm::file_io::LlmFileSession;
 use crate::llm::LlmClient;
 use crate::models::SourceCommit;
 
+/// A commit's diff as handed to the assessor.
+pub enum CommitDiff {
+    /// Embedded in the prompt, truncated to fit
+    Inline(String),
+    /// Written in full to a temp file the LLM reads (`FileBasedLlmIo`)
+    File(LlmFileSession),
+}
+
 /// LLM-based assessor that evaluates all criteria in a single call.
 pub struct LlmAssessor {
     client: Arc<dyn LlmClient>,
@@ -56,7 +65,7 @@ impl LlmAssessor {
     pub fn assess_commit(
         &self,
         commit: &SourceCommit,
-        diff_content: &str,
+        diff: &CommitDiff,
         range_context: &RangeContext,
         position: usize,
         total: usize,
@@ -64,7 +73,7 @@ impl LlmAssessor {
         let prompt_text = prompt::build_assessment_prompt(
             &self.definitions,
             commit,
-            diff_content,
+            diff,
             range_context,
             self.max_context_commits,
         );
@@ -131,6 +140,8 @@ impl LlmAssessor {
         CommitAssessment {
             commit_sha: commit.sha.clone(),
             commit_message: commit.message.short.clone(),
+            author: commit.author_ident(),
+            authored_at: commit.author_date,
             criterion_scores,
             overall_score,
             position,
@@ -173,7 +184,7 @@ mod tests {
         let context = RangeContext::new(vec![commit.clone()], 0);
 
         let assessment = assessor
-            .assess_commit(&commit, "+code", &context, 0, 1)
+            .assess_commit(&commit, &CommitDiff::Inline("+code".into()), &context, 0, 1)
             .unwrap();
 
         assert_eq!(assessment.criterion_scores.len(), 1);
@@ -204,7 +215,7 @@ mod tests {
         let context = RangeContext::new(vec![commit.clone()], 0);
 
         let assessment = assessor
-            .assess_commit(&commit, "+code", &context, 0, 1)
+            .assess_commit(&commit, &CommitDiff::Inline("+code".into()), &context, 0, 1)
             .unwrap();
 
         assert_eq!(assessment.criterion_scores.len(), 2);
@@ -1,13 +1,21 @@
 //! Prompt construction for LLM-based commit assessment.
 
+use super::CommitDiff;
 use crate::assessment::criteria::{CriterionDefinition, RangeContext};
 use crate::models::SourceCommit;
+use crate::utils::short_sha;
+
+/// Rough character budget for the earlier-commits section (~500 tokens).
+const EARLIER_COMMITS_BUDGET: usize = 2000;
+
+/// Files listed per earlier commit before summarising the rest.
+const MAX_FILES_PER_COMMIT: usize = 5;
 
 /// Builds a batched assessment prompt for all criteria at once.
 pub fn build_assessment_prompt(
     definitions: &[CriterionDefinition],
     commit: &SourceCommit,
-    diff_content: &str,
+    diff: &CommitDiff,
     range_context: &RangeContext,
     max_context_commits: usize,
 ) -> String {
@@ -32,7 +40,7 @@ pub fn build_assessment_prompt(
     }
 
     // Commit context
-    let short_sha = &commit.sha[..8.min(commit.sha.len())];
+    let sha = short_sha(&commit.sha);
     prompt.push_str(&format!(
         r#"## Commit to Assess
 
@@ -44,17 +52,14 @@ pub fn build_assessment_prompt(
 
 **Position in range**: {} of {}
 
-**Diff**:
-```diff
 {}
-```
 
 "#,
-        short_sha,
+        sha,
         commit.message.long,
         range_context.position + 1,
         range_context.commits.len(),
-        truncate_diff(diff_content, 3000)
+        render_diff(diff)
     ));
 
     // Range context with capping
@@ -68,7 +73,7 @@ pub fn build_assessment_prompt(
         );
 
         for (i, c) in &selected {
-            let sha = &c.sha[..8.min(c.sha.len())];
+            let sha = short_sha(&c.sha);
             prompt.push_str(&format!(
                 "- [{}/{}] {} {}\n",
                 i + 1,
@@ -82,8 +87,8 @@ pub fn build_assessment_prompt(
         if selected.len() < total_others {
             let first = &range_context.commits[0];
             let last = &range_context.commits[range_context.commits.len() - 1];
-            let first_sha = &first.sha[..8.min(first.sha.len())];
-            let last_sha = &last.sha[..8.min(last.sha.len())];
+            let first_sha = short_sha(&first.sha);
+            let last_sha = short_sha(&last.sha);
             prompt.push_str(&format!(
                 "\n... and {} more commits not shown (range: {}..{})\n",
                 total_others - selected.len(),
@@ -94,6 +99,14 @@ pub fn build_assessment_prompt(
         prompt.push('\n');
     }
 
+    // What came before, so sequence dependencies can be judged
+    if range_context.position > 0 {
+        prompt.push_str(&format!(
+            "## Earlier commits (applied before this one)\n\n{}\n",
+            render_earlier_commits(range_context, EARLIER_COMMITS_BUDGET)
+        ));
+    }
+
     // Build criterion ID list for the JSON example
     let criterion_examples: Vec<String> = definitions
         .iter()
@@ -179,6 +192,62 @@ fn select_context_commits(
     result
 }
 
+/// List the commits before `range_context.position` with the files they
+/// touched, oldest first. Keeps the nearest commits that fit in `budget`
+/// characters and notes how many older ones were left out.
+fn render_earlier_commits(range_context: &RangeContext, budget: usize) -> String {
+    let earlier = &range_context.commits[..range_context.position.min(range_context.commits.len())];
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut used = 0;
+    for (i, commit) in earlier.iter().enumerate().rev() {
+        let mut line = format!("- [{}] {}", i + 1, commit.message.short);
+        if let Some(files) = range_context.files_by_commit.get(&commit.sha) {
+            if !files.is_empty() {
+                let shown: Vec<&str> = files
+                    .iter()
+                    .take(MAX_FILES_PER_COMMIT)
+                    .map(String::as_str)
+                    .collect();
+                line.push_str(&format!(" (files: {}", shown.join(", ")));
+                if files.len() > shown.len() {
+                    line.push_str(&format!(", +{} more", files.len() - shown.len()));
+                }
+                line.push(')');
+            }
+        }
+        line.push('\n');
+        if used + line.len() > budget && !lines.is_empty() {
+            break;
+        }
+        used += line.len();
+        lines.push(line);
+    }
+
+    let omitted = earlier.len() - lines.len();
+    let mut output = String::new();
+    if omitted > 0 {
+        output.push_str(&format!("- ... {} earlier commits omitted\n", omitted));
+    }
+    for line in lines.iter().rev() {
+        output.push_str(line);
+    }
+    output
+}
+
+/// The diff section: inline (truncated) or a pointer to the full diff file.
+fn render_diff(diff: &CommitDiff) -> String {
+    match diff {
+        CommitDiff::Inline(content) => {
+            format!("**Diff**:\n```diff\n{}\n```", truncate_diff(content, 3000))
+        }
+        CommitDiff::File(session) => format!(
+            "**Diff**: the full diff is in the file `{}`. Read it before assessing.",
+            session.input_path.display()
+        ),
+    }
+}
+
 /// Truncate diff content to avoid exceeding token limits.
 fn truncate_diff(diff: &str, max_chars: usize) -> &str {
     if diff.len() <= max_chars {
@@ -197,6 +266,8 @@ fn truncate_diff(diff: &str, max_chars: usize) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
+
     use crate::assessment::criteria::atomicity;
     use crate::models::SourceCommit;
 
@@ -209,9 +280,9 @@ mod tests {
             "Add feature\n\nThis adds a new feature.",
         );
         let context = RangeContext::new(vec![commit.clone()], 0);
-        let diff = "+fn new_function() {}";
+        let diff = CommitDiff::Inline("+fn new_function() {}".to_string());
 
-        let prompt = build_assessment_prompt(&defs, &commit, diff, &context, 10);
+        let prompt = build_assessment_prompt(&defs, &commit, &diff, &context, 10);
 
         assert!(prompt.contains("Atomicity"));
         assert!(prompt.contains("abc123de"));
@@ -292,11 +363,72 @@ mod tests {
         let defs = vec![atomicity::definition()];
         let context = RangeContext::new(commits, 10);
 
-        let prompt =
-            build_assessment_prompt(&defs, &context.commits[10].clone(), "+code", &context, 4);
+        let prompt = build_assessment_prompt(
+            &defs,
+            &context.commits[10].clone(),
+            &CommitDiff::Inline("+code".into()),
+            &context,
+            4,
+        );
 
         assert!(prompt.contains("more commits not shown"));
         assert!(prompt.contains("sha00abc")); // first sha in range note
         assert!(prompt.contains("sha19abc")); // last sha in range note
     }
+
+    #[test]
+    fn prompt_lists_earlier_commits_with_files() {
+        let commits: Vec<SourceCommit> = (0..4)
+            .map(|i| SourceCommit::new(format!("sha{}", i), format!("Commit {}", i), ""))
+            .collect();
+        let files = HashMap::from([
+            ("sha0".to_string(), vec!["src/parser.rs".to_string()]),
+            ("sha1".to_string(), vec!["src/lexer.rs".to_string()]),
+        ]);
+        let defs = vec![atomicity::definition()];
+        let context = RangeContext::new(commits, 2).with_commit_files(files);
+
+        let prompt = build_assessment_prompt(
+            &defs,
+            &context.commits[2].clone(),
+            &CommitDiff::Inline("+code".into()),
+            &context,
+            10,
+        );
+
+        assert!(prompt.contains("## Earlier commits"));
+        assert!(prompt.contains("- [1] Commit 0 (files: src/parser.rs)"));
+        assert!(prompt.contains("- [2] Commit 1 (files: src/lexer.rs)"));
+        assert!(!prompt.contains("- [4] Commit 3"));
+
+        let first = RangeContext::new(context.commits.clone(), 0);
+        let prompt = build_assessment_prompt(
+            &defs,
+            &first.commits[0].clone(),
+            &CommitDiff::Inline("+code".into()),
+            &first,
+            10,
+        );
+        assert!(!prompt.contains("## Earlier commits"));
+    }
+
+    #[test]
+    fn earlier_commits_keep_nearest_within_budget() {
+        let commits: Vec<SourceCommit> = (0..50)
+            .map(|i| SourceCommit::new(format!("sha{}", i), format!("Commit {:02}", i), ""))
+            .collect();
+        let files = HashMap::from([(
+            "sha48".to_string(),
+            (0..8).map(|i| format!("f{}.rs", i)).collect(),
+        )]);
+        let context = RangeContext::new(commits, 49).with_commit_files(files);
+
+        let rendered = render_earlier_commits(&context, 200);
+
+        assert!(rendered.len() < 300);
+        assert!(rendered.starts_with("- ... "));
+        assert!(rendered.contains("f4.rs, +3 more"));
+        assert!(rendered.trim_end().ends_with("+3 more)"));
+        assert!(!rendered.contains("Commit 00"));
+    }
 }
@@ -3,31 +3,39 @@
 //! This module provides tools to assess commits against a rubric of criteria,
 //! with LLM-based assessment and before/after comparison support.
 
+pub mod checkpoint;
 pub mod comparison;
 pub mod criteria;
 pub mod llm;
 pub mod report;
 pub mod types;
 
+pub use checkpoint::AssessmentCheckpoint;
 pub use comparison::{compare_assessments, load_assessment, save_assessment};
 pub use criteria::{AssessmentError, CriterionId, RangeContext};
 pub use types::{
-    AggregateScore, AssessmentComparison, AssessmentLevel, CommitAssessment, CriterionScore,
-    RangeAssessment,
+    AggregateScore, AssessmentComparison, AssessmentLevel, CommitAssessment, CriterionDelta,
+    CriterionScore, RangeAssessment, Verdict, VerdictBands,
 };
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
+use crate::features::Feature;
 use crate::git::GitOps;
-use crate::llm::LlmClient;
+use crate::llm::file_io::{LlmFileSession, REABSORB_TMP_DIR};
+use crate::llm::{LlmClient, Redaction};
 use crate::models::SourceCommit;
+use crate::reorganize::DIFF_SOURCE_SHA;
+use crate::utils::short_sha;
 
 use criteria::get_definition;
-use llm::LlmAssessor;
+use llm::{CommitDiff, LlmAssessor};
 
 /// Main assessment engine for evaluating commit quality.
 pub struct AssessmentEngine {
@@ -35,6 +43,15 @@ pub struct AssessmentEngine {
     criterion_ids: Vec<CriterionId>,
     max_parallel: usize,
     max_context_commits: usize,
+    report_progress: bool,
+    /// Where diffs are written for the LLM to read; inline when `None`
+    file_io_dir: Option<PathBuf>,
+    /// Rewritten with every finished commit so a failed run can resume
+    checkpoint: Option<PathBuf>,
+    /// Assessments from an earlier run, reused for commits with the same sha
+    completed: Vec<CommitAssessment>,
+    redaction: Redaction,
+    verdict_bands: VerdictBands,
 }
 
 impl AssessmentEngine {
@@ -45,6 +62,14 @@ impl AssessmentEngine {
             criterion_ids: criterion_ids.to_vec(),
             max_parallel: 4,
             max_context_commits: 10,
+            report_progress: true,
+            file_io_dir: Feature::FileBasedLlmIo
+                .is_enabled()
+                .then(|| PathBuf::from(REABSORB_TMP_DIR)),
+            checkpoint: None,
+            completed: Vec::new(),
+            redaction: Redaction::default(),
+            verdict_bands: VerdictBands::default(),
         }
     }
 
@@ -65,6 +90,44 @@ impl AssessmentEngine {
         self
     }
 
+    /// Enable or disable per-commit progress and ETA reporting.
+    pub fn with_progress(mut self, report_progress: bool) -> Self {
+        self.report_progress = report_progress;
+        self
+    }
+
+    /// Write each commit's diff to a file in `dir` for the LLM to read instead
+    /// of embedding it in the prompt. Defaults to `.git/reabsorb/tmp` when
+    /// `FileBasedLlmIo` is enabled.
+    pub fn with_file_io_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.file_io_dir = dir;
+        self
+    }
+
+    /// Save finished commits to `path` as they complete.
+    pub fn with_checkpoint(mut self, path: Option<PathBuf>) -> Self {
+        self.checkpoint = path;
+        self
+    }
+
+    /// Reuse these assessments instead of re-assessing their commits.
+    pub fn with_completed(mut self, completed: Vec<CommitAssessment>) -> Self {
+        self.completed = completed;
+        self
+    }
+
+    /// Show the files `redaction` matches as placeholders in prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Label overall scores with `bands` instead of the defaults.
+    pub fn with_verdict_bands(mut self, bands: VerdictBands) -> Self {
+        self.verdict_bands = bands;
+        self
+    }
+
     /// Assess a range of commits in parallel.
     pub fn assess_range<G: GitOps>(
         &self,
@@ -75,15 +138,46 @@ impl AssessmentEngine {
     ) -> Result<RangeAssessment, AssessmentError> {
         let total = commits.len();
 
-        // Collect all files changed in the range for context
-        let files_in_range = self.collect_files_in_range(git, commits);
+        // Collect the files each commit (and so the whole range) changed for context
+        let files_by_commit = self.collect_commit_files(git, commits);
+        let mut files_in_range: Vec<String> = Vec::new();
+        for commit in commits {
+            for file in files_by_commit.get(&commit.sha).into_iter().flatten() {
+                if !files_in_range.contains(file) {
+                    files_in_range.push(file.clone());
+                }
+            }
+        }
+
+        // Commits finished by an earlier run keep their scores, renumbered
+        // for this range
+        let mut reused = Vec::new();
+        for (position, commit) in commits.iter().enumerate() {
+            if let Some(done) = self.completed.iter().find(|a| a.commit_sha == commit.sha) {
+                reused.push(CommitAssessment {
+                    position,
+                    total_commits: total,
+                    ..done.clone()
+                });
+            }
+        }
+        if !reused.is_empty() {
+            info!(
+                "Resuming: {} of {} commits already assessed",
+                reused.len(),
+                total
+            );
+        }
 
         // Pre-fetch all diffs (git operations are fast, do sequentially)
-        info!("Fetching diffs for {} commits...", total);
-        let mut commit_data: Vec<(usize, SourceCommit, String)> = Vec::new();
+        info!("Fetching diffs for {} commits...", total - reused.len());
+        let mut commit_data: Vec<(usize, SourceCommit, Arc<CommitDiff>)> = Vec::new();
         for (position, commit) in commits.iter().enumerate() {
-            let diff_content = self.get_diff_content(git, &commit.sha)?;
-            commit_data.push((position, commit.clone(), diff_content));
+            if reused.iter().any(|a| a.position == position) {
+                continue;
+            }
+            let diff = self.fetch_diff(git, &commit.sha)?;
+            commit_data.push((position, commit.clone(), Arc::new(diff)));
         }
 
         // Create a shared assessor for all threads
@@ -96,49 +190,84 @@ impl AssessmentEngine {
         // Assess commits in parallel batches
         info!(
             "Assessing {} commits ({} parallel)...",
-            total, self.max_parallel
+            commit_data.len(),
+            self.max_parallel
         );
 
-        let results: Arc<Mutex<Vec<CommitAssessment>>> = Arc::new(Mutex::new(Vec::new()));
+        let already_done = reused.len();
+        let results: Arc<Mutex<Vec<CommitAssessment>>> = Arc::new(Mutex::new(reused));
         let errors: Arc<Mutex<Vec<(usize, AssessmentError)>>> = Arc::new(Mutex::new(Vec::new()));
+        let progress = Arc::new(Mutex::new(Progress::new(
+            commit_data.len(),
+            self.max_parallel,
+        )));
+        let report_progress = self.report_progress;
+        let checkpoint = self.checkpoint.clone();
 
         let chunks: Vec<_> = commit_data.chunks(self.max_parallel).collect();
 
         for chunk in chunks {
             let handles: Vec<_> = chunk
                 .iter()
-                .map(|(position, commit, diff_content)| {
+                .map(|(position, commit, diff)| {
                     let assessor = Arc::clone(&assessor);
                     let results = Arc::clone(&results);
                     let errors = Arc::clone(&errors);
+                    let progress = Arc::clone(&progress);
                     let commits_clone = commits.to_vec();
                     let files_clone = files_in_range.clone();
+                    let commit_files_clone = files_by_commit.clone();
                     let position = *position;
                     let commit = commit.clone();
-                    let diff_content = diff_content.clone();
+                    let diff = Arc::clone(diff);
+                    let checkpoint = checkpoint.clone();
+                    let criteria = self.criterion_ids.clone();
 
                     thread::spawn(move || {
                         debug!(
                             "[{}/{}] {} {}",
                             position + 1,
                             total,
-                            &commit.sha[..8.min(commit.sha.len())],
+                            short_sha(&commit.sha),
                             commit.message.short
                         );
 
-                        let range_context =
-                            RangeContext::new(commits_clone, position).with_files(files_clone);
+                        let range_context = RangeContext::new(commits_clone, position)
+                            .with_files(files_clone)
+                            .with_commit_files(commit_files_clone);
+
+                        let started = Instant::now();
+                        let outcome =
+                            assessor.assess_commit(&commit, &diff, &range_context, position, total);
+
+                        let mut progress = progress.lock().unwrap();
+                        let eta = progress.record(started.elapsed());
+                        if report_progress {
+                            info!(
+                                "[{}/{}] Assessed {}{}",
+                                already_done + progress.completed,
+                                total,
+                                short_sha(&commit.sha),
+                                eta.map(|d| format!(" (ETA ~{})", format_eta(d)))
+                                    .unwrap_or_default()
+                            );
+                        }
+                        drop(progress);
 
-                        match assessor.assess_commit(
-                            &commit,
-                            &diff_content,
-                            &range_context,
-                            position,
-                            total,
-                        ) {
+                        match outcome {
                             Ok(assessment) => {
                                 let mut results = results.lock().unwrap();
                                 results.push(assessment);
+                                if let Some(path) = &checkpoint {
+                                    let saved = AssessmentCheckpoint {
+                                        criteria,
+                                        commit_assessments: results.clone(),
+                                    }
+                                    .save(path);
+                                    if let Err(e) = saved {
+                                        warn!("Could not save assessment checkpoint: {}", e);
+                                    }
+                                }
                             }
                             Err(e) => {
                                 let mut errors = errors.lock().unwrap();
@@ -166,6 +295,17 @@ impl AssessmentEngine {
         let mut commit_assessments = Arc::try_unwrap(results).unwrap().into_inner().unwrap();
         commit_assessments.sort_by_key(|ca| ca.position);
 
+        Ok(self.summarize(base_sha, head_sha, commit_assessments))
+    }
+
+    /// Collect commit assessments into a range assessment, with aggregate
+    /// and overall scores.
+    pub fn summarize(
+        &self,
+        base_sha: &str,
+        head_sha: &str,
+        commit_assessments: Vec<CommitAssessment>,
+    ) -> RangeAssessment {
         let aggregate_scores = self.calculate_aggregates(&commit_assessments);
         let overall_score = if commit_assessments.is_empty() {
             0.0
@@ -177,7 +317,7 @@ impl AssessmentEngine {
                 / commit_assessments.len() as f32
         };
 
-        Ok(RangeAssessment {
+        RangeAssessment {
             base_sha: base_sha.to_string(),
             head_sha: head_sha.to_string(),
             assessed_at: chrono::Utc::now().to_rfc3339(),
@@ -185,7 +325,80 @@ impl AssessmentEngine {
             aggregate_scores,
             overall_score,
             range_observations: Vec::new(),
-        })
+            verdict: Some(self.verdict_bands.verdict(overall_score).to_string()),
+        }
+    }
+
+    /// Assess a standalone change, without a repository.
+    ///
+    /// `message` is the full commit message, subject line first, and `diff`
+    /// a unified diff as `git diff` or `git show` prints it. The change is
+    /// assessed on its own, with no surrounding range for context.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use git_reabsorb::assessment::{AssessmentEngine, CriterionId};
+    /// use git_reabsorb::llm::{LlmClient, LlmError};
+    ///
+    /// struct Canned;
+    ///
+    /// impl LlmClient for Canned {
+    ///     fn complete(&self, _prompt: &str) -> Result<String, LlmError> {
+    ///         Ok(r#"{"scores": [{"criterion": "atomicity", "level": 4,
+    ///             "rationale": "One change", "evidence": [], "suggestions": []}]}"#
+    ///             .to_string())
+    ///     }
+    /// }
+    ///
+    /// let diff = "diff --git a/a.txt b/a.txt
+    /// --- a/a.txt
+    /// +++ b/a.txt
+    /// @@ -1 +1 @@
+    /// -old
+    /// +new
+    /// ";
+    /// let engine = AssessmentEngine::new(Arc::new(Canned), &[CriterionId::Atomicity]);
+    /// let assessment = engine.assess_diff("Update a.txt", diff).unwrap();
+    /// assert_eq!(assessment.commit_message, "Update a.txt");
+    /// assert_eq!(assessment.criterion_scores[0].level, 4);
+    /// ```
+    pub fn assess_diff(
+        &self,
+        message: &str,
+        diff: &str,
+    ) -> Result<CommitAssessment, AssessmentError> {
+        let short = message.lines().next().unwrap_or_default().trim();
+        let commit = SourceCommit::new(DIFF_SOURCE_SHA, short, message.trim());
+        let diff = self.prepare_diff(self.redaction.diff(diff), DIFF_SOURCE_SHA)?;
+        let range_context = RangeContext::new(vec![commit.clone()], 0);
+        LlmAssessor::new(
+            Arc::clone(&self.client),
+            &self.criterion_ids,
+            self.max_context_commits,
+        )
+        .assess_commit(&commit, &diff, &range_context, 0, 1)
+    }
+
+    fn fetch_diff<G: GitOps>(&self, git: &G, sha: &str) -> Result<CommitDiff, AssessmentError> {
+        let content = self.get_diff_content(git, sha)?;
+        self.prepare_diff(content, sha)
+    }
+
+    /// Wrap `content` for the assessor, writing it to a session file when
+    /// file I/O is on so only one commit's diff is held in memory at a time.
+    fn prepare_diff(&self, content: String, label: &str) -> Result<CommitDiff, AssessmentError> {
+        let Some(dir) = &self.file_io_dir else {
+            return Ok(CommitDiff::Inline(content));
+        };
+        let session = LlmFileSession::in_dir(dir, "diff")
+            .and_then(|session| session.write_input(&content).map(|()| session))
+            .map_err(|e| AssessmentError::LlmFailed(e.to_string()))?;
+        debug!(
+            "File-based LLM I/O: diff for {}={}",
+            label,
+            session.input_path.display()
+        );
+        Ok(CommitDiff::File(session))
     }
 
     fn get_diff_content<G: GitOps>(&self, git: &G, sha: &str) -> Result<String, AssessmentError> {
@@ -195,23 +408,30 @@ impl AssessmentEngine {
 
         Ok(hunks
             .iter()
-            .map(|h| h.to_patch())
+            .map(|h| self.redaction.hunk_patch(h))
             .collect::<Vec<_>>()
             .join("\n"))
     }
 
-    fn collect_files_in_range<G: GitOps>(&self, git: &G, commits: &[SourceCommit]) -> Vec<String> {
-        let mut files = Vec::new();
-        for commit in commits {
-            if let Ok(changed) = git.get_files_changed_in_commit(&commit.sha) {
-                for file in changed {
-                    if !files.contains(&file) {
-                        files.push(file);
-                    }
-                }
-            }
-        }
-        files
+    fn collect_commit_files<G: GitOps>(
+        &self,
+        git: &G,
+        commits: &[SourceCommit],
+    ) -> HashMap<String, Vec<String>> {
+        commits
+            .iter()
+            .filter_map(|commit| {
+                git.get_files_changed_in_commit(&commit.sha)
+                    .ok()
+                    .map(|files| {
+                        let files = files
+                            .iter()
+                            .map(|file| self.redaction.path(Path::new(file)))
+                            .collect();
+                        (commit.sha.clone(), files)
+                    })
+            })
+            .collect()
     }
 
     fn calculate_aggregates(
@@ -258,6 +478,52 @@ impl AssessmentEngine {
     }
 }
 
+/// Tracks completed assessments to estimate the time remaining.
+struct Progress {
+    total: usize,
+    parallel: usize,
+    completed: usize,
+    elapsed: Duration,
+}
+
+impl Progress {
+    fn new(total: usize, parallel: usize) -> Self {
+        Self {
+            total,
+            parallel: parallel.max(1),
+            completed: 0,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Record a finished assessment and return the estimated time remaining.
+    fn record(&mut self, duration: Duration) -> Option<Duration> {
+        self.completed += 1;
+        self.elapsed += duration;
+
+        let remaining = self.total.saturating_sub(self.completed);
+        if remaining == 0 {
+            return None;
+        }
+
+        let mean = self.elapsed / self.completed as u32;
+        let waves = remaining.div_ceil(self.parallel) as u32;
+        Some(mean * waves)
+    }
+}
+
+/// Format a duration as a coarse human-readable estimate (e.g. "45s", "3m", "1h 5m").
+fn format_eta(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs.max(1))
+    } else if secs < 3600 {
+        format!("{}m", (secs + 30) / 60)
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
 /// Get definitions for specific criterion IDs.
 pub fn get_definitions(ids: &[CriterionId]) -> Vec<criteria::CriterionDefinition> {
     ids.iter().map(|id| get_definition(*id)).collect()
@@ -266,10 +532,125 @@ pub fn get_definitions(ids: &[CriterionId]) -> Vec<criteria::CriterionDefinition
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::{make_hunk, make_source_commit, MockGit};
 
     #[test]
     fn criterion_id_all() {
         let all = CriterionId::all();
         assert_eq!(all.len(), 5);
     }
+
+    #[test]
+    fn progress_eta_accounts_for_parallelism() {
+        let mut progress = Progress::new(5, 2);
+
+        // 4 remaining at 30s each, 2 at a time
+        let eta = progress.record(Duration::from_secs(30)).unwrap();
+        assert_eq!(eta, Duration::from_secs(60));
+
+        // Mean is now 20s, 3 remaining -> 2 waves
+        let eta = progress.record(Duration::from_secs(10)).unwrap();
+        assert_eq!(eta, Duration::from_secs(40));
+
+        progress.record(Duration::from_secs(10));
+        progress.record(Duration::from_secs(10));
+        assert_eq!(progress.record(Duration::from_secs(10)), None);
+    }
+
+    #[test]
+    fn format_eta_units() {
+        assert_eq!(format_eta(Duration::from_millis(200)), "1s");
+        assert_eq!(format_eta(Duration::from_secs(45)), "45s");
+        assert_eq!(format_eta(Duration::from_secs(170)), "3m");
+        assert_eq!(format_eta(Duration::from_secs(3900)), "1h 5m");
+    }
+
+    /// Reads back the diff file each prompt points at, while it still exists.
+    struct DiffFileReader {
+        diffs: Mutex<Vec<String>>,
+    }
+
+    impl LlmClient for DiffFileReader {
+        fn complete(&self, prompt: &str) -> Result<String, crate::llm::LlmError> {
+            let path = prompt
+                .split("the full diff is in the file `")
+                .nth(1)
+                .and_then(|rest| rest.split('`').next())
+                .expect("prompt references a diff file");
+            self.diffs
+                .lock()
+                .unwrap()
+                .push(std::fs::read_to_string(path)?);
+            Ok(r#"{"scores": [{"criterion": "atomicity", "level": 4, "rationale": "ok", "evidence": [], "suggestions": []}]}"#.to_string())
+        }
+    }
+
+    #[test]
+    fn file_io_writes_each_diff_to_a_session_file() {
+        let hunk = make_hunk(0);
+        let commit = make_source_commit("c1", "Add a");
+        let git = MockGit::new().with_commit(commit.clone(), vec![hunk.clone()]);
+        let commits = vec![commit];
+        let dir = tempfile::tempdir().unwrap();
+        let client = Arc::new(DiffFileReader {
+            diffs: Mutex::new(Vec::new()),
+        });
+
+        let result = AssessmentEngine::new(client.clone(), &[CriterionId::Atomicity])
+            .with_progress(false)
+            .with_file_io_dir(Some(dir.path().to_path_buf()))
+            .assess_range(&git, "", "c1", &commits)
+            .unwrap();
+
+        assert_eq!(result.commit_assessments.len(), 1);
+        assert_eq!(*client.diffs.lock().unwrap(), vec![hunk.to_patch()]);
+        // Sessions clean up once the assessment is done
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn resumed_commits_are_reused_and_checkpointed() {
+        let first = make_source_commit("c1", "Add a");
+        let second = make_source_commit("c2", "Add b");
+        let git = MockGit::new()
+            .with_commit(first.clone(), vec![make_hunk(0)])
+            .with_commit(second.clone(), vec![make_hunk(1)]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("assess-progress.json");
+        let client = Arc::new(crate::llm::test_support::MockLlmClient::new(
+            r#"{"scores": [{"criterion": "atomicity", "level": 2, "rationale": "ok", "evidence": [], "suggestions": []}]}"#,
+        ));
+        let earlier = CommitAssessment {
+            commit_sha: "c1".to_string(),
+            commit_message: "Add a".to_string(),
+            author: String::new(),
+            authored_at: 0,
+            criterion_scores: Vec::new(),
+            overall_score: 0.9,
+            position: 7,
+            total_commits: 9,
+        };
+
+        let result = AssessmentEngine::new(client, &[CriterionId::Atomicity])
+            .with_progress(false)
+            .with_checkpoint(Some(path.clone()))
+            .with_completed(vec![earlier])
+            .assess_range(&git, "", "c2", &[first, second])
+            .unwrap();
+
+        let reused = &result.commit_assessments[0];
+        assert_eq!((reused.position, reused.total_commits), (0, 2));
+        assert_eq!(reused.overall_score, 0.9);
+        assert_eq!(result.commit_assessments[1].commit_sha, "c2");
+        // Only c2's diff was fetched
+        assert_eq!(git.calls().iter().filter(|c| *c == "read_hunks").count(), 1);
+
+        let saved = AssessmentCheckpoint::load(&path).unwrap().unwrap();
+        let shas: Vec<&str> = saved
+            .commit_assessments
+            .iter()
+            .map(|a| a.commit_sha.as_str())
+            .collect();
+        assert_eq!(shas, vec!["c1", "c2"]);
+    }
 }
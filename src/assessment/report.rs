@@ -1,7 +1,13 @@
 //! Report formatting for assessment output.
 
 use crate::assessment::criteria::get_definition;
-use crate::assessment::types::{AssessmentComparison, CommitAssessment, RangeAssessment};
+use serde::Serialize;
+
+use crate::assessment::types::{
+    AssessmentComparison, CommitAssessment, CriterionDelta, RangeAssessment, Verdict,
+};
+use crate::color::{paint, Style};
+use crate::utils::{format_timestamp, short_sha};
 
 /// Output format for assessment reports.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,13 +23,16 @@ pub enum OutputFormat {
 }
 
 /// Format a range assessment for output.
+///
+/// `color` only affects `Pretty`; the other formats are always plain text.
 pub fn format_assessment(
     assessment: &RangeAssessment,
     format: OutputFormat,
     verbose: bool,
+    color: bool,
 ) -> String {
     match format {
-        OutputFormat::Pretty => format_pretty(assessment, verbose),
+        OutputFormat::Pretty => format_pretty(assessment, verbose, color),
         OutputFormat::Json => format_json(assessment),
         OutputFormat::Markdown => format_markdown(assessment, verbose),
         OutputFormat::Compact => format_compact(assessment),
@@ -40,18 +49,15 @@ pub fn format_comparison(comparison: &AssessmentComparison, format: OutputFormat
     }
 }
 
-fn format_pretty(assessment: &RangeAssessment, verbose: bool) -> String {
+fn format_pretty(assessment: &RangeAssessment, verbose: bool, color: bool) -> String {
     let mut output = String::new();
 
     output.push_str(&format!(
         "Assessment: {}..{}\n",
-        &assessment.base_sha[..8.min(assessment.base_sha.len())],
-        &assessment.head_sha[..8.min(assessment.head_sha.len())]
-    ));
-    output.push_str(&format!(
-        "Overall Score: {:.1}%\n\n",
-        assessment.overall_score * 100.0
+        short_sha(&assessment.base_sha),
+        short_sha(&assessment.head_sha)
     ));
+    output.push_str(&format!("Overall Score: {}\n\n", overall(assessment)));
 
     // Aggregate scores
     output.push_str("Aggregate Scores:\n");
@@ -73,36 +79,53 @@ fn format_pretty(assessment: &RangeAssessment, verbose: bool) -> String {
     // Per-commit details
     output.push_str("Commits:\n");
     for commit in &assessment.commit_assessments {
-        output.push_str(&format_commit_pretty(commit, verbose));
+        output.push_str(&format_commit_pretty(commit, verbose, color));
     }
 
     output
 }
 
-fn format_commit_pretty(commit: &CommitAssessment, verbose: bool) -> String {
+fn format_commit_pretty(commit: &CommitAssessment, verbose: bool, color: bool) -> String {
     let mut output = String::new();
-    let sha = &commit.commit_sha[..8.min(commit.commit_sha.len())];
+    let sha = short_sha(&commit.commit_sha);
 
     output.push_str(&format!(
-        "\n{} {} ({:.1}%)\n\n",
+        "\n{} {} ({:.1}%)\n",
         sha,
         commit.commit_message,
         commit.overall_score * 100.0
     ));
+    if let Some(authored) = format_authored(commit) {
+        output.push_str(&format!("{}\n", authored));
+    }
+    output.push('\n');
 
     // Format each criterion as a visual rubric
     for score in &commit.criterion_scores {
-        output.push_str(&format_criterion_rubric(score, verbose));
+        output.push_str(&format_criterion_rubric(score, verbose, color));
         output.push('\n');
     }
 
     output
 }
 
+/// "Authored by X on DATE", or `None` for assessments saved without author info.
+fn format_authored(commit: &CommitAssessment) -> Option<String> {
+    if commit.author.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Authored by {} on {}",
+        commit.author,
+        format_timestamp(commit.authored_at)
+    ))
+}
+
 /// Format a single criterion score as a visual rubric table.
 fn format_criterion_rubric(
     score: &crate::assessment::types::CriterionScore,
     verbose: bool,
+    color: bool,
 ) -> String {
     let mut output = String::new();
 
@@ -114,7 +137,11 @@ fn format_criterion_rubric(
     let col_width = 24;
 
     // Criterion name header
-    output.push_str(&format!("\x1b[1m{}\x1b[0m (Level {})\n", name, score.level));
+    output.push_str(&format!(
+        "{} (Level {})\n",
+        paint(name, Style::Bold, color),
+        score.level
+    ));
 
     // Top border
     output.push('┌');
@@ -130,14 +157,11 @@ fn format_criterion_rubric(
     output.push('│');
     for i in 1..=5 {
         let is_hit = i == score.level;
+        let cell = format!("{:^width$}", i, width = col_width);
         if is_hit {
-            output.push_str(&format!(
-                "\x1b[42;30m{:^width$}\x1b[0m",
-                i,
-                width = col_width
-            ));
+            output.push_str(&paint(&cell, Style::Highlight, color));
         } else {
-            output.push_str(&format!("{:^width$}", i, width = col_width));
+            output.push_str(&cell);
         }
         output.push('│');
     }
@@ -168,14 +192,11 @@ fn format_criterion_rubric(
             let is_hit = (col + 1) as u8 == score.level;
             let text = lines.get(line_idx).map(|s| s.as_str()).unwrap_or("");
 
+            let cell = format!(" {:^width$} ", text, width = col_width - 2);
             if is_hit {
-                output.push_str(&format!(
-                    "\x1b[42;30m {:^width$} \x1b[0m",
-                    text,
-                    width = col_width - 2
-                ));
+                output.push_str(&paint(&cell, Style::Highlight, color));
             } else {
-                output.push_str(&format!(" {:^width$} ", text, width = col_width - 2));
+                output.push_str(&cell);
             }
             output.push('│');
         }
@@ -245,6 +266,15 @@ fn wrap_text(text: &str, width: usize) -> Vec<String> {
     lines
 }
 
+/// The overall score as a percentage, followed by its verdict if it has one.
+fn overall(assessment: &RangeAssessment) -> String {
+    let score = format!("{:.1}%", assessment.overall_score * 100.0);
+    match &assessment.verdict {
+        Some(verdict) => format!("{} ({})", score, verdict),
+        None => score,
+    }
+}
+
 fn format_json(assessment: &RangeAssessment) -> String {
     serde_json::to_string_pretty(assessment).unwrap_or_else(|e| format!("Error: {}", e))
 }
@@ -253,11 +283,15 @@ fn format_markdown(assessment: &RangeAssessment, verbose: bool) -> String {
     let mut output = String::new();
 
     output.push_str(&format!(
-        "# Commit Assessment Report\n\n**Range**: `{}..{}`\n**Overall Score**: {:.1}%\n\n",
-        &assessment.base_sha[..8.min(assessment.base_sha.len())],
-        &assessment.head_sha[..8.min(assessment.head_sha.len())],
+        "# Commit Assessment Report\n\n**Range**: `{}..{}`\n**Overall Score**: {:.1}%\n",
+        short_sha(&assessment.base_sha),
+        short_sha(&assessment.head_sha),
         assessment.overall_score * 100.0
     ));
+    if let Some(verdict) = &assessment.verdict {
+        output.push_str(&format!("**Verdict**: {}\n", verdict));
+    }
+    output.push('\n');
 
     // Aggregate table
     output.push_str("## Summary\n\n| Criterion | Mean | Min | Max | Std Dev |\n|-----------|------|-----|-----|--------|\n");
@@ -280,13 +314,16 @@ fn format_markdown(assessment: &RangeAssessment, verbose: bool) -> String {
     // Per-commit details
     output.push_str("## Commits\n\n");
     for commit in &assessment.commit_assessments {
-        let sha = &commit.commit_sha[..8.min(commit.commit_sha.len())];
+        let sha = short_sha(&commit.commit_sha);
         output.push_str(&format!(
             "### `{}` {}\n\n**Score**: {:.1}%\n\n",
             sha,
             commit.commit_message,
             commit.overall_score * 100.0
         ));
+        if let Some(authored) = format_authored(commit) {
+            output.push_str(&format!("{}\n\n", authored));
+        }
 
         output.push_str("| Criterion | Level |\n|-----------|-------|\n");
         for score in &commit.criterion_scores {
@@ -310,13 +347,10 @@ fn format_markdown(assessment: &RangeAssessment, verbose: bool) -> String {
 fn format_compact(assessment: &RangeAssessment) -> String {
     let mut output = String::new();
 
-    output.push_str(&format!(
-        "Overall: {:.1}%\n",
-        assessment.overall_score * 100.0
-    ));
+    output.push_str(&format!("Overall: {}\n", overall(assessment)));
 
     for commit in &assessment.commit_assessments {
-        let sha = &commit.commit_sha[..8.min(commit.commit_sha.len())];
+        let sha = short_sha(&commit.commit_sha);
         let scores: Vec<String> = commit
             .criterion_scores
             .iter()
@@ -372,8 +406,44 @@ fn format_comparison_pretty(comparison: &AssessmentComparison) -> String {
     output
 }
 
+/// The deltas of a comparison, without the per-commit detail behind them.
+#[derive(Serialize)]
+struct ComparisonJson<'a> {
+    before: RangeSummary<'a>,
+    after: RangeSummary<'a>,
+    overall_delta: f32,
+    verdict: Verdict,
+    criteria: &'a [CriterionDelta],
+}
+
+#[derive(Serialize)]
+struct RangeSummary<'a> {
+    base_sha: &'a str,
+    head_sha: &'a str,
+    commits: usize,
+    overall_score: f32,
+}
+
+impl<'a> From<&'a RangeAssessment> for RangeSummary<'a> {
+    fn from(assessment: &'a RangeAssessment) -> Self {
+        Self {
+            base_sha: &assessment.base_sha,
+            head_sha: &assessment.head_sha,
+            commits: assessment.commit_assessments.len(),
+            overall_score: assessment.overall_score,
+        }
+    }
+}
+
 fn format_comparison_json(comparison: &AssessmentComparison) -> String {
-    serde_json::to_string_pretty(comparison).unwrap_or_else(|e| format!("Error: {}", e))
+    let json = ComparisonJson {
+        before: (&comparison.before).into(),
+        after: (&comparison.after).into(),
+        overall_delta: comparison.overall_delta,
+        verdict: comparison.verdict,
+        criteria: &comparison.criteria,
+    };
+    serde_json::to_string_pretty(&json).unwrap_or_else(|e| format!("Error: {}", e))
 }
 
 fn format_comparison_markdown(comparison: &AssessmentComparison) -> String {
@@ -440,6 +510,8 @@ mod tests {
             commit_assessments: vec![CommitAssessment {
                 commit_sha: "abc12345".to_string(),
                 commit_message: "Test commit".to_string(),
+                author: "Test User <test@example.com>".to_string(),
+                authored_at: 1_700_000_000,
                 criterion_scores: vec![CriterionScore {
                     criterion_id: CriterionId::Atomicity,
                     level: 4,
@@ -455,28 +527,111 @@ mod tests {
             aggregate_scores: HashMap::new(),
             overall_score: 0.8,
             range_observations: vec![],
+            verdict: Some("good".to_string()),
         }
     }
 
     #[test]
     fn pretty_format_includes_score() {
         let assessment = make_test_assessment();
-        let output = format_assessment(&assessment, OutputFormat::Pretty, false);
+        let output = format_assessment(&assessment, OutputFormat::Pretty, false, true);
         assert!(output.contains("80.0%"));
+        assert!(output.contains("Authored by Test User <test@example.com>"));
     }
 
     #[test]
     fn compact_format_is_brief() {
         let assessment = make_test_assessment();
-        let output = format_assessment(&assessment, OutputFormat::Compact, false);
+        let output = format_assessment(&assessment, OutputFormat::Compact, false, true);
         assert!(output.lines().count() <= 3);
     }
 
     #[test]
     fn json_format_is_valid() {
         let assessment = make_test_assessment();
-        let output = format_assessment(&assessment, OutputFormat::Json, false);
+        let output = format_assessment(&assessment, OutputFormat::Json, false, true);
         let parsed: Result<RangeAssessment, _> = serde_json::from_str(&output);
         assert!(parsed.is_ok());
     }
+
+    #[test]
+    fn every_format_shows_the_verdict() {
+        let mut assessment = make_test_assessment();
+        let shows = |assessment: &RangeAssessment, format, text: &str| {
+            format_assessment(assessment, format, false, false).contains(text)
+        };
+        assert!(shows(
+            &assessment,
+            OutputFormat::Pretty,
+            "Overall Score: 80.0% (good)"
+        ));
+        assert!(shows(
+            &assessment,
+            OutputFormat::Markdown,
+            "**Verdict**: good"
+        ));
+        assert!(shows(
+            &assessment,
+            OutputFormat::Compact,
+            "Overall: 80.0% (good)"
+        ));
+        assert!(shows(
+            &assessment,
+            OutputFormat::Json,
+            "\"verdict\": \"good\""
+        ));
+
+        // Saved assessments from before verdicts still load and print
+        assessment.verdict = None;
+        assert!(shows(
+            &assessment,
+            OutputFormat::Pretty,
+            "Overall Score: 80.0%\n"
+        ));
+        assert!(!shows(&assessment, OutputFormat::Json, "verdict"));
+    }
+
+    #[test]
+    fn pretty_format_uses_color_only_when_enabled() {
+        let assessment = make_test_assessment();
+        let colored = format_assessment(&assessment, OutputFormat::Pretty, false, true);
+        let plain = format_assessment(&assessment, OutputFormat::Pretty, false, false);
+        assert!(colored.contains('\x1b'));
+        assert!(!plain.contains('\x1b'));
+    }
+
+    #[test]
+    fn non_pretty_formats_are_never_colored() {
+        let assessment = make_test_assessment();
+        for format in [
+            OutputFormat::Json,
+            OutputFormat::Markdown,
+            OutputFormat::Compact,
+        ] {
+            let output = format_assessment(&assessment, format, true, true);
+            assert!(!output.contains('\x1b'), "{:?} output was colored", format);
+        }
+    }
+
+    #[test]
+    fn comparison_json_lists_deltas_and_verdict() {
+        let mut comparison =
+            crate::assessment::compare_assessments(make_test_assessment(), make_test_assessment());
+        comparison.criteria.push(CriterionDelta {
+            criterion_id: CriterionId::Atomicity,
+            before: 4.0,
+            after: 3.0,
+            delta: -1.0,
+            verdict: Verdict::Regressed,
+        });
+        comparison.verdict = Verdict::Regressed;
+
+        let output = format_comparison(&comparison, OutputFormat::Json);
+        let parsed: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(parsed["verdict"], "regressed");
+        assert_eq!(parsed["criteria"][0]["criterion_id"], "atomicity");
+        assert_eq!(parsed["criteria"][0]["verdict"], "regressed");
+        assert_eq!(parsed["before"]["commits"], 1);
+        assert!(parsed["before"].get("commit_assessments").is_none());
+    }
 }
@@ -1,9 +1,10 @@
 //! Core types for commit assessment.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::assessment::criteria::CriterionId;
+use crate::repo_config::RepoConfigError;
 
 /// A single level within a criterion's rubric (1-5 scale).
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -58,6 +59,12 @@ pub struct CommitAssessment {
     pub commit_sha: String,
     /// Short message of the commit.
     pub commit_message: String,
+    /// Author of the commit as `Name <email>`.
+    #[serde(default)]
+    pub author: String,
+    /// Author date in seconds since the Unix epoch.
+    #[serde(default)]
+    pub authored_at: i64,
     /// Individual criterion scores.
     pub criterion_scores: Vec<CriterionScore>,
     /// Overall weighted score (0.0 to 1.0).
@@ -95,6 +102,85 @@ pub struct RangeAssessment {
     pub overall_score: f32,
     /// Range-level observations.
     pub range_observations: Vec<String>,
+    /// Label of the band the overall score falls in (see [`VerdictBands`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verdict: Option<String>,
+}
+
+/// Named score bands giving the overall score a label, such as "good" from
+/// 0.75 up. A score gets the label of the highest threshold it reaches, or
+/// the lowest band's below all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerdictBands {
+    /// Highest threshold first
+    bands: Vec<(String, f32)>,
+}
+
+impl VerdictBands {
+    pub fn new(bands: impl IntoIterator<Item = (String, f32)>) -> Self {
+        let mut bands: Vec<_> = bands.into_iter().collect();
+        bands.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Self { bands }
+    }
+
+    /// Move the bands named in `thresholds` and add the ones not named yet.
+    /// Thresholds are scores, so anything outside 0.0..=1.0 (or NaN) is
+    /// rejected.
+    pub fn with_thresholds(
+        self,
+        thresholds: &BTreeMap<String, f32>,
+    ) -> Result<Self, RepoConfigError> {
+        if let Some((name, value)) = thresholds.iter().find(|(_, t)| !(0.0..=1.0).contains(*t)) {
+            return Err(RepoConfigError::VerdictThreshold {
+                name: name.clone(),
+                value: *value,
+            });
+        }
+        let mut bands: BTreeMap<String, f32> = self.bands.into_iter().collect();
+        bands.extend(thresholds.iter().map(|(name, t)| (name.clone(), *t)));
+        Ok(Self::new(bands))
+    }
+
+    /// The label for `score` (0.0 to 1.0).
+    pub fn verdict(&self, score: f32) -> &str {
+        self.bands
+            .iter()
+            .find(|(_, threshold)| score >= *threshold)
+            .or(self.bands.last())
+            .map_or("", |(name, _)| name.as_str())
+    }
+}
+
+impl Default for VerdictBands {
+    fn default() -> Self {
+        Self::new([
+            ("excellent".to_string(), 0.9),
+            ("good".to_string(), 0.75),
+            ("needs-work".to_string(), 0.5),
+            ("poor".to_string(), 0.0),
+        ])
+    }
+}
+
+/// Whether a score moved by more than noise between two assessments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Improved,
+    Regressed,
+    #[default]
+    Unchanged,
+}
+
+/// Change in one criterion's mean score between two assessments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriterionDelta {
+    pub criterion_id: CriterionId,
+    pub before: f32,
+    pub after: f32,
+    /// `after - before` (positive = improvement).
+    pub delta: f32,
+    pub verdict: Verdict,
 }
 
 /// Comparison between two assessments (before/after).
@@ -104,14 +190,26 @@ pub struct AssessmentComparison {
     pub after: RangeAssessment,
     /// Change in overall score.
     pub overall_delta: f32,
-    /// Per-criterion deltas (positive = improvement).
-    pub criterion_deltas: HashMap<CriterionId, f32>,
+    /// Per-criterion deltas, for criteria scored in both assessments.
+    pub criteria: Vec<CriterionDelta>,
+    /// Regressed if any criterion regressed, else improved if any improved.
+    #[serde(default)]
+    pub verdict: Verdict,
     /// Summary of improvements.
     pub improvements: Vec<String>,
     /// Summary of regressions.
     pub regressions: Vec<String>,
 }
 
+impl AssessmentComparison {
+    /// Criteria whose mean score dropped by more than noise.
+    pub fn regressed(&self) -> impl Iterator<Item = &CriterionDelta> {
+        self.criteria
+            .iter()
+            .filter(|c| c.verdict == Verdict::Regressed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -128,6 +226,41 @@ mod tests {
         assert_eq!(level.indicators.len(), 2);
     }
 
+    #[test]
+    fn verdict_bands_label_scores() {
+        let bands = VerdictBands::default();
+        assert_eq!(bands.verdict(0.95), "excellent");
+        assert_eq!(bands.verdict(0.75), "good");
+        assert_eq!(bands.verdict(0.6), "needs-work");
+        assert_eq!(bands.verdict(0.1), "poor");
+
+        let custom = bands
+            .with_thresholds(&BTreeMap::from([
+                ("good".to_string(), 0.8),
+                ("ship-it".to_string(), 0.99),
+            ]))
+            .unwrap();
+        assert_eq!(custom.verdict(0.78), "needs-work");
+        assert_eq!(custom.verdict(0.8), "good");
+        assert_eq!(custom.verdict(1.0), "ship-it");
+
+        let no_floor = VerdictBands::new([("fine".to_string(), 0.5)]);
+        assert_eq!(no_floor.verdict(0.2), "fine");
+        assert_eq!(VerdictBands::new([]).verdict(0.2), "");
+    }
+
+    #[test]
+    fn verdict_thresholds_must_be_scores() {
+        for bad in [f32::NAN, -0.1, 1.5] {
+            let err = VerdictBands::default()
+                .with_thresholds(&BTreeMap::from([("good".to_string(), bad)]))
+                .unwrap_err();
+            assert!(
+                matches!(err, RepoConfigError::VerdictThreshold { ref name, .. } if name == "good")
+            );
+        }
+    }
+
     #[test]
     fn criterion_score_serialization() {
         let score = CriterionScore {
@@ -0,0 +1,219 @@
+//! Structured audit trail of a reabsorb run.
+//!
+//! The app, the plan executor and LLM clients emit [`AuditEvent`]s to an
+//! [`AuditSink`]. The CLI's `--audit-log` writes them as JSON lines with
+//! [`JsonlAuditLog`]; library users can plug in their own sink.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use log::warn;
+use serde::Serialize;
+
+use crate::llm::{LlmClient, LlmError};
+use crate::models::Strategy;
+
+/// Something that happened during a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// The commit range being planned
+    RangeResolved { base: String, head: String },
+    /// The strategy that drafted the plan
+    StrategyChosen { strategy: Strategy },
+    /// One LLM round trip. Prompts are hashed rather than stored, as they
+    /// contain the whole diff.
+    LlmCall {
+        prompt_hash: String,
+        prompt_bytes: usize,
+        duration_ms: u64,
+        ok: bool,
+    },
+    /// A validation issue in the drafted plan
+    ValidationIssue { issue: String },
+    /// A new commit and the source commits its changes came from
+    CommitCreated {
+        /// 1-based position in the plan
+        index: usize,
+        source_shas: Vec<String>,
+        sha: String,
+        /// Hunks the commit applies
+        hunks: usize,
+    },
+    /// Whether the rewritten branch matched the original tree
+    IntegrityChecked { ok: bool, detail: Option<String> },
+}
+
+/// Receives audit events. Recording must not fail the run, so sinks handle
+/// their own errors.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: &AuditEvent);
+}
+
+/// Every sink in the list receives each event, in order.
+impl AuditSink for Vec<Arc<dyn AuditSink>> {
+    fn record(&self, event: &AuditEvent) {
+        for sink in self {
+            sink.record(event);
+        }
+    }
+}
+
+/// Appends each event as one JSON object per line, with a timestamp.
+pub struct JsonlAuditLog {
+    file: Mutex<File>,
+}
+
+#[derive(Serialize)]
+struct Line<'a> {
+    ts: String,
+    #[serde(flatten)]
+    event: &'a AuditEvent,
+}
+
+impl JsonlAuditLog {
+    /// Open `path` for appending, creating it if needed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl AuditSink for JsonlAuditLog {
+    fn record(&self, event: &AuditEvent) {
+        let line = Line {
+            ts: chrono::Utc::now().to_rfc3339(),
+            event,
+        };
+        let result = serde_json::to_string(&line)
+            .map_err(io::Error::other)
+            .and_then(|json| {
+                let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+                writeln!(file, "{}", json)
+            });
+        if let Err(err) = result {
+            warn!("Failed to write audit log: {}", err);
+        }
+    }
+}
+
+/// Wraps an LLM client to record an [`AuditEvent::LlmCall`] per completion.
+pub struct AuditedLlmClient {
+    inner: Arc<dyn LlmClient>,
+    sink: Arc<dyn AuditSink>,
+}
+
+impl AuditedLlmClient {
+    pub fn new(inner: Arc<dyn LlmClient>, sink: Arc<dyn AuditSink>) -> Self {
+        Self { inner, sink }
+    }
+}
+
+impl LlmClient for AuditedLlmClient {
+    fn complete(&self, prompt: &str) -> Result<String, LlmError> {
+        let started = Instant::now();
+        let result = self.inner.complete(prompt);
+        self.sink.record(&AuditEvent::LlmCall {
+            prompt_hash: prompt_hash(prompt),
+            prompt_bytes: prompt.len(),
+            duration_ms: started.elapsed().as_millis() as u64,
+            ok: result.is_ok(),
+        });
+        result
+    }
+}
+
+/// Wrap `client` for auditing if there is a sink.
+pub fn audited(
+    client: Arc<dyn LlmClient>,
+    sink: Option<&Arc<dyn AuditSink>>,
+) -> Arc<dyn LlmClient> {
+    match sink {
+        Some(sink) => Arc::new(AuditedLlmClient::new(client, sink.clone())),
+        None => client,
+    }
+}
+
+/// 64-bit FNV-1a of the prompt, stable across runs and toolchains so the same
+/// prompt can be recognised in different logs.
+fn prompt_hash(prompt: &str) -> String {
+    let hash = prompt.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::test_support::MockLlmClient;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        events: Mutex<Vec<AuditEvent>>,
+    }
+
+    impl AuditSink for RecordingSink {
+        fn record(&self, event: &AuditEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn audited_client_records_each_call() {
+        let sink = Arc::new(RecordingSink::default());
+        let client = audited(
+            Arc::new(MockLlmClient::new("ok")),
+            Some(&(sink.clone() as Arc<dyn AuditSink>)),
+        );
+
+        assert_eq!(client.complete("hello").unwrap(), "ok");
+        client.complete("hello").unwrap();
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        let AuditEvent::LlmCall {
+            prompt_hash: hash,
+            prompt_bytes,
+            ok,
+            ..
+        } = &events[0]
+        else {
+            panic!("expected an LLM call, got {:?}", events[0]);
+        };
+        assert_eq!(hash, &prompt_hash("hello"));
+        assert_eq!(*prompt_bytes, 5);
+        assert!(ok);
+        assert_ne!(prompt_hash("hello"), prompt_hash("hello!"));
+    }
+
+    #[test]
+    fn jsonl_log_appends_tagged_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = JsonlAuditLog::open(&path).unwrap();
+        log.record(&AuditEvent::RangeResolved {
+            base: "abc".to_string(),
+            head: "def".to_string(),
+        });
+        log.record(&AuditEvent::StrategyChosen {
+            strategy: Strategy::Preserve,
+        });
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "range_resolved");
+        assert_eq!(lines[0]["base"], "abc");
+        assert!(lines[0]["ts"].is_string());
+        assert_eq!(lines[1]["event"], "strategy_chosen");
+    }
+}
@@ -1,13 +1,20 @@
 use clap::Parser;
+use env_logger::WriteStyle;
 use log::LevelFilter;
 
-use git_reabsorb::app::{App, StrategyFactory};
-use git_reabsorb::cli::{Cli, Command};
+use std::sync::Arc;
+
+use git_reabsorb::app::{doctor, list_models, open_repository, App, StrategyFactory};
+use git_reabsorb::audit::{AuditSink, JsonlAuditLog};
+use git_reabsorb::cli::{Cli, ColorChoice, Command};
+use git_reabsorb::color::should_color;
 use git_reabsorb::editor::SystemEditor;
 use git_reabsorb::features::Features;
-use git_reabsorb::git::{Git, GitOps};
-use git_reabsorb::llm::{LlmConfig, LlmProvider};
-use git_reabsorb::plan_store::FilePlanStore;
+use git_reabsorb::git::{base_branches_from_env, ref_prefix_from_env, CachingGit, GitOps};
+use git_reabsorb::llm::{LlmConfig, LlmProvider, Redaction};
+use git_reabsorb::plan_store::{FilePlanStore, STATE_DIR_ENV};
+use git_reabsorb::reorganize::HierarchicalConfig;
+use git_reabsorb::repo_config::{repo_config_path, RepoConfig};
 
 fn main() {
     let cli = Cli::parse();
@@ -26,8 +33,27 @@ fn main() {
         .filter_level(log_level)
         .format_target(true)
         .format_timestamp(None)
+        .write_style(match cli.color {
+            ColorChoice::Auto => WriteStyle::Auto,
+            ColorChoice::Always => WriteStyle::Always,
+            ColorChoice::Never => WriteStyle::Never,
+        })
         .init();
 
+    // Like `git -C`: everything after this, including plan storage, the
+    // editor and hooks, sees DIR as the working directory
+    if let Some(dir) = &cli.repo {
+        if let Err(err) = std::env::set_current_dir(dir) {
+            log::error!("Cannot change to {}: {}", dir.display(), err);
+            std::process::exit(1);
+        }
+    }
+    // The plan store reads the override from the environment; nothing has
+    // spawned threads yet, so setting it here is safe
+    if let Some(dir) = &cli.state_dir {
+        std::env::set_var(STATE_DIR_ENV, dir);
+    }
+
     // Initialize feature flags from environment, then apply CLI overrides
     let features = Features::from_env().with_overrides(cli.features.as_deref());
     Features::init_global(features);
@@ -38,17 +64,136 @@ fn main() {
         .provider
         .as_ref()
         .and_then(|s| s.parse::<LlmProvider>().ok());
-    let llm_config = LlmConfig::from_env().with_overrides(
+    let mut llm_config = LlmConfig::from_env().with_overrides(
         provider,
         cli.llm.model.clone(),
         cli.llm.opencode_backend.clone(),
     );
+    if cli.llm.claude_json {
+        llm_config = llm_config.with_claude_json(true);
+    }
+    if !cli.llm.fallback.is_empty() {
+        llm_config = llm_config.with_fallback(cli.llm.fallback.clone());
+    }
+
+    let to_usize = |n: Option<u64>| n.map(|n| n as usize);
+    if cli.no_llm_is_ignored() {
+        log::warn!("--no-llm only applies to --strategy hierarchical; ignoring it");
+    }
+    let base_config = if cli.hierarchical.no_llm {
+        HierarchicalConfig::heuristic_only()
+    } else {
+        HierarchicalConfig::default()
+    };
+    let hierarchical_config = base_config
+        .with_overrides(
+            to_usize(cli.hierarchical.parallel),
+            to_usize(cli.hierarchical.cluster_max_size),
+            to_usize(cli.hierarchical.cluster_cross_file_threshold),
+        )
+        .with_phase_models(
+            cli.hierarchical.analyze_model.clone(),
+            cli.hierarchical.plan_model.clone(),
+        )
+        .with_debug_dir(cli.hierarchical.debug_dir.clone());
+    if cli.hierarchical.print_config {
+        print!("{}", hierarchical_config);
+        return;
+    }
 
-    let git = Git::with_repo_root().expect("Not a git repository");
-    let editor = SystemEditor::new();
+    let ref_prefix = match ref_prefix_from_env() {
+        Ok(prefix) => prefix,
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // Doctor diagnoses a missing repo or git itself, so it runs before we require them
+    if let Some(Command::Doctor(args)) = &cli.command {
+        if let Err(err) = doctor::run(
+            &llm_config,
+            &ref_prefix,
+            args.check_llm,
+            should_color(cli.color),
+        ) {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(Command::Models) = &cli.command {
+        if let Err(err) = list_models(&llm_config) {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let git = match open_repository() {
+        Ok(git) => CachingGit::new(git.with_base_branches(base_branches_from_env())),
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let config_path = match repo_config_path(&git) {
+        Ok(path) => path,
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // Privacy settings come from the CLI and .reabsorb.toml together
+    let repo_config = match RepoConfig::load(&config_path) {
+        Ok(config) => config.llm,
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    let redact: Vec<String> = repo_config
+        .redact
+        .iter()
+        .chain(&cli.llm.redact)
+        .cloned()
+        .collect();
+    match Redaction::new(&redact) {
+        Ok(redaction) => {
+            let hide_paths = cli.llm.redact_paths || repo_config.redact_paths;
+            if hide_paths && !redaction.is_active() {
+                log::warn!("--llm-redact-paths has no effect without --llm-redact");
+            }
+            llm_config = llm_config.with_redaction(redaction.with_hide_paths(hide_paths));
+        }
+        Err(err) => {
+            log::error!("{}", err);
+            std::process::exit(1);
+        }
+    }
+
+    let audit = match cli.audit_log.as_deref().map(JsonlAuditLog::open) {
+        Some(Ok(log)) => Some(Arc::new(log) as Arc<dyn AuditSink>),
+        Some(Err(err)) => {
+            log::error!("Could not open audit log: {}", err);
+            std::process::exit(1);
+        }
+        None => None,
+    };
+    let editor = SystemEditor::new().with_command(cli.editor.clone());
     let namespace = determine_namespace(&git);
     let plan_store = FilePlanStore::new(namespace.clone());
-    let strategies = StrategyFactory::new().with_llm_config(llm_config.clone());
+    let strategies = StrategyFactory::new()
+        .with_llm_config(llm_config.clone())
+        .with_llm_chunk_size(cli.llm.chunk_size)
+        .with_llm_max_hunks(cli.llm.max_hunks)
+        .with_llm_compact_diff(cli.llm.compact_diff)
+        .with_time_budget(cli.llm.time_budget)
+        .with_rules_file(config_path)
+        .with_min_confidence(cli.llm.min_confidence)
+        .with_hierarchical_config(hierarchical_config);
 
     let mut app = App::new(
         git,
@@ -57,7 +202,11 @@ fn main() {
         strategies,
         llm_config,
         namespace.clone(),
-    );
+    )
+    .with_ref_prefix(&ref_prefix)
+    .with_color(should_color(cli.color))
+    .with_audit(audit)
+    .with_stats(cli.stats);
     match cli.command {
         Some(cmd) => {
             if let Err(err) = app.run(cmd) {
@@ -67,25 +216,7 @@ fn main() {
         }
         None => {
             // No subcommand: plan (with save) then apply
-            let mut plan_args = cli.plan.clone();
-            plan_args.save_plan = true;
-
-            if let Err(err) = app.run(Command::Plan(plan_args.clone())) {
-                log::error!("{}", err);
-                std::process::exit(1);
-            }
-
-            // If dry-run was specified, don't apply
-            if cli.plan.dry_run {
-                return;
-            }
-
-            let apply_args = git_reabsorb::cli::ApplyArgs {
-                resume: false,
-                execution: cli.execution.clone(),
-            };
-
-            if let Err(err) = app.run(Command::Apply(apply_args)) {
+            if let Err(err) = app.run_default(cli.plan, cli.execution) {
                 log::error!("{}", err);
                 std::process::exit(1);
             }
@@ -93,7 +224,7 @@ fn main() {
     }
 }
 
-fn determine_namespace(git: &Git) -> String {
+fn determine_namespace(git: &impl GitOps) -> String {
     let branch = git
         .current_branch_name()
         .unwrap_or_else(|_| "HEAD".to_string());
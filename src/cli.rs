@@ -1,11 +1,16 @@
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
 
 use crate::assessment::criteria::CriterionId;
 use crate::features::Feature;
-use crate::git::{GitError, GitOps};
+use crate::git::{GitError, GitOps, DEFAULT_BACKUP_NAME, EMPTY_TREE};
+use crate::llm::ProviderSpec;
+use crate::reorganize::llm::DEFAULT_MAX_HUNKS;
+use crate::reorganize::DEFAULT_HINT_MARKER;
+use crate::utils::{parse_confidence, parse_duration};
 
 /// Commit range (base is exclusive, head is inclusive).
 ///
@@ -37,6 +42,32 @@ impl FromStr for CommitRange {
     }
 }
 
+/// How a resolved range's base was chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RangeOrigin {
+    /// An explicit `base..head` (or single ref) range argument
+    Explicit,
+    /// The tip of the branch given with `--base`
+    BaseTip(String),
+    /// The merge-base of HEAD with an auto-detected base branch
+    AutoDetected { branch: String },
+    /// Every commit up to HEAD, from `--root`
+    Root,
+}
+
+impl std::fmt::Display for RangeOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeOrigin::Explicit => write!(f, "used explicit range"),
+            RangeOrigin::BaseTip(branch) => write!(f, "used --base tip of '{}'", branch),
+            RangeOrigin::AutoDetected { branch } => {
+                write!(f, "auto-detected base '{}' (merge-base with HEAD)", branch)
+            }
+            RangeOrigin::Root => write!(f, "--root: from the first commit"),
+        }
+    }
+}
+
 impl CommitRange {
     /// Resolve refs to actual commit SHAs.
     ///
@@ -47,6 +78,15 @@ impl CommitRange {
         base_override: Option<&str>,
         git: &G,
     ) -> Result<CommitRange, GitError> {
+        Self::resolve_with_origin(range, base_override, git).map(|(range, _)| range)
+    }
+
+    /// Like [`CommitRange::resolve`], but also report how the base was chosen.
+    pub fn resolve_with_origin<G: GitOps>(
+        range: Option<&CommitRange>,
+        base_override: Option<&str>,
+        git: &G,
+    ) -> Result<(CommitRange, RangeOrigin), GitError> {
         match (range, base_override) {
             (Some(r), None) => {
                 let base = git.resolve_ref(&r.base)?;
@@ -54,19 +94,33 @@ impl CommitRange {
                     Some(h) => git.resolve_ref(h)?,
                     None => git.get_head()?,
                 };
-                Ok(CommitRange {
-                    base,
-                    head: Some(head),
-                })
+                Ok((
+                    CommitRange {
+                        base,
+                        head: Some(head),
+                    },
+                    RangeOrigin::Explicit,
+                ))
+            }
+            (None, Some(branch)) => Ok((
+                CommitRange {
+                    base: git.resolve_ref(branch)?,
+                    head: Some(git.get_head()?),
+                },
+                RangeOrigin::BaseTip(branch.to_string()),
+            )),
+            (None, None) => {
+                let detected = git.detect_branch_base()?;
+                Ok((
+                    CommitRange {
+                        base: detected.sha,
+                        head: Some(git.get_head()?),
+                    },
+                    RangeOrigin::AutoDetected {
+                        branch: detected.branch,
+                    },
+                ))
             }
-            (None, Some(branch)) => Ok(CommitRange {
-                base: git.resolve_ref(branch)?,
-                head: Some(git.get_head()?),
-            }),
-            (None, None) => Ok(CommitRange {
-                base: git.find_branch_base()?,
-                head: Some(git.get_head()?),
-            }),
             (Some(_), Some(_)) => Err(GitError::CommandFailed(
                 "Cannot specify both range and --base".to_string(),
             )),
@@ -78,9 +132,18 @@ impl CommitRange {
         self.head.as_ref().expect("CommitRange not resolved")
     }
 
+    /// Every commit up to HEAD, based on [`EMPTY_TREE`].
+    pub fn root<G: GitOps>(git: &G) -> Result<CommitRange, GitError> {
+        Ok(CommitRange {
+            base: EMPTY_TREE.to_string(),
+            head: Some(git.get_head()?),
+        })
+    }
+
     /// Resolve for a single commit (used by reword command).
     ///
-    /// When head is None (single ref), returns that commit and its parent.
+    /// When head is None (single ref), returns that commit and its parent
+    /// ([`EMPTY_TREE`] for a root commit).
     /// When head is Some (range), resolves both refs.
     pub fn resolve_single_or_range<G: GitOps>(&self, git: &G) -> Result<CommitRange, GitError> {
         match &self.head {
@@ -91,10 +154,11 @@ impl CommitRange {
             None => {
                 // Single ref - get parent as base
                 let commit_sha = git.resolve_ref(&self.base)?;
-                let parent_ref = format!("{}^", commit_sha);
                 let parent_sha = git
-                    .resolve_ref(&parent_ref)
-                    .unwrap_or_else(|_| commit_sha.clone());
+                    .parents_of(&commit_sha)?
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| EMPTY_TREE.to_string());
                 Ok(CommitRange {
                     base: parent_sha,
                     head: Some(commit_sha),
@@ -113,6 +177,9 @@ pub struct Cli {
     #[command(flatten)]
     pub llm: LlmArgs,
 
+    #[command(flatten)]
+    pub hierarchical: HierarchicalArgs,
+
     #[command(flatten)]
     pub plan: PlanArgs,
 
@@ -129,6 +196,26 @@ pub struct Cli {
     )]
     pub features: Option<Vec<Feature>>,
 
+    /// Run as if started in DIR instead of the current directory (like `git -C`)
+    #[arg(short = 'C', long = "repo", global = true, value_name = "DIR")]
+    pub repo: Option<PathBuf>,
+
+    /// Keep saved plans in DIR instead of .git/reabsorb; resuming needs the same DIR
+    /// Can also be set via GIT_REABSORB_DIR env var
+    #[arg(
+        long = "state-dir",
+        global = true,
+        value_name = "DIR",
+        env = "GIT_REABSORB_DIR"
+    )]
+    pub state_dir: Option<PathBuf>,
+
+    /// Editor for commit messages and todo lists (may include arguments,
+    /// e.g. "code --wait"); takes precedence over $EDITOR and $VISUAL
+    /// Can also be set via GIT_REABSORB_EDITOR env var
+    #[arg(long, global = true, value_name = "CMD")]
+    pub editor: Option<String>,
+
     /// Increase verbosity (-v for debug, -vv for trace with LLM streaming)
     #[arg(short = 'v', long = "verbose", global = true, action = clap::ArgAction::Count)]
     pub verbosity: u8,
@@ -137,10 +224,57 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub quiet: bool,
 
+    /// When to colorize output (auto: only on a terminal, and never if NO_COLOR is set)
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto"
+    )]
+    pub color: ColorChoice,
+
+    /// Append a JSON-lines record of the run (range, strategy, LLM calls,
+    /// created commits, validation and integrity results) to PATH
+    #[arg(long, global = true, value_name = "PATH")]
+    pub audit_log: Option<PathBuf>,
+
+    /// After planning or applying, print how long the run took, the LLM calls
+    /// made (and how many failed), commits created, hunks moved and whether
+    /// the integrity check passed. `--stats=json` prints it as JSON
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "pretty"
+    )]
+    pub stats: Option<StatsFormat>,
+
     #[command(subcommand)]
     pub command: Option<Command>,
 }
 
+impl Cli {
+    /// The strategy this invocation plans with, if it plans at all.
+    pub fn planning_strategy(&self) -> Option<crate::models::Strategy> {
+        match &self.command {
+            None => Some(self.plan.strategy),
+            Some(Command::Plan(args)) => Some(args.strategy),
+            Some(_) => None,
+        }
+    }
+
+    /// Whether `--no-llm` was given to a run it has no effect on: it only
+    /// changes the hierarchical strategy.
+    pub fn no_llm_is_ignored(&self) -> bool {
+        self.hierarchical.no_llm
+            && self.planning_strategy() != Some(crate::models::Strategy::Hierarchical)
+    }
+}
+
 /// Global LLM configuration options.
 #[derive(Args, Debug, Clone, Default)]
 pub struct LlmArgs {
@@ -166,6 +300,169 @@ pub struct LlmArgs {
         env = "GIT_REABSORB_OPENCODE_BACKEND"
     )]
     pub opencode_backend: Option<String>,
+
+    /// Split the llm strategy's prompt into chunks of at most this many hunks,
+    /// planned in parallel (default: one prompt with every hunk)
+    /// Can also be set via GIT_REABSORB_LLM_CHUNK_SIZE env var
+    #[arg(
+        long = "llm-chunk-size",
+        global = true,
+        env = "GIT_REABSORB_LLM_CHUNK_SIZE",
+        value_name = "HUNKS"
+    )]
+    pub chunk_size: Option<usize>,
+
+    /// Refuse to send more than this many hunks to the llm strategy in one
+    /// prompt (0 for no limit); larger ranges suit --strategy hierarchical
+    /// Can also be set via GIT_REABSORB_LLM_MAX_HUNKS env var
+    #[arg(
+        long = "llm-max-hunks",
+        global = true,
+        env = "GIT_REABSORB_LLM_MAX_HUNKS",
+        value_name = "HUNKS",
+        default_value_t = DEFAULT_MAX_HUNKS
+    )]
+    pub max_hunks: usize,
+
+    /// Stop calling the LLM after this long (e.g. 90s, 5m, 1h30m) and finish
+    /// the plan with heuristics
+    /// Can also be set via GIT_REABSORB_TIME_BUDGET env var
+    #[arg(
+        long = "time-budget",
+        global = true,
+        env = "GIT_REABSORB_TIME_BUDGET",
+        value_name = "DURATION",
+        value_parser = parse_duration
+    )]
+    pub time_budget: Option<Duration>,
+
+    /// Put hunks the llm strategy places with less than this confidence
+    /// (0 to 1) into a final commit for manual review
+    /// Can also be set via GIT_REABSORB_MIN_CONFIDENCE env var
+    #[arg(
+        long = "min-confidence",
+        global = true,
+        env = "GIT_REABSORB_MIN_CONFIDENCE",
+        value_name = "SCORE",
+        value_parser = parse_confidence
+    )]
+    pub min_confidence: Option<f32>,
+
+    /// Ask the claude CLI for JSON output (--output-format json) so replies
+    /// wrapped in prose still parse; ignored by CLIs without the option
+    /// Can also be enabled via GIT_REABSORB_CLAUDE_JSON=1
+    #[arg(long = "claude-json", global = true)]
+    pub claude_json: bool,
+
+    /// Providers to try in order when one fails or returns an unparseable
+    /// reply, e.g. `opencode,claude`. Entries may pick a model with
+    /// `provider:model`; otherwise --llm-model applies to --llm-provider's entry
+    /// Can also be set via GIT_REABSORB_LLM_FALLBACK env var
+    #[arg(
+        long = "llm-fallback",
+        global = true,
+        env = "GIT_REABSORB_LLM_FALLBACK",
+        value_name = "PROVIDERS",
+        value_delimiter = ','
+    )]
+    pub fallback: Vec<ProviderSpec>,
+
+    /// Show hunks of files matching GLOB (e.g. `*.env`, `secrets/**`) to the
+    /// LLM only as "[redacted: N lines in PATH]"; they are applied as usual.
+    /// Repeatable, and added to `[llm] redact` in .reabsorb.toml
+    /// Can also be set via GIT_REABSORB_LLM_REDACT env var
+    #[arg(
+        long = "llm-redact",
+        global = true,
+        env = "GIT_REABSORB_LLM_REDACT",
+        value_name = "GLOB",
+        value_delimiter = ','
+    )]
+    pub redact: Vec<String>,
+
+    /// Also hide the paths of --llm-redact files, which are otherwise still
+    /// shown to the LLM
+    #[arg(long = "llm-redact-paths", global = true)]
+    pub redact_paths: bool,
+
+    /// Show hunks to the llm strategy as word diffs (`[-old-]{+new+}`)
+    /// instead of full removed and added lines, to cut prompt size on
+    /// reformatting-heavy branches. The applied hunks are unchanged
+    #[arg(long = "llm-compact-diff", global = true)]
+    pub compact_diff: bool,
+}
+
+/// Tuning for the hierarchical strategy. Unset flags keep the defaults.
+#[derive(Args, Debug, Clone, Default)]
+pub struct HierarchicalArgs {
+    /// Split clusters with more than this many hunks (default: 20)
+    #[arg(
+        long = "cluster-max-size",
+        global = true,
+        value_name = "HUNKS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub cluster_max_size: Option<u64>,
+
+    /// Only ask the LLM about cross-file relationships once there are at
+    /// least this many hunks (default: 5)
+    #[arg(
+        long = "cluster-cross-file-threshold",
+        global = true,
+        value_name = "HUNKS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub cluster_cross_file_threshold: Option<u64>,
+
+    /// Maximum concurrent LLM calls per hierarchical phase (default: 8)
+    #[arg(
+        id = "hierarchical_parallel",
+        long = "hierarchical-parallel",
+        global = true,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..=64)
+    )]
+    pub parallel: Option<u64>,
+
+    /// Model for hierarchical analysis and clustering (many small calls; a
+    /// fast, cheap model usually suffices). Defaults to --llm-model
+    #[arg(
+        long = "analyze-model",
+        global = true,
+        value_name = "MODEL",
+        env = "GIT_REABSORB_ANALYZE_MODEL"
+    )]
+    pub analyze_model: Option<String>,
+
+    /// Model for hierarchical commit planning and repair (fewer calls that
+    /// decide the commit messages). Defaults to --llm-model
+    #[arg(
+        long = "plan-model",
+        global = true,
+        value_name = "MODEL",
+        env = "GIT_REABSORB_PLAN_MODEL"
+    )]
+    pub plan_model: Option<String>,
+
+    /// Run the hierarchical strategy without any LLM calls: offline and
+    /// deterministic, grouping by path and topic with template messages.
+    /// Other strategies ignore it, with a warning
+    #[arg(long = "no-llm", global = true)]
+    pub no_llm: bool,
+
+    /// Write each hierarchical phase's intermediate results to DIR as JSON
+    /// (analysis, clusters, planned commits, validation, final plan)
+    #[arg(
+        long = "debug-dir",
+        visible_alias = "output-dir",
+        global = true,
+        value_name = "DIR"
+    )]
+    pub debug_dir: Option<PathBuf>,
+
+    /// Print the effective hierarchical configuration and exit
+    #[arg(long = "print-config", global = true)]
+    pub print_config: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -174,6 +471,13 @@ pub enum Command {
     Plan(PlanArgs),
     /// Apply a previously saved plan
     Apply(ApplyArgs),
+    /// Validate a saved plan without changing it
+    Check(CheckArgs),
+    /// Validate a saved plan and repair it in place (e.g. after hand-editing)
+    Repair,
+    /// Reorder the saved plan's commits or fold them together in an editor,
+    /// like a rebase todo list
+    Reorder,
     /// Reset to the pre-reabsorb ref created during planning
     Reset,
     /// Show status of current plan (for debugging)
@@ -184,18 +488,168 @@ pub enum Command {
     Compare(CompareArgs),
     /// Reword commit messages using LLM
     Reword(RewordArgs),
+    /// List past pre-reabsorb states, optionally resetting to one
+    Reflog(ReflogArgs),
+    /// Check git, the repository, the LLM setup, and leftover reabsorb state
+    Doctor(DoctorArgs),
+    /// Rewrite the last commit's message without touching its changes
+    Amend(AmendArgs),
+    /// Squash a range into the commit at its base, keeping that commit's message
+    #[command(alias = "squash-into-existing")]
+    Fold(FoldArgs),
+    /// List saved plans, or archived ones with --archived
+    List(ListArgs),
+    /// List the models the configured LLM provider offers, for --llm-model
+    Models,
+}
+
+/// Default limit on how many commits a plan may create before apply asks for confirmation.
+pub const DEFAULT_MAX_APPLY_COMMITS: usize = 50;
+
+/// Environment variable that turns on `--no-verify`
+pub const NO_VERIFY_ENV: &str = "GIT_REABSORB_NO_VERIFY";
+
+/// Read a yes/no environment value the way clap's boolish flags do.
+fn parse_boolish(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "y" | "on" => Some(true),
+        "" | "0" | "false" | "no" | "n" | "off" => Some(false),
+        _ => None,
+    }
 }
 
 /// Shared args for commit execution (used by both plan+apply and apply)
-#[derive(Args, Debug, Clone, Default)]
+#[derive(Args, Debug, Clone)]
 pub struct ExecutionArgs {
-    /// Skip pre-commit and commit-msg hooks
-    #[arg(long)]
+    /// Skip pre-commit and commit-msg hooks. Can also be set with
+    /// GIT_REABSORB_NO_VERIFY=1 or `no-verify = true` in .reabsorb.toml
+    #[arg(long, overrides_with = "verify")]
     pub no_verify: bool,
 
+    /// Run commit hooks even if GIT_REABSORB_NO_VERIFY or .reabsorb.toml
+    /// turns them off
+    #[arg(long, overrides_with = "no_verify")]
+    pub verify: bool,
+
     /// Use planned messages without opening an editor
     #[arg(long = "no-editor")]
     pub no_editor: bool,
+
+    /// Refuse to apply plans that would create more commits than this
+    /// Can also be set via GIT_REABSORB_MAX_COMMITS env var
+    #[arg(
+        long = "max-commits",
+        env = "GIT_REABSORB_MAX_COMMITS",
+        default_value_t = DEFAULT_MAX_APPLY_COMMITS
+    )]
+    pub max_commits: usize,
+
+    /// Apply even if the plan exceeds --max-commits
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
+    /// Rewrite commits even if they are already on a remote-tracking branch
+    #[arg(long)]
+    pub allow_pushed: bool,
+
+    /// Run this shell command after each new commit (like `rebase --exec`)
+    /// and stop at the first commit where it fails
+    /// Can also be set via GIT_REABSORB_TEST_CMD env var
+    #[arg(
+        long = "test-cmd",
+        visible_alias = "exec",
+        env = "GIT_REABSORB_TEST_CMD",
+        value_name = "COMMAND"
+    )]
+    pub test_cmd: Option<String>,
+
+    /// Build commits in a temporary index with commit-tree instead of staging
+    /// into the repository's index, which keeps anything you have staged.
+    /// Hooks don't run in this mode
+    #[arg(long = "index-free")]
+    pub index_free: bool,
+
+    /// Skip the commit message checks configured in .reabsorb.toml's [lint]
+    #[arg(long = "no-lint")]
+    pub no_lint: bool,
+
+    /// Check the tree after every commit and stop at the first one that
+    /// leaves a file in the wrong state. Slower; for debugging
+    #[arg(long = "verify-each")]
+    pub verify_each: bool,
+
+    /// After a successful apply, move the plan (with the created SHAs) to
+    /// .git/reabsorb/archive/<timestamp>.json instead of deleting it
+    #[arg(long = "keep-plan")]
+    pub keep_plan: bool,
+
+    /// Before rewriting, create a branch at the original HEAD. Without a
+    /// name, uses reabsorb/backup/{timestamp}; a taken name gets a -2, -3...
+    /// suffix
+    #[arg(
+        long = "backup-branch",
+        value_name = "NAME",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = DEFAULT_BACKUP_NAME
+    )]
+    pub backup_branch: Option<String>,
+
+    /// Like --backup-branch, but creates a tag
+    #[arg(
+        long = "backup-tag",
+        value_name = "NAME",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = DEFAULT_BACKUP_NAME
+    )]
+    pub backup_tag: Option<String>,
+}
+
+impl ExecutionArgs {
+    /// Whether to skip commit hooks: `--verify` or `--no-verify` (the last
+    /// one given), then GIT_REABSORB_NO_VERIFY, then the repository default.
+    pub fn skip_hooks(&self, repo_default: bool) -> bool {
+        let env = std::env::var(NO_VERIFY_ENV).ok();
+        self.skip_hooks_with_env(env.as_deref(), repo_default)
+    }
+
+    fn skip_hooks_with_env(&self, env: Option<&str>, repo_default: bool) -> bool {
+        if self.verify || self.no_verify {
+            return self.no_verify;
+        }
+        match env {
+            Some(value) => parse_boolish(value).unwrap_or_else(|| {
+                log::warn!(
+                    "Ignoring {}={:?}: expected 1/0, true/false, yes/no or on/off",
+                    NO_VERIFY_ENV,
+                    value
+                );
+                repo_default
+            }),
+            None => repo_default,
+        }
+    }
+}
+
+impl Default for ExecutionArgs {
+    fn default() -> Self {
+        Self {
+            no_verify: false,
+            verify: false,
+            no_editor: false,
+            max_commits: DEFAULT_MAX_APPLY_COMMITS,
+            yes: false,
+            allow_pushed: false,
+            test_cmd: None,
+            index_free: false,
+            no_lint: false,
+            verify_each: false,
+            keep_plan: false,
+            backup_branch: None,
+            backup_tag: None,
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone)]
@@ -210,6 +664,11 @@ pub struct PlanArgs {
     #[arg(short, long)]
     pub base: Option<String>,
 
+    /// Reabsorb every commit up to HEAD, including the repository's first
+    /// (like `git rebase --root`)
+    #[arg(long, conflicts_with_all = ["range", "base"])]
+    pub root: bool,
+
     /// Reorganization strategy
     #[arg(short = 's', long, value_enum, default_value = "preserve")]
     pub strategy: crate::models::Strategy,
@@ -221,6 +680,52 @@ pub struct PlanArgs {
     /// Save plan to disk for later execution with 'apply'
     #[arg(long = "save-plan")]
     pub save_plan: bool,
+
+    /// Only reorganize commits whose author contains PATTERN (case-insensitive,
+    /// matched against "Name <email>"); other commits are kept as-is.
+    /// Filters by the recorded commit author only, not co-authors.
+    #[arg(long, value_name = "PATTERN")]
+    pub author: Option<String>,
+
+    /// Show why changes were grouped into each commit (most useful with
+    /// the llm and hierarchical strategies)
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Keep whitespace-only hunks out of planning; they are still applied,
+    /// in a final "Whitespace changes" commit
+    #[arg(long)]
+    pub ignore_whitespace: bool,
+
+    /// Fail instead of warning when the drafted plan doesn't validate
+    #[arg(long)]
+    pub strict: bool,
+
+    /// With --strategy clean-preserve, keep whitespace-only commits instead
+    /// of folding them into the commit before
+    #[arg(long)]
+    pub keep_empty: bool,
+
+    /// Comment marker for --strategy comment-hint: an added line holding
+    /// only a comment like "// reabsorb: Add login" groups its hunk
+    #[arg(long, value_name = "MARKER", default_value = DEFAULT_HINT_MARKER)]
+    pub hint_marker: String,
+
+    /// Leave hint comment lines out of the new commits; they stay in the
+    /// work tree as uncommitted changes
+    #[arg(long)]
+    pub strip_hints: bool,
+
+    /// Also merge commits whose changes are all whitespace into the commit
+    /// before them (commits that change nothing are always dropped)
+    #[arg(long)]
+    pub fold_whitespace_commits: bool,
+
+    /// Print the full plan (including hunk contents) to stdout as the JSON
+    /// that would be saved. Can be large; combine with --dry-run to skip
+    /// saving it.
+    #[arg(long)]
+    pub dump_plan: bool,
 }
 
 #[derive(Args, Debug)]
@@ -229,6 +734,15 @@ pub struct ApplyArgs {
     #[arg(long)]
     pub resume: bool,
 
+    /// Resume even if HEAD no longer contains the commits already created
+    #[arg(long, requires = "resume")]
+    pub force: bool,
+
+    /// Redo already-created commits starting at this one (1-based), e.g. to
+    /// fix a bad message: HEAD moves back to the commit before it
+    #[arg(long, requires = "resume", value_name = "N")]
+    pub from: Option<usize>,
+
     #[command(flatten)]
     pub execution: ExecutionArgs,
 }
@@ -244,11 +758,32 @@ pub struct AssessArgs {
     #[arg(short, long)]
     pub base: Option<String>,
 
+    /// Assess exactly these commits (comma-separated SHAs or revisions)
+    /// instead of a range, e.g. the non-contiguous commits of a PR
+    #[arg(long, value_delimiter = ',', value_name = "SHAS", conflicts_with_all = ["range", "base"])]
+    pub commits: Option<Vec<String>>,
+
+    /// Assess uncommitted changes to tracked files (staged or not) as one
+    /// pseudo-commit: is this too much to commit at once?
+    #[arg(
+        long,
+        conflicts_with_all = ["range", "base", "commits", "compare", "compare_range", "resume", "dry_run"]
+    )]
+    pub working: bool,
+
+    /// Draft commit message for --working; message quality is skipped without it
+    #[arg(short, long, requires = "working")]
+    pub message: Option<String>,
+
     /// Criteria to assess (default: all)
     /// Options: atomicity, message_quality, logical_cohesion, scope, reversibility
     #[arg(short, long, value_delimiter = ',')]
     pub criteria: Option<Vec<String>>,
 
+    /// Named set of criteria; an explicit --criteria wins over it
+    #[arg(long, value_enum)]
+    pub preset: Option<CriteriaPreset>,
+
     /// Output format
     #[arg(short, long, value_enum, default_value = "pretty")]
     pub format: OutputFormat,
@@ -261,6 +796,11 @@ pub struct AssessArgs {
     #[arg(long)]
     pub compare: Option<PathBuf>,
 
+    /// Also assess this range and compare against it as the "before", e.g.
+    /// the pre-reabsorb history. Doubles the LLM calls
+    #[arg(long, value_name = "RANGE", conflicts_with = "compare")]
+    pub compare_range: Option<CommitRange>,
+
     /// Show full rationale and evidence in output
     #[arg(long)]
     pub full: bool,
@@ -268,6 +808,27 @@ pub struct AssessArgs {
     /// Maximum parallel commit assessments (default: 4)
     #[arg(short = 'j', long, default_value = "4")]
     pub parallel: usize,
+
+    /// List the commits and criteria that would be assessed, with the
+    /// number of LLM calls, without calling the LLM
+    #[arg(long, conflicts_with_all = ["save", "compare", "compare_range"])]
+    pub dry_run: bool,
+
+    /// Skip commits a failed or interrupted run already assessed, using its
+    /// checkpoint (assess-progress.json in the state directory)
+    #[arg(long, conflicts_with = "dry_run")]
+    pub resume: bool,
+}
+
+impl AssessArgs {
+    /// The criteria to assess: `--criteria`, else `--preset`, else all.
+    pub fn criterion_ids(&self) -> Result<Vec<CriterionId>, String> {
+        match (&self.criteria, self.preset) {
+            (Some(names), _) => names.iter().map(|name| name.parse()).collect(),
+            (None, Some(preset)) => Ok(preset.criteria().to_vec()),
+            (None, None) => Ok(CriterionId::all().to_vec()),
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone)]
@@ -283,6 +844,69 @@ pub struct CompareArgs {
     /// Output format
     #[arg(short, long, value_enum, default_value = "pretty")]
     pub format: OutputFormat,
+
+    /// Exit non-zero if any criterion regressed
+    #[arg(long)]
+    pub fail_on_regression: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReflogArgs {
+    /// Hard-reset to the entry with this index (as shown in the listing)
+    #[arg(long, value_name = "INDEX")]
+    pub reset: Option<usize>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct CheckArgs {
+    /// Output format (pretty or json)
+    #[arg(short, long, value_enum, default_value = "pretty")]
+    pub format: OutputFormat,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct AmendArgs {
+    /// New message (default: edit the current one)
+    #[arg(short, long)]
+    pub message: Option<String>,
+
+    /// Skip pre-commit and commit-msg hooks
+    #[arg(long)]
+    pub no_verify: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct FoldArgs {
+    /// Commits to fold into the base commit; required unless --base is given
+    /// Example: HEAD~3..HEAD folds the last three commits into HEAD~3
+    #[arg(value_name = "RANGE")]
+    pub range: Option<CommitRange>,
+
+    /// Fold into the tip of this branch
+    #[arg(short, long)]
+    pub base: Option<String>,
+
+    /// Show what would be folded without rewriting anything
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Rewrite commits even if they are already on a remote-tracking branch
+    #[arg(long)]
+    pub allow_pushed: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ListArgs {
+    /// List plans kept by 'apply --keep-plan' instead of pending ones
+    #[arg(long)]
+    pub archived: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct DoctorArgs {
+    /// Also send a short test prompt to the configured LLM provider
+    #[arg(long)]
+    pub check_llm: bool,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -301,6 +925,30 @@ pub struct RewordArgs {
     pub dry_run: bool,
 }
 
+/// Named criterion sets for `assess --preset`
+#[derive(Clone, Copy, Debug, ValueEnum, PartialEq, Eq)]
+pub enum CriteriaPreset {
+    /// Message quality and atomicity, for a quick check such as pre-push
+    Quick,
+    /// Every criterion
+    Full,
+    /// Logical cohesion and scope
+    Structure,
+}
+
+impl CriteriaPreset {
+    pub fn criteria(self) -> &'static [CriterionId] {
+        match self {
+            Self::Quick => &[CriterionId::MessageQuality, CriterionId::Atomicity],
+            Self::Full => CriterionId::all(),
+            Self::Structure => &[
+                CriterionId::LogicalCohesion,
+                CriterionId::ScopeAppropriateness,
+            ],
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum, Default)]
 pub enum OutputFormat {
     /// Human-readable formatted output
@@ -313,3 +961,185 @@ pub enum OutputFormat {
     /// Compact single-line per commit
     Compact,
 }
+
+/// How `--stats` prints the run summary.
+#[derive(Clone, Copy, Debug, ValueEnum, Default, PartialEq, Eq)]
+pub enum StatsFormat {
+    /// Human-readable summary
+    #[default]
+    Pretty,
+    /// One JSON object
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    /// Always emit color
+    Always,
+    /// Never emit color
+    Never,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_source_commit, MockGit};
+
+    fn repo() -> MockGit {
+        MockGit::new()
+            .with_commit(make_source_commit("base", "Initial"), vec![])
+            .with_ref("develop", "base")
+            .with_commit(make_source_commit("feature", "Feature"), vec![])
+            .with_branch_base("develop")
+    }
+
+    #[test]
+    fn resolves_range_from_each_source() {
+        let git = repo();
+
+        let (range, origin) = CommitRange::resolve_with_origin(None, None, &git).unwrap();
+        assert_eq!((range.base.as_str(), range.head()), ("base", "feature"));
+        assert!(matches!(origin, RangeOrigin::AutoDetected { branch } if branch == "develop"));
+
+        let (range, origin) =
+            CommitRange::resolve_with_origin(None, Some("develop"), &git).unwrap();
+        assert_eq!(range.base, "base");
+        assert!(matches!(origin, RangeOrigin::BaseTip(_)));
+
+        let explicit: CommitRange = "base..feature".parse().unwrap();
+        let (range, origin) =
+            CommitRange::resolve_with_origin(Some(&explicit), None, &git).unwrap();
+        assert_eq!(range.head(), "feature");
+        assert!(matches!(origin, RangeOrigin::Explicit));
+
+        let root = CommitRange::root(&git).unwrap();
+        assert_eq!((root.base.as_str(), root.head()), (EMPTY_TREE, "feature"));
+    }
+
+    #[test]
+    fn single_root_commit_is_based_on_the_empty_tree() {
+        let git = repo();
+
+        let single: CommitRange = "feature".parse().unwrap();
+        let range = single.resolve_single_or_range(&git).unwrap();
+        assert_eq!((range.base.as_str(), range.head()), ("base", "feature"));
+
+        let single: CommitRange = "base".parse().unwrap();
+        let range = single.resolve_single_or_range(&git).unwrap();
+        assert_eq!((range.base.as_str(), range.head()), (EMPTY_TREE, "base"));
+    }
+
+    #[test]
+    fn resolve_reports_missing_refs() {
+        let git = repo();
+        let missing: CommitRange = "nope..HEAD".parse().unwrap();
+        assert!(matches!(
+            CommitRange::resolve(Some(&missing), None, &git),
+            Err(GitError::RefNotFound { reference, .. }) if reference == "nope"
+        ));
+        assert!(CommitRange::resolve(Some(&missing), Some("develop"), &git).is_err());
+
+        let no_base = MockGit::new().with_commit(make_source_commit("root", "Root"), vec![]);
+        assert!(matches!(
+            CommitRange::resolve(None, None, &no_base),
+            Err(GitError::NoBranchBase(_))
+        ));
+    }
+
+    #[test]
+    fn verify_overrides_the_repo_no_verify_default() {
+        let mut args = ExecutionArgs::default();
+        assert!(!args.skip_hooks(false));
+        assert!(args.skip_hooks(true));
+
+        args.verify = true;
+        assert!(!args.skip_hooks(true));
+
+        args.verify = false;
+        args.no_verify = true;
+        assert!(args.skip_hooks(false));
+    }
+
+    #[test]
+    fn no_verify_env_sits_between_the_flags_and_the_repo_default() {
+        let args = ExecutionArgs::default();
+        assert!(args.skip_hooks_with_env(Some("1"), false));
+        assert!(args.skip_hooks_with_env(Some("yes"), false));
+        assert!(!args.skip_hooks_with_env(Some("0"), true));
+        assert!(args.skip_hooks_with_env(Some("maybe"), true));
+
+        let cli = Cli::try_parse_from(["git-reabsorb", "apply", "--verify"]).unwrap();
+        let Some(Command::Apply(apply)) = cli.command else {
+            panic!("expected apply");
+        };
+        assert!(!apply.execution.skip_hooks_with_env(Some("1"), true));
+
+        // The last of --verify and --no-verify wins
+        let cli =
+            Cli::try_parse_from(["git-reabsorb", "apply", "--verify", "--no-verify"]).unwrap();
+        let Some(Command::Apply(apply)) = cli.command else {
+            panic!("expected apply");
+        };
+        assert!(apply.execution.skip_hooks_with_env(None, false));
+    }
+
+    #[test]
+    fn criteria_override_the_preset() {
+        let cli = Cli::try_parse_from(["git-reabsorb", "assess", "--preset", "quick"]).unwrap();
+        let Some(Command::Assess(args)) = cli.command else {
+            panic!("expected assess");
+        };
+        assert_eq!(
+            args.criterion_ids().unwrap(),
+            vec![CriterionId::MessageQuality, CriterionId::Atomicity]
+        );
+
+        let cli = Cli::try_parse_from([
+            "git-reabsorb",
+            "assess",
+            "--preset",
+            "full",
+            "--criteria",
+            "scope",
+        ])
+        .unwrap();
+        let Some(Command::Assess(args)) = cli.command else {
+            panic!("expected assess");
+        };
+        assert_eq!(
+            args.criterion_ids().unwrap(),
+            vec![CriterionId::ScopeAppropriateness]
+        );
+    }
+
+    #[test]
+    fn no_llm_only_counts_for_hierarchical_plans() {
+        let ignored = |args: &[&str]| {
+            Cli::try_parse_from(["git-reabsorb"].iter().chain(args))
+                .unwrap()
+                .no_llm_is_ignored()
+        };
+
+        assert!(!ignored(&["--no-llm", "-s", "hierarchical"]));
+        assert!(!ignored(&["plan", "--no-llm", "-s", "hierarchical"]));
+        assert!(ignored(&["--no-llm", "-s", "llm"]));
+        assert!(ignored(&["plan", "--no-llm", "-s", "preserve"]));
+        assert!(!ignored(&["-s", "llm"]));
+    }
+
+    #[test]
+    fn stats_only_offers_pretty_and_json() {
+        let stats = |args: &[&str]| {
+            Cli::try_parse_from(["git-reabsorb"].iter().chain(args)).map(|cli| cli.stats)
+        };
+
+        assert_eq!(stats(&[]).unwrap(), None);
+        assert_eq!(stats(&["--stats"]).unwrap(), Some(StatsFormat::Pretty));
+        assert_eq!(stats(&["--stats=json"]).unwrap(), Some(StatsFormat::Json));
+        assert!(stats(&["--stats=markdown"]).is_err());
+        assert!(stats(&["--stats=compact"]).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Terminal color handling for human-readable output.
+
+use std::io::IsTerminal;
+
+use crate::cli::ColorChoice;
+
+/// Decide whether stdout output should be colorized.
+///
+/// `auto` colors only when stdout is a terminal and `NO_COLOR` is unset or
+/// empty (see <https://no-color.org>).
+pub fn should_color(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !no_color_env() && std::io::stdout().is_terminal(),
+    }
+}
+
+fn no_color_env() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+/// Text styles used by human-readable output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Bold,
+    /// Black on green, used to mark the achieved rubric level.
+    Highlight,
+    Green,
+    Yellow,
+    Red,
+}
+
+impl Style {
+    fn sgr(self) -> &'static str {
+        match self {
+            Style::Bold => "1",
+            Style::Highlight => "42;30",
+            Style::Green => "32",
+            Style::Yellow => "33",
+            Style::Red => "31",
+        }
+    }
+}
+
+/// Wrap `text` in the ANSI escapes for `style`, or return it unchanged when
+/// `enabled` is false.
+pub fn paint(text: &str, style: Style, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", style.sgr(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paint_respects_enabled() {
+        assert_eq!(paint("x", Style::Bold, true), "\x1b[1mx\x1b[0m");
+        assert_eq!(paint("x", Style::Highlight, false), "x");
+    }
+
+    #[test]
+    fn explicit_choices_ignore_environment() {
+        assert!(should_color(ColorChoice::Always));
+        assert!(!should_color(ColorChoice::Never));
+    }
+}
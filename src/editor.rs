@@ -1,6 +1,7 @@
 use std::env;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 use std::process::Command;
 
 /// Errors from editor operations
@@ -19,21 +20,50 @@ pub enum EditorError {
 /// Trait for opening an editor - allows mocking in tests
 pub trait Editor {
     /// Open editor with initial content, return the edited content.
-    /// The comment_help is appended as commented lines (# prefix) for guidance.
+    /// The comment_help is appended below a scissors line, as commented lines
+    /// (# prefix) for guidance; the message's own `#` lines are kept.
     fn edit(&self, initial: &str, comment_help: &str) -> Result<String, EditorError>;
 }
 
-/// System editor implementation - uses $EDITOR, $VISUAL, or fallbacks
-pub struct SystemEditor;
+/// Environment variable naming an editor for reabsorb only, checked before
+/// $EDITOR and $VISUAL.
+pub const EDITOR_ENV: &str = "GIT_REABSORB_EDITOR";
+
+/// Marks the start of the help block, as with `git commit --cleanup=scissors`.
+/// It and everything below it are dropped from the edited message.
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// System editor implementation - uses $GIT_REABSORB_EDITOR, $EDITOR, $VISUAL, or fallbacks
+pub struct SystemEditor {
+    command: Option<String>,
+}
 
 impl SystemEditor {
     pub fn new() -> Self {
-        Self
+        Self { command: None }
+    }
+
+    /// Always run `command` (which may carry arguments, e.g. `code --wait`)
+    /// instead of looking an editor up.
+    pub fn with_command(mut self, command: Option<String>) -> Self {
+        self.command = command;
+        self
     }
 
     /// Find the editor command to use
-    fn find_editor() -> Result<String, EditorError> {
-        // Try $EDITOR first, then $VISUAL, then fallbacks
+    fn find_editor(&self) -> Result<String, EditorError> {
+        // An editor chosen for reabsorb is used as is: if it fails, that is an
+        // error rather than a reason to try the next one
+        if let Some(command) = &self.command {
+            return Ok(command.clone());
+        }
+        if let Ok(editor) = env::var(EDITOR_ENV) {
+            if !editor.trim().is_empty() {
+                return Ok(editor);
+            }
+        }
+
+        // Then $EDITOR, then $VISUAL, then fallbacks
         if let Ok(editor) = env::var("EDITOR") {
             return Ok(editor);
         }
@@ -65,7 +95,7 @@ impl Default for SystemEditor {
 
 impl Editor for SystemEditor {
     fn edit(&self, initial: &str, comment_help: &str) -> Result<String, EditorError> {
-        let editor = Self::find_editor()?;
+        let editor = self.find_editor()?;
 
         // Create temp file with initial content
         let mut temp_file = tempfile::Builder::new()
@@ -78,7 +108,9 @@ impl Editor for SystemEditor {
 
         // Add comment help
         if !comment_help.is_empty() {
-            temp_file.write_all(b"\n\n")?;
+            temp_file.write_all(format!("\n\n{}\n", SCISSORS).as_bytes())?;
+            temp_file.write_all(b"# Do not modify or remove the line above.\n")?;
+            temp_file.write_all(b"# Everything below it will be ignored.\n#\n")?;
             for line in comment_help.lines() {
                 temp_file.write_all(b"# ")?;
                 temp_file.write_all(line.as_bytes())?;
@@ -94,14 +126,16 @@ impl Editor for SystemEditor {
 
         // Parse editor command (might have args like "code --wait")
         let mut parts = editor.split_whitespace();
-        let cmd = parts.next().unwrap();
+        let cmd = parts
+            .next()
+            .ok_or_else(|| EditorError::EditorFailed("empty editor command".to_string()))?;
         let args: Vec<&str> = parts.collect();
 
         let status = Command::new(cmd)
             .args(&args)
             .arg(&temp_path)
             .status()
-            .map_err(|e| EditorError::EditorFailed(e.to_string()))?;
+            .map_err(|e| EditorError::EditorFailed(format!("{}: {}", cmd, e)))?;
 
         if !status.success() {
             return Err(EditorError::EditorFailed(format!(
@@ -115,8 +149,8 @@ impl Editor for SystemEditor {
 
         // temp_path is dropped here, which deletes the file
 
-        // Strip comment lines and trailing whitespace
-        let cleaned = strip_comments(&content);
+        // Drop the help block and trailing whitespace
+        let cleaned = strip_help(&content);
 
         if cleaned.trim().is_empty() {
             return Err(EditorError::EmptyMessage);
@@ -126,11 +160,48 @@ impl Editor for SystemEditor {
     }
 }
 
-/// Strip lines starting with # and normalize whitespace
-fn strip_comments(content: &str) -> String {
-    content
+/// Read a `commit.template` file.
+///
+/// Returns `None` if the template is empty.
+pub fn load_commit_template(path: &Path) -> Result<Option<String>, EditorError> {
+    let content = fs::read_to_string(path)?;
+    Ok((!content.trim().is_empty()).then_some(content))
+}
+
+/// Place a commit template below the planned message in the editor buffer.
+///
+/// As with git, non-comment template lines become part of the message unless
+/// the user removes them. The template's comment lines are left out, since
+/// only the help block is stripped after editing; pass them to the editor as
+/// help with [`commit_template_help`].
+pub fn with_commit_template(message: &str, template: &str) -> String {
+    let template: Vec<&str> = template
         .lines()
         .filter(|line| !line.starts_with('#'))
+        .collect();
+    format!(
+        "{}\n\n{}",
+        message.trim_end(),
+        template.join("\n").trim_end()
+    )
+}
+
+/// A commit template's comment lines, without their `#`, to show as help.
+pub fn commit_template_help(template: &str) -> String {
+    template
+        .lines()
+        .filter_map(|line| line.strip_prefix('#'))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cut everything from the scissors line down and normalize whitespace.
+/// Other `#` lines are message content, e.g. markdown headings or `#123`.
+fn strip_help(content: &str) -> String {
+    content
+        .lines()
+        .take_while(|line| *line != SCISSORS)
         .collect::<Vec<_>>()
         .join("\n")
         .trim()
@@ -142,15 +213,60 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_strip_comments() {
-        let input = "Title\n\nBody text\n# This is a comment\nMore body\n# Another comment";
-        let expected = "Title\n\nBody text\nMore body";
-        assert_eq!(strip_comments(input), expected);
+    fn test_strip_help() {
+        let input = format!(
+            "Title\n\nBody text\n#123 stays\nMore body\n\n{}\n# help\nnot message",
+            SCISSORS
+        );
+        let expected = "Title\n\nBody text\n#123 stays\nMore body";
+        assert_eq!(strip_help(&input), expected);
+    }
+
+    #[test]
+    fn test_strip_help_empty() {
+        let input = format!("\n\n{}\n# Just help", SCISSORS);
+        assert_eq!(strip_help(&input), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hash_lines_in_the_message_survive_the_round_trip() {
+        // `true` leaves the buffer as written, like saving without changes
+        let editor = SystemEditor::new().with_command(Some("true".to_string()));
+        let message = "Add docs\n\n# Heading\n\nFixes #123";
+        assert_eq!(editor.edit(message, "Files:\n  a.md").unwrap(), message);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_configured_command_takes_arguments_and_does_not_fall_back() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let script = dir.path().join("edit.sh");
+        fs::write(
+            &script,
+            "#!/bin/sh\nprintf '%s message\\n' \"$1\" > \"$2\"\n",
+        )
+        .unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let editor =
+            SystemEditor::new().with_command(Some(format!("{} Scripted", script.display())));
+        assert_eq!(editor.edit("initial", "help").unwrap(), "Scripted message");
+
+        let missing = SystemEditor::new().with_command(Some("/nonexistent/editor".to_string()));
+        let err = missing.edit("initial", "").unwrap_err();
+        assert!(
+            matches!(err, EditorError::EditorFailed(msg) if msg.contains("/nonexistent/editor"))
+        );
     }
 
     #[test]
-    fn test_strip_comments_empty() {
-        let input = "# Just comments\n# More comments";
-        assert_eq!(strip_comments(input), "");
+    fn test_with_commit_template() {
+        let template = "Ticket: \n# Describe the change above\n";
+        let buffer = with_commit_template("Add parser\n", template);
+        assert_eq!(buffer, "Add parser\n\nTicket:");
+        assert_eq!(commit_template_help(template), "Describe the change above");
     }
 }
@@ -24,6 +24,9 @@ pub enum Feature {
     /// Use file-based I/O for LLM prompts to reduce token usage and handle larger patches.
     /// Writes hunks to a temp file and instructs LLM to read from it.
     FileBasedLlmIo,
+    /// Let the LLM run read-only `git cat-file -p` to inspect context
+    /// beyond the hunks in its prompt.
+    LlmRepoRead,
 }
 
 impl Feature {
@@ -0,0 +1,351 @@
+//! In-memory caching decorator for `GitOps`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::models::{FileChange, Hunk, SourceCommit};
+use crate::patch::PatchContext;
+
+use super::{CommitResult, DetectedBase, FileStatuses, GitError, GitOps, PreReabsorbEntry};
+
+/// Wraps a `GitOps` and caches read-only diff queries for the life of the process.
+///
+/// `diff_trees`, `read_hunks` and `get_file_statuses_in_commit` (and so
+/// `get_files_changed_in_commit`) are served from memory after the first call.
+/// Operations that can move refs clear the cache, since cached keys may be
+/// symbolic refs like `HEAD`. Index-only operations pass straight through:
+/// every cached result describes commits or trees, never the index.
+pub struct CachingGit<G: GitOps> {
+    inner: G,
+    diffs: Mutex<HashMap<(String, String), String>>,
+    hunks: Mutex<HashMap<(String, usize), Vec<Hunk>>>,
+    file_statuses: Mutex<HashMap<String, FileStatuses>>,
+}
+
+impl<G: GitOps> CachingGit<G> {
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            diffs: Mutex::new(HashMap::new()),
+            hunks: Mutex::new(HashMap::new()),
+            file_statuses: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The wrapped implementation.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Drop every cached result.
+    pub fn invalidate(&self) {
+        self.diffs.lock().unwrap().clear();
+        self.hunks.lock().unwrap().clear();
+        self.file_statuses.lock().unwrap().clear();
+    }
+
+    /// Run a ref-moving operation, then invalidate the cache.
+    fn mutate<T>(&self, op: impl FnOnce(&G) -> Result<T, GitError>) -> Result<T, GitError> {
+        let result = op(&self.inner);
+        self.invalidate();
+        result
+    }
+}
+
+/// Return the cached value for `key`, or compute, store and return it.
+fn cached<K, V>(
+    cache: &Mutex<HashMap<K, V>>,
+    key: K,
+    compute: impl FnOnce() -> Result<V, GitError>,
+) -> Result<V, GitError>
+where
+    K: std::hash::Hash + Eq,
+    V: Clone,
+{
+    if let Some(value) = cache.lock().unwrap().get(&key) {
+        return Ok(value.clone());
+    }
+    // Compute without holding the lock so parallel callers don't serialize
+    let value = compute()?;
+    cache.lock().unwrap().insert(key, value.clone());
+    Ok(value)
+}
+
+impl<G: GitOps> GitOps for CachingGit<G> {
+    fn detect_branch_base(&self) -> Result<DetectedBase, GitError> {
+        self.inner.detect_branch_base()
+    }
+
+    fn find_merge_base(&self, branch: &str) -> Result<String, GitError> {
+        self.inner.find_merge_base(branch)
+    }
+
+    fn get_head(&self) -> Result<String, GitError> {
+        self.inner.get_head()
+    }
+
+    fn resolve_ref(&self, ref_name: &str) -> Result<String, GitError> {
+        self.inner.resolve_ref(ref_name)
+    }
+
+    fn abbrev(&self, sha: &str) -> String {
+        self.inner.abbrev(sha)
+    }
+
+    fn read_commits(&self, base: &str, head: &str) -> Result<Vec<SourceCommit>, GitError> {
+        self.inner.read_commits(base, head)
+    }
+
+    fn read_commit(&self, rev: &str) -> Result<SourceCommit, GitError> {
+        self.inner.read_commit(rev)
+    }
+
+    fn commits_on_remote(&self, base: &str, head: &str) -> Result<Vec<String>, GitError> {
+        self.inner.commits_on_remote(base, head)
+    }
+
+    fn parents_of(&self, sha: &str) -> Result<Vec<String>, GitError> {
+        self.inner.parents_of(sha)
+    }
+
+    fn is_ancestor(&self, maybe_ancestor: &str, descendant: &str) -> Result<bool, GitError> {
+        self.inner.is_ancestor(maybe_ancestor, descendant)
+    }
+
+    fn count_commits(&self, base: &str, head: &str) -> Result<usize, GitError> {
+        self.inner.count_commits(base, head)
+    }
+
+    fn read_hunks(&self, commit_sha: &str, hunk_id_start: usize) -> Result<Vec<Hunk>, GitError> {
+        cached(&self.hunks, (commit_sha.to_string(), hunk_id_start), || {
+            self.inner.read_hunks(commit_sha, hunk_id_start)
+        })
+    }
+
+    fn get_working_tree_diff(&self) -> Result<String, GitError> {
+        self.inner.get_working_tree_diff()
+    }
+
+    fn diff_trees(&self, left: &str, right: &str) -> Result<String, GitError> {
+        cached(&self.diffs, (left.to_string(), right.to_string()), || {
+            self.inner.diff_trees(left, right)
+        })
+    }
+
+    fn diff_trees_paths(
+        &self,
+        left: &str,
+        right: &str,
+        paths: &[&Path],
+    ) -> Result<String, GitError> {
+        self.inner.diff_trees_paths(left, right, paths)
+    }
+
+    fn diff_file_in_working_tree(&self, file_path: &str) -> Result<String, GitError> {
+        self.inner.diff_file_in_working_tree(file_path)
+    }
+
+    fn read_blob(&self, sha: &str, path: &Path) -> Result<Option<Vec<u8>>, GitError> {
+        self.inner.read_blob(sha, path)
+    }
+
+    fn get_file_statuses_in_commit(&self, commit_sha: &str) -> Result<FileStatuses, GitError> {
+        cached(&self.file_statuses, commit_sha.to_string(), || {
+            self.inner.get_file_statuses_in_commit(commit_sha)
+        })
+    }
+
+    fn get_file_statuses_for_commits(
+        &self,
+        commit_shas: &[&str],
+    ) -> Result<HashMap<String, FileStatuses>, GitError> {
+        let statuses = self.inner.get_file_statuses_for_commits(commit_shas)?;
+        let mut cache = self.file_statuses.lock().unwrap();
+        for (sha, files) in &statuses {
+            cache.insert(sha.clone(), files.clone());
+        }
+        Ok(statuses)
+    }
+
+    fn apply_hunk_to_index(&self, hunk: &Hunk) -> Result<(), GitError> {
+        self.inner.apply_hunk_to_index(hunk)
+    }
+
+    fn reset_to(&self, ref_name: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.reset_to(ref_name))
+    }
+
+    fn reset_hard(&self, ref_name: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.reset_hard(ref_name))
+    }
+
+    fn apply_hunks_to_index(
+        &self,
+        hunks: &[&Hunk],
+        patch_context: &PatchContext,
+    ) -> Result<(), GitError> {
+        self.inner.apply_hunks_to_index(hunks, patch_context)
+    }
+
+    fn stage_all(&self) -> Result<(), GitError> {
+        self.inner.stage_all()
+    }
+
+    fn stage_files(&self, files: &[&Path]) -> Result<(), GitError> {
+        self.inner.stage_files(files)
+    }
+
+    fn stage_deletion(&self, file_path: &Path) -> Result<(), GitError> {
+        self.inner.stage_deletion(file_path)
+    }
+
+    fn set_file_mode(&self, file_path: &Path, mode: &str) -> Result<(), GitError> {
+        self.inner.set_file_mode(file_path, mode)
+    }
+
+    fn commit(
+        &self,
+        message: &str,
+        no_verify: bool,
+        allow_empty: bool,
+    ) -> Result<CommitResult, GitError> {
+        self.mutate(|git| git.commit(message, no_verify, allow_empty))
+    }
+
+    fn amend_message(&self, message: &str, no_verify: bool) -> Result<String, GitError> {
+        self.mutate(|git| git.amend_message(message, no_verify))
+    }
+
+    fn use_index_file(&self, index_file: Option<&Path>) {
+        self.inner.use_index_file(index_file)
+    }
+
+    fn read_tree(&self, tree_ish: &str) -> Result<(), GitError> {
+        self.inner.read_tree(tree_ish)
+    }
+
+    fn write_tree(&self) -> Result<String, GitError> {
+        self.inner.write_tree()
+    }
+
+    fn tree_of(&self, rev: &str) -> Result<String, GitError> {
+        self.inner.tree_of(rev)
+    }
+
+    fn commit_tree(
+        &self,
+        tree: &str,
+        parent: &str,
+        message: &str,
+        author: Option<&SourceCommit>,
+    ) -> Result<String, GitError> {
+        self.inner.commit_tree(tree, parent, message, author)
+    }
+
+    fn update_head(&self, sha: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.update_head(sha))
+    }
+
+    fn save_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.save_pre_reabsorb_head(ref_name))
+    }
+
+    fn get_pre_reabsorb_head(&self, ref_name: &str) -> Result<String, GitError> {
+        self.inner.get_pre_reabsorb_head(ref_name)
+    }
+
+    fn has_pre_reabsorb_head(&self, ref_name: &str) -> bool {
+        self.inner.has_pre_reabsorb_head(ref_name)
+    }
+
+    fn clear_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.clear_pre_reabsorb_head(ref_name))
+    }
+
+    fn record_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+        max_entries: usize,
+    ) -> Result<(), GitError> {
+        self.mutate(|git| git.record_pre_reabsorb_history(history_prefix, max_entries))
+    }
+
+    fn list_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+    ) -> Result<Vec<PreReabsorbEntry>, GitError> {
+        self.inner.list_pre_reabsorb_history(history_prefix)
+    }
+
+    fn create_branch_at(&self, name: &str, sha: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.create_branch_at(name, sha))
+    }
+
+    fn create_tag(&self, name: &str, sha: &str) -> Result<(), GitError> {
+        self.mutate(|git| git.create_tag(name, sha))
+    }
+
+    fn current_branch_name(&self) -> Result<String, GitError> {
+        self.inner.current_branch_name()
+    }
+
+    fn upstream_of(&self, branch: &str) -> Result<Option<String>, GitError> {
+        self.inner.upstream_of(branch)
+    }
+
+    fn get_config_path(&self, key: &str) -> Result<Option<String>, GitError> {
+        self.inner.get_config_path(key)
+    }
+
+    fn file_in_index(&self, file_path: &Path) -> Result<bool, GitError> {
+        self.inner.file_in_index(file_path)
+    }
+
+    fn operation_in_progress(&self) -> Result<bool, GitError> {
+        self.inner.operation_in_progress()
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        self.inner.repo_root()
+    }
+
+    fn run_git_output(&self, args: &[&str]) -> Result<String, GitError> {
+        // Arbitrary commands may move refs
+        self.mutate(|git| git.run_git_output(args))
+    }
+
+    fn apply_binary_files(&self, changes: &[&FileChange]) -> Result<(), GitError> {
+        self.inner.apply_binary_files(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_source_commit, MockGit};
+
+    #[test]
+    fn reuses_results_until_a_ref_moves() {
+        let git = CachingGit::new(
+            MockGit::new()
+                .with_commit(make_source_commit("base", "Initial"), vec![])
+                .with_diff("base", "HEAD", "diff --git a/a b/a\n"),
+        );
+        let calls = |method: &str| git.inner().calls().iter().filter(|c| *c == method).count();
+
+        for _ in 0..2 {
+            git.diff_trees("base", "HEAD").unwrap();
+            git.read_hunks("base", 0).unwrap();
+            git.get_files_changed_in_commit("base").unwrap();
+        }
+        assert_eq!(calls("diff_trees"), 1);
+        assert_eq!(calls("read_hunks"), 1);
+        assert_eq!(calls("get_file_statuses_in_commit"), 1);
+
+        // Committing moves HEAD, so the symbolic diff is asked for again
+        git.commit("Next", true, true).unwrap();
+        git.diff_trees("base", "HEAD").unwrap();
+        assert_eq!(calls("diff_trees"), 2);
+    }
+}
@@ -0,0 +1,1741 @@
+mod caching;
+
+pub use caching::CachingGit;
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use crate::models::{Hunk, SourceCommit};
+use crate::patch::parse;
+use crate::utils::short_sha;
+
+/// Errors from git operations
+#[derive(Debug, thiserror::Error)]
+pub enum GitError {
+    #[error("Git command failed: {0}")]
+    CommandFailed(String),
+    #[error("Failed to execute git: {0}")]
+    ExecutionFailed(#[from] std::io::Error),
+    #[error("Failed to parse git output: {0}")]
+    ParseError(String),
+    #[error("Not a git repository")]
+    NotARepository,
+    #[error("git executable not found; is git installed and on PATH?")]
+    GitNotFound,
+    #[error("No commits found in range {0}")]
+    NoCommitsInRange(String),
+    #[error("Failed to parse diff: {0}")]
+    DiffParseError(#[from] crate::patch::ParseError),
+    #[error("No pre-reabsorb state saved. Run 'git reabsorb plan' first.")]
+    NoSavedState,
+    #[error("Unknown revision '{reference}'{}", did_you_mean(.suggestion))]
+    RefNotFound {
+        reference: String,
+        suggestion: Option<String>,
+    },
+    #[error("Ambiguous revision '{0}': it names more than one ref or object")]
+    AmbiguousRef(String),
+    #[error("Failed to apply changes to '{file}': {detail}")]
+    ApplyFailed { file: String, detail: String },
+    #[error("Merge conflict: {0}")]
+    MergeConflict(String),
+    #[error("Could not find a base branch (tried {}); pass --base or set GIT_REABSORB_BASE_BRANCHES", .0.join(", "))]
+    NoBranchBase(Vec<String>),
+    #[error("Invalid ref prefix '{prefix}': {reason}")]
+    InvalidRefPrefix { prefix: String, reason: String },
+    #[error("'{0}' already exists")]
+    RefExists(String),
+}
+
+fn did_you_mean(suggestion: &Option<String>) -> String {
+    suggestion
+        .as_ref()
+        .map(|s| format!(" (did you mean '{}'?)", s))
+        .unwrap_or_default()
+}
+
+/// The installed git's version string (e.g. "git version 2.43.0").
+pub fn git_version() -> Result<String, GitError> {
+    let output = Command::new("git")
+        .arg("--version")
+        .output()
+        .map_err(spawn_error)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(classify_failure(&["--version"], &stderr));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Map a failure to start git, distinguishing a missing binary.
+fn spawn_error(err: std::io::Error) -> GitError {
+    match err.kind() {
+        std::io::ErrorKind::NotFound => GitError::GitNotFound,
+        _ => GitError::ExecutionFailed(err),
+    }
+}
+
+/// Branches tried, in order, when auto-detecting the base of the current branch.
+pub const DEFAULT_BASE_BRANCHES: &[&str] = &["main", "master"];
+
+/// Environment variable that overrides [`DEFAULT_BASE_BRANCHES`] (comma-separated).
+pub const BASE_BRANCHES_ENV: &str = "GIT_REABSORB_BASE_BRANCHES";
+
+/// Read the candidate base branches from `GIT_REABSORB_BASE_BRANCHES`, falling
+/// back to the defaults when unset or empty.
+pub fn base_branches_from_env() -> Vec<String> {
+    let configured: Vec<String> = std::env::var(BASE_BRANCHES_ENV)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    if configured.is_empty() {
+        DEFAULT_BASE_BRANCHES
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    } else {
+        configured
+    }
+}
+
+/// The tree with no files. As a range base it stands for "before the first
+/// commit": `EMPTY_TREE..head` is every commit reachable from head, and
+/// resetting to it leaves the branch unborn so the next commit is a root.
+pub const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Default root under which reabsorb keeps its refs.
+pub const DEFAULT_REF_PREFIX: &str = "refs/reabsorb";
+
+/// Name of the backup branch or tag when `--backup-branch`/`--backup-tag`
+/// is given without one. `{timestamp}` becomes the UTC time of the apply.
+pub const DEFAULT_BACKUP_NAME: &str = "reabsorb/backup/{timestamp}";
+
+/// Environment variable that overrides [`DEFAULT_REF_PREFIX`].
+pub const REF_PREFIX_ENV: &str = "GIT_REABSORB_REF_PREFIX";
+
+/// Read the ref prefix from `GIT_REABSORB_REF_PREFIX`, falling back to the default.
+pub fn ref_prefix_from_env() -> Result<String, GitError> {
+    match std::env::var(REF_PREFIX_ENV) {
+        Ok(prefix) if !prefix.trim().is_empty() => validate_ref_prefix(prefix.trim()),
+        _ => Ok(DEFAULT_REF_PREFIX.to_string()),
+    }
+}
+
+/// Check that `prefix` is a legal ref path under `refs/` (following the rules of
+/// `git check-ref-format`), returning it without any trailing slash.
+pub fn validate_ref_prefix(prefix: &str) -> Result<String, GitError> {
+    let invalid = |reason: &str| GitError::InvalidRefPrefix {
+        prefix: prefix.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let trimmed = prefix.trim_end_matches('/');
+    let Some(rest) = trimmed.strip_prefix("refs/") else {
+        return Err(invalid("must start with 'refs/'"));
+    };
+    if rest.is_empty() {
+        return Err(invalid("needs at least one component after 'refs/'"));
+    }
+    if trimmed.contains("..") || trimmed.contains("@{") {
+        return Err(invalid("may not contain '..' or '@{'"));
+    }
+    if trimmed
+        .chars()
+        .any(|c| c.is_control() || " ~^:?*[\\".contains(c))
+    {
+        return Err(invalid("contains a character git does not allow in refs"));
+    }
+    for component in trimmed.split('/') {
+        if component.is_empty() {
+            return Err(invalid("may not contain empty components ('//')"));
+        }
+        if component.starts_with('.') || component.ends_with(".lock") {
+            return Err(invalid(
+                "components may not start with '.' or end with '.lock'",
+            ));
+        }
+    }
+    if trimmed.ends_with('.') {
+        return Err(invalid("may not end with '.'"));
+    }
+
+    Ok(trimmed.to_string())
+}
+
+/// Build the ref used to store the pre-reabsorb HEAD for a namespace
+pub fn pre_reabsorb_ref_for(prefix: &str, namespace: &str) -> String {
+    format!("{}/pre-reabsorb/{}", prefix, namespace)
+}
+
+/// Build the ref prefix under which past pre-reabsorb HEADs are kept for a namespace
+pub fn pre_reabsorb_history_ref_for(prefix: &str, namespace: &str) -> String {
+    format!("{}/history/{}", prefix, namespace)
+}
+
+/// An auto-detected branch base: the merge-base with HEAD and the branch it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectedBase {
+    pub sha: String,
+    pub branch: String,
+}
+
+/// What `GitOps::commit` did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitResult {
+    /// The new commit, or the unchanged HEAD when none was made
+    pub sha: String,
+    /// Whether a commit was made; false when nothing was staged and empty
+    /// commits weren't allowed
+    pub created: bool,
+    /// Whether the index matched HEAD, so any commit made changes nothing
+    pub empty: bool,
+}
+
+/// A past pre-reabsorb HEAD, stored as `<history prefix>/<unix millis>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreReabsorbEntry {
+    pub ref_name: String,
+    pub sha: String,
+    /// When the entry was recorded, in milliseconds since the Unix epoch
+    pub saved_at: i64,
+}
+
+/// How a file changed in a single commit, as reported by `--name-status`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+    /// Renamed from the given path
+    Renamed {
+        from: String,
+    },
+    /// Copied from the given path (which is left unchanged)
+    Copied {
+        from: String,
+    },
+    /// File type changed (e.g. regular file to symlink)
+    TypeChanged,
+}
+
+/// Paths changed in a commit with how each changed.
+pub type FileStatuses = Vec<(String, FileStatus)>;
+
+/// Trait for git operations - allows mocking in tests
+pub trait GitOps {
+    /// Find the merge-base between current HEAD and the first candidate base
+    /// branch that exists, falling back to the upstream branch (auto-detect)
+    fn find_branch_base(&self) -> Result<String, GitError> {
+        self.detect_branch_base().map(|base| base.sha)
+    }
+
+    /// Like `find_branch_base`, but also report which branch was used
+    fn detect_branch_base(&self) -> Result<DetectedBase, GitError>;
+
+    /// Find the merge-base between current HEAD and a specific branch
+    fn find_merge_base(&self, branch: &str) -> Result<String, GitError>;
+
+    /// Get the current HEAD SHA
+    fn get_head(&self) -> Result<String, GitError>;
+
+    /// Resolve a ref (branch name, tag, SHA prefix) to a full SHA
+    fn resolve_ref(&self, ref_name: &str) -> Result<String, GitError>;
+
+    /// Abbreviate a SHA for display, respecting `core.abbrev` and long enough
+    /// to be unambiguous. Falls back to `utils::short_sha` if git can't.
+    fn abbrev(&self, sha: &str) -> String;
+
+    /// Read commits in range (exclusive base, inclusive head)
+    fn read_commits(&self, base: &str, head: &str) -> Result<Vec<SourceCommit>, GitError>;
+
+    /// Read a single commit by SHA or other revision
+    fn read_commit(&self, rev: &str) -> Result<SourceCommit, GitError>;
+
+    /// Full SHAs of the parents of `sha`, in order; empty for a root commit
+    fn parents_of(&self, sha: &str) -> Result<Vec<String>, GitError>;
+
+    /// SHAs of commits in `base..head` that are reachable from a remote-tracking
+    /// ref, i.e. that have already been pushed. Newest first.
+    fn commits_on_remote(&self, base: &str, head: &str) -> Result<Vec<String>, GitError>;
+
+    /// Whether `maybe_ancestor` is reachable from `descendant` (a commit is its
+    /// own ancestor).
+    fn is_ancestor(&self, maybe_ancestor: &str, descendant: &str) -> Result<bool, GitError>;
+
+    /// Number of commits in `base..head`, without reading them
+    fn count_commits(&self, base: &str, head: &str) -> Result<usize, GitError>;
+
+    /// Read hunks from a commit's diff against its parent
+    fn read_hunks(&self, commit_sha: &str, hunk_id_start: usize) -> Result<Vec<Hunk>, GitError>;
+
+    /// Get the raw diff output between HEAD and working tree
+    fn get_working_tree_diff(&self) -> Result<String, GitError>;
+
+    /// Get diff between two tree-ish references
+    fn diff_trees(&self, left: &str, right: &str) -> Result<String, GitError>;
+
+    /// Like `diff_trees`, limited to `paths` (files or directories), so git
+    /// only diffs what the caller cares about. No paths gives an empty diff.
+    fn diff_trees_paths(
+        &self,
+        left: &str,
+        right: &str,
+        paths: &[&Path],
+    ) -> Result<String, GitError>;
+
+    /// Get diff for a specific file between index and working tree
+    fn diff_file_in_working_tree(&self, file_path: &str) -> Result<String, GitError>;
+
+    /// Read the raw contents of `path` as of commit `sha`.
+    ///
+    /// Returns `None` when the path does not exist at that commit.
+    fn read_blob(&self, sha: &str, path: &Path) -> Result<Option<Vec<u8>>, GitError>;
+
+    /// Get the path and status of each file changed in a commit, with rename
+    /// detection. Renamed and copied entries carry the destination path.
+    fn get_file_statuses_in_commit(&self, commit_sha: &str) -> Result<FileStatuses, GitError>;
+
+    /// Get file statuses for many commits at once, keyed by full commit SHA.
+    ///
+    /// Equivalent to calling `get_file_statuses_in_commit` for each commit,
+    /// but runs a single git process regardless of how many commits are given.
+    fn get_file_statuses_for_commits(
+        &self,
+        commit_shas: &[&str],
+    ) -> Result<HashMap<String, FileStatuses>, GitError>;
+
+    /// Get list of files changed in a specific commit
+    ///
+    /// Both sides of a rename are listed, as without rename detection.
+    fn get_files_changed_in_commit(&self, commit_sha: &str) -> Result<Vec<String>, GitError> {
+        let mut files = Vec::new();
+        for (path, status) in self.get_file_statuses_in_commit(commit_sha)? {
+            if let FileStatus::Renamed { from } = status {
+                files.push(from);
+            }
+            files.push(path);
+        }
+        Ok(files)
+    }
+
+    /// Get list of newly added files in a specific commit (files that didn't exist before)
+    ///
+    /// Rename and copy destinations count as new files, as without rename detection.
+    fn get_new_files_in_commit(&self, commit_sha: &str) -> Result<Vec<String>, GitError> {
+        Ok(self
+            .get_file_statuses_in_commit(commit_sha)?
+            .into_iter()
+            .filter(|(_, status)| {
+                matches!(
+                    status,
+                    FileStatus::Added | FileStatus::Renamed { .. } | FileStatus::Copied { .. }
+                )
+            })
+            .map(|(path, _)| path)
+            .collect())
+    }
+
+    /// Apply a single hunk to the index using git apply
+    fn apply_hunk_to_index(&self, hunk: &Hunk) -> Result<(), GitError>;
+
+    /// Reset to a ref (mixed reset - unstages to working tree)
+    fn reset_to(&self, ref_name: &str) -> Result<(), GitError>;
+
+    /// Hard reset to a ref (discards all changes)
+    fn reset_hard(&self, ref_name: &str) -> Result<(), GitError>;
+
+    /// Apply hunks to the index (stage them)
+    ///
+    /// The `patch_context` provides information about which files are new in the
+    /// commit range, enabling correct patch header generation.
+    fn apply_hunks_to_index(
+        &self,
+        hunks: &[&Hunk],
+        patch_context: &crate::patch::PatchContext,
+    ) -> Result<(), GitError>;
+
+    /// Stage all changes in the working tree (git add -A)
+    fn stage_all(&self) -> Result<(), GitError>;
+
+    /// Stage specific files (git add <files>)
+    fn stage_files(&self, files: &[&Path]) -> Result<(), GitError>;
+
+    /// Stage the deletion of a file, removing it from the index only
+    fn stage_deletion(&self, file_path: &Path) -> Result<(), GitError>;
+
+    /// Set the mode of a file already in the index to `mode` ("100644" or
+    /// "100755"), leaving its content alone
+    fn set_file_mode(&self, file_path: &Path, mode: &str) -> Result<(), GitError>;
+
+    /// Create a commit with the currently staged changes. With nothing
+    /// staged, makes an empty commit if `allow_empty` is set and otherwise
+    /// leaves HEAD alone; the result says which.
+    fn commit(
+        &self,
+        message: &str,
+        no_verify: bool,
+        allow_empty: bool,
+    ) -> Result<CommitResult, GitError>;
+
+    /// Point every index operation at `index_file` instead of the
+    /// repository's index, or back at it with `None`.
+    fn use_index_file(&self, index_file: Option<&Path>);
+
+    /// Replace the index's contents with `tree_ish`, leaving the work tree alone
+    fn read_tree(&self, tree_ish: &str) -> Result<(), GitError>;
+
+    /// Write the index out as a tree object and return its SHA
+    fn write_tree(&self) -> Result<String, GitError>;
+
+    /// The SHA of the tree `rev` points at
+    fn tree_of(&self, rev: &str) -> Result<String, GitError>;
+
+    /// Create a commit object for `tree` on top of `parent`, without moving
+    /// any ref or running hooks. With `author`, the commit keeps that
+    /// commit's author name, email and date, as `git commit --amend` would.
+    fn commit_tree(
+        &self,
+        tree: &str,
+        parent: &str,
+        message: &str,
+        author: Option<&SourceCommit>,
+    ) -> Result<String, GitError>;
+
+    /// Move HEAD (or the branch it points at) to `sha`, leaving the index and
+    /// work tree alone
+    fn update_head(&self, sha: &str) -> Result<(), GitError>;
+
+    /// Replace HEAD's message, keeping its tree. Staged changes are left
+    /// staged rather than folded into the commit.
+    fn amend_message(&self, message: &str, no_verify: bool) -> Result<String, GitError>;
+
+    /// Save the current HEAD as the pre-reabsorb state
+    fn save_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError>;
+
+    /// Get the saved pre-reabsorb HEAD, if any
+    fn get_pre_reabsorb_head(&self, ref_name: &str) -> Result<String, GitError>;
+
+    /// Check if a pre-reabsorb state is saved
+    fn has_pre_reabsorb_head(&self, ref_name: &str) -> bool;
+
+    /// Clear the saved pre-reabsorb state
+    fn clear_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError>;
+
+    /// Record the current HEAD under `history_prefix`, pruning the oldest
+    /// entries so at most `max_entries` remain.
+    fn record_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+        max_entries: usize,
+    ) -> Result<(), GitError>;
+
+    /// List recorded pre-reabsorb HEADs under `history_prefix`, newest first.
+    fn list_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+    ) -> Result<Vec<PreReabsorbEntry>, GitError>;
+
+    /// Create branch `name` at `sha`. Fails with [`GitError::RefExists`]
+    /// rather than moving a branch that already exists.
+    fn create_branch_at(&self, name: &str, sha: &str) -> Result<(), GitError>;
+
+    /// Create lightweight tag `name` at `sha`. Fails with
+    /// [`GitError::RefExists`] rather than moving a tag that already exists.
+    fn create_tag(&self, name: &str, sha: &str) -> Result<(), GitError>;
+
+    /// Get the current branch name ("HEAD" if detached)
+    fn current_branch_name(&self) -> Result<String, GitError>;
+
+    /// The upstream tracking branch of `branch` (e.g. "origin/main"), or
+    /// `None` if it has none. `branch` may be "HEAD" for the current branch.
+    fn upstream_of(&self, branch: &str) -> Result<Option<String>, GitError>;
+
+    /// Read a path-valued git config entry (with `~` expanded), if set
+    fn get_config_path(&self, key: &str) -> Result<Option<String>, GitError>;
+
+    /// Check if a file exists in the git index
+    fn file_in_index(&self, file_path: &Path) -> Result<bool, GitError>;
+
+    /// Whether the index has unmerged paths, or a merge, rebase, cherry-pick
+    /// or revert is waiting to be finished or aborted
+    fn operation_in_progress(&self) -> Result<bool, GitError>;
+
+    /// Top of the work tree
+    fn repo_root(&self) -> Result<PathBuf, GitError>;
+
+    /// Run a git command and return the output (for debugging)
+    fn run_git_output(&self, args: &[&str]) -> Result<String, GitError>;
+
+    /// Apply binary file changes to the index.
+    fn apply_binary_files(&self, changes: &[&crate::models::FileChange]) -> Result<(), GitError>;
+}
+
+/// Real implementation of GitOps that calls git commands
+pub struct Git {
+    /// Working directory for git commands
+    work_dir: Option<std::path::PathBuf>,
+    /// Candidate branches for `find_branch_base`, tried in order
+    base_branches: Vec<String>,
+    /// Index file to use instead of the repository's (`GIT_INDEX_FILE`)
+    index_file: Mutex<Option<PathBuf>>,
+}
+
+impl Git {
+    pub fn new() -> Self {
+        Self {
+            work_dir: None,
+            base_branches: DEFAULT_BASE_BRANCHES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            index_file: Mutex::new(None),
+        }
+    }
+
+    pub fn with_repo_root() -> Result<Self, GitError> {
+        Self::discover(".")
+    }
+
+    /// Open the repository whose work tree contains `dir`.
+    ///
+    /// Fails with `GitNotFound` if the git binary can't be run, and with
+    /// `NotARepository` if `dir` isn't inside a work tree (including bare repos
+    /// and the `.git` directory itself).
+    pub fn discover(dir: impl AsRef<Path>) -> Result<Self, GitError> {
+        let repo_root = Self::find_repo_root(dir)?;
+        Ok(Self::with_work_dir(repo_root))
+    }
+
+    fn find_repo_root(work_dir: impl AsRef<Path>) -> Result<String, GitError> {
+        let run = |args: &[&str]| -> Result<std::process::Output, GitError> {
+            Command::new("git")
+                .current_dir(work_dir.as_ref())
+                .args(args)
+                .output()
+                .map_err(spawn_error)
+        };
+
+        let inside = run(&["rev-parse", "--is-inside-work-tree"])?;
+        if !inside.status.success() || String::from_utf8_lossy(&inside.stdout).trim() != "true" {
+            return Err(GitError::NotARepository);
+        }
+
+        let args = ["rev-parse", "--show-toplevel"];
+        let output = run(&args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(classify_failure(&args, &stderr));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    pub fn with_work_dir(work_dir: impl AsRef<Path>) -> Self {
+        Self {
+            work_dir: Some(work_dir.as_ref().to_path_buf()),
+            base_branches: DEFAULT_BASE_BRANCHES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            index_file: Mutex::new(None),
+        }
+    }
+
+    /// Set the branches `find_branch_base` tries, in order, before the upstream.
+    pub fn with_base_branches(mut self, branches: Vec<String>) -> Self {
+        self.base_branches = branches;
+        self
+    }
+
+    fn run_git<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<String, GitError> {
+        let stdout = self.run_git_bytes(args)?;
+        Ok(String::from_utf8_lossy(&stdout).to_string())
+    }
+
+    fn run_git_bytes<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<Vec<u8>, GitError> {
+        let output = self.git_output(args)?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(classify_failure(args, &stderr));
+        }
+
+        Ok(output.stdout)
+    }
+
+    /// Apply a patch file for `file` to the index.
+    fn apply_patch_to_index(&self, patch_path: &Path, file: &Path) -> Result<(), GitError> {
+        self.run_git(&[
+            OsStr::new("apply"),
+            OsStr::new("--cached"),
+            OsStr::new("--unidiff-zero"),
+            patch_path.as_os_str(),
+        ])
+        .map_err(|err| match err {
+            GitError::CommandFailed(detail) => GitError::ApplyFailed {
+                file: file.display().to_string(),
+                detail,
+            },
+            other => other,
+        })?;
+        Ok(())
+    }
+
+    /// Run git and return its raw output, without checking the exit status.
+    fn git_output<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<std::process::Output, GitError> {
+        self.git_output_with_env(args, &[])
+    }
+
+    /// Like `git_output`, with extra environment variables set for git.
+    fn git_output_with_env<S: AsRef<OsStr>>(
+        &self,
+        args: &[S],
+        env: &[(&str, &str)],
+    ) -> Result<std::process::Output, GitError> {
+        let mut cmd = Command::new("git");
+        if let Some(ref dir) = self.work_dir {
+            cmd.current_dir(dir);
+        }
+        if let Some(ref index_file) = *self.index_file.lock().unwrap() {
+            cmd.env("GIT_INDEX_FILE", index_file);
+        }
+        cmd.envs(env.iter().copied());
+        cmd.args(args);
+        Ok(cmd.output()?)
+    }
+
+    /// Read the commit at a full SHA (no ref resolution).
+    fn read_commit_at(&self, sha: &str) -> Result<SourceCommit, GitError> {
+        // Author name, email and dates on their own lines, then the full message
+        let output = self.run_git(&["log", "-1", "--format=%an%n%ae%n%at%n%ct%n%B", sha])?;
+        let mut fields = output.splitn(5, '\n');
+        let author_name = fields.next().unwrap_or("");
+        let author_email = fields.next().unwrap_or("");
+        let author_date = parse_timestamp(fields.next())?;
+        let commit_date = parse_timestamp(fields.next())?;
+        let message = fields.next().unwrap_or("").trim();
+        let short = message.lines().next().unwrap_or("").to_string();
+
+        Ok(SourceCommit::new(sha, short, message)
+            .with_author(author_name, author_email)
+            .with_dates(author_date, commit_date))
+    }
+
+    /// Delete the branch HEAD points at, keeping HEAD on it, so the next
+    /// commit starts a new history.
+    fn unborn_head(&self) -> Result<(), GitError> {
+        let branch = self.git_output(&["symbolic-ref", "-q", "HEAD"])?;
+        if !branch.status.success() {
+            // update-ref would delete a detached HEAD itself
+            return Err(GitError::CommandFailed(
+                "Cannot rewrite from the root commit on a detached HEAD; check out a branch first"
+                    .to_string(),
+            ));
+        }
+        self.run_git(&["update-ref", "-m", "reabsorb: reset to root", "-d", "HEAD"])?;
+        Ok(())
+    }
+
+    /// Create `full_name` (e.g. "refs/tags/x") at `sha`. The empty old value
+    /// makes update-ref refuse, atomically, if the ref already exists.
+    fn create_ref(&self, full_name: &str, name: &str, sha: &str) -> Result<(), GitError> {
+        let valid = self.git_output(&["check-ref-format", full_name])?;
+        if !valid.status.success() {
+            return Err(GitError::CommandFailed(format!(
+                "'{}' is not a valid branch or tag name",
+                name
+            )));
+        }
+
+        // Ask git rather than matching its (translated) error message. The
+        // empty old value still refuses a ref created in between
+        let exists = || -> Result<bool, GitError> {
+            let output = self.git_output(&["rev-parse", "--verify", "-q", full_name])?;
+            Ok(output.status.success())
+        };
+        if exists()? {
+            return Err(GitError::RefExists(name.to_string()));
+        }
+        let args = ["update-ref", "-m", "reabsorb: backup", full_name, sha, ""];
+        let output = self.git_output(&args)?;
+        if output.status.success() {
+            return Ok(());
+        }
+        if exists()? {
+            return Err(GitError::RefExists(name.to_string()));
+        }
+        Err(classify_failure(
+            &args,
+            &String::from_utf8_lossy(&output.stderr),
+        ))
+    }
+
+    /// HEAD, or [`EMPTY_TREE`] while the branch is unborn.
+    fn head_or_empty_tree(&self) -> Result<String, GitError> {
+        let output = self.git_output(&["rev-parse", "-q", "--verify", "HEAD"])?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Ok(EMPTY_TREE.to_string())
+        }
+    }
+
+    /// Find the local, remote or tag ref name closest to a mistyped `ref_name`.
+    fn suggest_ref(&self, ref_name: &str) -> Option<String> {
+        let output = self
+            .run_git(&[
+                "for-each-ref",
+                "--format=%(refname:short)",
+                "refs/heads",
+                "refs/remotes",
+                "refs/tags",
+            ])
+            .ok()?;
+        let max_distance = (ref_name.chars().count() / 3).max(2);
+
+        output
+            .lines()
+            .map(|name| (edit_distance(ref_name, name), name))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, name)| name.to_string())
+    }
+}
+
+/// The rev-list arguments for `base..head`; from [`EMPTY_TREE`] that is all
+/// of head's history.
+fn rev_range(base: &str, head: &str) -> String {
+    if base == EMPTY_TREE {
+        head.to_string()
+    } else {
+        format!("{}..{}", base, head)
+    }
+}
+
+/// Parse NUL-separated `--name-status -z` output into paths and statuses.
+fn parse_name_status(output: &str) -> Result<FileStatuses, GitError> {
+    let mut fields = output.split('\0').filter(|f| !f.is_empty());
+    let mut statuses = Vec::new();
+
+    while let Some(code) = fields.next() {
+        statuses.push(parse_status_entry(code, &mut fields)?);
+    }
+
+    Ok(statuses)
+}
+
+/// Files and directories in the git dir that mean an operation is unfinished.
+const IN_PROGRESS_MARKERS: &[&str] = &[
+    "MERGE_HEAD",
+    "CHERRY_PICK_HEAD",
+    "REVERT_HEAD",
+    "rebase-merge",
+    "rebase-apply",
+];
+
+/// Prefix marking the start of each commit in `get_file_statuses_for_commits` output.
+const LOG_COMMIT_MARKER: char = '\x01';
+
+/// Parse `git log --name-status -z` output into per-commit file statuses, in log order.
+fn parse_log_name_status(output: &str) -> Result<Vec<(String, FileStatuses)>, GitError> {
+    // git puts a newline between each commit line and its first entry
+    let mut fields = output
+        .split('\0')
+        .map(|f| f.trim_start_matches('\n'))
+        .filter(|f| !f.is_empty());
+    let mut commits: Vec<(String, FileStatuses)> = Vec::new();
+
+    while let Some(field) = fields.next() {
+        if let Some(sha) = field.strip_prefix(LOG_COMMIT_MARKER) {
+            commits.push((sha.trim().to_string(), Vec::new()));
+            continue;
+        }
+        let entry = parse_status_entry(field, &mut fields)?;
+        match commits.last_mut() {
+            Some((_, files)) => files.push(entry),
+            None => {
+                return Err(GitError::ParseError(
+                    "File status before any commit in git log output".to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(commits)
+}
+
+/// Parse one `--name-status -z` entry whose status code has already been read.
+fn parse_status_entry<'a>(
+    code: &str,
+    fields: &mut impl Iterator<Item = &'a str>,
+) -> Result<(String, FileStatus), GitError> {
+    let mut path = || {
+        fields
+            .next()
+            .map(String::from)
+            .ok_or_else(|| GitError::ParseError(format!("Missing path for status {}", code)))
+    };
+    Ok(match code.chars().next() {
+        Some('A') => (path()?, FileStatus::Added),
+        Some('M') => (path()?, FileStatus::Modified),
+        Some('D') => (path()?, FileStatus::Deleted),
+        Some('T') => (path()?, FileStatus::TypeChanged),
+        Some('R') => {
+            let from = path()?;
+            (path()?, FileStatus::Renamed { from })
+        }
+        Some('C') => {
+            let from = path()?;
+            (path()?, FileStatus::Copied { from })
+        }
+        _ => {
+            return Err(GitError::ParseError(format!(
+                "Unknown file status '{}'",
+                code
+            )))
+        }
+    })
+}
+
+/// Parse a Unix timestamp field from `git log` output.
+fn parse_timestamp(field: Option<&str>) -> Result<i64, GitError> {
+    let field = field.unwrap_or("").trim();
+    field
+        .parse()
+        .map_err(|_| GitError::ParseError(format!("Invalid timestamp '{}'", field)))
+}
+
+/// Levenshtein distance between two strings, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current.push(substitution.min(prev[j + 1] + 1).min(current[j] + 1));
+        }
+        prev = current;
+    }
+
+    prev[b.len()]
+}
+
+/// Turn a failed git command's stderr into the most specific `GitError`.
+fn classify_failure<S: AsRef<OsStr>>(args: &[S], stderr: &str) -> GitError {
+    let detail = stderr.trim();
+    let lower = detail.to_lowercase();
+
+    if lower.contains("not a git repository") {
+        return GitError::NotARepository;
+    }
+    if lower.contains("unknown revision") || lower.contains("bad revision") {
+        if let Some(reference) = first_quoted(detail) {
+            return GitError::RefNotFound {
+                reference,
+                suggestion: None,
+            };
+        }
+    }
+    if lower.contains("conflict") || lower.contains("unmerged") || lower.contains("needs merge") {
+        return GitError::MergeConflict(detail.to_string());
+    }
+
+    let command = args
+        .iter()
+        .map(|arg| arg.as_ref().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ");
+    GitError::CommandFailed(format!("git {} failed: {}", command, detail))
+}
+
+/// The first '...'-quoted substring of a git message, if any.
+fn first_quoted(message: &str) -> Option<String> {
+    let start = message.find('\'')? + 1;
+    let len = message[start..].find('\'')?;
+    Some(message[start..start + len].to_string())
+}
+
+impl Default for Git {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GitOps for Git {
+    fn detect_branch_base(&self) -> Result<DetectedBase, GitError> {
+        // Try each candidate in order, then the upstream tracking branch
+        let upstream = self.upstream_of("HEAD").ok().flatten();
+        for base_branch in self.base_branches.iter().chain(&upstream) {
+            if let Ok(sha) = self.run_git(&["merge-base", base_branch, "HEAD"]) {
+                return Ok(DetectedBase {
+                    sha: sha.trim().to_string(),
+                    branch: base_branch.clone(),
+                });
+            }
+        }
+
+        let mut tried = self.base_branches.clone();
+        tried.push(upstream.unwrap_or_else(|| "@{u}".to_string()));
+        Err(GitError::NoBranchBase(tried))
+    }
+
+    fn find_merge_base(&self, branch: &str) -> Result<String, GitError> {
+        let output = self.run_git(&["merge-base", branch, "HEAD"])?;
+        Ok(output.trim().to_string())
+    }
+
+    fn get_head(&self) -> Result<String, GitError> {
+        let output = self.run_git(&["rev-parse", "HEAD"])?;
+        Ok(output.trim().to_string())
+    }
+
+    fn resolve_ref(&self, ref_name: &str) -> Result<String, GitError> {
+        let spec = format!("{}^{{commit}}", ref_name);
+        // git warns but still succeeds for an ambiguous refname, so check
+        // stderr before trusting the result. The warning is translated, so
+        // ask for it untranslated
+        let output =
+            self.git_output_with_env(&["rev-parse", "--verify", &spec], &[("LC_ALL", "C")])?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("is ambiguous") {
+            return Err(GitError::AmbiguousRef(ref_name.to_string()));
+        }
+
+        if !output.status.success() {
+            return Err(GitError::RefNotFound {
+                reference: ref_name.to_string(),
+                suggestion: self.suggest_ref(ref_name),
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn abbrev(&self, sha: &str) -> String {
+        // A full-length hex SHA is echoed back even if it doesn't exist, so
+        // ask git to look the object up
+        let spec = format!("{}^{{object}}", sha);
+        match self.git_output(&["rev-parse", "--short", &spec]) {
+            Ok(output) if output.status.success() => {
+                String::from_utf8_lossy(&output.stdout).trim().to_string()
+            }
+            _ => short_sha(sha).to_string(),
+        }
+    }
+
+    fn commits_on_remote(&self, base: &str, head: &str) -> Result<Vec<String>, GitError> {
+        let range = rev_range(base, head);
+        let all = self.run_git(&["rev-list", &range])?;
+        let unpushed = self.run_git(&["rev-list", &range, "--not", "--remotes"])?;
+        let unpushed: HashSet<&str> = unpushed.lines().collect();
+
+        Ok(all
+            .lines()
+            .filter(|sha| !unpushed.contains(sha))
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn count_commits(&self, base: &str, head: &str) -> Result<usize, GitError> {
+        let range = rev_range(base, head);
+        let output = self.run_git(&["rev-list", "--count", &range])?;
+        output.trim().parse().map_err(|_| {
+            GitError::CommandFailed(format!("unexpected rev-list --count output: {}", output))
+        })
+    }
+
+    fn is_ancestor(&self, maybe_ancestor: &str, descendant: &str) -> Result<bool, GitError> {
+        if maybe_ancestor == EMPTY_TREE {
+            return Ok(true);
+        }
+        let args = ["merge-base", "--is-ancestor", maybe_ancestor, descendant];
+        let output = self.git_output(&args)?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(classify_failure(
+                &args,
+                &String::from_utf8_lossy(&output.stderr),
+            )),
+        }
+    }
+
+    fn read_commits(&self, base: &str, head: &str) -> Result<Vec<SourceCommit>, GitError> {
+        // Get commit SHAs in range (oldest first)
+        // Note: base..head is exclusive of base (merge-base is not included)
+        let range = rev_range(base, head);
+        let output = self.run_git(&["rev-list", "--reverse", &range])?;
+
+        let shas: Vec<&str> = output.lines().filter(|s| !s.is_empty()).collect();
+        if shas.is_empty() {
+            return Err(GitError::NoCommitsInRange(range));
+        }
+
+        shas.into_iter()
+            .map(|sha| self.read_commit_at(sha))
+            .collect()
+    }
+
+    fn read_commit(&self, rev: &str) -> Result<SourceCommit, GitError> {
+        let sha = self.resolve_ref(rev)?;
+        self.read_commit_at(&sha)
+    }
+
+    fn parents_of(&self, sha: &str) -> Result<Vec<String>, GitError> {
+        // Prints the commit followed by its parents
+        let output = self.run_git(&["rev-list", "--parents", "-n", "1", sha])?;
+        Ok(output
+            .split_whitespace()
+            .skip(1)
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn read_hunks(&self, commit_sha: &str, hunk_id_start: usize) -> Result<Vec<Hunk>, GitError> {
+        // Get diff for this commit against its parent (the empty tree for a
+        // root commit)
+        let diff_output = self.run_git(&["show", "--format=", "-p", commit_sha])?;
+
+        let hunks = parse(&diff_output, &[commit_sha.to_string()], hunk_id_start)?.hunks;
+        Ok(hunks)
+    }
+
+    fn get_working_tree_diff(&self) -> Result<String, GitError> {
+        // Disable rename detection to get explicit deletion and creation hunks
+        // This ensures renamed files are handled as delete + create, not just modify
+        let output = self.run_git(&["diff", "HEAD", "--no-color", "--no-renames"])?;
+        Ok(output)
+    }
+
+    fn diff_trees(&self, left: &str, right: &str) -> Result<String, GitError> {
+        // Disable rename detection to get explicit deletion and creation hunks
+        let output = self.run_git(&["diff", left, right, "--no-color", "--no-renames"])?;
+        Ok(output)
+    }
+
+    fn diff_trees_paths(
+        &self,
+        left: &str,
+        right: &str,
+        paths: &[&Path],
+    ) -> Result<String, GitError> {
+        // Without any pathspec git would diff the whole tree
+        if paths.is_empty() {
+            return Ok(String::new());
+        }
+        let mut args: Vec<&OsStr> = ["diff", left, right, "--no-color", "--no-renames", "--"]
+            .into_iter()
+            .map(OsStr::new)
+            .collect();
+        args.extend(paths.iter().map(|p| p.as_os_str()));
+        self.run_git(&args)
+    }
+
+    fn diff_file_in_working_tree(&self, file_path: &str) -> Result<String, GitError> {
+        let output = self.run_git(&["diff", "--no-color", "--", file_path])?;
+        Ok(output)
+    }
+
+    fn read_blob(&self, sha: &str, path: &Path) -> Result<Option<Vec<u8>>, GitError> {
+        let mut object = OsString::from(format!("{}:", sha));
+        object.push(path);
+        if self
+            .run_git(&[OsStr::new("cat-file"), OsStr::new("-e"), &object])
+            .is_err()
+        {
+            // Distinguish a missing path from an unknown commit
+            self.run_git(&["cat-file", "-e", &format!("{}^{{commit}}", sha)])?;
+            return Ok(None);
+        }
+        self.run_git_bytes(&[OsStr::new("cat-file"), OsStr::new("blob"), &object])
+            .map(Some)
+    }
+
+    fn get_file_statuses_in_commit(&self, commit_sha: &str) -> Result<FileStatuses, GitError> {
+        // -z keeps unusual paths unquoted; fields are NUL-separated
+        let output = self.run_git(&[
+            "diff-tree",
+            "--no-commit-id",
+            "--name-status",
+            "-r",
+            "-M",
+            "-z",
+            // Without it a root commit lists no files
+            "--root",
+            commit_sha,
+        ])?;
+        parse_name_status(&output)
+    }
+
+    fn get_file_statuses_for_commits(
+        &self,
+        commit_shas: &[&str],
+    ) -> Result<HashMap<String, FileStatuses>, GitError> {
+        if commit_shas.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Each commit starts with a marker-prefixed SHA, followed by the same
+        // NUL-separated entries as diff-tree's --name-status -z
+        let format = format!("--format={}%H", LOG_COMMIT_MARKER);
+        let mut args = vec![
+            "log",
+            "--no-walk=unsorted",
+            "--name-status",
+            "-M",
+            "-z",
+            format.as_str(),
+        ];
+        args.extend_from_slice(commit_shas);
+        let output = self.run_git(&args)?;
+
+        Ok(parse_log_name_status(&output)?.into_iter().collect())
+    }
+
+    fn apply_hunk_to_index(&self, hunk: &Hunk) -> Result<(), GitError> {
+        let patch = hunk.to_full_patch();
+
+        // Write patch to temp file
+        let mut temp_file = tempfile::NamedTempFile::new()?;
+        temp_file.write_all(patch.as_bytes())?;
+        temp_file.flush()?;
+
+        self.apply_patch_to_index(temp_file.path(), &hunk.file_path)
+    }
+
+    fn reset_to(&self, ref_name: &str) -> Result<(), GitError> {
+        if ref_name == EMPTY_TREE {
+            self.unborn_head()?;
+            self.run_git(&["read-tree", "--empty"])?;
+            return Ok(());
+        }
+        self.run_git(&["reset", ref_name])?;
+        Ok(())
+    }
+
+    fn reset_hard(&self, ref_name: &str) -> Result<(), GitError> {
+        self.run_git(&["reset", "--hard", ref_name])?;
+        Ok(())
+    }
+
+    fn apply_hunks_to_index(
+        &self,
+        hunks: &[&Hunk],
+        patch_context: &crate::patch::PatchContext,
+    ) -> Result<(), GitError> {
+        if hunks.is_empty() {
+            return Ok(());
+        }
+
+        // Group hunks by file
+        let mut hunks_by_file: HashMap<std::path::PathBuf, Vec<&Hunk>> = HashMap::new();
+        for hunk in hunks {
+            hunks_by_file
+                .entry(hunk.file_path.clone())
+                .or_default()
+                .push(hunk);
+        }
+
+        // For each file, use PatchContext to generate correct patch and apply it
+        for (file_path, mut file_hunks) in hunks_by_file {
+            let file_path = file_path.as_path();
+            // Sort hunks by line number - git expects hunks in order
+            file_hunks.sort_by_key(|h| h.old_start);
+
+            // Check actual git index state
+            let file_in_index = self.file_in_index(file_path)?;
+
+            // Use PatchContext to generate the patch with correct headers
+            let (patch, _change_type) =
+                patch_context.generate_patch(file_path, &file_hunks, file_in_index);
+
+            if patch.is_empty() {
+                continue;
+            }
+
+            // Write patch to temp file and apply
+            let mut temp_file = tempfile::NamedTempFile::new()?;
+            temp_file.write_all(patch.as_bytes())?;
+            temp_file.flush()?;
+
+            self.apply_patch_to_index(temp_file.path(), file_path)?;
+        }
+
+        Ok(())
+    }
+
+    fn stage_all(&self) -> Result<(), GitError> {
+        self.run_git(&["add", "-A"])?;
+        Ok(())
+    }
+
+    fn stage_files(&self, files: &[&Path]) -> Result<(), GitError> {
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let mut args = vec![OsStr::new("add"), OsStr::new("--")];
+        args.extend(files.iter().map(|file| file.as_os_str()));
+        self.run_git(&args)?;
+        Ok(())
+    }
+
+    fn stage_deletion(&self, file_path: &Path) -> Result<(), GitError> {
+        self.run_git(&[
+            OsStr::new("rm"),
+            OsStr::new("--cached"),
+            OsStr::new("--quiet"),
+            OsStr::new("--"),
+            file_path.as_os_str(),
+        ])?;
+        Ok(())
+    }
+
+    fn set_file_mode(&self, file_path: &Path, mode: &str) -> Result<(), GitError> {
+        let chmod = match mode {
+            "100755" => "--chmod=+x",
+            "100644" => "--chmod=-x",
+            other => {
+                return Err(GitError::CommandFailed(format!(
+                    "cannot set mode {} on {}: only 100644 and 100755 are supported",
+                    other,
+                    file_path.display()
+                )))
+            }
+        };
+        self.run_git(&[
+            OsStr::new("update-index"),
+            OsStr::new(chmod),
+            OsStr::new("--"),
+            file_path.as_os_str(),
+        ])?;
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        message: &str,
+        no_verify: bool,
+        allow_empty: bool,
+    ) -> Result<CommitResult, GitError> {
+        // Exits 1 when something is staged
+        let staged_args = ["diff", "--cached", "--quiet"];
+        let staged = self.git_output(&staged_args)?;
+        let empty = match staged.status.code() {
+            Some(0) => true,
+            Some(1) => false,
+            _ => {
+                let stderr = String::from_utf8_lossy(&staged.stderr);
+                return Err(classify_failure(&staged_args, &stderr));
+            }
+        };
+        if empty && !allow_empty {
+            return Ok(CommitResult {
+                sha: self.head_or_empty_tree()?,
+                created: false,
+                empty,
+            });
+        }
+
+        // Write message to temp file to handle multiline messages
+        let mut temp_file = tempfile::NamedTempFile::new()?;
+        temp_file.write_all(message.as_bytes())?;
+        temp_file.flush()?;
+
+        let mut args = vec![
+            OsStr::new("commit"),
+            OsStr::new("-F"),
+            temp_file.path().as_os_str(),
+        ];
+        if no_verify {
+            args.push(OsStr::new("--no-verify"));
+        }
+        if allow_empty {
+            args.push(OsStr::new("--allow-empty"));
+        }
+        self.run_git(&args)?;
+
+        Ok(CommitResult {
+            sha: self.get_head()?,
+            created: true,
+            empty,
+        })
+    }
+
+    fn use_index_file(&self, index_file: Option<&Path>) {
+        *self.index_file.lock().unwrap() = index_file.map(Path::to_path_buf);
+    }
+
+    fn read_tree(&self, tree_ish: &str) -> Result<(), GitError> {
+        self.run_git(&["read-tree", tree_ish])?;
+        Ok(())
+    }
+
+    fn write_tree(&self) -> Result<String, GitError> {
+        Ok(self.run_git(&["write-tree"])?.trim().to_string())
+    }
+
+    fn tree_of(&self, rev: &str) -> Result<String, GitError> {
+        let spec = format!("{}^{{tree}}", rev);
+        Ok(self
+            .run_git(&["rev-parse", "--verify", &spec])?
+            .trim()
+            .to_string())
+    }
+
+    fn commit_tree(
+        &self,
+        tree: &str,
+        parent: &str,
+        message: &str,
+        author: Option<&SourceCommit>,
+    ) -> Result<String, GitError> {
+        // Match `git commit`'s default cleanup of a message given with -F
+        let mut temp_file = tempfile::NamedTempFile::new()?;
+        writeln!(temp_file, "{}", message.trim())?;
+        temp_file.flush()?;
+
+        let mut args = vec![OsStr::new("commit-tree"), OsStr::new(tree)];
+        if parent != EMPTY_TREE {
+            args.extend([OsStr::new("-p"), OsStr::new(parent)]);
+        }
+        args.extend([OsStr::new("-F"), temp_file.path().as_os_str()]);
+
+        let date = author.map(|commit| format!("@{}", commit.author_date));
+        let env: Vec<(&str, &str)> = match (author, &date) {
+            (Some(commit), Some(date)) => vec![
+                ("GIT_AUTHOR_NAME", commit.author_name.as_str()),
+                ("GIT_AUTHOR_EMAIL", commit.author_email.as_str()),
+                ("GIT_AUTHOR_DATE", date.as_str()),
+            ],
+            _ => Vec::new(),
+        };
+        let output = self.git_output_with_env(&args, &env)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(classify_failure(&args, &stderr));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn update_head(&self, sha: &str) -> Result<(), GitError> {
+        if sha == EMPTY_TREE {
+            return self.unborn_head();
+        }
+        self.run_git(&["update-ref", "-m", "reabsorb: commit", "HEAD", sha])?;
+        Ok(())
+    }
+
+    fn amend_message(&self, message: &str, no_verify: bool) -> Result<String, GitError> {
+        let mut temp_file = tempfile::NamedTempFile::new()?;
+        temp_file.write_all(message.as_bytes())?;
+        temp_file.flush()?;
+
+        // --only with no paths commits nothing from the index
+        let mut args = vec![
+            OsStr::new("commit"),
+            OsStr::new("--amend"),
+            OsStr::new("--only"),
+            OsStr::new("--allow-empty"),
+            OsStr::new("-F"),
+            temp_file.path().as_os_str(),
+        ];
+        if no_verify {
+            args.push(OsStr::new("--no-verify"));
+        }
+        self.run_git(&args)?;
+
+        self.get_head()
+    }
+
+    fn save_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError> {
+        let head = self.get_head()?;
+        self.run_git(&["update-ref", ref_name, &head])?;
+        Ok(())
+    }
+
+    fn get_pre_reabsorb_head(&self, ref_name: &str) -> Result<String, GitError> {
+        let result = self.run_git(&["rev-parse", ref_name]);
+        match result {
+            Ok(sha) => Ok(sha.trim().to_string()),
+            Err(_) => Err(GitError::NoSavedState),
+        }
+    }
+
+    fn has_pre_reabsorb_head(&self, ref_name: &str) -> bool {
+        self.run_git(&["rev-parse", "--verify", ref_name]).is_ok()
+    }
+
+    fn clear_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError> {
+        if self.has_pre_reabsorb_head(ref_name) {
+            self.run_git(&["update-ref", "-d", ref_name])?;
+        }
+        Ok(())
+    }
+
+    fn record_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+        max_entries: usize,
+    ) -> Result<(), GitError> {
+        let head = self.get_head()?;
+        let saved_at = chrono::Utc::now().timestamp_millis();
+        let ref_name = format!("{}/{}", history_prefix, saved_at);
+        self.run_git(&["update-ref", &ref_name, &head])?;
+
+        for stale in self
+            .list_pre_reabsorb_history(history_prefix)?
+            .iter()
+            .skip(max_entries)
+        {
+            self.run_git(&["update-ref", "-d", &stale.ref_name])?;
+        }
+        Ok(())
+    }
+
+    fn list_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+    ) -> Result<Vec<PreReabsorbEntry>, GitError> {
+        let pattern = format!("{}/", history_prefix);
+        let output = self.run_git(&[
+            "for-each-ref",
+            "--format=%(refname) %(objectname)",
+            &pattern,
+        ])?;
+
+        let mut entries: Vec<PreReabsorbEntry> = output
+            .lines()
+            .filter_map(|line| {
+                let (ref_name, sha) = line.split_once(' ')?;
+                // Ignore refs that weren't written by record_pre_reabsorb_history
+                let saved_at = ref_name.strip_prefix(&pattern)?.parse().ok()?;
+                Some(PreReabsorbEntry {
+                    ref_name: ref_name.to_string(),
+                    sha: sha.to_string(),
+                    saved_at,
+                })
+            })
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.saved_at));
+        Ok(entries)
+    }
+
+    fn create_branch_at(&self, name: &str, sha: &str) -> Result<(), GitError> {
+        self.create_ref(&format!("refs/heads/{}", name), name, sha)
+    }
+
+    fn create_tag(&self, name: &str, sha: &str) -> Result<(), GitError> {
+        self.create_ref(&format!("refs/tags/{}", name), name, sha)
+    }
+
+    fn current_branch_name(&self) -> Result<String, GitError> {
+        let output = self.run_git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+        Ok(output.trim().to_string())
+    }
+
+    fn upstream_of(&self, branch: &str) -> Result<Option<String>, GitError> {
+        // Look the upstream up from the branch's ref rather than matching
+        // @{u}'s (translated) error messages. A detached HEAD resolves to
+        // "HEAD" and a tag to refs/tags/..., neither of which has one
+        let full_name = self.run_git(&["rev-parse", "--symbolic-full-name", branch])?;
+        let full_name = full_name.trim();
+        if !full_name.starts_with("refs/heads/") {
+            return Ok(None);
+        }
+        let upstream = self.run_git(&["for-each-ref", "--format=%(upstream:short)", full_name])?;
+        let upstream = upstream.trim();
+        Ok((!upstream.is_empty()).then(|| upstream.to_string()))
+    }
+
+    fn get_config_path(&self, key: &str) -> Result<Option<String>, GitError> {
+        // `git config --get` exits non-zero when the key is unset
+        match self.run_git(&["config", "--path", "--get", key]) {
+            Ok(value) => {
+                let value = value.trim();
+                Ok((!value.is_empty()).then(|| value.to_string()))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn file_in_index(&self, file_path: &Path) -> Result<bool, GitError> {
+        // Check with ls-files
+        let result = self.run_git(&[
+            OsStr::new("ls-files"),
+            OsStr::new("--"),
+            file_path.as_os_str(),
+        ]);
+        if let Ok(output) = &result {
+            if !output.trim().is_empty() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        let root = self.run_git(&["rev-parse", "--show-toplevel"])?;
+        Ok(PathBuf::from(root.trim()))
+    }
+
+    fn operation_in_progress(&self) -> Result<bool, GitError> {
+        if !self.run_git(&["ls-files", "--unmerged"])?.trim().is_empty() {
+            return Ok(true);
+        }
+        // These stay after the conflicts are resolved, until the operation
+        // is continued or aborted
+        let git_dir = self.run_git(&["rev-parse", "--absolute-git-dir"])?;
+        let git_dir = Path::new(git_dir.trim());
+        Ok(IN_PROGRESS_MARKERS
+            .iter()
+            .any(|marker| git_dir.join(marker).exists()))
+    }
+
+    fn run_git_output(&self, args: &[&str]) -> Result<String, GitError> {
+        self.run_git(args)
+    }
+
+    fn apply_binary_files(&self, changes: &[&crate::models::FileChange]) -> Result<(), GitError> {
+        use crate::models::ChangeType;
+
+        let binary_changes: Vec<_> = changes.iter().filter(|fc| fc.is_binary).collect();
+
+        for fc in binary_changes {
+            let path = fc.file_path.as_os_str();
+
+            match fc.change_type {
+                ChangeType::Added | ChangeType::Modified => {
+                    self.run_git(&[OsStr::new("add"), OsStr::new("--"), path])?;
+                }
+                ChangeType::Deleted => {
+                    self.run_git(&[
+                        OsStr::new("rm"),
+                        OsStr::new("--cached"),
+                        OsStr::new("--"),
+                        path,
+                    ])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChangeType, DiffLine, FileChange, HunkId};
+    use crate::patch::PatchContext;
+    use std::path::PathBuf;
+
+    fn make_modification_hunk() -> Hunk {
+        Hunk {
+            id: HunkId(0),
+            file_path: PathBuf::from("test.rs"),
+            old_start: 1,
+            old_count: 3,
+            new_start: 1,
+            new_count: 4,
+            lines: vec![
+                DiffLine::Context("fn main() {".to_string()),
+                DiffLine::Added("    println!(\"Hello\");".to_string()),
+                DiffLine::Context("    println!(\"World\");".to_string()),
+                DiffLine::Context("}".to_string()),
+            ],
+            likely_source_commits: vec!["abc123".to_string()],
+            old_missing_newline_at_eof: false,
+            new_missing_newline_at_eof: false,
+        }
+    }
+
+    #[test]
+    fn test_patch_for_existing_file() {
+        let hunk = make_modification_hunk();
+        let ctx = PatchContext::empty();
+        // File exists in index -> modification patch
+        let (patch, _) = ctx.generate_patch(Path::new("test.rs"), &[&hunk], true);
+        assert!(patch.contains("--- a/test.rs"), "Should have old path");
+        assert!(patch.contains("+++ b/test.rs"), "Should have new path");
+        assert!(patch.contains("@@ -1,3 +1,4 @@"));
+    }
+
+    #[test]
+    fn test_patch_for_new_file() {
+        let hunk = make_modification_hunk();
+        let ctx = PatchContext::empty();
+        // File does NOT exist in index -> new file patch (transformed)
+        let (patch, _) = ctx.generate_patch(Path::new("test.rs"), &[&hunk], false);
+        assert!(patch.contains("--- /dev/null"), "Should be new file");
+        assert!(patch.contains("+++ b/test.rs"));
+    }
+
+    #[test]
+    fn test_patch_for_new_file_in_range() {
+        let hunk = make_modification_hunk();
+        // Mark file as new in range - even with modification hunks,
+        // it should generate a new file patch
+        let file_changes = vec![FileChange {
+            file_path: PathBuf::from("test.rs"),
+            change_type: ChangeType::Added,
+            old_mode: None,
+            new_mode: Some("100644".to_string()),
+            is_binary: false,
+            has_content_hunks: true,
+            likely_source_commits: vec![],
+            commit_index: None,
+        }];
+        let ctx = PatchContext::new(&file_changes);
+        let (patch, _) = ctx.generate_patch(Path::new("test.rs"), &[&hunk], false);
+        assert!(patch.contains("--- /dev/null"), "Should be new file");
+        assert!(patch.contains("+++ b/test.rs"));
+    }
+
+    #[test]
+    fn test_patch_for_deletion() {
+        let hunk = Hunk {
+            id: HunkId(0),
+            file_path: PathBuf::from("test.rs"),
+            old_start: 1,
+            old_count: 3,
+            new_start: 0,
+            new_count: 0,
+            lines: vec![
+                DiffLine::Removed("line1".to_string()),
+                DiffLine::Removed("line2".to_string()),
+                DiffLine::Removed("line3".to_string()),
+            ],
+            likely_source_commits: vec![],
+            old_missing_newline_at_eof: false,
+            new_missing_newline_at_eof: false,
+        };
+
+        let ctx = PatchContext::empty();
+        // File exists and all hunks are deletions -> delete patch
+        let (patch, _) = ctx.generate_patch(Path::new("test.rs"), &[&hunk], true);
+        assert!(patch.contains("--- a/test.rs"));
+        assert!(patch.contains("+++ /dev/null"), "Should delete file");
+    }
+
+    #[test]
+    fn test_parse_name_status() {
+        let output = "M\0src/lib.rs\0R087\0old.rs\0new.rs\0A\0added file.rs\0D\0gone.rs\0";
+        let statuses = parse_name_status(output).unwrap();
+        assert_eq!(
+            statuses,
+            vec![
+                ("src/lib.rs".to_string(), FileStatus::Modified),
+                (
+                    "new.rs".to_string(),
+                    FileStatus::Renamed {
+                        from: "old.rs".to_string()
+                    }
+                ),
+                ("added file.rs".to_string(), FileStatus::Added),
+                ("gone.rs".to_string(), FileStatus::Deleted),
+            ]
+        );
+        assert!(parse_name_status("M\0").is_err());
+    }
+
+    #[test]
+    fn test_parse_log_name_status() {
+        let output = "\x01abc\0\nM\0b\0R100\0a\0c\0\x01def\0\x01123\0\nA\0a\0";
+        let commits = parse_log_name_status(output).unwrap();
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[0].0, "abc");
+        assert_eq!(commits[0].1.len(), 2);
+        assert_eq!(commits[1], ("def".to_string(), vec![]));
+        assert_eq!(
+            commits[2],
+            (
+                "123".to_string(),
+                vec![("a".to_string(), FileStatus::Added)]
+            )
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("main", "main"), 0);
+        assert_eq!(edit_distance("mian", "main"), 2);
+        assert_eq!(edit_distance("feature", "featur"), 1);
+        assert_eq!(edit_distance("", "dev"), 3);
+    }
+
+    #[test]
+    fn test_classify_failure() {
+        assert!(matches!(
+            classify_failure(
+                &["status"],
+                "fatal: not a git repository (or any of the parent directories): .git"
+            ),
+            GitError::NotARepository
+        ));
+        assert!(matches!(
+            classify_failure(
+                &["log", "nope"],
+                "fatal: ambiguous argument 'nope': unknown revision or path not in the working tree."
+            ),
+            GitError::RefNotFound { reference, .. } if reference == "nope"
+        ));
+        assert!(matches!(
+            classify_failure(
+                &["commit"],
+                "error: Committing is not possible because you have unmerged files."
+            ),
+            GitError::MergeConflict(_)
+        ));
+        assert!(matches!(
+            classify_failure(&["push"], "fatal: something else"),
+            GitError::CommandFailed(msg) if msg.contains("git push failed")
+        ));
+    }
+
+    #[test]
+    fn test_validate_ref_prefix() {
+        assert_eq!(
+            validate_ref_prefix("refs/reabsorb").unwrap(),
+            "refs/reabsorb"
+        );
+        assert_eq!(
+            validate_ref_prefix("refs/tools/reabsorb/").unwrap(),
+            "refs/tools/reabsorb"
+        );
+        assert_eq!(
+            pre_reabsorb_ref_for(DEFAULT_REF_PREFIX, "main"),
+            "refs/reabsorb/pre-reabsorb/main"
+        );
+
+        for bad in [
+            "reabsorb",
+            "refs/",
+            "refs/a..b",
+            "refs/a b",
+            "refs//x",
+            "refs/.hidden",
+            "refs/x.lock",
+            "refs/x@{1}",
+        ] {
+            assert!(
+                matches!(
+                    validate_ref_prefix(bad),
+                    Err(GitError::InvalidRefPrefix { .. })
+                ),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+}
@@ -1,17 +1,22 @@
 pub mod app;
 pub mod assessment;
+pub mod audit;
 pub mod cancel;
 pub mod cli;
+pub mod color;
 pub mod editor;
 pub mod features;
 pub mod git;
+pub mod lint;
 pub mod llm;
 pub mod models;
 pub mod patch;
 pub mod plan_store;
 pub mod reorganize;
+pub mod repo_config;
+pub mod stats;
 pub mod utils;
 pub mod validation;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "testing"))]
 pub mod test_utils;
@@ -0,0 +1,171 @@
+//! Deterministic commit message checks run just before each commit.
+//!
+//! Rules are read from the `[lint]` table of `.reabsorb.toml` (see
+//! [`crate::repo_config`]).
+
+use serde::Deserialize;
+
+use crate::models::{DiffLine, Hunk};
+
+/// Subject length limit when none is configured.
+pub const DEFAULT_MAX_SUBJECT_LENGTH: usize = 72;
+
+/// Message rules. Every rule has a default, so an absent `[lint]` table
+/// still checks subjects and whitespace.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct LintConfig {
+    pub max_subject_length: usize,
+    /// Changed-line count above which a message needs a body
+    pub require_body_for_large_diffs: Option<usize>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            max_subject_length: DEFAULT_MAX_SUBJECT_LENGTH,
+            require_body_for_large_diffs: None,
+        }
+    }
+}
+
+/// A rule a message breaks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintIssue {
+    EmptySubject,
+    SubjectTooLong {
+        length: usize,
+        max: usize,
+    },
+    /// 1-based line of the message
+    TrailingWhitespace {
+        line: usize,
+    },
+    MissingBody {
+        changed_lines: usize,
+        threshold: usize,
+    },
+}
+
+impl std::fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptySubject => write!(f, "subject is empty"),
+            Self::SubjectTooLong { length, max } => {
+                write!(f, "subject is {} chars (max {})", length, max)
+            }
+            Self::TrailingWhitespace { line } => {
+                write!(f, "line {} has trailing whitespace", line)
+            }
+            Self::MissingBody {
+                changed_lines,
+                threshold,
+            } => write!(
+                f,
+                "{} changed lines (more than {}) need a message body",
+                changed_lines, threshold
+            ),
+        }
+    }
+}
+
+/// Check `message` for a commit made of `hunks`.
+#[must_use]
+pub fn lint_message(message: &str, hunks: &[&Hunk], config: &LintConfig) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+
+    let length = subject.trim().chars().count();
+    if length == 0 {
+        issues.push(LintIssue::EmptySubject);
+    } else if length > config.max_subject_length {
+        issues.push(LintIssue::SubjectTooLong {
+            length,
+            max: config.max_subject_length,
+        });
+    }
+
+    for (idx, line) in message.lines().enumerate() {
+        if line.ends_with(char::is_whitespace) {
+            issues.push(LintIssue::TrailingWhitespace { line: idx + 1 });
+        }
+    }
+
+    if let Some(threshold) = config.require_body_for_large_diffs {
+        let changed_lines = hunks
+            .iter()
+            .flat_map(|h| &h.lines)
+            .filter(|l| !matches!(l, DiffLine::Context(_)))
+            .count();
+        let has_body = lines.any(|l| !l.trim().is_empty());
+        if changed_lines > threshold && !has_body {
+            issues.push(LintIssue::MissingBody {
+                changed_lines,
+                threshold,
+            });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_hunk;
+
+    #[test]
+    fn clean_message_passes() {
+        let config = LintConfig::default();
+        assert!(lint_message("Add parser\n\nWith a body", &[], &config).is_empty());
+    }
+
+    #[test]
+    fn reports_subject_and_whitespace_issues() {
+        let config = LintConfig {
+            max_subject_length: 10,
+            ..LintConfig::default()
+        };
+
+        let issues = lint_message("A subject that is too long \n\nbody\t", &[], &config);
+        assert_eq!(
+            issues,
+            vec![
+                LintIssue::SubjectTooLong {
+                    length: 26,
+                    max: 10
+                },
+                LintIssue::TrailingWhitespace { line: 1 },
+                LintIssue::TrailingWhitespace { line: 3 },
+            ]
+        );
+        assert_eq!(
+            lint_message("", &[], &config),
+            vec![LintIssue::EmptySubject]
+        );
+    }
+
+    #[test]
+    fn large_diffs_need_a_body_when_configured() {
+        let hunk = make_hunk(0);
+        let changed = hunk
+            .lines
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Context(_)))
+            .count();
+        let config = LintConfig {
+            require_body_for_large_diffs: Some(changed - 1),
+            ..LintConfig::default()
+        };
+
+        assert_eq!(
+            lint_message("Subject only", &[&hunk], &config),
+            vec![LintIssue::MissingBody {
+                changed_lines: changed,
+                threshold: changed - 1
+            }]
+        );
+        assert!(lint_message("Subject\n\nExplained", &[&hunk], &config).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+//! Chain of LLM clients tried in order until one answers.
+
+use log::{debug, info, warn};
+
+use super::{LlmClient, LlmError};
+
+/// Tries each client in turn, returning the first usable completion.
+///
+/// Client failures, and replies the reply check rejects, move on to the next
+/// client; other errors are returned as they are. If every client answers
+/// but none usably, the last reply is returned so the caller's parser can
+/// report it. Callers that retry on a bad response go through the whole
+/// chain again, starting from the first client.
+pub struct FallbackLlmClient {
+    clients: Vec<(String, Box<dyn LlmClient>)>,
+    reply_check: fn(&str) -> bool,
+}
+
+impl FallbackLlmClient {
+    pub fn new() -> Self {
+        Self {
+            clients: Vec::new(),
+            reply_check: |_| true,
+        }
+    }
+
+    /// Treat replies `check` rejects like a failed client, e.g. a local
+    /// model answering in prose when JSON was asked for.
+    pub fn with_reply_check(mut self, check: fn(&str) -> bool) -> Self {
+        self.reply_check = check;
+        self
+    }
+
+    /// Append a client to the chain. `label` names it in logs.
+    pub fn with_client(mut self, label: impl Into<String>, client: Box<dyn LlmClient>) -> Self {
+        self.clients.push((label.into(), client));
+        self
+    }
+}
+
+impl Default for FallbackLlmClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LlmClient for FallbackLlmClient {
+    fn complete(&self, prompt: &str) -> Result<String, LlmError> {
+        let mut last_error = None;
+        let mut unusable = None;
+        for (index, (label, client)) in self.clients.iter().enumerate() {
+            let has_next = index + 1 < self.clients.len();
+            match client.complete(prompt) {
+                Ok(response) if (self.reply_check)(&response) => {
+                    if index == 0 {
+                        debug!("LLM response from {}", label);
+                    } else {
+                        info!("LLM response from fallback provider {}", label);
+                    }
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    if has_next {
+                        warn!("{} gave an unusable reply, trying the next provider", label);
+                    }
+                    unusable = Some(response);
+                }
+                Err(err @ LlmError::ClientError(_)) => {
+                    if has_next {
+                        warn!("{} failed, trying the next provider: {}", label, err);
+                    }
+                    last_error = Some(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        if let Some(response) = unusable {
+            return Ok(response);
+        }
+        Err(last_error
+            .unwrap_or_else(|| LlmError::ClientError("No LLM providers configured".to_string())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::llm::test_support::MockLlmClient;
+
+    struct FailingClient {
+        calls: Arc<AtomicUsize>,
+        error: fn() -> LlmError,
+    }
+
+    impl LlmClient for FailingClient {
+        fn complete(&self, _prompt: &str) -> Result<String, LlmError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Err((self.error)())
+        }
+    }
+
+    fn failing(calls: &Arc<AtomicUsize>, error: fn() -> LlmError) -> Box<dyn LlmClient> {
+        Box::new(FailingClient {
+            calls: calls.clone(),
+            error,
+        })
+    }
+
+    fn is_json(reply: &str) -> bool {
+        reply.starts_with('{')
+    }
+
+    #[test]
+    fn falls_back_on_client_errors_and_unusable_replies() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = FallbackLlmClient::new()
+            .with_reply_check(is_json)
+            .with_client(
+                "opencode",
+                failing(&calls, || LlmError::ClientError("down".into())),
+            )
+            .with_client(
+                "opencode:other",
+                Box::new(MockLlmClient::new("Sure! Here is the plan.")),
+            )
+            .with_client("claude", Box::new(MockLlmClient::new("{}")));
+
+        assert_eq!(client.complete("prompt").unwrap(), "{}");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn returns_the_last_unusable_reply_when_no_provider_helps() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = FallbackLlmClient::new()
+            .with_reply_check(is_json)
+            .with_client("opencode", Box::new(MockLlmClient::new("prose")))
+            .with_client(
+                "claude",
+                failing(&calls, || LlmError::ClientError("down".into())),
+            );
+
+        assert_eq!(client.complete("prompt").unwrap(), "prose");
+    }
+
+    #[test]
+    fn other_errors_stop_the_chain() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = FallbackLlmClient::new()
+            .with_client(
+                "opencode",
+                failing(&calls, || LlmError::InvalidResponse("bad".into())),
+            )
+            .with_client("claude", failing(&calls, || unreachable!()));
+
+        let err = client.complete("prompt").unwrap_err();
+        assert!(matches!(err, LlmError::InvalidResponse(_)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn returns_last_error_when_every_provider_fails() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let client = FallbackLlmClient::new()
+            .with_client("a", failing(&calls, || LlmError::ClientError("a".into())))
+            .with_client("b", failing(&calls, || LlmError::ClientError("b".into())));
+
+        let err = client.complete("prompt").unwrap_err();
+        assert!(matches!(err, LlmError::ClientError(msg) if msg == "b"));
+        assert!(FallbackLlmClient::new().complete("prompt").is_err());
+    }
+}
@@ -1,17 +1,23 @@
 //! File-based I/O for large LLM prompts
 //!
 //! This module provides temporary file management for the file-based LLM I/O
-//! optimization. When enabled via the `FileBasedLlmIo` feature flag, hunks are
-//! written to a temporary file and the LLM is instructed to read from that file,
-//! reducing token usage for large diffs.
+//! optimization. When enabled via the `FileBasedLlmIo` feature flag, hunks
+//! (for reorganization) or commit diffs (for assessment) are written to a
+//! temporary file and the LLM is instructed to read from that file, reducing
+//! token usage for large diffs.
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::llm::LlmError;
 
-const REABSORB_TMP_DIR: &str = ".git/reabsorb/tmp";
+pub const REABSORB_TMP_DIR: &str = ".git/reabsorb/tmp";
+
+/// Distinguishes sessions created in the same millisecond (assessment opens
+/// one per commit).
+static NEXT_SESSION: AtomicUsize = AtomicUsize::new(0);
 
 /// Manages temporary input file for a single LLM invocation.
 ///
@@ -26,18 +32,23 @@ pub struct LlmFileSession {
 impl LlmFileSession {
     /// Create a new session with a unique input file path.
     pub fn new() -> Result<Self, LlmError> {
+        Self::in_dir(Path::new(REABSORB_TMP_DIR), "hunks")
+    }
+
+    /// Create a session whose input file is `<prefix>-<unique>.txt` in `dir`.
+    pub fn in_dir(dir: &Path, prefix: &str) -> Result<Self, LlmError> {
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
             .as_millis();
 
         let pid = std::process::id();
+        let seq = NEXT_SESSION.fetch_add(1, Ordering::Relaxed);
 
-        let dir = PathBuf::from(REABSORB_TMP_DIR);
-        fs::create_dir_all(&dir)?;
+        fs::create_dir_all(dir)?;
 
         Ok(Self {
-            input_path: dir.join(format!("hunks-{}-{}.txt", timestamp, pid)),
+            input_path: dir.join(format!("{}-{}-{}-{}.txt", prefix, timestamp, pid, seq)),
             cleanup_on_drop: true,
         })
     }
@@ -11,13 +11,32 @@
 //! - Environment variables: `GIT_REABSORB_LLM_PROVIDER`, `GIT_REABSORB_LLM_MODEL`
 //!
 //! CLI arguments take precedence over environment variables.
+//!
+//! `--llm-fallback` turns the client into a [`FallbackLlmClient`] that tries
+//! several providers in order.
+//!
+//! The CLI executables default to `claude` and `opencode` on `PATH`, and can be
+//! overridden with `GIT_REABSORB_CLAUDE_BIN` and `GIT_REABSORB_OPENCODE_BIN`.
 
 use std::env;
 use std::io::{BufRead, BufReader, Write};
 use std::process::{Command, Stdio};
 use std::sync::Arc;
 
-use log::{debug, trace};
+use log::{debug, trace, warn};
+
+mod fallback;
+pub mod file_io;
+pub mod redact;
+
+pub use fallback::FallbackLlmClient;
+pub use redact::{Redaction, RedactionError};
+
+const DEFAULT_CLAUDE_BIN: &str = "claude";
+const DEFAULT_OPENCODE_BIN: &str = "opencode";
+const CLAUDE_BIN_ENV: &str = "GIT_REABSORB_CLAUDE_BIN";
+const OPENCODE_BIN_ENV: &str = "GIT_REABSORB_OPENCODE_BIN";
+const CLAUDE_JSON_ENV: &str = "GIT_REABSORB_CLAUDE_JSON";
 
 /// Available LLM providers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -53,6 +72,39 @@ impl std::str::FromStr for LlmProvider {
     }
 }
 
+/// One entry of a provider fallback chain: `claude` or `opencode:lmstudio/qwen`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderSpec {
+    pub provider: LlmProvider,
+    /// Model for this provider. Without one, the entry uses `--llm-model` if
+    /// it is the configured provider, and the provider's default otherwise.
+    pub model: Option<String>,
+}
+
+impl std::fmt::Display for ProviderSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.model {
+            Some(model) => write!(f, "{}:{}", self.provider, model),
+            None => write!(f, "{}", self.provider),
+        }
+    }
+}
+
+impl std::str::FromStr for ProviderSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (provider, model) = match s.trim().split_once(':') {
+            Some((provider, model)) => (provider, Some(model.to_string())),
+            None => (s.trim(), None),
+        };
+        Ok(Self {
+            provider: provider.parse()?,
+            model: model.filter(|m| !m.is_empty()),
+        })
+    }
+}
+
 /// Tool capability sets that can be granted to LLM clients.
 ///
 /// Each capability represents a logical grouping of related tools.
@@ -63,6 +115,17 @@ pub enum ToolCapability {
     /// - Claude: Read, Write
     /// - OpenCode: read, write, edit
     FileIo,
+    /// Read-only access to repository history, so the model can look at
+    /// surrounding code and earlier revisions.
+    /// - Claude: `git cat-file -p` only, through a Bash permission pattern.
+    ///   The claude CLI checks every command in a pipeline or chain against
+    ///   it, so nothing else can run. `git show` is left out: `--output`
+    ///   writes files and `--ext-diff`/textconv run configured programs,
+    ///   while `-p` excludes cat-file's other modes (`--textconv`,
+    ///   `--filters`).
+    /// - OpenCode: read, grep, glob, list. Its tool list can't scope shell
+    ///   commands, so it gets working-tree reads instead of git history.
+    RepoRead,
 }
 
 impl ToolCapability {
@@ -70,6 +133,7 @@ impl ToolCapability {
     pub fn to_claude_tools(self) -> &'static [&'static str] {
         match self {
             Self::FileIo => &["Read", "Write"],
+            Self::RepoRead => &["Bash(git cat-file -p:*)"],
         }
     }
 
@@ -77,6 +141,7 @@ impl ToolCapability {
     pub fn to_opencode_tools(self) -> &'static [&'static str] {
         match self {
             Self::FileIo => &["read", "write", "edit"],
+            Self::RepoRead => &["read", "grep", "glob", "list"],
         }
     }
 }
@@ -104,6 +169,16 @@ pub struct LlmConfig {
     pub opencode_backend: Option<String>,
     /// Tool capabilities to grant the LLM.
     pub capabilities: Option<Vec<ToolCapability>>,
+    /// Path to the claude executable (defaults to `claude` on PATH).
+    pub claude_bin: Option<String>,
+    /// Path to the opencode executable (defaults to `opencode` on PATH).
+    pub opencode_bin: Option<String>,
+    /// Request structured JSON output from the claude CLI.
+    pub claude_json: bool,
+    /// Providers to try in order. Empty means just `provider`.
+    pub fallback: Vec<ProviderSpec>,
+    /// Files whose contents prompts leave out.
+    pub redaction: Redaction,
 }
 
 impl LlmConfig {
@@ -118,6 +193,9 @@ impl LlmConfig {
     /// - `GIT_REABSORB_LLM_PROVIDER` - provider name (claude, opencode)
     /// - `GIT_REABSORB_LLM_MODEL` - model name
     /// - `GIT_REABSORB_OPENCODE_BACKEND` - backend for opencode (e.g., lmstudio, ollama)
+    /// - `GIT_REABSORB_CLAUDE_BIN` - path to the claude executable
+    /// - `GIT_REABSORB_OPENCODE_BIN` - path to the opencode executable
+    /// - `GIT_REABSORB_CLAUDE_JSON` - `1` or `true` to request JSON output from claude
     pub fn from_env() -> Self {
         let provider = env::var("GIT_REABSORB_LLM_PROVIDER")
             .ok()
@@ -126,12 +204,21 @@ impl LlmConfig {
 
         let model = env::var("GIT_REABSORB_LLM_MODEL").ok();
         let opencode_backend = env::var("GIT_REABSORB_OPENCODE_BACKEND").ok();
+        let claude_bin = env::var(CLAUDE_BIN_ENV).ok().filter(|s| !s.is_empty());
+        let opencode_bin = env::var(OPENCODE_BIN_ENV).ok().filter(|s| !s.is_empty());
+        let claude_json = env::var(CLAUDE_JSON_ENV)
+            .is_ok_and(|s| matches!(s.to_lowercase().as_str(), "1" | "true"));
 
         Self {
             provider,
             model,
             opencode_backend,
             capabilities: None,
+            claude_bin,
+            opencode_bin,
+            claude_json,
+            fallback: Vec::new(),
+            redaction: Redaction::default(),
         }
     }
 
@@ -153,6 +240,38 @@ impl LlmConfig {
         self
     }
 
+    /// Set the path to the claude executable.
+    pub fn with_claude_bin(mut self, path: impl Into<String>) -> Self {
+        self.claude_bin = Some(path.into());
+        self
+    }
+
+    /// Set the path to the opencode executable.
+    pub fn with_opencode_bin(mut self, path: impl Into<String>) -> Self {
+        self.opencode_bin = Some(path.into());
+        self
+    }
+
+    /// Ask the claude CLI for JSON output, falling back to plain text if the
+    /// installed version doesn't support it.
+    pub fn with_claude_json(mut self, claude_json: bool) -> Self {
+        self.claude_json = claude_json;
+        self
+    }
+
+    /// Try these providers in order instead of just the configured one,
+    /// moving on when one fails or returns something unparseable.
+    pub fn with_fallback(mut self, fallback: Vec<ProviderSpec>) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Keep the files `redaction` matches out of prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
     /// Set tool capabilities for the LLM.
     pub fn with_capabilities(mut self, capabilities: Vec<ToolCapability>) -> Self {
         self.capabilities = Some(capabilities);
@@ -187,35 +306,137 @@ impl LlmConfig {
 
     /// Create an LLM client from this configuration.
     pub fn create_client(&self) -> Arc<dyn LlmClient> {
-        let allowed_tools = self.allowed_tools();
-        match self.provider {
-            LlmProvider::Claude => Arc::new(ClaudeCliClient {
-                model: self.model.clone(),
-                allowed_tools,
-            }),
-            LlmProvider::OpenCode => Arc::new(OpenCodeClient {
-                model: self.model.clone(),
-                backend: self.opencode_backend.clone(),
-                allowed_tools,
-            }),
-        }
+        Arc::from(self.create_boxed_client())
     }
 
     /// Create a boxed LLM client from this configuration.
     pub fn create_boxed_client(&self) -> Box<dyn LlmClient> {
+        if self.fallback.is_empty() {
+            return self.create_provider_client();
+        }
+        // Every prompt asks for JSON, so a reply without any is worth
+        // another provider's attempt
+        let start = FallbackLlmClient::new()
+            .with_reply_check(|reply| crate::utils::extract_json_str(reply).is_some());
+        let chain = self.fallback.iter().fold(start, |chain, spec| {
+            chain.with_client(
+                spec.to_string(),
+                self.for_spec(spec).create_provider_client(),
+            )
+        });
+        Box::new(chain)
+    }
+
+    /// This config narrowed to one entry of the fallback chain.
+    fn for_spec(&self, spec: &ProviderSpec) -> LlmConfig {
+        let model = match &spec.model {
+            Some(model) => Some(model.clone()),
+            None if spec.provider == self.provider => self.model.clone(),
+            None => None,
+        };
+        LlmConfig {
+            provider: spec.provider,
+            model,
+            fallback: Vec::new(),
+            ..self.clone()
+        }
+    }
+
+    fn create_provider_client(&self) -> Box<dyn LlmClient> {
         let allowed_tools = self.allowed_tools();
         match self.provider {
             LlmProvider::Claude => Box::new(ClaudeCliClient {
                 model: self.model.clone(),
                 allowed_tools,
+                binary: self.claude_binary(),
+                json_output: self.claude_json,
             }),
             LlmProvider::OpenCode => Box::new(OpenCodeClient {
                 model: self.model.clone(),
                 backend: self.opencode_backend.clone(),
                 allowed_tools,
+                binary: self.opencode_binary(),
             }),
         }
     }
+
+    /// Models the configured provider offers, or `None` if its CLI can't
+    /// list them (the claude CLI has no such command).
+    pub fn list_models(&self) -> Result<Option<Vec<String>>, LlmError> {
+        match self.provider {
+            LlmProvider::Claude => Ok(None),
+            LlmProvider::OpenCode => {
+                let binary = self.opencode_binary();
+                let mut args = vec!["models"];
+                if let Some(backend) = &self.opencode_backend {
+                    args.push(backend);
+                }
+                let output = Command::new(&binary)
+                    .args(&args)
+                    .output()
+                    .map_err(|e| spawn_error("opencode", &binary, OPENCODE_BIN_ENV, e))?;
+                if !output.status.success() {
+                    return Err(LlmError::ClientError(format!(
+                        "opencode models failed (exit {}): {}",
+                        output.status.code().unwrap_or(-1),
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )));
+                }
+                let models = String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect();
+                Ok(Some(models))
+            }
+        }
+    }
+
+    /// Whether the configured model is one the provider lists. True when
+    /// there is no model set or the provider can't list its models.
+    pub fn model_is_listed(&self) -> Result<bool, LlmError> {
+        let model = match self.provider {
+            LlmProvider::Claude => self.model.clone(),
+            LlmProvider::OpenCode => {
+                opencode_model_arg(self.opencode_backend.as_deref(), self.model.as_deref())
+            }
+        };
+        let Some(model) = model else {
+            return Ok(true);
+        };
+        Ok(self
+            .list_models()?
+            .is_none_or(|models| models.contains(&model)))
+    }
+
+    /// The executable the configured provider will run.
+    pub fn binary(&self) -> String {
+        match self.provider {
+            LlmProvider::Claude => self.claude_binary(),
+            LlmProvider::OpenCode => self.opencode_binary(),
+        }
+    }
+
+    /// The environment variable that overrides [`LlmConfig::binary`].
+    pub fn binary_env(&self) -> &'static str {
+        match self.provider {
+            LlmProvider::Claude => CLAUDE_BIN_ENV,
+            LlmProvider::OpenCode => OPENCODE_BIN_ENV,
+        }
+    }
+
+    fn claude_binary(&self) -> String {
+        self.claude_bin
+            .clone()
+            .unwrap_or_else(|| DEFAULT_CLAUDE_BIN.to_string())
+    }
+
+    fn opencode_binary(&self) -> String {
+        self.opencode_bin
+            .clone()
+            .unwrap_or_else(|| DEFAULT_OPENCODE_BIN.to_string())
+    }
 }
 
 /// Trait for LLM completion clients.
@@ -228,6 +449,11 @@ pub trait LlmClient: Send + Sync {
 pub struct ClaudeCliClient {
     pub model: Option<String>,
     pub allowed_tools: Option<Vec<String>>,
+    /// Executable to run (name on PATH or full path).
+    pub binary: String,
+    /// Ask for `--output-format json` and take the assistant text from the
+    /// result object, instead of using stdout as-is.
+    pub json_output: bool,
 }
 
 impl ClaudeCliClient {
@@ -235,15 +461,27 @@ impl ClaudeCliClient {
         Self {
             model: None,
             allowed_tools: None,
+            binary: DEFAULT_CLAUDE_BIN.to_string(),
+            json_output: false,
         }
     }
 
     pub fn with_model(model: impl Into<String>) -> Self {
         Self {
             model: Some(model.into()),
-            allowed_tools: None,
+            ..Self::new()
         }
     }
+
+    pub fn with_binary(mut self, binary: impl Into<String>) -> Self {
+        self.binary = binary.into();
+        self
+    }
+
+    pub fn with_json_output(mut self, json_output: bool) -> Self {
+        self.json_output = json_output;
+        self
+    }
 }
 
 impl Default for ClaudeCliClient {
@@ -261,8 +499,27 @@ impl LlmClient for ClaudeCliClient {
         }
         trace!("[claude prompt] -------- END --------");
 
+        if self.json_output {
+            match self.run(prompt, true) {
+                Ok(output) => return parse_claude_json(&output),
+                // Older CLIs don't know the flag; carry on without it
+                Err(LlmError::ClientError(msg)) if msg.contains("unknown option") => {
+                    warn!("claude CLI doesn't support --output-format json; using plain text");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        self.run(prompt, false)
+    }
+}
+
+impl ClaudeCliClient {
+    fn run(&self, prompt: &str, json_output: bool) -> Result<String, LlmError> {
         // Use stdin for prompt to avoid command line length limits
         let mut args = vec!["--print"];
+        if json_output {
+            args.extend(["--output-format", "json"]);
+        }
 
         let model_str;
         if let Some(ref model) = self.model {
@@ -281,13 +538,13 @@ impl LlmClient for ClaudeCliClient {
             }
         }
 
-        let mut child = Command::new("claude")
+        let mut child = Command::new(&self.binary)
             .args(&args)
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| LlmError::ClientError(format!("Failed to run claude CLI: {}", e)))?;
+            .map_err(|e| spawn_error("claude", &self.binary, CLAUDE_BIN_ENV, e))?;
 
         // Write prompt to stdin
         if let Some(mut stdin) = child.stdin.take() {
@@ -339,7 +596,7 @@ impl LlmClient for ClaudeCliClient {
             }
 
             // Wait for stderr thread
-            let _ = stderr_handle.join();
+            let stderr_output = stderr_handle.join().unwrap_or_default();
 
             // Wait for process to finish
             let status = child.wait().map_err(|e| {
@@ -348,8 +605,9 @@ impl LlmClient for ClaudeCliClient {
 
             if !status.success() {
                 return Err(LlmError::ClientError(format!(
-                    "claude CLI failed with exit code: {:?}",
-                    status.code()
+                    "claude CLI failed with exit code {:?}: {}",
+                    status.code(),
+                    stderr_output.trim()
                 )));
             }
 
@@ -382,12 +640,34 @@ impl LlmClient for ClaudeCliClient {
     }
 }
 
+/// Take the assistant text out of `claude --output-format json` output. Output
+/// that isn't the expected result object is returned as-is.
+fn parse_claude_json(output: &str) -> Result<String, LlmError> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(output.trim()) else {
+        debug!("claude output is not JSON; using it as plain text");
+        return Ok(output.to_string());
+    };
+    let Some(result) = json.get("result").and_then(|r| r.as_str()) else {
+        debug!("claude JSON output has no result; using it as plain text");
+        return Ok(output.to_string());
+    };
+    if json.get("is_error").and_then(|e| e.as_bool()) == Some(true) {
+        return Err(LlmError::ClientError(format!(
+            "claude CLI reported an error: {}",
+            result
+        )));
+    }
+    Ok(result.to_string())
+}
+
 /// OpenCode CLI client implementation.
 pub struct OpenCodeClient {
     pub model: Option<String>,
     /// Backend provider (e.g., "lmstudio", "ollama").
     pub backend: Option<String>,
     pub allowed_tools: Option<Vec<String>>,
+    /// Executable to run (name on PATH or full path).
+    pub binary: String,
 }
 
 impl OpenCodeClient {
@@ -396,14 +676,14 @@ impl OpenCodeClient {
             model: None,
             backend: None,
             allowed_tools: None,
+            binary: DEFAULT_OPENCODE_BIN.to_string(),
         }
     }
 
     pub fn with_model(model: impl Into<String>) -> Self {
         Self {
             model: Some(model.into()),
-            backend: None,
-            allowed_tools: None,
+            ..Self::new()
         }
     }
 
@@ -411,6 +691,11 @@ impl OpenCodeClient {
         self.backend = Some(backend.into());
         self
     }
+
+    pub fn with_binary(mut self, binary: impl Into<String>) -> Self {
+        self.binary = binary.into();
+        self
+    }
 }
 
 impl Default for OpenCodeClient {
@@ -432,33 +717,10 @@ impl LlmClient for OpenCodeClient {
         // Model format is "provider/model" (e.g., "lmstudio/qwen/qwen3-coder-30b")
         let mut args = vec!["run", prompt, "--format", "json"];
 
-        // Build model string in format "backend/model"
-        let model_arg;
-        match (&self.backend, &self.model) {
-            (Some(backend), Some(model)) => {
-                // If model already contains a slash, use it as-is under the backend
-                // Otherwise combine them
-                if model.contains('/') {
-                    model_arg = model.clone()
-                } else {
-                    model_arg = format!("{}/{}", backend, model);
-                }
-                args.push("-m");
-                args.push(&model_arg);
-            }
-            (Some(_backend), None) => {
-                // Just backend without model - this won't work, need a full model path
-                // Skip the -m flag and let opencode use defaults
-            }
-            (None, Some(model)) => {
-                // Model specified - use as-is (should be in provider/model format)
-                model_arg = model.clone();
-                args.push("-m");
-                args.push(&model_arg);
-            }
-            (None, None) => {
-                // Use opencode defaults
-            }
+        let model_arg = opencode_model_arg(self.backend.as_deref(), self.model.as_deref());
+        if let Some(model) = &model_arg {
+            args.push("-m");
+            args.push(model);
         }
 
         // Add allowed tools if specified (comma-separated)
@@ -471,10 +733,10 @@ impl LlmClient for OpenCodeClient {
             }
         }
 
-        let output = Command::new("opencode")
+        let output = Command::new(&self.binary)
             .args(&args)
             .output()
-            .map_err(|e| LlmError::ClientError(format!("Failed to run opencode CLI: {}", e)))?;
+            .map_err(|e| spawn_error("opencode", &self.binary, OPENCODE_BIN_ENV, e))?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -516,6 +778,34 @@ impl LlmClient for OpenCodeClient {
     }
 }
 
+/// The `-m` argument for opencode, in "backend/model" form.
+fn opencode_model_arg(backend: Option<&str>, model: Option<&str>) -> Option<String> {
+    match (backend, model) {
+        // A model that already contains a slash is used as-is
+        (Some(backend), Some(model)) if !model.contains('/') => {
+            Some(format!("{}/{}", backend, model))
+        }
+        // Model specified - use as-is (should be in provider/model format)
+        (_, Some(model)) => Some(model.to_string()),
+        // A backend alone isn't a model path, so let opencode use its defaults
+        (_, None) => None,
+    }
+}
+
+/// Map a failure to launch an LLM CLI into a client error.
+///
+/// A missing executable gets a message naming it and the env var that overrides it.
+fn spawn_error(name: &str, binary: &str, env_var: &str, err: std::io::Error) -> LlmError {
+    if err.kind() == std::io::ErrorKind::NotFound {
+        LlmError::ClientError(format!(
+            "{} CLI not found (tried '{}'). Install it or set {} to its path",
+            name, binary, env_var
+        ))
+    } else {
+        LlmError::ClientError(format!("Failed to run {} CLI '{}': {}", name, binary, err))
+    }
+}
+
 /// Errors from LLM operations.
 #[derive(Debug, thiserror::Error)]
 pub enum LlmError {
@@ -616,4 +906,188 @@ mod tests {
         assert_eq!(updated3.model, Some("gpt-4".to_string()));
         assert_eq!(updated3.opencode_backend, Some("lmstudio".to_string()));
     }
+
+    #[test]
+    fn test_repo_read_tools_are_read_only() {
+        let claude = capabilities_to_tools(&[ToolCapability::RepoRead], LlmProvider::Claude);
+        assert_eq!(claude, vec!["Bash(git cat-file -p:*)"]);
+        assert!(!claude.iter().any(|t| t == "Bash" || t == "Write"));
+
+        let opencode = capabilities_to_tools(
+            &[ToolCapability::FileIo, ToolCapability::RepoRead],
+            LlmProvider::OpenCode,
+        );
+        assert_eq!(
+            opencode,
+            vec!["read", "write", "edit", "read", "grep", "glob", "list"]
+        );
+        let repo_only = capabilities_to_tools(&[ToolCapability::RepoRead], LlmProvider::OpenCode);
+        assert!(!repo_only
+            .iter()
+            .any(|t| matches!(t.as_str(), "bash" | "write" | "edit")));
+    }
+
+    #[test]
+    fn test_provider_spec_parse() {
+        let spec: ProviderSpec = "opencode:lmstudio/qwen".parse().unwrap();
+        assert_eq!(spec.provider, LlmProvider::OpenCode);
+        assert_eq!(spec.model.as_deref(), Some("lmstudio/qwen"));
+        assert_eq!(spec.to_string(), "opencode:lmstudio/qwen");
+
+        let spec: ProviderSpec = " claude ".parse().unwrap();
+        assert_eq!(spec.model, None);
+        assert_eq!(spec.to_string(), "claude");
+        assert!("gpt:4".parse::<ProviderSpec>().is_err());
+    }
+
+    #[test]
+    fn test_fallback_entries_keep_their_own_model() {
+        let config = LlmConfig::new()
+            .with_provider(LlmProvider::OpenCode)
+            .with_model("lmstudio/qwen")
+            .with_fallback(vec![
+                "opencode".parse().unwrap(),
+                "claude".parse().unwrap(),
+                "claude:opus".parse().unwrap(),
+            ]);
+
+        let models: Vec<_> = config
+            .fallback
+            .iter()
+            .map(|spec| {
+                let narrowed = config.for_spec(spec);
+                assert!(narrowed.fallback.is_empty());
+                (narrowed.provider, narrowed.model)
+            })
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                (LlmProvider::OpenCode, Some("lmstudio/qwen".to_string())),
+                (LlmProvider::Claude, None),
+                (LlmProvider::Claude, Some("opus".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_binary_error() {
+        let missing = "/nonexistent/git-reabsorb-test-bin";
+
+        let err = ClaudeCliClient::new()
+            .with_binary(missing)
+            .complete("prompt")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(missing), "{}", err);
+        assert!(err.contains("GIT_REABSORB_CLAUDE_BIN"), "{}", err);
+
+        let err = OpenCodeClient::new()
+            .with_binary(missing)
+            .complete("prompt")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(missing), "{}", err);
+        assert!(err.contains("GIT_REABSORB_OPENCODE_BIN"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_claude_json_extracts_result() {
+        let output =
+            r#"{"type":"result","subtype":"success","is_error":false,"result":"Sure! {\"a\": 1}"}"#;
+        assert_eq!(parse_claude_json(output).unwrap(), "Sure! {\"a\": 1}");
+
+        let error = r#"{"type":"result","is_error":true,"result":"rate limited"}"#;
+        assert!(matches!(
+            parse_claude_json(error),
+            Err(LlmError::ClientError(msg)) if msg.contains("rate limited")
+        ));
+
+        // Anything else is passed through untouched
+        assert_eq!(parse_claude_json("plain text").unwrap(), "plain text");
+        assert_eq!(parse_claude_json("{\"a\": 1}").unwrap(), "{\"a\": 1}");
+    }
+
+    /// A fake claude CLI that only knows `--output-format` if `json_ok`.
+    #[cfg(unix)]
+    fn fake_claude(dir: &std::path::Path, json_ok: bool) -> String {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("claude");
+        let json_branch = if json_ok {
+            r#"echo '{"type":"result","is_error":false,"result":"from json"}'; exit 0"#
+        } else {
+            "echo \"error: unknown option '--output-format'\" >&2; exit 1"
+        };
+        let script = format!(
+            "#!/bin/sh\ncat >/dev/null\ncase \"$*\" in *--output-format*) {} ;; esac\necho 'plain reply'\n",
+            json_branch
+        );
+        std::fs::write(&path, script).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path.display().to_string()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_claude_json_output_and_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let supported = ClaudeCliClient::new()
+            .with_binary(fake_claude(dir.path(), true))
+            .with_json_output(true);
+        assert_eq!(supported.complete("hi").unwrap(), "from json");
+
+        let unsupported_dir = dir.path().join("old");
+        std::fs::create_dir(&unsupported_dir).unwrap();
+        let unsupported = ClaudeCliClient::new()
+            .with_binary(fake_claude(&unsupported_dir, false))
+            .with_json_output(true);
+        assert_eq!(unsupported.complete("hi").unwrap().trim(), "plain reply");
+
+        let plain = ClaudeCliClient::new().with_binary(fake_claude(dir.path(), true));
+        assert_eq!(plain.complete("hi").unwrap().trim(), "plain reply");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_models_and_check_the_configured_one() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("opencode");
+        std::fs::write(
+            &path,
+            "#!/bin/sh\n[ \"$1\" = models ] || exit 1\necho 'lmstudio/qwen'\necho\necho 'ollama/llama3'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let opencode = LlmConfig::new()
+            .with_overrides(Some(LlmProvider::OpenCode), None, None)
+            .with_opencode_bin(path.display().to_string());
+
+        assert_eq!(
+            opencode.list_models().unwrap(),
+            Some(vec![
+                "lmstudio/qwen".to_string(),
+                "ollama/llama3".to_string()
+            ])
+        );
+        assert!(opencode.model_is_listed().unwrap());
+        let backend_and_model = opencode.clone().with_overrides(
+            None,
+            Some("qwen".to_string()),
+            Some("lmstudio".to_string()),
+        );
+        assert!(backend_and_model.model_is_listed().unwrap());
+        let typo = opencode.with_overrides(None, Some("ollama/lama3".to_string()), None);
+        assert!(!typo.model_is_listed().unwrap());
+
+        // The claude CLI can't list models, so any model passes
+        let claude = LlmConfig::new()
+            .with_overrides(Some(LlmProvider::Claude), Some("sonet".to_string()), None)
+            .with_claude_bin("/nonexistent/claude");
+        assert_eq!(claude.list_models().unwrap(), None);
+        assert!(claude.model_is_listed().unwrap());
+    }
 }
@@ -0,0 +1,239 @@
+//! Keeping the contents of sensitive files out of prompts.
+//!
+//! Hunks of files matching `--llm-redact` globs are replaced in prompts by a
+//! placeholder such as `[redacted: 12 lines in secrets.env]`, so the model
+//! still knows the file changed and by how much. The hunks themselves are
+//! applied as usual. The file path stays visible unless `--llm-redact-paths`
+//! is also given.
+
+use std::path::Path;
+
+use glob::{MatchOptions, Pattern};
+
+use crate::models::{DiffLine, Hunk};
+use crate::utils::format_diff_lines;
+
+/// Errors building a [`Redaction`]
+#[derive(Debug, thiserror::Error)]
+pub enum RedactionError {
+    #[error("Invalid redaction pattern '{pattern}': {source}")]
+    BadPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+}
+
+/// Which files' contents (and optionally paths) prompts leave out. The
+/// default redacts nothing.
+#[derive(Debug, Clone, Default)]
+pub struct Redaction {
+    /// Patterns without a `/` match the file name anywhere in the tree (like
+    /// `.gitignore`); others match the whole repo-relative path.
+    patterns: Vec<(Pattern, bool)>,
+    hide_paths: bool,
+}
+
+impl Redaction {
+    pub fn new(globs: &[String]) -> Result<Self, RedactionError> {
+        let patterns = globs
+            .iter()
+            .map(|glob| {
+                let pattern = Pattern::new(glob).map_err(|source| RedactionError::BadPattern {
+                    pattern: glob.clone(),
+                    source,
+                })?;
+                Ok((pattern, !glob.contains('/')))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            patterns,
+            hide_paths: false,
+        })
+    }
+
+    /// Also replace the paths of redacted files with an opaque name.
+    pub fn with_hide_paths(mut self, hide_paths: bool) -> Self {
+        self.hide_paths = hide_paths;
+        self
+    }
+
+    /// Whether any file is redacted.
+    pub fn is_active(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+
+    /// Whether redacted paths are hidden as well.
+    pub fn hides_paths(&self) -> bool {
+        self.hide_paths
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        self.patterns.iter().any(|(pattern, file_name_only)| {
+            if *file_name_only {
+                path.file_name()
+                    .is_some_and(|name| pattern.matches_with(&name.to_string_lossy(), options))
+            } else {
+                pattern.matches_path_with(path, options)
+            }
+        })
+    }
+
+    /// `path` as prompts should show it: unchanged, or an opaque name that
+    /// is the same for every mention of the file.
+    pub fn path(&self, path: &Path) -> String {
+        if self.hide_paths && self.matches(path) {
+            format!("redacted-{:08x}", fnv1a(path.to_string_lossy().as_bytes()))
+        } else {
+            path.to_string_lossy().into_owned()
+        }
+    }
+
+    /// The +/- lines of `hunk` for a prompt, or a placeholder.
+    pub fn hunk_lines(&self, hunk: &Hunk) -> String {
+        if self.matches(&hunk.file_path) {
+            self.placeholder(&hunk.file_path, changed_lines(hunk))
+        } else {
+            format_diff_lines(&hunk.lines)
+        }
+    }
+
+    /// `hunk` as a patch body for a prompt, or its header and a placeholder.
+    pub fn hunk_patch(&self, hunk: &Hunk) -> String {
+        if self.matches(&hunk.file_path) {
+            format!(
+                "@@ -{},{} +{},{} @@\n{}\n",
+                hunk.old_start,
+                hunk.old_count,
+                hunk.new_start,
+                hunk.new_count,
+                self.placeholder(&hunk.file_path, changed_lines(hunk))
+            )
+        } else {
+            hunk.to_patch()
+        }
+    }
+
+    /// Redact a `git diff` text, replacing each matching file's section after
+    /// its `diff --git` line with a placeholder.
+    pub fn diff(&self, diff: &str) -> String {
+        if !self.is_active() {
+            return diff.to_string();
+        }
+        let mut output = String::new();
+        let mut redacting: Option<(String, usize)> = None;
+        for line in diff.lines() {
+            if let Some(paths) = line.strip_prefix("diff --git ") {
+                if let Some((path, count)) = redacting.take() {
+                    output.push_str(&self.placeholder(Path::new(&path), count));
+                    output.push('\n');
+                }
+                let path = paths
+                    .rsplit_once(" b/")
+                    .map_or(paths, |(_, new_path)| new_path);
+                if self.matches(Path::new(path)) {
+                    let shown = self.path(Path::new(path));
+                    output.push_str(&format!("diff --git a/{} b/{}\n", shown, shown));
+                    redacting = Some((path.to_string(), 0));
+                    continue;
+                }
+            }
+            match &mut redacting {
+                Some((_, count)) => {
+                    let is_change = (line.starts_with('+') && !line.starts_with("+++"))
+                        || (line.starts_with('-') && !line.starts_with("---"));
+                    *count += usize::from(is_change);
+                }
+                None => {
+                    output.push_str(line);
+                    output.push('\n');
+                }
+            }
+        }
+        if let Some((path, count)) = redacting {
+            output.push_str(&self.placeholder(Path::new(&path), count));
+            output.push('\n');
+        }
+        output
+    }
+
+    fn placeholder(&self, path: &Path, lines: usize) -> String {
+        format!("[redacted: {} lines in {}]", lines, self.path(path))
+    }
+}
+
+fn changed_lines(hunk: &Hunk) -> usize {
+    hunk.lines
+        .iter()
+        .filter(|line| !matches!(line, DiffLine::Context(_)))
+        .count()
+}
+
+/// Stable across runs and platforms, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::make_hunk_in_file;
+
+    fn redaction(globs: &[&str]) -> Redaction {
+        Redaction::new(&globs.iter().map(|g| g.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn replaces_matching_hunks_with_a_placeholder() {
+        let redaction = redaction(&["*.env", "secrets/**"]);
+        let secret = make_hunk_in_file(0, "config/prod.env");
+        let public = make_hunk_in_file(1, "src/main.rs");
+
+        assert!(redaction.matches(Path::new("secrets/keys/api.txt")));
+        assert!(!redaction.matches(Path::new("src/secrets/api.txt")));
+        assert_eq!(
+            redaction.hunk_lines(&secret),
+            format!(
+                "[redacted: {} lines in config/prod.env]",
+                changed_lines(&secret)
+            )
+        );
+        assert_eq!(
+            redaction.hunk_lines(&public),
+            format_diff_lines(&public.lines)
+        );
+        assert!(!redaction.hunk_patch(&secret).contains(&secret.to_patch()));
+        assert_eq!(redaction.hunk_patch(&public), public.to_patch());
+
+        assert!(Redaction::new(&["[".to_string()]).is_err());
+        assert!(!Redaction::default().is_active());
+    }
+
+    #[test]
+    fn hides_paths_consistently() {
+        let redaction = redaction(&["*.env"]).with_hide_paths(true);
+        let hidden = redaction.path(Path::new("config/prod.env"));
+
+        assert!(hidden.starts_with("redacted-"));
+        assert_eq!(hidden, redaction.path(Path::new("config/prod.env")));
+        assert_ne!(hidden, redaction.path(Path::new("config/dev.env")));
+        assert_eq!(redaction.path(Path::new("src/main.rs")), "src/main.rs");
+    }
+
+    #[test]
+    fn redacts_sections_of_a_diff() {
+        let diff = "diff --git a/.env b/.env\n--- a/.env\n+++ b/.env\n@@ -1 +1 @@\n-TOKEN=old\n+TOKEN=new\ndiff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y\n";
+
+        let redacted = redaction(&[".env"]).diff(diff);
+
+        assert!(!redacted.contains("TOKEN"));
+        assert!(redacted.contains("diff --git a/.env b/.env\n[redacted: 2 lines in .env]\n"));
+        assert!(redacted
+            .ends_with("diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y\n"));
+    }
+}
@@ -23,6 +23,27 @@ pub enum Strategy {
     Hierarchical,
     /// Use git-absorb to fixup commits
     Absorb,
+    /// Preserve original commit structure but reword messages via LLM
+    Reword,
+    /// Bucket hunks by the glob rules in .reabsorb.toml (one commit per bucket)
+    Rules,
+    /// Preserve original commits, folding whitespace-only ones into the
+    /// previous commit and rewording "wip"-style messages
+    CleanPreserve,
+    /// One commit per "reabsorb: ..." hint comment in the added code; other
+    /// hunks keep their original commits
+    CommentHint,
+}
+
+impl Strategy {
+    /// Whether planning with this strategy calls the LLM when configured to.
+    /// Hierarchical can also run heuristic-only; see `StrategyFactory::uses_llm`.
+    pub fn requires_llm(self) -> bool {
+        matches!(
+            self,
+            Self::Llm | Self::Hierarchical | Self::Reword | Self::CleanPreserve
+        )
+    }
 }
 
 /// Unique identifier for a hunk within a reabsorb operation
@@ -50,6 +71,16 @@ impl std::fmt::Display for PlannedCommitId {
 pub struct SourceCommit {
     pub sha: String,
     pub message: CommitDescription,
+    #[serde(default)]
+    pub author_name: String,
+    #[serde(default)]
+    pub author_email: String,
+    /// Author date, in seconds since the Unix epoch
+    #[serde(default)]
+    pub author_date: i64,
+    /// Committer date, in seconds since the Unix epoch
+    #[serde(default)]
+    pub commit_date: i64,
 }
 
 impl SourceCommit {
@@ -57,8 +88,38 @@ impl SourceCommit {
         Self {
             sha: sha.into(),
             message: CommitDescription::new(short, long),
+            author_name: String::new(),
+            author_email: String::new(),
+            author_date: 0,
+            commit_date: 0,
         }
     }
+
+    pub fn with_author(mut self, name: impl Into<String>, email: impl Into<String>) -> Self {
+        self.author_name = name.into();
+        self.author_email = email.into();
+        self
+    }
+
+    pub fn with_dates(mut self, author_date: i64, commit_date: i64) -> Self {
+        self.author_date = author_date;
+        self.commit_date = commit_date;
+        self
+    }
+
+    /// The author as `Name <email>`
+    pub fn author_ident(&self) -> String {
+        format!("{} <{}>", self.author_name, self.author_email)
+    }
+
+    /// Whether `pattern` occurs in the author's `Name <email>`, ignoring case.
+    ///
+    /// Only the recorded commit author is considered, not co-authors.
+    pub fn matches_author(&self, pattern: &str) -> bool {
+        self.author_ident()
+            .to_lowercase()
+            .contains(&pattern.to_lowercase())
+    }
 }
 
 /// The type of change to a file.
@@ -82,9 +143,19 @@ pub struct FileChange {
     #[serde(default)]
     pub has_content_hunks: bool,
     pub likely_source_commits: Vec<String>,
+    /// Position in the plan of the commit that stages this change when it
+    /// has no hunks to carry it (binary or mode-only files). `None` stages
+    /// it with the first commit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_index: Option<usize>,
 }
 
 impl FileChange {
+    /// Whether no hunk carries this change, so it must be staged on its own
+    pub fn is_hunkless(&self) -> bool {
+        self.is_binary || !self.has_content_hunks
+    }
+
     pub fn with_path(path: PathBuf) -> Self {
         Self {
             file_path: path,
@@ -94,6 +165,7 @@ impl FileChange {
             is_binary: false,
             has_content_hunks: false,
             likely_source_commits: vec![],
+            commit_index: None,
         }
     }
 }
@@ -184,6 +256,122 @@ impl Hunk {
     pub fn to_full_patch(&self) -> String {
         crate::patch::PatchWriter::write_single_hunk(self)
     }
+
+    /// Split into two hunks at diff line `at` (0-based) so that applying the
+    /// first and then the second equals applying this hunk.
+    ///
+    /// The first half makes the changes in `lines[..at]` and carries the rest
+    /// of the region as old-side context; the second half sees the first
+    /// half's changes as context and makes the rest. The first half keeps
+    /// this hunk's id and the second takes `second_id`.
+    ///
+    /// # Panics
+    ///
+    /// If `at` is 0 or not less than the number of lines.
+    #[must_use]
+    pub fn split_lines(&self, at: usize, second_id: HunkId) -> (Hunk, Hunk) {
+        assert!(
+            at > 0 && at < self.lines.len(),
+            "split point {} out of range 1..{}",
+            at,
+            self.lines.len()
+        );
+        let (head, tail) = self.lines.split_at(at);
+
+        // Before: head applied, tail still in its old state
+        let mut first_lines = head.to_vec();
+        first_lines.extend(tail.iter().filter_map(|line| match line {
+            DiffLine::Added(_) => None,
+            DiffLine::Removed(text) | DiffLine::Context(text) => {
+                Some(DiffLine::Context(text.clone()))
+            }
+        }));
+
+        // After: head already in its new state, tail applied
+        let mut second_lines: Vec<DiffLine> = head
+            .iter()
+            .filter_map(|line| match line {
+                DiffLine::Removed(_) => None,
+                DiffLine::Added(text) | DiffLine::Context(text) => {
+                    Some(DiffLine::Context(text.clone()))
+                }
+            })
+            .collect();
+        second_lines.extend_from_slice(tail);
+
+        // Between the halves the file ends with the tail's last old line. A
+        // tail that only appends leaves the head's last line at the end, and
+        // that line is followed by more in the new file, so it keeps its newline
+        let tail_has_old_lines = tail.iter().any(|l| !matches!(l, DiffLine::Added(_)));
+        let middle_missing_newline = tail_has_old_lines && self.old_missing_newline_at_eof;
+
+        // A side with no lines starts at the line before the region, so a
+        // half whose side gains lines the whole hunk's side lacks moves on one
+        let start = |start: u32, whole_count: u32, count: u32| match (whole_count, count) {
+            (0, c) if c > 0 => start + 1,
+            (w, 0) if w > 0 => start.saturating_sub(1),
+            _ => start,
+        };
+
+        let half = |id: HunkId, lines: Vec<DiffLine>, old_missing: bool, new_missing: bool| {
+            let old_count = lines
+                .iter()
+                .filter(|l| !matches!(l, DiffLine::Added(_)))
+                .count() as u32;
+            let new_count = lines
+                .iter()
+                .filter(|l| !matches!(l, DiffLine::Removed(_)))
+                .count() as u32;
+            Hunk {
+                id,
+                old_start: start(self.old_start, self.old_count, old_count),
+                old_count,
+                new_start: start(self.new_start, self.new_count, new_count),
+                new_count,
+                lines,
+                old_missing_newline_at_eof: old_missing,
+                new_missing_newline_at_eof: new_missing,
+                ..self.clone()
+            }
+        };
+
+        (
+            half(
+                self.id,
+                first_lines,
+                self.old_missing_newline_at_eof,
+                middle_missing_newline,
+            ),
+            half(
+                second_id,
+                second_lines,
+                middle_missing_newline,
+                self.new_missing_newline_at_eof,
+            ),
+        )
+    }
+
+    /// Whether the removed and added lines differ only in whitespace
+    /// (indentation, spacing, blank lines).
+    #[must_use]
+    pub fn is_whitespace_only(&self) -> bool {
+        let mut removed = String::new();
+        let mut added = String::new();
+        for line in &self.lines {
+            match line {
+                DiffLine::Removed(text) => {
+                    removed.extend(text.chars().filter(|c| !c.is_whitespace()))
+                }
+                DiffLine::Added(text) => added.extend(text.chars().filter(|c| !c.is_whitespace())),
+                DiffLine::Context(_) => {}
+            }
+        }
+        let has_changes = self
+            .lines
+            .iter()
+            .any(|l| !matches!(l, DiffLine::Context(_)));
+        has_changes && removed == added
+    }
 }
 
 /// A commit description with short and long forms
@@ -265,6 +453,9 @@ pub struct PlannedCommit {
     /// Other planned commits this depends on (must be committed first)
     #[serde(default)]
     pub depends_on: Vec<PlannedCommitId>,
+    /// Why these changes were grouped together, when the strategy can say
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
 }
 
 impl PlannedCommit {
@@ -278,6 +469,7 @@ impl PlannedCommit {
             description,
             changes,
             depends_on: Vec::new(),
+            rationale: None,
         }
     }
 
@@ -293,6 +485,7 @@ impl PlannedCommit {
             description,
             changes,
             depends_on,
+            rationale: None,
         }
     }
 
@@ -310,8 +503,14 @@ impl PlannedCommit {
                 .map(PlannedChange::ExistingHunk)
                 .collect(),
             depends_on: Vec::new(),
+            rationale: None,
         }
     }
+
+    pub fn with_rationale(mut self, rationale: impl Into<String>) -> Self {
+        self.rationale = Some(rationale.into());
+        self
+    }
 }
 
 #[cfg(test)]
@@ -570,4 +769,194 @@ mod tests {
         assert_ne!(context, added);
         assert_ne!(added, removed);
     }
+
+    #[test]
+    fn test_matches_author() {
+        let commit =
+            SourceCommit::new("abc", "msg", "msg").with_author("Ada Lovelace", "ada@example.com");
+        assert!(commit.matches_author("ada"));
+        assert!(commit.matches_author("LOVELACE"));
+        assert!(commit.matches_author("<ada@example.com>"));
+        assert!(!commit.matches_author("grace"));
+    }
+
+    #[test]
+    fn test_is_whitespace_only() {
+        let mut hunk = make_test_hunk();
+        hunk.lines = vec![
+            DiffLine::Removed("if (x){".to_string()),
+            DiffLine::Added("if (x) {".to_string()),
+            DiffLine::Added("".to_string()),
+        ];
+        assert!(hunk.is_whitespace_only());
+
+        hunk.lines.push(DiffLine::Added("return;".to_string()));
+        assert!(!hunk.is_whitespace_only());
+
+        hunk.lines = vec![DiffLine::Context("unchanged".to_string())];
+        assert!(!hunk.is_whitespace_only());
+    }
+
+    /// Apply a hunk's lines to `file` in memory, checking the old side matches.
+    fn apply_in_memory(file: &[String], hunk: &Hunk) -> Vec<String> {
+        let start = hunk.old_start as usize - 1;
+        let mut result = file[..start].to_vec();
+        let mut pos = start;
+        for line in &hunk.lines {
+            match line {
+                DiffLine::Context(text) => {
+                    assert_eq!(&file[pos], text);
+                    result.push(text.clone());
+                    pos += 1;
+                }
+                DiffLine::Removed(text) => {
+                    assert_eq!(&file[pos], text);
+                    pos += 1;
+                }
+                DiffLine::Added(text) => result.push(text.clone()),
+            }
+        }
+        result.extend_from_slice(&file[pos..]);
+        result
+    }
+
+    fn make_mixed_hunk() -> Hunk {
+        let mut hunk = make_test_hunk();
+        hunk.old_start = 2;
+        hunk.new_start = 2;
+        hunk.lines = vec![
+            DiffLine::Context("a".to_string()),
+            DiffLine::Removed("b".to_string()),
+            DiffLine::Added("B".to_string()),
+            DiffLine::Context("c".to_string()),
+            DiffLine::Removed("d".to_string()),
+            DiffLine::Added("D1".to_string()),
+            DiffLine::Added("D2".to_string()),
+            DiffLine::Context("e".to_string()),
+        ];
+        hunk.old_count = 5;
+        hunk.new_count = 6;
+        hunk
+    }
+
+    #[test]
+    fn test_split_lines_halves_compose_to_original() {
+        let original: Vec<String> = ["top", "a", "b", "c", "d", "e", "bottom"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let hunk = make_mixed_hunk();
+        let expected = apply_in_memory(&original, &hunk);
+
+        // Split before a Removed, an Added, a Context, and mid-run of Added
+        for at in 1..hunk.lines.len() {
+            let (first, second) = hunk.split_lines(at, HunkId(99));
+            let middle = apply_in_memory(&original, &first);
+            assert_eq!(apply_in_memory(&middle, &second), expected, "at {}", at);
+        }
+    }
+
+    #[test]
+    fn test_split_lines_recomputes_counts() {
+        let hunk = make_mixed_hunk();
+
+        // After "b -> B": the first half only makes that change
+        let (first, second) = hunk.split_lines(3, HunkId(99));
+        assert_eq!((first.old_count, first.new_count), (5, 5));
+        assert_eq!((second.old_count, second.new_count), (5, 6));
+        assert_eq!(first.old_start, 2);
+        assert_eq!(second.new_start, 2);
+        assert_eq!(
+            first.lines[3..],
+            [
+                DiffLine::Context("c".to_string()),
+                DiffLine::Context("d".to_string()),
+                DiffLine::Context("e".to_string()),
+            ]
+        );
+        assert_eq!(
+            second.lines[..2],
+            [
+                DiffLine::Context("a".to_string()),
+                DiffLine::Context("B".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_lines_positions_each_side_on_its_own() {
+        // Inserts "x", "y" after line 2; an earlier hunk added three lines
+        let mut hunk = make_test_hunk();
+        hunk.old_start = 2;
+        hunk.old_count = 0;
+        hunk.new_start = 5;
+        hunk.new_count = 2;
+        hunk.lines = vec![
+            DiffLine::Added("x".to_string()),
+            DiffLine::Added("y".to_string()),
+        ];
+
+        let (first, second) = hunk.split_lines(1, HunkId(99));
+
+        assert_eq!(first.id, hunk.id);
+        assert_eq!(second.id, HunkId(99));
+        assert_eq!((first.old_start, first.old_count), (2, 0));
+        assert_eq!((first.new_start, first.new_count), (5, 1));
+        // The second half's old side is the "x" the first half inserted
+        assert_eq!((second.old_start, second.old_count), (3, 1));
+        assert_eq!((second.new_start, second.new_count), (5, 2));
+        let middle: Vec<String> = ["a", "b", "x", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(
+            apply_in_memory(&middle, &second),
+            vec!["a", "b", "x", "y", "c"]
+        );
+    }
+
+    #[test]
+    fn test_split_lines_carries_missing_newline() {
+        let mut hunk = make_test_hunk();
+        hunk.lines = vec![
+            DiffLine::Removed("old".to_string()),
+            DiffLine::Added("new".to_string()),
+        ];
+        hunk.old_missing_newline_at_eof = true;
+        hunk.new_missing_newline_at_eof = false;
+
+        let (first, second) = hunk.split_lines(1, HunkId(99));
+        assert_eq!(first.lines, vec![DiffLine::Removed("old".to_string())]);
+        assert_eq!(second.lines, vec![DiffLine::Added("new".to_string())]);
+        assert!(first.old_missing_newline_at_eof);
+        assert!(!second.new_missing_newline_at_eof);
+    }
+
+    #[test]
+    fn test_split_lines_appending_tail_keeps_newline_between_halves() {
+        let mut hunk = make_test_hunk();
+        hunk.old_start = 0;
+        hunk.old_count = 0;
+        hunk.new_count = 2;
+        hunk.lines = vec![
+            DiffLine::Added("first".to_string()),
+            DiffLine::Added("last".to_string()),
+        ];
+        hunk.new_missing_newline_at_eof = true;
+
+        let (first, second) = hunk.split_lines(1, HunkId(99));
+        // "first" is not the last line of the finished file, so the
+        // intermediate file must not lose its newline
+        assert!(!first.new_missing_newline_at_eof);
+        assert!(!second.old_missing_newline_at_eof);
+        assert!(second.new_missing_newline_at_eof);
+        assert!(!first.to_patch().contains("No newline"));
+        assert!(second
+            .to_patch()
+            .ends_with("+last\n\\ No newline at end of file\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_split_lines_rejects_edges() {
+        let hunk = make_test_hunk();
+        let _ = hunk.split_lines(hunk.lines.len(), HunkId(99));
+    }
 }
@@ -181,6 +181,7 @@ mod tests {
             is_binary: false,
             has_content_hunks: true,
             likely_source_commits: vec![],
+            commit_index: None,
         }
     }
 
@@ -7,6 +7,9 @@ mod writer;
 pub use context::PatchContext;
 pub use writer::PatchWriter;
 
+use std::io::{self, BufRead};
+use std::ops::ControlFlow;
+
 use crate::models::{FileChange, Hunk};
 
 #[derive(Debug, thiserror::Error)]
@@ -15,20 +18,96 @@ pub enum ParseError {
     InvalidHunkHeader(String),
     #[error("Unexpected diff format: {0}")]
     UnexpectedFormat(String),
+    #[error("Could not read diff: {0}")]
+    Io(#[from] io::Error),
 }
 
+/// A parsed unified diff.
 #[derive(Debug, Default)]
 pub struct Patch {
+    /// Content hunks, numbered from the `hunk_id_start` given to [`parse`]
     pub hunks: Vec<Hunk>,
+    /// Per-file metadata for files that need more than their hunks to
+    /// recreate: added/deleted files, mode changes (including mode-only
+    /// changes with no hunks) and binary files
     pub file_changes: Vec<FileChange>,
 }
 
+/// Parse `git diff` output into hunks and file changes.
+///
+/// Each hunk records `likely_source_commits`; pass an empty slice when the
+/// diff doesn't come from known commits.
 pub fn parse(
     diff_output: &str,
     likely_source_commits: &[String],
     hunk_id_start: usize,
 ) -> Result<Patch, ParseError> {
-    parser::PatchParser::new(likely_source_commits, hunk_id_start).parse(diff_output)
+    let mut hunks = Vec::new();
+    let file_changes = parser::PatchParser::new(likely_source_commits, hunk_id_start).parse(
+        diff_output.lines().map(Ok),
+        |hunk| {
+            hunks.push(hunk);
+            ControlFlow::Continue(())
+        },
+    )?;
+    Ok(Patch {
+        hunks,
+        file_changes,
+    })
+}
+
+/// Parse `git diff` output from `reader` a line at a time, passing each hunk
+/// to `on_hunk` as soon as it is complete, so a huge diff is never held in
+/// memory at once. Returns the file changes, as in [`Patch::file_changes`].
+///
+/// Returning `ControlFlow::Break` from `on_hunk` stops reading; the file
+/// changes then only cover the files finished so far. Invalid UTF-8 is
+/// replaced rather than rejected.
+pub fn parse_streaming<R: BufRead>(
+    reader: R,
+    likely_source_commits: &[String],
+    hunk_id_start: usize,
+    on_hunk: impl FnMut(Hunk) -> ControlFlow<()>,
+) -> Result<Vec<FileChange>, ParseError> {
+    parser::PatchParser::new(likely_source_commits, hunk_id_start)
+        .parse(Lines::new(reader), on_hunk)
+}
+
+/// Lines of `reader` with the endings `str::lines` strips removed, reusing
+/// one buffer.
+struct Lines<R> {
+    reader: R,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Lines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.clear();
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                if self.buf.ends_with(b"\n") {
+                    self.buf.pop();
+                    if self.buf.ends_with(b"\r") {
+                        self.buf.pop();
+                    }
+                }
+                Some(Ok(String::from_utf8_lossy(&self.buf).into_owned()))
+            }
+            Err(err) => Some(Err(err)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -372,4 +451,35 @@ new mode 100755
             PathBuf::from("script2.sh")
         );
     }
+
+    #[test]
+    fn test_streaming_matches_parse_and_can_stop_early() {
+        let diff = "diff --git a/script.sh b/script.sh\r\nold mode 100644\r\nnew mode 100755\r\n--- a/script.sh\r\n+++ b/script.sh\r\n@@ -1 +1,2 @@\r\n echo \"hello\"\r\n+echo \"world\"\r\ndiff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n\n@@ -10 +10 @@\n-x\n+y\n\\ No newline at end of file";
+        let commits = vec!["abc".to_string()];
+
+        let parsed = parse(diff, &commits, 5).unwrap();
+        let mut streamed = Vec::new();
+        let file_changes = parse_streaming(diff.as_bytes(), &commits, 5, |hunk| {
+            streamed.push(hunk);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+
+        assert_eq!(parsed.hunks.len(), 3);
+        assert_eq!(
+            serde_json::to_value(&streamed).unwrap(),
+            serde_json::to_value(&parsed.hunks).unwrap()
+        );
+        assert_eq!(file_changes.len(), parsed.file_changes.len());
+        assert_eq!(file_changes[0].new_mode, Some("100755".to_string()));
+        assert!(streamed[2].new_missing_newline_at_eof);
+
+        let mut seen = 0;
+        parse_streaming(diff.as_bytes(), &commits, 0, |_| {
+            seen += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+        assert_eq!(seen, 1);
+    }
 }
@@ -1,13 +1,17 @@
 //! Unified diff parsing.
 
+use std::io;
+use std::ops::ControlFlow;
 use std::path::PathBuf;
 
 use crate::models::{ChangeType, DiffLine, FileChange, Hunk, HunkId};
 
-use super::{ParseError, Patch};
+use super::ParseError;
 
+/// Line-at-a-time parser. Hunks are handed out as soon as the next hunk or
+/// file starts; only the hunk being read is held.
 pub(super) struct PatchParser<'a> {
-    result: Patch,
+    file_changes: Vec<FileChange>,
     likely_source_commits: &'a [String],
     next_hunk_id: usize,
     file: Option<FileChange>,
@@ -17,7 +21,7 @@ pub(super) struct PatchParser<'a> {
 impl<'a> PatchParser<'a> {
     pub fn new(likely_source_commits: &'a [String], hunk_id_start: usize) -> Self {
         Self {
-            result: Patch::default(),
+            file_changes: Vec::new(),
             likely_source_commits,
             next_hunk_id: hunk_id_start,
             file: None,
@@ -25,17 +29,34 @@ impl<'a> PatchParser<'a> {
         }
     }
 
-    pub fn parse(mut self, diff_output: &str) -> Result<Patch, ParseError> {
-        for line in diff_output.lines() {
-            self.process_line(line)?;
+    /// Parse `lines`, passing each finished hunk to `on_hunk`, and return
+    /// the file changes. Stops without finishing the current file when
+    /// `on_hunk` breaks.
+    pub fn parse<L: AsRef<str>>(
+        mut self,
+        lines: impl Iterator<Item = io::Result<L>>,
+        mut on_hunk: impl FnMut(Hunk) -> ControlFlow<()>,
+    ) -> Result<Vec<FileChange>, ParseError> {
+        for line in lines {
+            if let Some(hunk) = self.process_line(line?.as_ref())? {
+                if on_hunk(hunk).is_break() {
+                    return Ok(self.file_changes);
+                }
+            }
         }
-        self.finalize()
+        if let Some(hunk) = self.finalize_hunk() {
+            if on_hunk(hunk).is_break() {
+                return Ok(self.file_changes);
+            }
+        }
+        self.finalize_file();
+        Ok(self.file_changes)
     }
 
-    fn process_line(&mut self, line: &str) -> Result<(), ParseError> {
+    /// Handle one line, returning the hunk it finished, if any.
+    fn process_line(&mut self, line: &str) -> Result<Option<Hunk>, ParseError> {
         if line.starts_with("diff --git ") {
-            self.start_new_file(line);
-            return Ok(());
+            return Ok(self.start_new_file(line));
         }
 
         if let Some(rest) = line.strip_prefix("new file mode ") {
@@ -43,38 +64,38 @@ impl<'a> PatchParser<'a> {
                 file.change_type = ChangeType::Added;
                 file.new_mode = Some(rest.to_string());
             }
-            return Ok(());
+            return Ok(None);
         }
         if line.starts_with("new file") {
             if let Some(ref mut file) = self.file {
                 file.change_type = ChangeType::Added;
             }
-            return Ok(());
+            return Ok(None);
         }
         if let Some(rest) = line.strip_prefix("deleted file mode ") {
             if let Some(ref mut file) = self.file {
                 file.change_type = ChangeType::Deleted;
                 file.old_mode = Some(rest.to_string());
             }
-            return Ok(());
+            return Ok(None);
         }
         if line.starts_with("deleted file") {
             if let Some(ref mut file) = self.file {
                 file.change_type = ChangeType::Deleted;
             }
-            return Ok(());
+            return Ok(None);
         }
         if let Some(mode) = line.strip_prefix("old mode ") {
             if let Some(ref mut file) = self.file {
                 file.old_mode = Some(mode.to_string());
             }
-            return Ok(());
+            return Ok(None);
         }
         if let Some(mode) = line.strip_prefix("new mode ") {
             if let Some(ref mut file) = self.file {
                 file.new_mode = Some(mode.to_string());
             }
-            return Ok(());
+            return Ok(None);
         }
 
         if let Some(path) = line.strip_prefix("--- a/") {
@@ -83,26 +104,26 @@ impl<'a> PatchParser<'a> {
                     file.file_path = PathBuf::from(path);
                 }
             }
-            return Ok(());
+            return Ok(None);
         }
         if line.starts_with("--- ") {
-            return Ok(());
+            return Ok(None);
         }
         if let Some(path) = line.strip_prefix("+++ b/") {
             if let Some(ref mut file) = self.file {
                 file.file_path = PathBuf::from(path);
             }
-            return Ok(());
+            return Ok(None);
         }
         if line.starts_with("+++ ") {
-            return Ok(());
+            return Ok(None);
         }
 
         if line.starts_with("Binary files") {
             if let Some(ref mut file) = self.file {
                 file.is_binary = true;
             }
-            return Ok(());
+            return Ok(None);
         }
 
         if line.starts_with("index ")
@@ -110,29 +131,29 @@ impl<'a> PatchParser<'a> {
             || line.starts_with("rename from")
             || line.starts_with("rename to")
         {
-            return Ok(());
+            return Ok(None);
         }
 
         if line.starts_with("@@ ") {
-            self.start_hunk(line)?;
-            return Ok(());
+            return self.start_hunk(line);
         }
 
         self.process_hunk_line(line);
-        Ok(())
+        Ok(None)
     }
 
-    fn start_new_file(&mut self, line: &str) {
-        self.finalize_hunk();
+    fn start_new_file(&mut self, line: &str) -> Option<Hunk> {
+        let finished = self.finalize_hunk();
         self.finalize_file();
         self.file = parse_header(line).map(FileChange::with_path);
+        finished
     }
 
-    fn start_hunk(&mut self, line: &str) -> Result<(), ParseError> {
+    fn start_hunk(&mut self, line: &str) -> Result<Option<Hunk>, ParseError> {
         if let Some(ref mut file) = self.file {
             file.has_content_hunks = true;
         }
-        self.finalize_hunk();
+        let finished = self.finalize_hunk();
 
         let file_path = self
             .file
@@ -146,7 +167,7 @@ impl<'a> PatchParser<'a> {
                 .with_header(line)?,
         );
         self.next_hunk_id += 1;
-        Ok(())
+        Ok(finished)
     }
 
     fn process_hunk_line(&mut self, line: &str) {
@@ -176,12 +197,10 @@ impl<'a> PatchParser<'a> {
         }
     }
 
-    fn finalize_hunk(&mut self) {
-        if let Some(builder) = self.hunk.take() {
-            self.result
-                .hunks
-                .push(builder.build(self.likely_source_commits));
-        }
+    fn finalize_hunk(&mut self) -> Option<Hunk> {
+        self.hunk
+            .take()
+            .map(|builder| builder.build(self.likely_source_commits))
     }
 
     fn finalize_file(&mut self) {
@@ -200,7 +219,7 @@ impl<'a> PatchParser<'a> {
             ChangeType::Modified => (file.old_mode, file.new_mode),
         };
 
-        self.result.file_changes.push(FileChange {
+        self.file_changes.push(FileChange {
             file_path: file.file_path,
             change_type: file.change_type,
             old_mode,
@@ -208,14 +227,9 @@ impl<'a> PatchParser<'a> {
             is_binary: file.is_binary,
             has_content_hunks: file.has_content_hunks,
             likely_source_commits: self.likely_source_commits.to_vec(),
+            commit_index: None,
         });
     }
-
-    fn finalize(mut self) -> Result<Patch, ParseError> {
-        self.finalize_hunk();
-        self.finalize_file();
-        Ok(self.result)
-    }
 }
 
 fn parse_header(line: &str) -> Option<PathBuf> {
@@ -16,6 +16,11 @@ use crate::models::{
 
 const REABSORB_DIR: &str = ".git/reabsorb";
 const PLAN_FILE: &str = "plan.json";
+const ARCHIVE_DIR: &str = "archive";
+
+/// Environment variable naming a directory to keep plans in instead of the
+/// git dir. Refs such as the pre-reabsorb ref stay in the repository.
+pub const STATE_DIR_ENV: &str = "GIT_REABSORB_DIR";
 
 /// Errors from plan file operations.
 #[derive(Debug, thiserror::Error)]
@@ -41,6 +46,10 @@ pub struct SavedPlan {
     pub file_to_commits: Vec<(String, Vec<String>)>,
     #[serde(default)]
     pub file_changes: Vec<FileChange>,
+    /// Marker whose hint lines `--strip-hints` left out of the hunks, so
+    /// the new commits differ from `original_head` by those lines
+    #[serde(default)]
+    pub stripped_hint_marker: Option<String>,
 }
 
 /// A single commit in a saved plan.
@@ -74,6 +83,7 @@ impl SavedPlan {
                 .map(|(k, v)| (k.clone(), v.clone()))
                 .collect(),
             file_changes: file_changes.to_vec(),
+            stripped_hint_marker: None,
         }
     }
 
@@ -114,11 +124,39 @@ impl SavedPlan {
         }
     }
 
+    /// Mark commits from `index` (0-based) on as not yet created, so
+    /// execution redoes them.
+    pub fn rewind_to(&mut self, index: usize) {
+        for commit in self.commits.iter_mut().skip(index) {
+            commit.created_sha = None;
+        }
+        self.next_commit_index = self.next_commit_index.min(index);
+    }
+
+    /// The newest commit created before `index`, or the base if there is
+    /// none: what the commit at `index` gets built on.
+    pub fn created_before(&self, index: usize) -> &str {
+        self.commits[..index.min(self.commits.len())]
+            .iter()
+            .rev()
+            .filter_map(|c| c.created_sha.as_deref())
+            .find(|sha| *sha != SKIPPED_SHA)
+            .unwrap_or(&self.base_sha)
+    }
+
     pub fn is_complete(&self) -> bool {
         self.next_commit_index >= self.commits.len()
     }
+
+    /// The plan as pretty-printed JSON, exactly as it is written to disk.
+    pub fn to_json(&self) -> Result<String, PlanFileError> {
+        serde_json::to_string_pretty(self).map_err(|e| PlanFileError::Json(e.to_string()))
+    }
 }
 
+/// Recorded as a commit's `created_sha` when all its changes were already applied.
+pub const SKIPPED_SHA: &str = "SKIPPED";
+
 impl From<&PlannedCommit> for SavedCommit {
     fn from(pc: &PlannedCommit) -> Self {
         Self {
@@ -137,6 +175,8 @@ pub trait PlanStore {
     fn save(&self, plan: &SavedPlan) -> Result<(), PlanFileError>;
     fn delete(&self) -> Result<(), PlanFileError>;
     fn exists(&self) -> bool;
+    /// Keep `plan` in the archive and delete the saved plan.
+    fn archive(&self, plan: &SavedPlan) -> Result<PathBuf, PlanFileError>;
 }
 
 /// Filesystem-backed plan store using `.git/reabsorb/plan.json`.
@@ -174,11 +214,35 @@ impl PlanStore for FilePlanStore {
     fn exists(&self) -> bool {
         has_saved_plan(&self.namespace)
     }
+
+    fn archive(&self, plan: &SavedPlan) -> Result<PathBuf, PlanFileError> {
+        let path = archive_plan(plan)?;
+        delete_plan(&self.namespace)?;
+        Ok(path)
+    }
 }
 
 // --- File operations ---
 
+/// The `GIT_REABSORB_DIR` override, if set.
+fn state_dir() -> Option<PathBuf> {
+    env::var_os(STATE_DIR_ENV)
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Where new state is written: the `GIT_REABSORB_DIR` override, or
+/// `.git/reabsorb`.
+pub fn state_root() -> PathBuf {
+    state_dir().unwrap_or_else(|| PathBuf::from(REABSORB_DIR))
+}
+
 fn base_dirs() -> Vec<PathBuf> {
+    // An explicit state dir is the only place plans are read and written,
+    // so a plan never silently lands back in the git dir
+    if let Some(dir) = state_dir() {
+        return vec![dir];
+    }
     let mut dirs = Vec::new();
     if let Ok(dir) = env::var("GIT_REABSORB_PLAN_DIR") {
         if !dir.is_empty() {
@@ -209,14 +273,64 @@ fn existing_plan_path(namespace: &str) -> Option<PathBuf> {
 
 /// Get the path where a plan would be saved.
 pub fn plan_file_path(namespace: &str) -> PathBuf {
-    existing_plan_path(namespace)
-        .unwrap_or_else(|| PathBuf::from(REABSORB_DIR).join(namespace).join(PLAN_FILE))
+    existing_plan_path(namespace).unwrap_or_else(|| state_root().join(namespace).join(PLAN_FILE))
+}
+
+/// Paths of every saved plan, across all namespaces.
+pub fn list_saved_plans() -> Vec<PathBuf> {
+    let mut plans = Vec::new();
+    for dir in base_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path().join(PLAN_FILE);
+            if path.is_file() {
+                plans.push(path);
+            }
+        }
+    }
+    plans.sort();
+    plans
+}
+
+fn archive_dir() -> PathBuf {
+    state_root().join(ARCHIVE_DIR)
+}
+
+/// Write `plan` to `archive/<timestamp>.json` under the state directory.
+pub fn archive_plan(plan: &SavedPlan) -> Result<PathBuf, PlanFileError> {
+    let dir = archive_dir();
+    fs::create_dir_all(&dir)?;
+    let stamp = chrono::Utc::now().format("%Y%m%d_%H%M%S").to_string();
+    let mut path = dir.join(format!("{}.json", stamp));
+    // Two applies in the same second get a numbered suffix
+    let mut n = 1;
+    while path.exists() {
+        n += 1;
+        path = dir.join(format!("{}-{}.json", stamp, n));
+    }
+    fs::write(&path, plan.to_json()?)?;
+    Ok(path)
+}
+
+/// Archived plans, oldest first.
+pub fn list_archived_plans() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(archive_dir()) else {
+        return Vec::new();
+    };
+    let mut plans: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    plans.sort();
+    plans
 }
 
 /// Save a plan to disk.
 pub fn save_plan(namespace: &str, plan: &SavedPlan) -> Result<PathBuf, PlanFileError> {
-    let json =
-        serde_json::to_string_pretty(plan).map_err(|e| PlanFileError::Json(e.to_string()))?;
+    let json = plan.to_json()?;
     let mut last_err: Option<std::io::Error> = None;
 
     for dir in namespace_dirs(namespace) {
@@ -312,4 +426,39 @@ mod tests {
         assert_eq!(restored.len(), 1);
         assert_eq!(restored[0].changes.len(), 2);
     }
+
+    #[test]
+    fn rewind_clears_later_commits() {
+        let planned: Vec<_> = (0..4)
+            .map(|i| {
+                PlannedCommit::new(
+                    PlannedCommitId(i),
+                    CommitDescription::new(format!("Commit {}", i), ""),
+                    vec![],
+                )
+            })
+            .collect();
+        let mut saved = SavedPlan::new(
+            Strategy::Preserve,
+            "base".into(),
+            "head".into(),
+            &planned,
+            &[],
+            &HashMap::new(),
+            &[],
+        );
+        for sha in ["one", SKIPPED_SHA, "three"] {
+            saved.mark_commit_created(sha.to_string());
+        }
+        assert_eq!(saved.created_before(3), "three");
+        // Skipped commits have nothing to build on
+        assert_eq!(saved.created_before(2), "one");
+        assert_eq!(saved.created_before(0), "base");
+
+        saved.rewind_to(1);
+        assert_eq!(saved.next_commit_index, 1);
+        assert_eq!(saved.commits[0].created_sha.as_deref(), Some("one"));
+        assert!(saved.commits[1..].iter().all(|c| c.created_sha.is_none()));
+        assert_eq!(saved.created_before(3), "one");
+    }
 }
@@ -0,0 +1,57 @@
+//! Wall-clock budget for LLM-driven strategies.
+
+use std::time::{Duration, Instant};
+
+/// The point after which a strategy stops issuing new LLM calls and finishes
+/// with heuristics instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Deadline {
+    at: Option<Instant>,
+    budget: Option<Duration>,
+}
+
+impl Deadline {
+    /// A deadline `budget` from now, or none at all. A budget too long for
+    /// the clock to represent never runs out.
+    pub fn after(budget: Option<Duration>) -> Self {
+        Self {
+            at: budget.and_then(|b| Instant::now().checked_add(b)),
+            budget,
+        }
+    }
+
+    /// Whether the budget has run out. Always false without a budget.
+    pub fn is_past(&self) -> bool {
+        self.at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// The budget this deadline was created with, for reporting.
+    pub fn budget(&self) -> Option<Duration> {
+        self.budget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_without_budget_never_passes() {
+        assert!(!Deadline::default().is_past());
+        assert!(!Deadline::after(None).is_past());
+    }
+
+    #[test]
+    fn zero_budget_is_already_past() {
+        let deadline = Deadline::after(Some(Duration::ZERO));
+        assert!(deadline.is_past());
+        assert_eq!(deadline.budget(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn unrepresentable_budget_never_passes() {
+        let deadline = Deadline::after(Some(Duration::MAX));
+        assert!(!deadline.is_past());
+        assert_eq!(deadline.budget(), Some(Duration::MAX));
+    }
+}
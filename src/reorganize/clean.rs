@@ -0,0 +1,382 @@
+use std::collections::{HashMap, HashSet};
+
+use log::{debug, info};
+
+use crate::llm::{LlmClient, Redaction};
+use crate::models::{CommitDescription, Hunk, PlannedCommit, PlannedCommitId, SourceCommit};
+use crate::reorganize::reword::reword_commit;
+use crate::reorganize::{PreserveOriginal, ReorganizeError, Reorganizer};
+
+/// Subjects that say nothing about the change, matched case-insensitively
+/// after trimming trailing punctuation.
+const THROWAWAY_SUBJECTS: &[&str] = &[
+    "wip", "fixup", "fix", "fixes", "asdf", "tmp", "temp", "test", "stuff", "changes", "update",
+    "updates", "misc", "more", "oops", "x", "foo",
+];
+
+/// Keeps one commit per source commit, like `PreserveOriginal`, but tidies
+/// the branch: commits that only change whitespace are folded into the
+/// commit before them, and throwaway messages ("wip", "fixup! ...") are
+/// reworded from the diff by the LLM, or prefixed with "TODO: " when there
+/// is no LLM or it fails.
+pub struct CleanPreserve {
+    client: Option<Box<dyn LlmClient>>,
+    keep_empty: bool,
+    redaction: Redaction,
+}
+
+impl CleanPreserve {
+    pub fn new() -> Self {
+        Self {
+            client: None,
+            keep_empty: false,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Reword throwaway messages with `client` instead of marking them.
+    pub fn with_client(mut self, client: Option<Box<dyn LlmClient>>) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Keep whitespace-only commits as their own commits.
+    pub fn with_keep_empty(mut self, keep_empty: bool) -> Self {
+        self.keep_empty = keep_empty;
+        self
+    }
+
+    /// Show the files `redaction` matches as placeholders in prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    fn fix_message(&self, source: &SourceCommit, commit: &mut PlannedCommit, hunks: &[Hunk]) {
+        if let Some(client) = &self.client {
+            info!("Rewording \"{}\"", source.message.short);
+            if reword_commit(client.as_ref(), source, commit, hunks, &self.redaction) {
+                return;
+            }
+        }
+        commit.description = mark_todo(&commit.description);
+    }
+}
+
+impl Default for CleanPreserve {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reorganizer for CleanPreserve {
+    fn plan(
+        &self,
+        source_commits: &[SourceCommit],
+        hunks: &[Hunk],
+    ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
+        let preserved = PreserveOriginal.plan(source_commits, hunks)?;
+        let mut planned = fold_commits(preserved, |commit| {
+            if self.keep_empty || !is_whitespace_only(commit, hunks) {
+                return Disposition::Keep;
+            }
+            debug!(
+                "Folding whitespace-only commit \"{}\" into its predecessor",
+                commit.description.short
+            );
+            Disposition::Fold
+        });
+
+        for commit in &mut planned {
+            // PreserveOriginal numbers planned commits by source commit index
+            let Some(source) = source_commits.get(commit.id.0) else {
+                continue;
+            };
+            if is_throwaway_message(&source.message.short) {
+                self.fix_message(source, commit, hunks);
+            }
+        }
+
+        Ok(planned)
+    }
+
+    fn requires_llm(&self) -> bool {
+        // Without a client throwaway messages are marked instead
+        self.client.is_some()
+    }
+
+    fn name(&self) -> &'static str {
+        "clean-preserve"
+    }
+}
+
+/// What [`fold_commits`] does with one planned commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Disposition {
+    Keep,
+    /// Remove it along with its changes
+    Drop,
+    /// Move its changes into the kept commit before it
+    Fold,
+}
+
+/// Drop or fold the commits `classify` picks. A folded commit's changes join
+/// the kept commit before it, or the first kept one after it when there is
+/// none; if no commit is kept the folded ones stay as they are, so no change
+/// is lost. Dependencies on a folded commit move to the commit that took its
+/// changes, those on a dropped one go, and each list keeps one entry per
+/// commit. Ids are left as they are.
+pub fn fold_commits(
+    commits: Vec<PlannedCommit>,
+    classify: impl Fn(&PlannedCommit) -> Disposition,
+) -> Vec<PlannedCommit> {
+    let mut kept: Vec<PlannedCommit> = Vec::with_capacity(commits.len());
+    // Folded commit -> the commit that took its changes
+    let mut folded_into: HashMap<PlannedCommitId, PlannedCommitId> = HashMap::new();
+    let mut dropped = HashSet::new();
+    // Folded commits with nothing kept before them yet
+    let mut leading: Vec<PlannedCommit> = Vec::new();
+    for mut commit in commits {
+        match classify(&commit) {
+            Disposition::Drop => {
+                dropped.insert(commit.id);
+            }
+            Disposition::Fold => match kept.last_mut() {
+                Some(previous) => {
+                    folded_into.insert(commit.id, previous.id);
+                    previous.changes.append(&mut commit.changes);
+                }
+                None => leading.push(commit),
+            },
+            Disposition::Keep => {
+                if kept.is_empty() && !leading.is_empty() {
+                    let mut changes = Vec::new();
+                    for mut folded in leading.drain(..) {
+                        folded_into.insert(folded.id, commit.id);
+                        changes.append(&mut folded.changes);
+                    }
+                    changes.append(&mut commit.changes);
+                    commit.changes = changes;
+                }
+                kept.push(commit);
+            }
+        }
+    }
+    if kept.is_empty() {
+        kept = leading;
+    }
+
+    for commit in &mut kept {
+        let own_id = commit.id;
+        let mut seen = HashSet::new();
+        commit.depends_on = commit
+            .depends_on
+            .iter()
+            .filter(|dep| !dropped.contains(*dep))
+            .map(|dep| *folded_into.get(dep).unwrap_or(dep))
+            .filter(|&dep| dep != own_id && seen.insert(dep))
+            .collect();
+    }
+    kept
+}
+
+fn is_whitespace_only(commit: &PlannedCommit, hunks: &[Hunk]) -> bool {
+    let mut resolved = commit
+        .changes
+        .iter()
+        .filter_map(|change| change.resolve(hunks))
+        .peekable();
+    resolved.peek().is_some() && resolved.all(Hunk::is_whitespace_only)
+}
+
+/// Whether `subject` says nothing about the change: a throwaway word, or a
+/// `fixup!`/`squash!`/`amend!` or "wip" prefix.
+fn is_throwaway_message(subject: &str) -> bool {
+    let subject = subject.trim().to_lowercase();
+    if ["fixup!", "squash!", "amend!"]
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+    {
+        return true;
+    }
+    let first_word = subject
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .next()
+        .unwrap_or_default();
+    if first_word == "wip" {
+        return true;
+    }
+    let bare = subject.trim_end_matches(|c: char| c.is_ascii_punctuation());
+    bare.is_empty() || THROWAWAY_SUBJECTS.contains(&bare)
+}
+
+fn mark_todo(description: &CommitDescription) -> CommitDescription {
+    let short = format!("TODO: {}", description.short);
+    let body = description
+        .long
+        .split_once('\n')
+        .map(|(_, body)| body)
+        .unwrap_or_default();
+    let long = if body.is_empty() {
+        short.clone()
+    } else {
+        format!("{}\n{}", short, body)
+    };
+    CommitDescription::new(short, long)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::test_support::MockLlmClient;
+    use crate::models::{DiffLine, HunkId};
+    use crate::test_utils::make_hunk_with_source;
+
+    fn whitespace_hunk(id: usize, source: &str) -> Hunk {
+        let mut hunk = make_hunk_with_source(id, "a.rs", vec![source.to_string()]);
+        hunk.lines = vec![
+            DiffLine::Removed("fn a() {}".to_string()),
+            DiffLine::Added("fn a() {}  ".to_string()),
+        ];
+        hunk
+    }
+
+    #[test]
+    fn folds_whitespace_only_commits_and_marks_throwaway_messages() {
+        let commits = vec![
+            SourceCommit::new("abc", "Add parser", "Add parser\n\nDetails"),
+            SourceCommit::new("def", "Reformat", "Reformat"),
+            SourceCommit::new("ghi", "wip", "wip"),
+        ];
+        let hunks = vec![
+            make_hunk_with_source(0, "a.rs", vec!["abc".to_string()]),
+            whitespace_hunk(1, "def"),
+            make_hunk_with_source(2, "b.rs", vec!["ghi".to_string()]),
+        ];
+
+        let planned = CleanPreserve::new().plan(&commits, &hunks).unwrap();
+
+        assert_eq!(planned.len(), 2);
+        assert_eq!(planned[0].description.short, "Add parser");
+        assert_eq!(planned[0].changes.len(), 2);
+        assert_eq!(planned[1].description.short, "TODO: wip");
+
+        let kept = CleanPreserve::new()
+            .with_keep_empty(true)
+            .plan(&commits, &hunks)
+            .unwrap();
+        assert_eq!(kept.len(), 3);
+    }
+
+    #[test]
+    fn leading_whitespace_commit_joins_the_next_one() {
+        let commits = vec![
+            SourceCommit::new("abc", "Reformat", "Reformat"),
+            SourceCommit::new("def", "Add parser", "Add parser"),
+        ];
+        let hunks = vec![
+            whitespace_hunk(0, "abc"),
+            make_hunk_with_source(1, "b.rs", vec!["def".to_string()]),
+        ];
+
+        let planned = CleanPreserve::new().plan(&commits, &hunks).unwrap();
+
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].changes.len(), 2);
+    }
+
+    #[test]
+    fn fold_commits_moves_dependencies_to_the_surviving_commit() {
+        let commit = |id: usize, deps: &[usize]| {
+            let mut commit = PlannedCommit::from_hunk_ids(
+                PlannedCommitId(id),
+                CommitDescription::short_only(format!("Commit {}", id)),
+                vec![HunkId(id)],
+            );
+            commit.depends_on = deps.iter().copied().map(PlannedCommitId).collect();
+            commit
+        };
+        let commits = vec![
+            commit(0, &[]),
+            commit(1, &[]),
+            commit(2, &[]),
+            commit(3, &[]),
+            commit(4, &[0, 2, 1, 3]),
+        ];
+
+        let kept = fold_commits(commits, |c| match c.id.0 {
+            1 => Disposition::Fold,
+            3 => Disposition::Drop,
+            _ => Disposition::Keep,
+        });
+
+        let ids: Vec<usize> = kept.iter().map(|c| c.id.0).collect();
+        assert_eq!(ids, vec![0, 2, 4]);
+        assert_eq!(kept[0].changes.len(), 2);
+        // 1 folded into 0, which is already listed; 3 is gone
+        assert_eq!(
+            kept[2].depends_on,
+            vec![PlannedCommitId(0), PlannedCommitId(2)]
+        );
+    }
+
+    #[test]
+    fn fold_commits_keeps_everything_when_all_would_fold() {
+        let commits = vec![
+            PlannedCommit::from_hunk_ids(
+                PlannedCommitId(0),
+                CommitDescription::short_only("Reformat"),
+                vec![HunkId(0)],
+            ),
+            PlannedCommit::from_hunk_ids(
+                PlannedCommitId(1),
+                CommitDescription::short_only("Reindent"),
+                vec![HunkId(1)],
+            ),
+        ];
+
+        assert_eq!(fold_commits(commits, |_| Disposition::Fold).len(), 2);
+    }
+
+    #[test]
+    fn rewords_throwaway_messages_with_the_llm() {
+        let commits = vec![SourceCommit::new(
+            "abc",
+            "fixup! Add parser",
+            "fixup! Add parser",
+        )];
+        let hunks = vec![make_hunk_with_source(0, "a.rs", vec!["abc".to_string()])];
+        let client = MockLlmClient::new(
+            r#"{"description": {"short": "Handle empty input", "long": "Handle empty input"}}"#,
+        );
+
+        let planned = CleanPreserve::new()
+            .with_client(Some(Box::new(client)))
+            .plan(&commits, &hunks)
+            .unwrap();
+
+        assert_eq!(planned[0].description.short, "Handle empty input");
+    }
+
+    #[test]
+    fn throwaway_messages() {
+        for subject in [
+            "wip",
+            "WIP: parser",
+            "asdf",
+            "fix.",
+            "Fixup",
+            "squash! x",
+            "...",
+        ] {
+            assert!(is_throwaway_message(subject), "{}", subject);
+        }
+        for subject in [
+            "Fix parser crash",
+            "Update README links",
+            "Wipe cache on logout",
+        ] {
+            assert!(!is_throwaway_message(subject), "{}", subject);
+        }
+    }
+}
@@ -0,0 +1,239 @@
+//! Grouping hints written into the code itself. A hint is an added line
+//! holding only a comment whose text starts with the marker:
+//!
+//! ```text
+//! // reabsorb: Add token refresh
+//! # reabsorb: Add token refresh
+//! <!-- reabsorb: Add token refresh -->
+//! ```
+//!
+//! Every hunk carrying the same hint goes into one commit, with the hint as
+//! its subject. Hints on lines with code are not recognized, so stripping a
+//! hint always removes a whole line.
+
+use std::collections::HashMap;
+
+use crate::models::{
+    CommitDescription, DiffLine, Hunk, HunkId, PlannedCommit, PlannedCommitId, SourceCommit,
+};
+use crate::reorganize::{PreserveOriginal, ReorganizeError, Reorganizer};
+
+/// Marker used when none is configured.
+pub const DEFAULT_HINT_MARKER: &str = "reabsorb:";
+
+/// Comment openers a hint line may start with.
+const COMMENT_LEADERS: &[&str] = &["//", "/*", "<!--", "#", "--", ";", "%"];
+
+/// Comment closers trimmed from the end of a hint.
+const COMMENT_CLOSERS: &[&str] = &["*/", "-->"];
+
+/// The hint on `line`, if it is a comment starting with `marker`.
+pub fn hint_in_line<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let line = line.trim();
+    let comment = COMMENT_LEADERS
+        .iter()
+        .find_map(|leader| line.strip_prefix(leader))?;
+    let mut hint = comment.trim_start().strip_prefix(marker)?.trim();
+    for closer in COMMENT_CLOSERS {
+        hint = hint.strip_suffix(closer).unwrap_or(hint).trim_end();
+    }
+    (!hint.is_empty()).then_some(hint)
+}
+
+/// The first hint among the lines `hunk` adds.
+pub fn hint_in_hunk<'a>(hunk: &'a Hunk, marker: &str) -> Option<&'a str> {
+    hunk.lines.iter().find_map(|line| match line {
+        DiffLine::Added(text) => hint_in_line(text, marker),
+        _ => None,
+    })
+}
+
+/// Copies of `hunks` without their added hint lines, so the hints are not
+/// committed. Hunks left with no changes are dropped.
+pub fn strip_hints(hunks: &[Hunk], marker: &str) -> Vec<Hunk> {
+    hunks
+        .iter()
+        .filter_map(|hunk| {
+            let mut stripped = hunk.clone();
+            stripped.lines.retain(|line| match line {
+                DiffLine::Added(text) => hint_in_line(text, marker).is_none(),
+                _ => true,
+            });
+            let removed = hunk.lines.len() - stripped.lines.len();
+            stripped.new_count -= removed as u32;
+            let has_changes = stripped
+                .lines
+                .iter()
+                .any(|line| !matches!(line, DiffLine::Context(_)));
+            has_changes.then_some(stripped)
+        })
+        .collect()
+}
+
+/// Makes one commit per hint, named after it, in the order the hints first
+/// appear. Hunks without a hint keep their original commits, which come
+/// first. Deterministic and offline.
+pub struct CommentHint {
+    marker: String,
+}
+
+impl CommentHint {
+    pub fn new() -> Self {
+        Self {
+            marker: DEFAULT_HINT_MARKER.to_string(),
+        }
+    }
+
+    /// Look for `marker` instead of [`DEFAULT_HINT_MARKER`].
+    pub fn with_marker(mut self, marker: impl Into<String>) -> Self {
+        self.marker = marker.into();
+        self
+    }
+}
+
+impl Default for CommentHint {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Reorganizer for CommentHint {
+    fn plan(
+        &self,
+        source_commits: &[SourceCommit],
+        hunks: &[Hunk],
+    ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
+        if hunks.is_empty() {
+            return Err(ReorganizeError::NoHunks);
+        }
+
+        let mut order: Vec<&str> = Vec::new();
+        let mut hinted: HashMap<&str, Vec<HunkId>> = HashMap::new();
+        let mut unhinted = Vec::new();
+        for hunk in hunks {
+            match hint_in_hunk(hunk, &self.marker) {
+                Some(hint) => {
+                    if !hinted.contains_key(hint) {
+                        order.push(hint);
+                    }
+                    hinted.entry(hint).or_default().push(hunk.id);
+                }
+                None => unhinted.push(hunk.clone()),
+            }
+        }
+
+        let mut planned = if unhinted.is_empty() {
+            Vec::new()
+        } else {
+            PreserveOriginal.plan(source_commits, &unhinted)?
+        };
+        for hint in order {
+            planned.push(PlannedCommit::from_hunk_ids(
+                PlannedCommitId(0),
+                CommitDescription::short_only(hint),
+                hinted.remove(hint).unwrap_or_default(),
+            ));
+        }
+        for (i, commit) in planned.iter_mut().enumerate() {
+            commit.id = PlannedCommitId(i);
+        }
+
+        Ok(planned)
+    }
+
+    fn name(&self) -> &'static str {
+        "comment-hint"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PlannedChange;
+    use crate::test_utils::make_hunk_with_source;
+
+    fn hinted_hunk(id: usize, file: &str, source: &str, hint: &str) -> Hunk {
+        let mut hunk = make_hunk_with_source(id, file, vec![source.to_string()]);
+        hunk.lines.insert(0, DiffLine::Added(hint.to_string()));
+        hunk.new_count += 1;
+        hunk
+    }
+
+    fn hunk_ids(commit: &PlannedCommit) -> Vec<usize> {
+        commit
+            .changes
+            .iter()
+            .filter_map(|c| match c {
+                PlannedChange::ExistingHunk(id) => Some(id.0),
+                PlannedChange::NewHunk(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recognizes_hints_in_comment_lines_only() {
+        let marker = DEFAULT_HINT_MARKER;
+        assert_eq!(hint_in_line("  // reabsorb: auth", marker), Some("auth"));
+        assert_eq!(
+            hint_in_line("# reabsorb:  Add login ", marker),
+            Some("Add login")
+        );
+        assert_eq!(hint_in_line("/* reabsorb: auth */", marker), Some("auth"));
+        assert_eq!(
+            hint_in_line("<!-- reabsorb: docs -->", marker),
+            Some("docs")
+        );
+        assert_eq!(hint_in_line("let x = 1; // reabsorb: auth", marker), None);
+        assert_eq!(hint_in_line("// reabsorb:", marker), None);
+        assert_eq!(hint_in_line("// group: auth", marker), None);
+        assert_eq!(hint_in_line("// group: auth", "group:"), Some("auth"));
+    }
+
+    #[test]
+    fn groups_hinted_hunks_and_preserves_the_rest() {
+        let commits = vec![
+            SourceCommit::new("abc", "First", "First"),
+            SourceCommit::new("def", "Second", "Second"),
+        ];
+        let hunks = vec![
+            hinted_hunk(0, "a.rs", "abc", "// reabsorb: auth"),
+            make_hunk_with_source(1, "b.rs", vec!["abc".to_string()]),
+            hinted_hunk(2, "c.rs", "def", "// reabsorb: docs"),
+            hinted_hunk(3, "d.rs", "def", "# reabsorb: auth"),
+        ];
+
+        let planned = CommentHint::new().plan(&commits, &hunks).unwrap();
+
+        let subjects: Vec<&str> = planned
+            .iter()
+            .map(|c| c.description.short.as_str())
+            .collect();
+        assert_eq!(subjects, vec!["First", "auth", "docs"]);
+        assert_eq!(hunk_ids(&planned[0]), vec![1]);
+        assert_eq!(hunk_ids(&planned[1]), vec![0, 3]);
+        assert_eq!(hunk_ids(&planned[2]), vec![2]);
+        let ids: Vec<usize> = planned.iter().map(|c| c.id.0).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn strips_hint_lines_and_drops_emptied_hunks() {
+        let mut hint_only = make_hunk_with_source(1, "b.rs", vec!["abc".to_string()]);
+        hint_only.lines = vec![
+            DiffLine::Context("fn b() {}".to_string()),
+            DiffLine::Added("// reabsorb: auth".to_string()),
+        ];
+        hint_only.new_count = hint_only.old_count + 1;
+        let hunks = vec![
+            hinted_hunk(0, "a.rs", "abc", "// reabsorb: auth"),
+            hint_only,
+        ];
+
+        let stripped = strip_hints(&hunks, DEFAULT_HINT_MARKER);
+
+        assert_eq!(stripped.len(), 1);
+        assert_eq!(stripped[0].id, HunkId(0));
+        assert_eq!(stripped[0].new_count, hunks[0].new_count - 1);
+        assert!(hint_in_hunk(&stripped[0], DEFAULT_HINT_MARKER).is_none());
+    }
+}
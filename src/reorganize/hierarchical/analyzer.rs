@@ -1,20 +1,26 @@
 //! HunkAnalyzer - parallel per-hunk semantic analysis
 
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use log::debug;
+use log::{debug, warn};
 
-use crate::llm::LlmClient;
+use crate::llm::{LlmClient, Redaction};
 use crate::models::{Hunk, HunkId, SourceCommit};
-use crate::utils::{extract_json_str, format_diff_lines};
+use crate::reorganize::Deadline;
+use crate::utils::{extract_json_str, format_duration};
 
-use super::types::{AnalysisResults, HierarchicalError, HunkAnalysis, HunkAnalysisResponse};
+use super::types::{
+    AnalysisResults, ChangeCategory, HierarchicalError, HunkAnalysis, HunkAnalysisResponse,
+};
 
 /// Analyzes hunks to extract semantic metadata
 pub struct HunkAnalyzer {
     client: Arc<dyn LlmClient + Send + Sync>,
     max_parallel: usize,
+    deadline: Deadline,
+    redaction: Redaction,
 }
 
 impl HunkAnalyzer {
@@ -22,6 +28,8 @@ impl HunkAnalyzer {
         Self {
             client,
             max_parallel: 8, // Default parallelism
+            deadline: Deadline::default(),
+            redaction: Redaction::default(),
         }
     }
 
@@ -30,6 +38,18 @@ impl HunkAnalyzer {
         self
     }
 
+    /// Analyze hunks heuristically once `deadline` passes
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Show the files `redaction` matches as placeholders in prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
     /// Analyze all hunks in parallel
     pub fn analyze(
         &self,
@@ -46,7 +66,12 @@ impl HunkAnalyzer {
         // Process hunks in batches to limit parallelism
         let chunks: Vec<_> = hunks.chunks(self.max_parallel).collect();
 
+        let mut heuristic = Vec::new();
         for chunk in chunks {
+            if self.deadline.is_past() {
+                heuristic.extend(chunk.iter());
+                continue;
+            }
             let handles: Vec<_> = chunk
                 .iter()
                 .map(|hunk| {
@@ -55,10 +80,11 @@ impl HunkAnalyzer {
                     let errors = Arc::clone(&errors);
                     let hunk_id = hunk.id;
                     let file_path = hunk.file_path.to_string_lossy().to_string();
-                    let prompt = build_analysis_prompt(hunk, source_commits);
+                    let prompt = build_analysis_prompt(hunk, source_commits, &self.redaction);
+                    let deadline = self.deadline;
 
                     thread::spawn(move || {
-                        match analyze_single_hunk(&client, hunk_id, &file_path, &prompt) {
+                        match analyze_single_hunk(&client, hunk_id, &file_path, &prompt, deadline) {
                             Ok(analysis) => {
                                 let mut results = results.lock().unwrap();
                                 results.add(analysis);
@@ -78,14 +104,33 @@ impl HunkAnalyzer {
             }
         }
 
-        // Check for errors
+        // Check for errors; past the deadline, failed hunks fall back to heuristics
         let errors = Arc::try_unwrap(errors).unwrap().into_inner().unwrap();
-        if !errors.is_empty() {
+        if !errors.is_empty() && !self.deadline.is_past() {
             let (hunk_id, error) = errors.into_iter().next().unwrap();
             return Err(HierarchicalError::AnalysisFailed(hunk_id.0, error));
         }
+        for (hunk_id, _) in errors {
+            heuristic.extend(hunks.iter().find(|h| h.id == hunk_id));
+        }
 
-        Ok(Arc::try_unwrap(results).unwrap().into_inner().unwrap())
+        let mut results = Arc::try_unwrap(results).unwrap().into_inner().unwrap();
+        if !heuristic.is_empty() {
+            warn!(
+                "Time budget of {} exceeded: analyzed {} of {} hunks heuristically",
+                self.deadline
+                    .budget()
+                    .map(format_duration)
+                    .unwrap_or_default(),
+                heuristic.len(),
+                hunks.len()
+            );
+            for hunk in heuristic {
+                results.add(heuristic_analysis(hunk));
+            }
+        }
+
+        Ok(results)
     }
 
     /// Analyze a single hunk (for testing or sequential processing)
@@ -94,9 +139,9 @@ impl HunkAnalyzer {
         hunk: &Hunk,
         source_commits: &[SourceCommit],
     ) -> Result<HunkAnalysis, HierarchicalError> {
-        let prompt = build_analysis_prompt(hunk, source_commits);
+        let prompt = build_analysis_prompt(hunk, source_commits, &self.redaction);
         let file_path = hunk.file_path.to_string_lossy().to_string();
-        analyze_single_hunk(&self.client, hunk.id, &file_path, &prompt)
+        analyze_single_hunk(&self.client, hunk.id, &file_path, &prompt, self.deadline)
             .map_err(|e| HierarchicalError::AnalysisFailed(hunk.id.0, e))
     }
 }
@@ -106,12 +151,16 @@ fn analyze_single_hunk(
     hunk_id: HunkId,
     file_path: &str,
     prompt: &str,
+    deadline: Deadline,
 ) -> Result<HunkAnalysis, String> {
     const MAX_RETRIES: u32 = 5;
     let mut last_error = String::new();
 
     for attempt in 0..MAX_RETRIES {
         if attempt > 0 {
+            if deadline.is_past() {
+                break;
+            }
             debug!(
                 "Retrying hunk {} (attempt {}/{}): {}",
                 hunk_id.0,
@@ -154,9 +203,78 @@ fn analyze_single_hunk(
     Err(last_error)
 }
 
-fn build_analysis_prompt(hunk: &Hunk, source_commits: &[SourceCommit]) -> String {
-    let diff_content = format_diff_lines(&hunk.lines);
-    let file_path = hunk.file_path.to_string_lossy();
+/// Analyze every hunk from its path alone, without the LLM.
+pub fn analyze_heuristically(hunks: &[Hunk]) -> AnalysisResults {
+    let mut results = AnalysisResults::new();
+    for hunk in hunks {
+        results.add(heuristic_analysis(hunk));
+    }
+    results
+}
+
+/// Analyze a hunk from its path alone, without the LLM.
+fn heuristic_analysis(hunk: &Hunk) -> HunkAnalysis {
+    let path = &hunk.file_path;
+    let file_path = path.to_string_lossy().to_string();
+    let category = if hunk.is_whitespace_only() {
+        ChangeCategory::Formatting
+    } else {
+        category_from_path(path)
+    };
+    let topic = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .or_else(|| path.file_stem())
+        .map(|name| normalize_topic(&name.to_string_lossy()))
+        .unwrap_or_default();
+
+    HunkAnalysis {
+        hunk_id: hunk.id.0,
+        category,
+        semantic_units: vec![format!("update {}", file_path)],
+        topic,
+        depends_on_context: None,
+        file_path,
+    }
+}
+
+fn category_from_path(path: &Path) -> ChangeCategory {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let in_dir = |dir: &str| path.components().any(|c| c.as_os_str() == dir);
+
+    if in_dir("tests") || in_dir("test") || name.contains("_test.") || name.contains(".test.") {
+        ChangeCategory::Test
+    } else if in_dir("docs") || matches!(extension.as_str(), "md" | "rst" | "adoc" | "txt") {
+        ChangeCategory::Documentation
+    } else if matches!(
+        name.as_str(),
+        "cargo.toml" | "cargo.lock" | "package.json" | "package-lock.json" | "go.mod" | "go.sum"
+    ) {
+        ChangeCategory::Dependency
+    } else if matches!(
+        extension.as_str(),
+        "toml" | "yaml" | "yml" | "json" | "ini" | "cfg" | "conf"
+    ) {
+        ChangeCategory::Configuration
+    } else {
+        ChangeCategory::Other
+    }
+}
+
+fn build_analysis_prompt(
+    hunk: &Hunk,
+    source_commits: &[SourceCommit],
+    redaction: &Redaction,
+) -> String {
+    let diff_content = redaction.hunk_lines(hunk);
+    let file_path = redaction.path(&hunk.file_path);
 
     // Look up the original commit message to provide context about WHY this change was made
     let commit_context = hunk
@@ -220,3 +338,47 @@ fn normalize_topic(topic: &str) -> String {
         .filter(|c| c.is_alphanumeric() || *c == '_')
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::test_support::MockLlmClient;
+    use crate::test_utils::make_hunk_in_file;
+    use std::time::Duration;
+
+    #[test]
+    fn test_heuristic_category_from_path() {
+        assert_eq!(
+            category_from_path(Path::new("tests/api.rs")),
+            ChangeCategory::Test
+        );
+        assert_eq!(
+            category_from_path(Path::new("README.md")),
+            ChangeCategory::Documentation
+        );
+        assert_eq!(
+            category_from_path(Path::new("Cargo.toml")),
+            ChangeCategory::Dependency
+        );
+        assert_eq!(
+            category_from_path(Path::new("config/app.yaml")),
+            ChangeCategory::Configuration
+        );
+        assert_eq!(
+            category_from_path(Path::new("src/main.rs")),
+            ChangeCategory::Other
+        );
+    }
+
+    #[test]
+    fn test_spent_deadline_analyzes_heuristically() {
+        let hunks = vec![make_hunk_in_file(0, "src/auth/token.rs")];
+        let analyzer = HunkAnalyzer::new(Arc::new(MockLlmClient::new("not json")))
+            .with_deadline(Deadline::after(Some(Duration::ZERO)));
+
+        let results = analyzer.analyze(&hunks, &[]).unwrap();
+        let analysis = results.get(HunkId(0)).unwrap();
+        assert_eq!(analysis.topic, "auth");
+        assert_eq!(analysis.semantic_units, vec!["update src/auth/token.rs"]);
+    }
+}
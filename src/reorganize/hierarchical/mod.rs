@@ -23,7 +23,8 @@
 //! - Scales to thousands of hunks (each LLM call is small and focused)
 //! - Parallelizable (analysis and planning phases run concurrently)
 //! - Incremental repair (fix individual commits without redoing everything)
-//! - Debuggable (each phase produces inspectable intermediate results)
+//! - Debuggable (each phase produces inspectable intermediate results, written
+//!   as JSON when [`HierarchicalConfig::debug_dir`] is set)
 
 mod analyzer;
 mod clusterer;
@@ -32,21 +33,25 @@ mod planner;
 mod types;
 mod validator;
 
-pub use analyzer::HunkAnalyzer;
+pub use analyzer::{analyze_heuristically, HunkAnalyzer};
 pub use clusterer::{ClusterConfig, Clusterer};
 pub use orderer::GlobalOrderer;
 pub use planner::CommitPlanner;
 pub use types::*;
 pub use validator::{assign_orphans, deduplicate_across_commits, Validator};
 
+use std::fs;
+use std::path::PathBuf;
 use std::sync::Arc;
 
-use log::{debug, info};
+use log::{debug, info, warn};
+use serde::Serialize;
 
 use crate::features::Feature;
-use crate::llm::LlmClient;
+use crate::llm::{LlmClient, Redaction};
 use crate::models::{Hunk, PlannedCommit, SourceCommit};
-use crate::reorganize::{ReorganizeError, Reorganizer};
+use crate::reorganize::{Deadline, ReorganizeError, Reorganizer};
+use crate::utils::format_duration;
 use crate::validation::{apply_deterministic_fixes, ValidationResult};
 
 /// Configuration for the hierarchical reorganizer
@@ -56,6 +61,21 @@ pub struct HierarchicalConfig {
     pub max_parallel: usize,
     /// Cluster configuration
     pub cluster_config: ClusterConfig,
+    /// Whether to call the LLM at all; when false every phase uses heuristics
+    pub use_llm: bool,
+    /// Model for the many small analysis and clustering calls; the global
+    /// model when unset
+    pub analyze_model: Option<String>,
+    /// Model for the fewer, harder commit planning and repair calls; the
+    /// global model when unset
+    pub plan_model: Option<String>,
+    /// How many times to re-run clustering through validation when they
+    /// fail, before falling back to heuristic planning
+    pub max_pipeline_retries: usize,
+    /// Write each phase's results here as JSON: `analysis.json`,
+    /// `clusters.json`, `planned.json` (before ordering), `validation.json`
+    /// and `final.json`. Retries overwrite the previous attempt's files.
+    pub debug_dir: Option<PathBuf>,
 }
 
 impl Default for HierarchicalConfig {
@@ -63,85 +83,303 @@ impl Default for HierarchicalConfig {
         Self {
             max_parallel: 8,
             cluster_config: ClusterConfig::default(),
+            use_llm: true,
+            analyze_model: None,
+            plan_model: None,
+            max_pipeline_retries: 2,
+            debug_dir: None,
         }
     }
 }
 
+impl HierarchicalConfig {
+    /// Run every phase without the LLM: hunks are analyzed from their paths,
+    /// clustered by topic and file, and given template commit messages.
+    pub fn heuristic_only() -> Self {
+        Self {
+            cluster_config: ClusterConfig {
+                use_llm_relationships: false,
+                ..ClusterConfig::default()
+            },
+            use_llm: false,
+            ..Self::default()
+        }
+    }
+
+    /// Apply CLI overrides on top of this config.
+    pub fn with_overrides(
+        mut self,
+        max_parallel: Option<usize>,
+        max_cluster_size: Option<usize>,
+        cross_file_threshold: Option<usize>,
+    ) -> Self {
+        if let Some(n) = max_parallel {
+            self.max_parallel = n;
+        }
+        if let Some(n) = max_cluster_size {
+            self.cluster_config.max_cluster_size = n;
+        }
+        if let Some(n) = cross_file_threshold {
+            self.cluster_config.cross_file_threshold = n;
+        }
+        self
+    }
+
+    /// Write intermediate phase results to `dir`.
+    pub fn with_debug_dir(mut self, dir: Option<PathBuf>) -> Self {
+        if dir.is_some() {
+            self.debug_dir = dir;
+        }
+        self
+    }
+
+    /// Use different models for the analysis and planning phases.
+    pub fn with_phase_models(
+        mut self,
+        analyze_model: Option<String>,
+        plan_model: Option<String>,
+    ) -> Self {
+        if analyze_model.is_some() {
+            self.analyze_model = analyze_model;
+        }
+        if plan_model.is_some() {
+            self.plan_model = plan_model;
+        }
+        self
+    }
+}
+
+impl std::fmt::Display for HierarchicalConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cluster = &self.cluster_config;
+        writeln!(f, "llm = {}", self.use_llm)?;
+        writeln!(f, "hierarchical-parallel = {}", self.max_parallel)?;
+        let model = |m: &Option<String>| m.clone().unwrap_or_else(|| "(llm-model)".to_string());
+        writeln!(f, "analyze-model = {}", model(&self.analyze_model))?;
+        writeln!(f, "plan-model = {}", model(&self.plan_model))?;
+        writeln!(f, "pipeline-retries = {}", self.max_pipeline_retries)?;
+        writeln!(f, "cluster-max-size = {}", cluster.max_cluster_size)?;
+        writeln!(
+            f,
+            "cluster-cross-file-threshold = {}",
+            cluster.cross_file_threshold
+        )?;
+        writeln!(
+            f,
+            "cluster-llm-relationships = {}",
+            cluster.use_llm_relationships
+        )?;
+        writeln!(
+            f,
+            "cluster-group-tests-with-impl = {}",
+            cluster.group_tests_with_impl
+        )
+    }
+}
+
 /// Multi-phase hierarchical reorganizer
 pub struct HierarchicalReorganizer {
     client: Option<Arc<dyn LlmClient + Send + Sync>>,
+    analyze_client: Option<Arc<dyn LlmClient + Send + Sync>>,
+    plan_client: Option<Arc<dyn LlmClient + Send + Sync>>,
     config: HierarchicalConfig,
+    deadline: Deadline,
+    redaction: Redaction,
 }
 
 impl HierarchicalReorganizer {
     pub fn new(client: Option<Arc<dyn LlmClient + Send + Sync>>) -> Self {
         Self {
             client,
+            analyze_client: None,
+            plan_client: None,
             config: HierarchicalConfig::default(),
+            deadline: Deadline::default(),
+            redaction: Redaction::default(),
         }
     }
 
+    /// Use `client` instead of the main one for analysis and clustering.
+    pub fn with_analyze_client(mut self, client: Option<Arc<dyn LlmClient + Send + Sync>>) -> Self {
+        self.analyze_client = client;
+        self
+    }
+
+    /// Use `client` instead of the main one for planning and repair.
+    pub fn with_plan_client(mut self, client: Option<Arc<dyn LlmClient + Send + Sync>>) -> Self {
+        self.plan_client = client;
+        self
+    }
+
     pub fn with_config(mut self, config: HierarchicalConfig) -> Self {
         self.config = config;
         self
     }
 
+    /// Stop calling the LLM once `deadline` passes and finish the remaining
+    /// phases with heuristics.
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Show the contents of the files `redaction` matches as placeholders
+    /// when analyzing hunks. Later phases only see the analyses, which
+    /// still name every file.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// `client` for a phase starting now, or `None` in heuristic-only mode or
+    /// once the deadline has passed.
+    fn client_for_phase(
+        &self,
+        client: &Option<Arc<dyn LlmClient + Send + Sync>>,
+    ) -> Option<Arc<dyn LlmClient + Send + Sync>> {
+        if !self.config.use_llm || self.deadline.is_past() {
+            None
+        } else {
+            client.clone()
+        }
+    }
+
+    /// Write a phase's results to the debug dir, if set. Failures are only
+    /// logged; they never fail the plan.
+    fn write_artifact<T: Serialize + ?Sized>(&self, name: &str, value: &T) {
+        let Some(dir) = &self.config.debug_dir else {
+            return;
+        };
+        let path = dir.join(name);
+        let result = fs::create_dir_all(dir).and_then(|()| {
+            let json = serde_json::to_string_pretty(value).map_err(std::io::Error::other)?;
+            fs::write(&path, json)
+        });
+        match result {
+            Ok(()) => debug!("  Wrote {}", path.display()),
+            Err(err) => warn!("Could not write {}: {}", path.display(), err),
+        }
+    }
+
     /// Run the full reorganization pipeline
     fn run_pipeline(
         &self,
         source_commits: &[SourceCommit],
         hunks: &[Hunk],
     ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
-        let client = self.client.as_ref().ok_or_else(|| {
-            ReorganizeError::InvalidPlan(
-                "LLM client is required for hierarchical reorganization".to_string(),
+        let (analyze_client, plan_client) = if self.config.use_llm {
+            let client = self.client.as_ref().ok_or_else(|| {
+                ReorganizeError::InvalidPlan(
+                    "LLM client is required for hierarchical reorganization".to_string(),
+                )
+            })?;
+            (
+                Some(
+                    self.analyze_client
+                        .clone()
+                        .unwrap_or_else(|| Arc::clone(client)),
+                ),
+                Some(
+                    self.plan_client
+                        .clone()
+                        .unwrap_or_else(|| Arc::clone(client)),
+                ),
             )
-        })?;
+        } else {
+            (None, None)
+        };
 
         info!("Phase 1: Analyzing {} hunks...", hunks.len());
 
         // Phase 1: Analyze hunks
-        let analyzer =
-            HunkAnalyzer::new(Arc::clone(client)).with_parallelism(self.config.max_parallel);
-
-        let analysis = analyzer.analyze(hunks, source_commits)?;
+        let analysis = match &analyze_client {
+            Some(client) => HunkAnalyzer::new(Arc::clone(client))
+                .with_parallelism(self.config.max_parallel)
+                .with_deadline(self.deadline)
+                .with_redaction(self.redaction.clone())
+                .analyze(hunks, source_commits)?,
+            None => analyze_heuristically(hunks),
+        };
 
         debug!(
             "  Found {} topics: {:?}",
             analysis.by_topic.len(),
             analysis.topics().take(5).collect::<Vec<_>>()
         );
+        self.write_artifact("analysis.json", &analysis);
+
+        // Analysis is kept across retries; the later phases are where LLM
+        // hiccups turn into invalid plans
+        let attempts = if self.config.use_llm {
+            self.config.max_pipeline_retries + 1
+        } else {
+            1
+        };
+        let mut result = self.plan_from_analysis(hunks, &analysis, &analyze_client, &plan_client);
+        for attempt in 2..=attempts {
+            let Err(err) = &result else { break };
+            warn!("Hierarchical plan failed: {}", err);
+            info!(
+                "Retrying from clustering (attempt {}/{})...",
+                attempt, attempts
+            );
+            result = self.plan_from_analysis(hunks, &analysis, &analyze_client, &plan_client);
+        }
+
+        match result {
+            Err(err) if self.config.use_llm => {
+                warn!("Hierarchical plan failed: {}", err);
+                warn!("Falling back to heuristic planning; the plan will be coarser");
+                self.plan_from_analysis(hunks, &analysis, &None, &None)
+            }
+            result => result,
+        }
+    }
 
+    /// Phases 2-5: cluster, plan, order, and validate analyzed hunks.
+    fn plan_from_analysis(
+        &self,
+        hunks: &[Hunk],
+        analysis: &AnalysisResults,
+        analyze_client: &Option<Arc<dyn LlmClient + Send + Sync>>,
+        plan_client: &Option<Arc<dyn LlmClient + Send + Sync>>,
+    ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
         info!("Phase 2: Clustering hunks...");
 
         // Phase 2: Cluster hunks
-        let clusterer = Clusterer::new(Some(Arc::clone(client)))
+        let clusterer = Clusterer::new(self.client_for_phase(analyze_client))
             .with_config(self.config.cluster_config.clone());
 
-        let clusters = clusterer.cluster(hunks, &analysis)?;
+        let clusters = clusterer.cluster(hunks, analysis)?;
 
         debug!("  Created {} clusters", clusters.len());
+        self.write_artifact("clusters.json", &clusters);
 
         info!("Phase 3: Planning commits...");
 
         // Phase 3: Plan commits
-        let planner =
-            CommitPlanner::new(Some(Arc::clone(client))).with_parallelism(self.config.max_parallel);
+        let planner = CommitPlanner::new(plan_client.clone())
+            .with_parallelism(self.config.max_parallel)
+            .with_deadline(self.deadline)
+            .with_redaction(self.redaction.clone());
 
-        let commits = planner.plan(&clusters, hunks, &analysis)?;
+        let commits = planner.plan(&clusters, hunks, analysis)?;
 
         debug!("  Planned {} commits", commits.len());
+        self.write_artifact("planned.json", &commits);
 
         info!("Phase 4: Ordering commits...");
 
         // Phase 4: Order commits
-        let ordered = GlobalOrderer::order(commits, &analysis)?;
+        let ordered = GlobalOrderer::order(commits, analysis)?;
 
         info!("Phase 5: Validating and repairing...");
 
         // Phase 5: Validate and repair
-        let validator = Validator::new(self.client.clone());
+        let budget_spent = plan_client.is_some() && self.deadline.is_past();
+        let validator = Validator::new(self.client_for_phase(plan_client));
         let validations = validator.validate(&ordered, hunks);
+        self.write_artifact("validation.json", &validations);
 
         let invalid_count = validations.iter().filter(|v| !v.is_valid).count();
         if invalid_count > 0 {
@@ -149,7 +387,7 @@ impl HierarchicalReorganizer {
         }
 
         let repaired = validator
-            .repair(ordered, &validations, hunks, &analysis)
+            .repair(ordered, &validations, hunks, analysis)
             .map_err(|e| ReorganizeError::InvalidPlan(e.to_string()))?;
 
         // Apply cross-commit fixes under feature flag
@@ -157,11 +395,14 @@ impl HierarchicalReorganizer {
             // Deduplicate hunks assigned to multiple commits
             let deduped = deduplicate_across_commits(repaired);
             // Assign any orphaned hunks
-            assign_orphans(deduped, hunks, &analysis)
+            assign_orphans(deduped, hunks, analysis)
         } else {
             repaired
         };
 
+        // Written before the final check so a failing plan can be inspected
+        self.write_artifact("final.json", &final_commits);
+
         // Final validation
         validator
             .validate_complete_assignment(&final_commits, hunks)
@@ -169,6 +410,16 @@ impl HierarchicalReorganizer {
 
         debug!("  Final: {} commits", final_commits.len());
 
+        if budget_spent {
+            warn!(
+                "Time budget of {} exceeded: later phases used heuristics, so this plan is not fully LLM-driven",
+                self.deadline
+                    .budget()
+                    .map(format_duration)
+                    .unwrap_or_default()
+            );
+        }
+
         Ok(final_commits)
     }
 }
@@ -215,6 +466,15 @@ impl Reorganizer for HierarchicalReorganizer {
         Ok(apply_deterministic_fixes(commits, hunks))
     }
 
+    fn fixes_in_place(&self) -> bool {
+        Feature::AttemptValidationFix.is_enabled()
+    }
+
+    fn requires_llm(&self) -> bool {
+        // Heuristic-only mode is built without a client
+        self.client.is_some()
+    }
+
     fn name(&self) -> &'static str {
         "hierarchical"
     }
@@ -226,6 +486,16 @@ mod tests {
     use crate::models::DiffLine;
     use crate::test_utils::{make_hunk_full, make_source_commit};
 
+    #[test]
+    fn test_config_overrides_only_touch_given_values() {
+        let config = HierarchicalConfig::default().with_overrides(Some(2), None, Some(10));
+
+        assert_eq!(config.max_parallel, 2);
+        assert_eq!(config.cluster_config.max_cluster_size, 20);
+        assert_eq!(config.cluster_config.cross_file_threshold, 10);
+        assert!(config.to_string().contains("hierarchical-parallel = 2\n"));
+    }
+
     #[test]
     fn test_empty_hunks() {
         let reorganizer = HierarchicalReorganizer::new(None);
@@ -234,6 +504,78 @@ mod tests {
         assert!(matches!(result, Err(ReorganizeError::NoHunks)));
     }
 
+    #[test]
+    fn test_heuristic_only_plans_without_client() {
+        let hunks = vec![
+            make_hunk_full(
+                0,
+                "src/auth/token.rs",
+                vec![DiffLine::Added("fn validate() {}".to_string())],
+                vec!["abc123".to_string()],
+            ),
+            make_hunk_full(
+                1,
+                "README.md",
+                vec![DiffLine::Added("# Auth".to_string())],
+                vec!["abc123".to_string()],
+            ),
+        ];
+        let source_commits = vec![make_source_commit("abc123", "Add auth")];
+
+        let reorganizer =
+            HierarchicalReorganizer::new(None).with_config(HierarchicalConfig::heuristic_only());
+        let commits = reorganizer.plan(&source_commits, &hunks).unwrap();
+
+        let mut assigned: Vec<usize> = commits
+            .iter()
+            .flat_map(|c| c.changes.iter())
+            .filter_map(|c| match c {
+                crate::models::PlannedChange::ExistingHunk(id) => Some(id.0),
+                _ => None,
+            })
+            .collect();
+        assigned.sort();
+        assert_eq!(assigned, vec![0, 1]);
+        assert!(commits.iter().all(|c| c
+            .rationale
+            .as_deref()
+            .unwrap_or("")
+            .ends_with("(heuristic)")));
+    }
+
+    #[test]
+    fn test_debug_dir_gets_each_phase() {
+        let dir = tempfile::tempdir().unwrap();
+        let hunks = vec![make_hunk_full(
+            0,
+            "src/auth/token.rs",
+            vec![DiffLine::Added("fn validate() {}".to_string())],
+            vec!["abc123".to_string()],
+        )];
+        let source_commits = vec![make_source_commit("abc123", "Add auth")];
+
+        let config =
+            HierarchicalConfig::heuristic_only().with_debug_dir(Some(dir.path().join("phases")));
+        HierarchicalReorganizer::new(None)
+            .with_config(config)
+            .plan(&source_commits, &hunks)
+            .unwrap();
+
+        let read = |name: &str| -> serde_json::Value {
+            let path = dir.path().join("phases").join(name);
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+        };
+        assert_eq!(
+            read("analysis.json")["analyses"]["0"]["file_path"],
+            "src/auth/token.rs"
+        );
+        assert_eq!(read("clusters.json")[0]["hunk_ids"][0], 0);
+        assert!(read("clusters.json")[0]["formation_reason"]["kind"].is_string());
+        assert_eq!(read("planned.json").as_array().unwrap().len(), 1);
+        assert_eq!(read("validation.json")[0]["is_valid"], true);
+        assert_eq!(read("final.json").as_array().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_requires_llm_client() {
         let hunks = vec![make_hunk_full(
@@ -251,4 +593,109 @@ mod tests {
         // Should error without an LLM client
         assert!(matches!(result, Err(ReorganizeError::InvalidPlan(_))));
     }
+
+    /// Answers every prompt with `response` and counts the calls.
+    struct CountingClient {
+        response: &'static str,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl CountingClient {
+        fn new(response: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                response,
+                calls: Default::default(),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(std::sync::atomic::Ordering::SeqCst)
+        }
+    }
+
+    impl LlmClient for CountingClient {
+        fn complete(&self, _prompt: &str) -> Result<String, crate::llm::LlmError> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(self.response.to_string())
+        }
+    }
+
+    #[test]
+    fn test_phase_clients_split_analysis_and_planning() {
+        let hunks = vec![make_hunk_full(
+            0,
+            "src/auth.rs",
+            vec![DiffLine::Added("fn login() {}".to_string())],
+            vec!["abc123".to_string()],
+        )];
+        let source_commits = vec![make_source_commit("abc123", "Add auth")];
+
+        // Each client can only answer its own phase's prompts
+        let main = CountingClient::new("not json");
+        let analyze = CountingClient::new(
+            r#"{"category": "feature", "semantic_units": ["add login"], "suggested_topic": "auth"}"#,
+        );
+        let plan = CountingClient::new(
+            r#"{"short_message": "Add login", "long_message": "Add login\n\nAdds login."}"#,
+        );
+
+        let commits = HierarchicalReorganizer::new(Some(main.clone()))
+            .with_analyze_client(Some(analyze.clone()))
+            .with_plan_client(Some(plan.clone()))
+            .plan(&source_commits, &hunks)
+            .unwrap();
+
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].description.short, "Add login");
+        assert_eq!(main.calls(), 0);
+        assert_eq!(analyze.calls(), 1);
+        assert_eq!(plan.calls(), 1);
+    }
+
+    #[test]
+    fn test_failed_planning_retries_then_falls_back_to_heuristics() {
+        let hunks = vec![make_hunk_full(
+            0,
+            "src/auth.rs",
+            vec![DiffLine::Added("fn login() {}".to_string())],
+            vec!["abc123".to_string()],
+        )];
+        let source_commits = vec![make_source_commit("abc123", "Add auth")];
+
+        let analyze = CountingClient::new(
+            r#"{"category": "feature", "semantic_units": ["add login"], "suggested_topic": "auth"}"#,
+        );
+        let plan = CountingClient::new("not json");
+        let config = HierarchicalConfig {
+            max_pipeline_retries: 1,
+            ..HierarchicalConfig::default()
+        };
+
+        let commits = HierarchicalReorganizer::new(Some(analyze.clone()))
+            .with_plan_client(Some(plan.clone()))
+            .with_config(config)
+            .plan(&source_commits, &hunks)
+            .unwrap();
+
+        // Analysis ran once; planning (3 tries per cluster) ran in both attempts
+        assert_eq!(analyze.calls(), 1);
+        assert_eq!(plan.calls(), 6);
+        assert_eq!(commits.len(), 1);
+        assert!(commits[0]
+            .rationale
+            .as_deref()
+            .unwrap_or("")
+            .ends_with("(heuristic)"));
+    }
+
+    #[test]
+    fn test_phase_models_only_override_given_values() {
+        let config = HierarchicalConfig::default()
+            .with_phase_models(Some("haiku".to_string()), None)
+            .with_phase_models(None, Some("opus".to_string()));
+
+        assert_eq!(config.analyze_model.as_deref(), Some("haiku"));
+        assert_eq!(config.plan_model.as_deref(), Some("opus"));
+        assert!(config.to_string().contains("analyze-model = haiku\n"));
+    }
 }
@@ -3,26 +3,37 @@
 use std::sync::{Arc, Mutex};
 use std::thread;
 
-use log::debug;
+use log::{debug, warn};
 
-use crate::llm::LlmClient;
+use crate::llm::{LlmClient, Redaction};
 use crate::models::{
     CommitDescription, Hunk, HunkId, PlannedChange, PlannedCommit, PlannedCommitId,
 };
+use crate::reorganize::Deadline;
+use crate::utils::{format_diff_lines, format_duration};
 
-use super::types::{AnalysisResults, Cluster, CommitPlanResponse, HierarchicalError};
+use super::types::{
+    AnalysisResults, ChangeCategory, Cluster, CommitPlanResponse, HierarchicalError, HunkAnalysis,
+};
 
 /// Plans commits from clusters
 pub struct CommitPlanner {
     client: Option<Arc<dyn LlmClient + Send + Sync>>,
     max_parallel: usize,
+    deadline: Deadline,
+    redaction: Redaction,
 }
 
+/// Diff lines of each hunk shown in a commit prompt
+const DIFF_PREVIEW_LINES: usize = 10;
+
 impl CommitPlanner {
     pub fn new(client: Option<Arc<dyn LlmClient + Send + Sync>>) -> Self {
         Self {
             client,
             max_parallel: 4,
+            deadline: Deadline::default(),
+            redaction: Redaction::default(),
         }
     }
 
@@ -31,6 +42,18 @@ impl CommitPlanner {
         self
     }
 
+    /// Write heuristic commit messages once `deadline` passes
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Show the files `redaction` matches as placeholders in prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
     /// Plan commits from clusters
     pub fn plan(
         &self,
@@ -42,11 +65,13 @@ impl CommitPlanner {
             return Ok(Vec::new());
         }
 
-        let client = self.client.as_ref().ok_or_else(|| {
-            HierarchicalError::LlmError("LLM client is required for planning".to_string())
-        })?;
-
-        self.plan_with_llm(clusters, hunks, analysis, client)
+        match &self.client {
+            Some(client) => self.plan_with_llm(clusters, hunks, analysis, client),
+            None => Ok(clusters
+                .iter()
+                .map(|cluster| heuristic_commit(cluster, analysis))
+                .collect()),
+        }
     }
 
     /// Plan commits using LLM
@@ -63,7 +88,12 @@ impl CommitPlanner {
         // Process clusters in parallel batches
         let chunks: Vec<_> = clusters.chunks(self.max_parallel).collect();
 
+        let mut heuristic = Vec::new();
         for chunk in chunks {
+            if self.deadline.is_past() {
+                heuristic.extend(chunk.iter());
+                continue;
+            }
             let handles: Vec<_> = chunk
                 .iter()
                 .map(|cluster| {
@@ -71,6 +101,7 @@ impl CommitPlanner {
                     let results = Arc::clone(&results);
                     let errors = Arc::clone(&errors);
                     let cluster = cluster.clone();
+                    let redaction = self.redaction.clone();
 
                     // Build context for this cluster - clone hunks to avoid lifetime issues
                     let cluster_hunks: Vec<Hunk> = cluster
@@ -88,8 +119,13 @@ impl CommitPlanner {
 
                     thread::spawn(move || {
                         let hunk_refs: Vec<&Hunk> = cluster_hunks.iter().collect();
-                        match plan_single_cluster(&client, &cluster, &hunk_refs, &cluster_analysis)
-                        {
+                        match plan_single_cluster(
+                            &client,
+                            &cluster,
+                            &hunk_refs,
+                            &cluster_analysis,
+                            &redaction,
+                        ) {
                             Ok(commits) => {
                                 let mut results = results.lock().unwrap();
                                 results.extend(commits);
@@ -108,14 +144,31 @@ impl CommitPlanner {
             }
         }
 
-        // Check for errors
+        // Check for errors; past the deadline, failed clusters fall back to heuristics
         let errors = Arc::try_unwrap(errors).unwrap().into_inner().unwrap();
-        if !errors.is_empty() {
+        if !errors.is_empty() && !self.deadline.is_past() {
             let (cluster_id, error) = errors.into_iter().next().unwrap();
             return Err(HierarchicalError::PlanningFailed(cluster_id, error));
         }
+        for (cluster_id, _) in errors {
+            heuristic.extend(clusters.iter().find(|c| c.id == cluster_id));
+        }
 
         let mut commits = Arc::try_unwrap(results).unwrap().into_inner().unwrap();
+        if !heuristic.is_empty() {
+            warn!(
+                "Time budget of {} exceeded: {} of {} commits got heuristic messages",
+                self.deadline
+                    .budget()
+                    .map(format_duration)
+                    .unwrap_or_default(),
+                heuristic.len(),
+                clusters.len()
+            );
+            for cluster in heuristic {
+                commits.push(heuristic_commit(cluster, analysis));
+            }
+        }
 
         // Sort commits by ID for deterministic ordering
         commits.sort_by_key(|c| c.id.0);
@@ -129,8 +182,9 @@ fn plan_single_cluster(
     cluster: &Cluster,
     hunks: &[&Hunk],
     analysis: &[super::types::HunkAnalysis],
+    redaction: &Redaction,
 ) -> Result<Vec<PlannedCommit>, String> {
-    let prompt = build_commit_prompt(cluster, hunks, analysis);
+    let prompt = build_commit_prompt(cluster, hunks, analysis, redaction);
 
     const MAX_RETRIES: u32 = 3;
     let mut last_error = String::new();
@@ -187,6 +241,7 @@ fn plan_single_cluster(
                             .collect(),
                         depends_on,
                     )
+                    .with_rationale(cluster_rationale(cluster))
                 })
                 .collect());
         } else {
@@ -199,7 +254,8 @@ fn plan_single_cluster(
                     .iter()
                     .map(|id| PlannedChange::ExistingHunk(*id))
                     .collect(),
-            )]);
+            )
+            .with_rationale(cluster_rationale(cluster))]);
         }
     }
 
@@ -207,10 +263,72 @@ fn plan_single_cluster(
     Err(last_error)
 }
 
+/// Describe a cluster as a single commit without the LLM.
+fn heuristic_commit(cluster: &Cluster, analysis: &AnalysisResults) -> PlannedCommit {
+    let topic = cluster.topic.replace('_', " ");
+    let category = match cluster.categories.iter().collect::<Vec<_>>().as_slice() {
+        [only] => **only,
+        _ => ChangeCategory::Other,
+    };
+    let short = match category {
+        ChangeCategory::Feature => format!("Add {}", topic),
+        ChangeCategory::Bugfix => format!("Fix {}", topic),
+        ChangeCategory::Refactor => format!("Refactor {}", topic),
+        ChangeCategory::Test => format!("Update {} tests", topic),
+        ChangeCategory::Documentation => format!("Update {} documentation", topic),
+        ChangeCategory::Configuration => format!("Update {} configuration", topic),
+        ChangeCategory::Dependency => "Update dependencies".to_string(),
+        ChangeCategory::Formatting => format!("Format {}", topic),
+        ChangeCategory::Other => format!("Update {}", topic),
+    };
+    let units: Vec<String> = cluster
+        .hunk_ids
+        .iter()
+        .filter_map(|id| analysis.get(*id))
+        .flat_map(|a: &HunkAnalysis| a.semantic_units.iter().cloned())
+        .collect();
+    let long = if units.is_empty() {
+        short.clone()
+    } else {
+        units
+            .iter()
+            .map(|u| format!("- {}", u))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    PlannedCommit::new(
+        PlannedCommitId(cluster.id.0),
+        CommitDescription::new(short, long),
+        cluster
+            .hunk_ids
+            .iter()
+            .map(|id| PlannedChange::ExistingHunk(*id))
+            .collect(),
+    )
+    .with_rationale(format!("{} (heuristic)", cluster_rationale(cluster)))
+}
+
+/// Explain a cluster's commits by the topic and categories it was grouped on.
+fn cluster_rationale(cluster: &Cluster) -> String {
+    let mut categories: Vec<String> = cluster.categories.iter().map(|c| c.to_string()).collect();
+    categories.sort();
+    if categories.is_empty() {
+        format!("Grouped by topic '{}'", cluster.topic)
+    } else {
+        format!(
+            "Grouped by topic '{}' ({})",
+            cluster.topic,
+            categories.join(", ")
+        )
+    }
+}
+
 fn build_commit_prompt(
     cluster: &Cluster,
     hunks: &[&Hunk],
     analysis: &[super::types::HunkAnalysis],
+    redaction: &Redaction,
 ) -> String {
     let mut prompt = String::from(
         r#"Write a commit message for these code changes.
@@ -233,23 +351,17 @@ fn build_commit_prompt(
     for (hunk, analysis) in hunks.iter().zip(analysis.iter()) {
         prompt.push_str(&format!(
             "- {} ({}): {}\n",
-            analysis.file_path,
+            redaction.path(&hunk.file_path),
             analysis.category,
             analysis.semantic_units.join(", ")
         ));
 
         // Include abbreviated diff
-        let diff_preview: String = hunk
-            .lines
-            .iter()
-            .take(10)
-            .map(|l| match l {
-                crate::models::DiffLine::Context(s) => format!(" {}", s),
-                crate::models::DiffLine::Added(s) => format!("+{}", s),
-                crate::models::DiffLine::Removed(s) => format!("-{}", s),
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        let diff_preview = if redaction.matches(&hunk.file_path) {
+            redaction.hunk_lines(hunk)
+        } else {
+            format_diff_lines(&hunk.lines[..hunk.lines.len().min(DIFF_PREVIEW_LINES)])
+        };
 
         prompt.push_str(&format!("```diff\n{}\n```\n", diff_preview));
     }
@@ -294,3 +406,56 @@ fn parse_commit_response(response: &str) -> Result<CommitPlanResponse, String> {
 
     serde_json::from_str(json_str).map_err(|e| format!("Failed to parse commit plan: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::super::types::{ClusterFormationReason, ClusterId};
+    use super::*;
+    use crate::models::DiffLine;
+    use crate::test_utils::make_hunk_in_file;
+
+    fn analysis(hunk: &Hunk) -> HunkAnalysis {
+        HunkAnalysis {
+            hunk_id: hunk.id.0,
+            category: ChangeCategory::Feature,
+            semantic_units: vec!["rotate token".to_string()],
+            topic: "config".to_string(),
+            depends_on_context: None,
+            file_path: hunk.file_path.to_string_lossy().into_owned(),
+        }
+    }
+
+    #[test]
+    fn commit_prompt_leaves_out_redacted_hunks() {
+        let mut secret = make_hunk_in_file(0, "config/prod.env");
+        secret.lines = vec![
+            DiffLine::Removed("TOKEN=old".to_string()),
+            DiffLine::Added("TOKEN=supersecret123".to_string()),
+        ];
+        let public = make_hunk_in_file(1, "src/main.rs");
+        let cluster = Cluster {
+            id: ClusterId(0),
+            hunk_ids: vec![secret.id, public.id],
+            topic: "config".to_string(),
+            categories: HashSet::from([ChangeCategory::Feature]),
+            formation_reason: ClusterFormationReason::Fallback,
+        };
+        let analyses = vec![analysis(&secret), analysis(&public)];
+        let redaction = Redaction::new(&["*.env".to_string()]).unwrap();
+
+        let prompt = build_commit_prompt(&cluster, &[&secret, &public], &analyses, &redaction);
+
+        assert!(!prompt.contains("supersecret123"));
+        assert!(!prompt.contains("TOKEN="));
+        assert!(prompt.contains("[redacted: 2 lines in config/prod.env]"));
+        assert!(prompt.contains(&format_diff_lines(&public.lines)));
+
+        let hidden = Redaction::new(&["*.env".to_string()])
+            .unwrap()
+            .with_hide_paths(true);
+        let prompt = build_commit_prompt(&cluster, &[&secret, &public], &analyses, &hidden);
+        assert!(!prompt.contains("prod.env"));
+    }
+}
@@ -69,7 +69,7 @@ pub struct HunkAnalysisResponse {
 }
 
 /// A cluster of hunks that should be in the same commit
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Cluster {
     /// Unique identifier for this cluster
     pub id: ClusterId,
@@ -84,7 +84,7 @@ pub struct Cluster {
 }
 
 /// Unique identifier for a cluster
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub struct ClusterId(pub usize);
 
 impl std::fmt::Display for ClusterId {
@@ -94,7 +94,8 @@ impl std::fmt::Display for ClusterId {
 }
 
 /// Why a cluster was formed
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
 pub enum ClusterFormationReason {
     /// Hunks are in the same file
     SameFile(String),
@@ -154,7 +155,7 @@ pub struct RelatedGroup {
 }
 
 /// Analysis results for all hunks
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct AnalysisResults {
     /// Per-hunk analysis
     pub analyses: HashMap<HunkId, HunkAnalysis>,
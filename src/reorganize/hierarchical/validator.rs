@@ -3,6 +3,8 @@
 use std::collections::HashSet;
 use std::sync::Arc;
 
+use serde::Serialize;
+
 use crate::llm::LlmClient;
 use crate::models::{
     CommitDescription, Hunk, HunkId, PlannedChange, PlannedCommit, PlannedCommitId,
@@ -11,7 +13,7 @@ use crate::models::{
 use super::types::{AnalysisResults, HierarchicalError};
 
 /// Result of validating a single commit
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CommitValidation {
     pub commit_id: PlannedCommitId,
     pub is_valid: bool,
@@ -19,7 +21,8 @@ pub struct CommitValidation {
 }
 
 /// Types of validation issues
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
 pub enum ValidationIssue {
     /// Commit message is empty or too short
     EmptyMessage,
@@ -7,7 +7,6 @@
 //!
 //! Generic LLM infrastructure lives in `crate::llm`.
 
-mod file_io;
 mod parser;
 mod prompt;
 mod types;
@@ -15,22 +14,38 @@ mod types;
 pub use prompt::build_reword_prompt;
 pub use types::{ChangeSpec, FixMessageResponse};
 
+use std::collections::HashSet;
+use std::path::Path;
+use std::thread;
+
 use log::{debug, info, warn};
 
 use crate::features::Feature;
-use crate::llm::{LlmClient, LlmError};
+use crate::llm::{file_io, LlmClient, LlmError, Redaction};
 use crate::models::{
     CommitDescription, Hunk, HunkId, PlannedChange, PlannedCommit, PlannedCommitId, SourceCommit,
 };
-use crate::reorganize::{ReorganizeError, Reorganizer};
-use crate::utils::extract_json_str;
-use crate::validation::{ValidationIssue, ValidationResult};
+use crate::reorganize::{Deadline, PreserveOriginal, ReorganizeError, Reorganizer};
+use crate::utils::{extract_json_str, format_duration};
+use crate::validation::{apply_deterministic_fixes, ValidationIssue, ValidationResult};
 
 use types::{FixDuplicateResponse, FixOverlappingResponse, FixUnassignedResponse, HunkAssignment};
 
+/// How many chunks are planned concurrently when chunking is enabled.
+const MAX_PARALLEL_CHUNKS: usize = 4;
+
+/// Most hunks planned in a single prompt unless configured otherwise.
+pub const DEFAULT_MAX_HUNKS: usize = 200;
+
 pub struct LlmReorganizer {
     client: Box<dyn LlmClient>,
     max_retries: usize,
+    chunk_size: Option<usize>,
+    deadline: Deadline,
+    min_confidence: Option<f32>,
+    max_hunks: usize,
+    redaction: Redaction,
+    compact_diff: bool,
 }
 
 impl LlmReorganizer {
@@ -38,6 +53,12 @@ impl LlmReorganizer {
         Self {
             client,
             max_retries: 3,
+            chunk_size: None,
+            deadline: Deadline::default(),
+            min_confidence: None,
+            max_hunks: DEFAULT_MAX_HUNKS,
+            redaction: Redaction::default(),
+            compact_diff: false,
         }
     }
 
@@ -46,13 +67,128 @@ impl LlmReorganizer {
         self
     }
 
+    /// Plan at most `chunk_size` hunks per LLM call, planning chunks in
+    /// parallel. `None` sends every hunk in a single prompt.
+    pub fn with_chunk_size(mut self, chunk_size: Option<usize>) -> Self {
+        self.chunk_size = chunk_size.filter(|&size| size > 0);
+        self
+    }
+
+    /// Stop calling the LLM once `deadline` passes, planning whatever is left
+    /// with the original commit structure instead.
+    pub fn with_deadline(mut self, deadline: Deadline) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Move hunks the model places with less than `min_confidence` (0 to 1)
+    /// into a final "uncertain" commit instead of trusting the guess.
+    pub fn with_min_confidence(mut self, min_confidence: Option<f32>) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// Refuse to plan more than `max_hunks` hunks in a single prompt (0 for
+    /// no limit). Chunked plans are not limited.
+    pub fn with_max_hunks(mut self, max_hunks: usize) -> Self {
+        self.max_hunks = max_hunks;
+        self
+    }
+
+    /// Show the files `redaction` matches as placeholders in prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Show hunks to the planning prompt as word diffs, which costs far fewer
+    /// tokens on reformatting-heavy branches. The applied hunks are unchanged.
+    pub fn with_compact_diff(mut self, compact_diff: bool) -> Self {
+        self.compact_diff = compact_diff;
+        self
+    }
+
+    fn budget_label(&self) -> String {
+        self.deadline
+            .budget()
+            .map(format_duration)
+            .unwrap_or_default()
+    }
+
+    /// Plan each chunk independently and concatenate the results in chunk order.
+    fn plan_chunked(
+        &self,
+        source_commits: &[SourceCommit],
+        chunks: &[Vec<Hunk>],
+        hunks: &[Hunk],
+    ) -> Result<Vec<PlannedCommit>, LlmError> {
+        info!(
+            "Planning {} hunks in {} chunks ({} parallel)...",
+            hunks.len(),
+            chunks.len(),
+            MAX_PARALLEL_CHUNKS
+        );
+
+        let mut chunk_plans = Vec::with_capacity(chunks.len());
+        let mut batches = chunks.chunks(MAX_PARALLEL_CHUNKS);
+        for batch in batches.by_ref() {
+            if self.deadline.is_past() {
+                break;
+            }
+            let results: Vec<_> = thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|chunk| {
+                        let commits = commits_for_chunk(source_commits, chunk);
+                        scope.spawn(move || self.invoke_with_retry(&commits, chunk))
+                    })
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| {
+                        handle.join().unwrap_or_else(|_| {
+                            Err(LlmError::ClientError("chunk planning panicked".to_string()))
+                        })
+                    })
+                    .collect()
+            });
+            for result in results {
+                chunk_plans.push(result?);
+            }
+        }
+
+        let remaining: Vec<&Vec<Hunk>> = batches.flatten().collect();
+        if !remaining.is_empty() {
+            warn!(
+                "Time budget of {} exceeded: keeping the original commits for the last {} of {} chunks",
+                self.budget_label(),
+                remaining.len(),
+                chunks.len()
+            );
+            for chunk in remaining {
+                let commits = commits_for_chunk(source_commits, chunk);
+                let plan = PreserveOriginal
+                    .plan(&commits, chunk)
+                    .map_err(|e| LlmError::ClientError(e.to_string()))?;
+                chunk_plans.push(plan);
+            }
+        }
+
+        let mut merged = merge_chunk_plans(chunk_plans, hunks);
+        if let Some(min) = self.min_confidence {
+            parser::add_uncertain(&mut merged, Vec::new(), min);
+        }
+        Ok(merged)
+    }
+
     /// Invoke LLM with retry for parse errors only
     fn invoke_with_retry(
         &self,
         source_commits: &[SourceCommit],
         hunks: &[Hunk],
     ) -> Result<Vec<PlannedCommit>, LlmError> {
-        let context = prompt::build_context(source_commits, hunks);
+        let context =
+            prompt::build_context(source_commits, hunks, &self.redaction, self.compact_diff);
 
         // Set up file-based I/O if feature is enabled
         let (prompt_text, _file_session, use_file_io) = if Feature::FileBasedLlmIo.is_enabled() {
@@ -73,10 +209,20 @@ impl LlmReorganizer {
         };
 
         let mut last_error = None;
+        // Why the last response was rejected, quoted in the next prompt
+        let mut feedback: Option<String> = None;
 
         for attempt in 1..=self.max_retries {
+            if attempt > 1 && self.deadline.is_past() {
+                debug!("Time budget exceeded, not retrying");
+                break;
+            }
             info!("LLM attempt {}/{}...", attempt, self.max_retries);
-            match self.client.complete(&prompt_text) {
+            let attempt_prompt = match &feedback {
+                Some(error) => prompt::with_retry_feedback(&prompt_text, error),
+                None => prompt_text.clone(),
+            };
+            match self.client.complete(&attempt_prompt) {
                 Ok(stdout_response) => {
                     // Get response from file (via path in stdout) or directly from stdout
                     let response = if use_file_io {
@@ -98,16 +244,25 @@ impl LlmReorganizer {
                     match parser::extract_json(&response) {
                         Ok(llm_commits) => {
                             // Convert to PlannedCommits immediately
-                            match parser::to_planned_commits(llm_commits, hunks) {
+                            match parser::to_planned_commits(
+                                llm_commits,
+                                hunks,
+                                self.min_confidence,
+                            ) {
                                 Ok(commits) => return Ok(commits),
                                 Err(e) => {
                                     debug!("Conversion error: {}", e);
+                                    feedback = Some(e.to_string());
                                     last_error = Some(e);
                                 }
                             }
                         }
                         Err(e) => {
                             debug!("Parse error: {}", e);
+                            feedback = Some(match &e {
+                                LlmError::ValidationError(msg) => msg.clone(),
+                                other => other.to_string(),
+                            });
                             last_error = Some(e);
                         }
                     }
@@ -127,11 +282,38 @@ impl LlmReorganizer {
         commits: &mut Vec<PlannedCommit>,
         fix: FixUnassignedResponse,
     ) {
+        let mut uncertain = Vec::new();
         for assignment in fix.assignments {
+            if let Some(min) = self
+                .min_confidence
+                .filter(|&min| assignment.confidence() < min)
+            {
+                debug!(
+                    "  Hunk {} placed with confidence {:.2} (< {}); marking uncertain",
+                    assignment.hunk_id(),
+                    assignment.confidence(),
+                    min
+                );
+                let suggested = match &assignment {
+                    HunkAssignment::AddToExisting {
+                        commit_description, ..
+                    } => commit_description.clone(),
+                    HunkAssignment::NewCommit {
+                        short_description, ..
+                    } => short_description.clone(),
+                };
+                uncertain.push(parser::UncertainChange {
+                    change: PlannedChange::ExistingHunk(HunkId(assignment.hunk_id())),
+                    suggested,
+                    confidence: assignment.confidence(),
+                });
+                continue;
+            }
             match assignment {
                 HunkAssignment::AddToExisting {
                     hunk_id,
                     commit_description,
+                    ..
                 } => {
                     // Find the commit by description and add the hunk
                     if let Some(commit) = commits
@@ -156,6 +338,7 @@ impl LlmReorganizer {
                     hunk_id,
                     short_description,
                     long_description,
+                    ..
                 } => {
                     // Create a new commit
                     let next_id = commits.iter().map(|c| c.id.0).max().unwrap_or(0) + 1;
@@ -171,6 +354,9 @@ impl LlmReorganizer {
                 }
             }
         }
+        if let Some(min) = self.min_confidence {
+            parser::add_uncertain(commits, uncertain, min);
+        }
     }
 }
 
@@ -183,8 +369,38 @@ impl Reorganizer for LlmReorganizer {
         if hunks.is_empty() {
             return Err(ReorganizeError::NoHunks);
         }
-        self.invoke_with_retry(source_commits, hunks)
-            .map_err(|e| ReorganizeError::InvalidPlan(e.to_string()))
+        let chunked = self.chunk_size.is_some_and(|size| hunks.len() > size);
+        if !chunked && self.max_hunks > 0 && hunks.len() > self.max_hunks {
+            return Err(ReorganizeError::TooManyHunks {
+                count: hunks.len(),
+                limit: self.max_hunks,
+            });
+        }
+        if self.deadline.is_past() {
+            warn!(
+                "Time budget of {} exceeded before planning: keeping the original commits",
+                self.budget_label()
+            );
+            return PreserveOriginal.plan(source_commits, hunks);
+        }
+        let result = match self.chunk_size {
+            Some(size) if chunked => {
+                self.plan_chunked(source_commits, &chunk_hunks(hunks, size), hunks)
+            }
+            _ => self.invoke_with_retry(source_commits, hunks),
+        };
+        match result {
+            Ok(commits) => Ok(commits),
+            Err(e) if self.deadline.is_past() => {
+                warn!(
+                    "LLM planning failed ({}) and the time budget of {} is spent: keeping the original commits",
+                    e,
+                    self.budget_label()
+                );
+                PreserveOriginal.plan(source_commits, hunks)
+            }
+            Err(e) => Err(ReorganizeError::InvalidPlan(e.to_string())),
+        }
     }
 
     fn fix_plan(
@@ -200,10 +416,18 @@ impl Reorganizer for LlmReorganizer {
             return self.plan(source_commits, hunks);
         }
 
+        if self.deadline.is_past() {
+            warn!(
+                "Time budget of {} exceeded: fixing the plan without the LLM",
+                self.budget_label()
+            );
+            return Ok(apply_deterministic_fixes(commits, hunks));
+        }
+
         debug!("Applying LLM-based fixes to plan...");
 
-        // Build context for prompts
-        let context = prompt::build_context(source_commits, hunks);
+        // Fix prompts may ask for partial hunks, so they show full lines
+        let context = prompt::build_context(source_commits, hunks, &self.redaction, false);
 
         // Fix duplicate hunks using LLM
         for (hunk_id, commit_ids) in validation.duplicate_hunks() {
@@ -277,7 +501,7 @@ impl Reorganizer for LlmReorganizer {
                 commit_a_idx,
                 hunk_b.0,
                 commit_b_idx,
-                &file_path,
+                Path::new(&self.redaction.path(&file_path)),
             );
 
             match self.client.complete(&fix_prompt) {
@@ -373,7 +597,12 @@ impl Reorganizer for LlmReorganizer {
 
                 // Find the commit to fix
                 if let Some(commit) = commits.iter_mut().find(|c| c.id == *commit_id) {
-                    let fix_prompt = prompt::build_fix_message_prompt(commit, assessment, hunks);
+                    let fix_prompt = prompt::build_fix_message_prompt(
+                        commit,
+                        assessment,
+                        hunks,
+                        &self.redaction,
+                    );
 
                     match self.client.complete(&fix_prompt) {
                         Ok(response) => {
@@ -403,7 +632,214 @@ impl Reorganizer for LlmReorganizer {
         Ok(commits)
     }
 
+    fn fixes_in_place(&self) -> bool {
+        Feature::AttemptValidationFix.is_enabled()
+    }
+
+    fn requires_llm(&self) -> bool {
+        true
+    }
+
     fn name(&self) -> &'static str {
         "llm"
     }
 }
+
+/// Split hunks into chunks of at most `size` hunks, keeping each file's hunks
+/// together. A file with more than `size` hunks gets a chunk of its own.
+fn chunk_hunks(hunks: &[Hunk], size: usize) -> Vec<Vec<Hunk>> {
+    let mut files: Vec<(&Path, Vec<Hunk>)> = Vec::new();
+    for hunk in hunks {
+        match files.iter_mut().find(|(path, _)| *path == hunk.file_path) {
+            Some((_, file_hunks)) => file_hunks.push(hunk.clone()),
+            None => files.push((&hunk.file_path, vec![hunk.clone()])),
+        }
+    }
+
+    let mut chunks: Vec<Vec<Hunk>> = Vec::new();
+    let mut current: Vec<Hunk> = Vec::new();
+    for (_, file_hunks) in files {
+        if !current.is_empty() && current.len() + file_hunks.len() > size {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.extend(file_hunks);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Source commits that touched any hunk in `chunk`, in their original order.
+fn commits_for_chunk(source_commits: &[SourceCommit], chunk: &[Hunk]) -> Vec<SourceCommit> {
+    let shas: HashSet<&str> = chunk
+        .iter()
+        .flat_map(|h| h.likely_source_commits.iter().map(String::as_str))
+        .collect();
+    source_commits
+        .iter()
+        .filter(|c| shas.contains(c.sha.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Concatenate per-chunk plans, renumbering commits and split hunks.
+///
+/// Each chunk numbers its commits and split hunks from scratch, so both are
+/// reassigned to stay unique across the merged plan. Chunks are disjoint, so
+/// no hunk can be duplicated across them.
+fn merge_chunk_plans(chunk_plans: Vec<Vec<PlannedCommit>>, hunks: &[Hunk]) -> Vec<PlannedCommit> {
+    let mut next_hunk_id = hunks.iter().map(|h| h.id.0).max().unwrap_or(0) + 1;
+    let mut merged = Vec::new();
+
+    for plan in chunk_plans {
+        let offset = merged.len();
+        for mut commit in plan {
+            commit.id = PlannedCommitId(offset + commit.id.0);
+            for dep in &mut commit.depends_on {
+                dep.0 += offset;
+            }
+            for change in &mut commit.changes {
+                if let PlannedChange::NewHunk(hunk) = change {
+                    hunk.id = HunkId(next_hunk_id);
+                    next_hunk_id += 1;
+                }
+            }
+            merged.push(commit);
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::test_support::MockLlmClient;
+    use crate::test_utils::{make_hunk_in_file, make_source_commit};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_chunk_hunks_keeps_files_together() {
+        let hunks = vec![
+            make_hunk_in_file(0, "a.rs"),
+            make_hunk_in_file(1, "b.rs"),
+            make_hunk_in_file(2, "a.rs"),
+            make_hunk_in_file(3, "c.rs"),
+        ];
+
+        let chunks = chunk_hunks(&hunks, 2);
+        let ids: Vec<Vec<usize>> = chunks
+            .iter()
+            .map(|c| c.iter().map(|h| h.id.0).collect())
+            .collect();
+        assert_eq!(ids, vec![vec![0, 2], vec![1, 3]]);
+    }
+
+    #[test]
+    fn test_chunked_plan_renumbers_commits() {
+        let commits = vec![make_source_commit("abc", "Original")];
+        let hunks: Vec<Hunk> = (0..3)
+            .map(|i| {
+                let mut hunk = make_hunk_in_file(i, &format!("file{}.rs", i));
+                hunk.likely_source_commits = vec!["abc".to_string()];
+                hunk
+            })
+            .collect();
+
+        // Every chunk gets the same single-commit answer
+        let client = MockLlmClient::new(
+            r#"{"commits": [{"short_description": "Chunk", "long_description": "Chunk", "changes": [{"type": "hunk", "id": 0}]}]}"#,
+        );
+        let planned = LlmReorganizer::new(Box::new(client))
+            .with_chunk_size(Some(1))
+            .plan(&commits, &hunks)
+            .unwrap();
+
+        let ids: Vec<usize> = planned.iter().map(|c| c.id.0).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_single_prompt_refuses_too_many_hunks() {
+        let commits = vec![make_source_commit("abc", "Original")];
+        let hunks: Vec<Hunk> = (0..3)
+            .map(|i| make_hunk_in_file(i, &format!("file{}.rs", i)))
+            .collect();
+        let reorganizer = || {
+            LlmReorganizer::new(Box::new(MockLlmClient::new(
+                r#"{"commits": [{"short_description": "All", "long_description": "All", "changes": [{"type": "hunk", "id": 0}]}]}"#,
+            )))
+            .with_max_hunks(2)
+        };
+
+        let err = reorganizer().plan(&commits, &hunks).unwrap_err();
+        assert!(matches!(
+            err,
+            ReorganizeError::TooManyHunks { count: 3, limit: 2 }
+        ));
+        assert!(err.to_string().contains("--strategy hierarchical"));
+
+        // Chunked prompts stay small, so the limit doesn't apply
+        assert!(reorganizer()
+            .with_chunk_size(Some(1))
+            .plan(&commits, &hunks)
+            .is_ok());
+    }
+
+    /// Answers with `responses` in turn and records every prompt.
+    struct ScriptedClient {
+        responses: Mutex<Vec<&'static str>>,
+        prompts: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl LlmClient for ScriptedClient {
+        fn complete(&self, prompt: &str) -> Result<String, LlmError> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.responses.lock().unwrap().remove(0).to_string())
+        }
+    }
+
+    #[test]
+    fn test_retry_prompt_quotes_the_validation_error() {
+        let commits = vec![make_source_commit("abc", "Original")];
+        let hunks = vec![make_hunk_in_file(0, "a.rs")];
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let client = ScriptedClient {
+            responses: Mutex::new(vec![
+                r#"{"commits": [{"short_description": "A", "long_description": "A"}]}"#,
+                r#"{"commits": [{"short_description": "A", "long_description": "A", "changes": [{"type": "hunk", "id": 0}]}]}"#,
+            ]),
+            prompts: Arc::clone(&prompts),
+        };
+
+        let planned = LlmReorganizer::new(Box::new(client))
+            .plan(&commits, &hunks)
+            .unwrap();
+
+        assert_eq!(planned.len(), 1);
+        let prompts = prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 2);
+        assert!(!prompts[0].contains("Previous Attempt Rejected"));
+        assert!(prompts[1].contains(
+            "Your previous response was rejected: field 'commits[0].changes' is missing"
+        ));
+        assert!(prompts[1].ends_with("```json\n"));
+    }
+
+    #[test]
+    fn test_spent_time_budget_keeps_original_commits() {
+        let commits = vec![make_source_commit("abc", "Original")];
+        let mut hunk = make_hunk_in_file(0, "a.rs");
+        hunk.likely_source_commits = vec!["abc".to_string()];
+
+        let client = MockLlmClient::new("not json");
+        let planned = LlmReorganizer::new(Box::new(client))
+            .with_deadline(Deadline::after(Some(std::time::Duration::ZERO)))
+            .plan(&commits, &[hunk])
+            .unwrap();
+
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].description.short, "Original");
+    }
+}
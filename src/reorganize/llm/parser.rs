@@ -3,7 +3,9 @@
 use std::fs;
 use std::path::PathBuf;
 
-use crate::models::{DiffLine, Hunk, HunkId, PlannedChange, PlannedCommit, PlannedCommitId};
+use crate::models::{
+    CommitDescription, DiffLine, Hunk, HunkId, PlannedChange, PlannedCommit, PlannedCommitId,
+};
 use crate::utils::extract_json_str;
 
 use super::types::{ChangeSpec, LlmCommit};
@@ -24,6 +26,7 @@ fn dump_to_tmp(label: &str, content: &str) -> Option<PathBuf> {
 
 /// Wrapper for deserializing the LLM's JSON response
 #[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
 struct LlmResponse {
     commits: Vec<LlmCommit>,
 }
@@ -46,7 +49,7 @@ pub fn extract_json(response: &str) -> Result<Vec<LlmCommit>, LlmError> {
         }
     };
 
-    let parsed: LlmResponse = serde_json::from_str(json_str).map_err(|e| {
+    let parse_error = |e: &dyn std::fmt::Display| {
         let detail = match dump_to_tmp("json-parse-error", json_str) {
             Some(path) => format!("Full JSON dumped to {}", path.display()),
             None => format!(
@@ -55,51 +58,360 @@ pub fn extract_json(response: &str) -> Result<Vec<LlmCommit>, LlmError> {
             ),
         };
         LlmError::ParseError(format!("{}: {}", e, detail))
-    })?;
+    };
+    let value: serde_json::Value = serde_json::from_str(json_str).map_err(|e| parse_error(&e))?;
+    validate_plan_response(&value).map_err(LlmError::ValidationError)?;
+    let parsed: LlmResponse = serde_json::from_value(value).map_err(|e| parse_error(&e))?;
     Ok(parsed.commits)
 }
 
-/// Convert LlmCommits to PlannedCommits, processing Partial and Raw specs
+/// Check a plan response against the schema the prompt asks for, naming
+/// every offending field (e.g. "field 'commits[2].changes' is empty").
+///
+/// Serde alone would accept unknown fields and report missing ones without
+/// saying which commit they belong to; these messages are quoted back to
+/// the model when retrying.
+pub fn validate_plan_response(value: &serde_json::Value) -> Result<(), String> {
+    let mut errors = Vec::new();
+    let Some(root) = value.as_object() else {
+        return Err("the response must be a JSON object with a 'commits' array".to_string());
+    };
+    check_fields(root, "", &["commits"], &mut errors);
+    match root.get("commits") {
+        None => errors.push("field 'commits' is missing".to_string()),
+        Some(serde_json::Value::Array(commits)) if commits.is_empty() => {
+            errors.push("field 'commits' is empty".to_string())
+        }
+        Some(serde_json::Value::Array(commits)) => {
+            for (i, commit) in commits.iter().enumerate() {
+                validate_commit(commit, &format!("commits[{}]", i), &mut errors);
+            }
+        }
+        Some(_) => errors.push("field 'commits' must be an array".to_string()),
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn validate_commit(value: &serde_json::Value, path: &str, errors: &mut Vec<String>) {
+    let Some(commit) = value.as_object() else {
+        errors.push(format!("field '{}' must be an object", path));
+        return;
+    };
+    check_fields(
+        commit,
+        path,
+        &[
+            "short_description",
+            "short",
+            "long_description",
+            "long",
+            "rationale",
+            "changes",
+        ],
+        errors,
+    );
+    require_string(commit, path, &["short_description", "short"], true, errors);
+    require_string(commit, path, &["long_description", "long"], false, errors);
+    if let Some(rationale) = commit.get("rationale") {
+        if !rationale.is_string() && !rationale.is_null() {
+            errors.push(format!("field '{}.rationale' must be a string", path));
+        }
+    }
+
+    let changes_path = format!("{}.changes", path);
+    match commit.get("changes") {
+        None => errors.push(format!("field '{}' is missing", changes_path)),
+        Some(serde_json::Value::Array(changes)) if changes.is_empty() => {
+            errors.push(format!("field '{}' is empty", changes_path))
+        }
+        Some(serde_json::Value::Array(changes)) => {
+            for (i, change) in changes.iter().enumerate() {
+                validate_change(change, &format!("{}[{}]", changes_path, i), errors);
+            }
+        }
+        Some(_) => errors.push(format!("field '{}' must be an array", changes_path)),
+    }
+}
+
+fn validate_change(value: &serde_json::Value, path: &str, errors: &mut Vec<String>) {
+    let Some(change) = value.as_object() else {
+        errors.push(format!("field '{}' must be an object", path));
+        return;
+    };
+    let kind = change.get("type").and_then(|t| t.as_str());
+    let allowed: &[&str] = match kind {
+        Some("hunk") => &["type", "id", "confidence"],
+        Some("partial") => &["type", "hunk_id", "lines", "confidence"],
+        Some("raw") => &["type", "file_path", "diff"],
+        Some(other) => {
+            errors.push(format!(
+                "field '{}.type' is '{}', expected 'hunk', 'partial' or 'raw'",
+                path, other
+            ));
+            return;
+        }
+        None => {
+            errors.push(format!("field '{}.type' is missing", path));
+            return;
+        }
+    };
+    check_fields(change, path, allowed, errors);
+
+    match kind {
+        Some("hunk") => require_index(change, path, "id", errors),
+        Some("partial") => {
+            require_index(change, path, "hunk_id", errors);
+            match change.get("lines") {
+                None => errors.push(format!("field '{}.lines' is missing", path)),
+                Some(serde_json::Value::Array(lines)) if lines.is_empty() => {
+                    errors.push(format!("field '{}.lines' is empty", path))
+                }
+                Some(serde_json::Value::Array(lines)) => {
+                    if lines.iter().any(|l| l.as_u64().is_none_or(|n| n == 0)) {
+                        errors.push(format!(
+                            "field '{}.lines' must hold 1-based line numbers",
+                            path
+                        ));
+                    }
+                }
+                Some(_) => errors.push(format!("field '{}.lines' must be an array", path)),
+            }
+        }
+        _ => {
+            require_string(change, path, &["file_path"], true, errors);
+            require_string(change, path, &["diff"], true, errors);
+        }
+    }
+
+    if let Some(confidence) = change.get("confidence").filter(|c| !c.is_null()) {
+        if confidence
+            .as_f64()
+            .is_none_or(|c| !(0.0..=1.0).contains(&c))
+        {
+            errors.push(format!(
+                "field '{}.confidence' must be a number from 0 to 1",
+                path
+            ));
+        }
+    }
+}
+
+/// Report keys of `object` not in `allowed`.
+fn check_fields(
+    object: &serde_json::Map<String, serde_json::Value>,
+    path: &str,
+    allowed: &[&str],
+    errors: &mut Vec<String>,
+) {
+    for key in object.keys() {
+        if !allowed.contains(&key.as_str()) {
+            errors.push(format!("field '{}' is not expected", join_path(path, key)));
+        }
+    }
+}
+
+/// Require a string under the first of `names` present (later names are
+/// aliases), and that it is not blank when `non_empty`.
+fn require_string(
+    object: &serde_json::Map<String, serde_json::Value>,
+    path: &str,
+    names: &[&str],
+    non_empty: bool,
+    errors: &mut Vec<String>,
+) {
+    let field = join_path(path, names[0]);
+    match names.iter().find_map(|name| object.get(*name)) {
+        None => errors.push(format!("field '{}' is missing", field)),
+        Some(serde_json::Value::String(s)) if non_empty && s.trim().is_empty() => {
+            errors.push(format!("field '{}' is empty", field))
+        }
+        Some(serde_json::Value::String(_)) => {}
+        Some(_) => errors.push(format!("field '{}' must be a string", field)),
+    }
+}
+
+fn require_index(
+    object: &serde_json::Map<String, serde_json::Value>,
+    path: &str,
+    name: &str,
+    errors: &mut Vec<String>,
+) {
+    let field = join_path(path, name);
+    match object.get(name) {
+        None => errors.push(format!("field '{}' is missing", field)),
+        Some(value) if value.as_u64().is_none() => {
+            errors.push(format!("field '{}' must be a non-negative integer", field))
+        }
+        Some(_) => {}
+    }
+}
+
+fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Short description prefix of the commit holding low-confidence changes.
+pub const UNCERTAIN_PREFIX: &str = "UNCERTAIN:";
+
+/// A change the model placed with less than the minimum confidence.
+pub struct UncertainChange {
+    pub change: PlannedChange,
+    /// Where the model wanted to put it
+    pub suggested: String,
+    pub confidence: f32,
+}
+
+/// Convert LlmCommits to PlannedCommits, processing Partial and Raw specs.
+///
+/// With `min_confidence`, changes placed less confidently than that are
+/// moved to a trailing "uncertain" commit for manual review; commits left
+/// empty by this are dropped.
 pub fn to_planned_commits(
     llm_commits: Vec<LlmCommit>,
     hunks: &[Hunk],
+    min_confidence: Option<f32>,
 ) -> Result<Vec<PlannedCommit>, LlmError> {
     let mut next_hunk_id = hunks.iter().map(|h| h.id.0).max().unwrap_or(0) + 1;
+    let mut uncertain = Vec::new();
+
+    let mut commits = Vec::with_capacity(llm_commits.len());
+    for llm_commit in llm_commits {
+        let had_changes = !llm_commit.changes.is_empty();
+        let mut changes = Vec::with_capacity(llm_commit.changes.len());
+        for spec in llm_commit.changes {
+            let confidence = spec.confidence();
+            let change = match spec {
+                ChangeSpec::Hunk { id, .. } => PlannedChange::ExistingHunk(HunkId(id)),
+                ChangeSpec::Partial { hunk_id, lines, .. } => {
+                    let source = hunks
+                        .iter()
+                        .find(|h| h.id.0 == hunk_id)
+                        .ok_or(LlmError::InvalidId(hunk_id))?;
+                    let new_hunk = extract_partial_hunk(source, &lines, next_hunk_id)?;
+                    next_hunk_id += 1;
+                    PlannedChange::NewHunk(new_hunk)
+                }
+                ChangeSpec::Raw { file_path, diff } => {
+                    let new_hunk = parse_raw_diff(&file_path, &diff, next_hunk_id)?;
+                    next_hunk_id += 1;
+                    PlannedChange::NewHunk(new_hunk)
+                }
+            };
+            if min_confidence.is_some_and(|min| confidence < min) {
+                uncertain.push(UncertainChange {
+                    change,
+                    suggested: llm_commit.description.short.clone(),
+                    confidence,
+                });
+            } else {
+                changes.push(change);
+            }
+        }
+        if had_changes && changes.is_empty() {
+            continue;
+        }
+
+        let commit = PlannedCommit::new(
+            PlannedCommitId(commits.len()),
+            llm_commit.description,
+            changes,
+        );
+        commits.push(match llm_commit.rationale {
+            Some(rationale) if !rationale.trim().is_empty() => {
+                commit.with_rationale(rationale.trim())
+            }
+            _ => commit,
+        });
+    }
+
+    if let Some(min) = min_confidence {
+        add_uncertain(&mut commits, uncertain, min);
+    }
+    Ok(commits)
+}
 
-    llm_commits
+/// Append `changes` to the uncertain commit, creating it if needed, and keep
+/// that commit last. Several uncertain commits (e.g. one per chunk) are
+/// merged into one.
+pub fn add_uncertain(
+    commits: &mut Vec<PlannedCommit>,
+    changes: Vec<UncertainChange>,
+    min_confidence: f32,
+) {
+    let (mut existing, rest): (Vec<_>, Vec<_>) = std::mem::take(commits)
         .into_iter()
-        .enumerate()
-        .map(|(commit_idx, llm_commit)| {
-            let changes = llm_commit
-                .changes
-                .into_iter()
-                .map(|spec| -> Result<PlannedChange, LlmError> {
-                    match spec {
-                        ChangeSpec::Hunk { id } => Ok(PlannedChange::ExistingHunk(HunkId(id))),
-                        ChangeSpec::Partial { hunk_id, lines } => {
-                            let source = hunks
-                                .iter()
-                                .find(|h| h.id.0 == hunk_id)
-                                .ok_or(LlmError::InvalidId(hunk_id))?;
-                            let new_hunk = extract_partial_hunk(source, &lines, next_hunk_id)?;
-                            next_hunk_id += 1;
-                            Ok(PlannedChange::NewHunk(new_hunk))
-                        }
-                        ChangeSpec::Raw { file_path, diff } => {
-                            let new_hunk = parse_raw_diff(&file_path, &diff, next_hunk_id)?;
-                            next_hunk_id += 1;
-                            Ok(PlannedChange::NewHunk(new_hunk))
-                        }
-                    }
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-            Ok(PlannedCommit::new(
-                PlannedCommitId(commit_idx),
-                llm_commit.description,
-                changes,
+        .partition(|c| c.description.short.starts_with(UNCERTAIN_PREFIX));
+    *commits = rest;
+    let mut notes: Vec<String> = existing
+        .iter()
+        .flat_map(|c| c.description.long.lines())
+        .filter(|line| line.starts_with("- "))
+        .map(str::to_string)
+        .collect();
+
+    let mut commit = match existing.len() {
+        0 if changes.is_empty() => return,
+        0 => {
+            let id = commits.iter().map(|c| c.id.0 + 1).max().unwrap_or(0);
+            PlannedCommit::new(
+                PlannedCommitId(id),
+                CommitDescription::new("", ""),
+                Vec::new(),
+            )
+            .with_rationale(format!(
+                "The model's confidence in these placements was below {}",
+                min_confidence
             ))
-        })
-        .collect()
+        }
+        _ => {
+            let mut first = existing.remove(0);
+            for other in existing {
+                first.changes.extend(other.changes);
+            }
+            first
+        }
+    };
+
+    for item in changes {
+        notes.push(format!(
+            "- {} (suggested: '{}', confidence {:.2})",
+            describe_change(&item.change),
+            item.suggested,
+            item.confidence
+        ));
+        commit.changes.push(item.change);
+    }
+
+    let short = format!(
+        "{} review placement of {} change(s)",
+        UNCERTAIN_PREFIX,
+        commit.changes.len()
+    );
+    let long = format!(
+        "{}\n\nThe planner was unsure where these changes belong. Move them into\n\
+         the right commits before applying, or keep them here.\n\n{}",
+        short,
+        notes.join("\n")
+    );
+    commit.description = CommitDescription::new(short, long);
+    commits.push(commit);
+}
+
+fn describe_change(change: &PlannedChange) -> String {
+    match change {
+        PlannedChange::ExistingHunk(id) => format!("hunk {}", id.0),
+        PlannedChange::NewHunk(hunk) => {
+            format!("part of {} (hunk {})", hunk.file_path.display(), hunk.id.0)
+        }
+    }
 }
 
 fn extract_partial_hunk(
@@ -221,12 +533,57 @@ That's it!"#;
 
     #[test]
     fn test_extract_json_raw() {
-        let response = r#"{"commits": [{"short_description": "Test", "long_description": "Test", "changes": []}]}"#;
+        let response = r#"{"commits": [{"short_description": "Test", "long_description": "Test", "changes": [{"type": "hunk", "id": 0}]}]}"#;
 
         let commits = extract_json(response).unwrap();
         assert_eq!(commits.len(), 1);
     }
 
+    #[test]
+    fn test_validation_names_the_offending_fields() {
+        let response = r#"{"commits": [
+            {"short_description": "A", "long_description": "A", "changes": [{"type": "hunk", "id": 0}]},
+            {"short_description": "B", "long_description": "B", "changes": [{"type": "hunk", "id": 1}]},
+            {"short_description": "C", "long_description": "C"}
+        ]}"#;
+        let Err(LlmError::ValidationError(msg)) = extract_json(response) else {
+            panic!("missing changes should fail validation");
+        };
+        assert_eq!(msg, "field 'commits[2].changes' is missing");
+
+        let response =
+            r#"{"commits": [{"short_description": "", "long_description": "A", "changes": []}]}"#;
+        let Err(LlmError::ValidationError(msg)) = extract_json(response) else {
+            panic!("empty fields should fail validation");
+        };
+        assert_eq!(
+            msg,
+            "field 'commits[0].short_description' is empty; field 'commits[0].changes' is empty"
+        );
+
+        let response = r#"{"commits": [{"short_description": "A", "long_description": "A", "files": [],
+            "changes": [{"type": "partial", "hunk_id": 0, "lines": [0], "confidence": 2}]}], "notes": "x"}"#;
+        let Err(LlmError::ValidationError(msg)) = extract_json(response) else {
+            panic!("unexpected fields should fail validation");
+        };
+        assert!(msg.contains("field 'notes' is not expected"), "{}", msg);
+        assert!(
+            msg.contains("field 'commits[0].files' is not expected"),
+            "{}",
+            msg
+        );
+        assert!(
+            msg.contains("'commits[0].changes[0].lines' must hold 1-based"),
+            "{}",
+            msg
+        );
+        assert!(
+            msg.contains("'commits[0].changes[0].confidence'"),
+            "{}",
+            msg
+        );
+    }
+
     #[test]
     fn test_extract_json_no_json_found() {
         let response = "Running node v24.8.0 (npm v11.6.0)";
@@ -246,4 +603,127 @@ That's it!"#;
             );
         }
     }
+
+    #[test]
+    fn test_rationale_carried_to_planned_commit() {
+        let response = r#"{"commits": [
+            {"short_description": "A", "long_description": "A", "rationale": "Same feature", "changes": [{"type": "raw", "file_path": "a.rs", "diff": "+x"}]},
+            {"short_description": "B", "long_description": "B", "changes": [{"type": "raw", "file_path": "a.rs", "diff": "+x"}]}
+        ]}"#;
+
+        let planned = to_planned_commits(extract_json(response).unwrap(), &[], None).unwrap();
+        assert_eq!(planned[0].rationale.as_deref(), Some("Same feature"));
+        assert_eq!(planned[1].rationale, None);
+    }
+
+    #[test]
+    fn test_low_confidence_changes_move_to_trailing_uncertain_commit() {
+        let response = r#"{"commits": [
+            {"short_description": "A", "long_description": "A", "changes": [
+                {"type": "hunk", "id": 0, "confidence": 0.9},
+                {"type": "hunk", "id": 1, "confidence": 0.3}
+            ]},
+            {"short_description": "B", "long_description": "B", "changes": [
+                {"type": "hunk", "id": 2, "confidence": 0.2}
+            ]},
+            {"short_description": "C", "long_description": "C", "changes": [
+                {"type": "hunk", "id": 3}
+            ]}
+        ]}"#;
+
+        let planned = to_planned_commits(extract_json(response).unwrap(), &[], Some(0.5)).unwrap();
+        let shorts: Vec<&str> = planned
+            .iter()
+            .map(|c| c.description.short.as_str())
+            .collect();
+        assert_eq!(shorts[..2], ["A", "C"]);
+        let uncertain = planned.last().unwrap();
+        assert!(uncertain.description.short.starts_with(UNCERTAIN_PREFIX));
+        let uncertain_ids: Vec<usize> = uncertain
+            .changes
+            .iter()
+            .map(|c| match c {
+                PlannedChange::ExistingHunk(id) => id.0,
+                PlannedChange::NewHunk(h) => h.id.0,
+            })
+            .collect();
+        assert_eq!(uncertain_ids, vec![1, 2]);
+        assert!(uncertain.description.long.contains("suggested: 'B'"));
+        let ids: Vec<usize> = planned.iter().map(|c| c.id.0).collect();
+        assert_eq!(ids, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_confidence_ignored_without_threshold() {
+        let response = r#"{"commits": [
+            {"short_description": "A", "long_description": "A", "changes": [
+                {"type": "hunk", "id": 0, "confidence": 0.1}
+            ]}
+        ]}"#;
+
+        let planned = to_planned_commits(extract_json(response).unwrap(), &[], None).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].description.short, "A");
+    }
+
+    #[test]
+    fn test_add_uncertain_keeps_commit_last() {
+        let mut commits = vec![PlannedCommit::new(
+            PlannedCommitId(0),
+            CommitDescription::new("A", "A"),
+            vec![PlannedChange::ExistingHunk(HunkId(0))],
+        )];
+        let uncertain = |id| UncertainChange {
+            change: PlannedChange::ExistingHunk(HunkId(id)),
+            suggested: "A".to_string(),
+            confidence: 0.1,
+        };
+        add_uncertain(&mut commits, vec![uncertain(1)], 0.5);
+        commits.push(PlannedCommit::new(
+            PlannedCommitId(2),
+            CommitDescription::new("B", "B"),
+            vec![PlannedChange::ExistingHunk(HunkId(2))],
+        ));
+        add_uncertain(&mut commits, vec![uncertain(3)], 0.5);
+
+        assert_eq!(commits.len(), 3);
+        let last = commits.last().unwrap();
+        assert_eq!(last.changes.len(), 2);
+        assert!(last.description.short.contains("2 change(s)"));
+        assert!(last.description.long.contains("hunk 1 "));
+        assert!(last.description.long.contains("hunk 3 "));
+    }
+
+    #[test]
+    fn test_add_uncertain_merges_chunk_commits() {
+        let uncertain = |id, short: &str| {
+            let mut commits = Vec::new();
+            add_uncertain(
+                &mut commits,
+                vec![UncertainChange {
+                    change: PlannedChange::ExistingHunk(HunkId(id)),
+                    suggested: short.to_string(),
+                    confidence: 0.1,
+                }],
+                0.5,
+            );
+            commits.remove(0)
+        };
+        let mut commits = vec![
+            uncertain(0, "A"),
+            PlannedCommit::new(
+                PlannedCommitId(1),
+                CommitDescription::new("B", "B"),
+                vec![PlannedChange::ExistingHunk(HunkId(1))],
+            ),
+            uncertain(2, "C"),
+        ];
+
+        add_uncertain(&mut commits, Vec::new(), 0.5);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].description.short, "B");
+        assert_eq!(commits[1].changes.len(), 2);
+        assert!(commits[1].description.long.contains("suggested: 'A'"));
+        assert!(commits[1].description.long.contains("suggested: 'C'"));
+    }
 }
@@ -2,12 +2,24 @@
 
 use std::path::Path;
 
+use crate::llm::Redaction;
 use crate::models::{Hunk, SourceCommit};
-use crate::utils::format_diff_lines;
+use crate::utils::{format_word_diff, short_sha};
 
 use super::types::{CommitContext, HunkContext, LlmContext};
 
-pub fn build_context(source_commits: &[SourceCommit], hunks: &[Hunk]) -> LlmContext {
+/// Explains the notation of compact hunks, ahead of the hunks themselves.
+const COMPACT_DIFF_NOTE: &str = "Hunks are shown as word diffs. A line starting with `~` stands for one removed line followed by one added line: words removed are marked `[-like this-]` and words added `{+like this+}`, and a `~` line without markers only changed whitespace. Other lines use the usual ` `, `+` and `-` prefixes. Hunk line numbers are not visible in this form, so assign whole hunks rather than `partial` changes.\n\n";
+
+/// Everything prompts may show about `hunks`, with the files `redaction`
+/// matches reduced to placeholders. `compact_diff` renders the other hunks
+/// as word diffs, which is much shorter for reformatting.
+pub fn build_context(
+    source_commits: &[SourceCommit],
+    hunks: &[Hunk],
+    redaction: &Redaction,
+    compact_diff: bool,
+) -> LlmContext {
     let commit_contexts: Vec<CommitContext> = source_commits
         .iter()
         .map(|c| CommitContext {
@@ -19,10 +31,14 @@ pub fn build_context(source_commits: &[SourceCommit], hunks: &[Hunk]) -> LlmCont
         .iter()
         .map(|h| HunkContext {
             id: h.id.0,
-            file_path: h.file_path.to_string_lossy().to_string(),
+            file_path: redaction.path(&h.file_path),
             old_start: h.old_start,
             new_start: h.new_start,
-            diff_content: format_diff_lines(&h.lines),
+            diff_content: if compact_diff && !redaction.matches(&h.file_path) {
+                format_word_diff(&h.lines)
+            } else {
+                redaction.hunk_lines(h)
+            },
             source_commit_shas: h.likely_source_commits.clone(),
         })
         .collect();
@@ -30,6 +46,7 @@ pub fn build_context(source_commits: &[SourceCommit], hunks: &[Hunk]) -> LlmCont
     LlmContext {
         source_commits: commit_contexts,
         hunks: hunk_contexts,
+        compact_diff,
     }
 }
 
@@ -56,6 +73,7 @@ You must output a JSON object with the following structure:
     {
       "short_description": "Brief commit message (50 chars or less)",
       "long_description": "Detailed commit message explaining the change",
+      "rationale": "One sentence on why these changes belong together",
       "changes": [
         {"type": "hunk", "id": 0},
         {"type": "hunk", "id": 1}
@@ -73,6 +91,10 @@ Each change in a commit can be one of:
 2. `{"type": "partial", "hunk_id": N, "lines": [1, 2, 3]}` - Include only specific lines from hunk N (1-indexed)
 3. `{"type": "raw", "file_path": "path/to/file", "diff": "+new line\n-old line"}` - Raw diff content
 
+Hunk and partial changes may carry an optional `"confidence"` between 0 and 1
+(e.g. `{"type": "hunk", "id": N, "confidence": 0.4}`) when you are unsure which
+commit they belong to. Omit it when you are sure.
+
 ## Guidelines
 
 0. You should ALWAYS emphasise WHY a change was made, if that information was available.
@@ -95,12 +117,15 @@ Each change in a commit can be one of:
     for commit in &context.source_commits {
         prompt.push_str(&format!(
             "### Commit {}\n```\n{}\n```\n\n",
-            &commit.source_commit.sha[..8.min(commit.source_commit.sha.len())],
+            short_sha(&commit.source_commit.sha),
             commit.source_commit.message.long
         ));
     }
 
     prompt.push_str("## Hunks to Reorganize\n\n");
+    if context.compact_diff {
+        prompt.push_str(COMPACT_DIFF_NOTE);
+    }
 
     for hunk in &context.hunks {
         prompt.push_str(&format!("### Hunk {} - {}\n", hunk.id, hunk.file_path));
@@ -118,7 +143,7 @@ Each change in a commit can be one of:
                     .unwrap_or("(unknown)");
                 prompt.push_str(&format!(
                     "  - {} - {}\n",
-                    &sha[..8.min(sha.len())],
+                    short_sha(sha),
                     commit_msg.lines().next().unwrap_or("(no message)")
                 ));
             }
@@ -141,6 +166,20 @@ Analyze the hunks above and reorganize them into logical commits. Output ONLY va
     prompt
 }
 
+/// Add the reason the previous response was rejected to a plan prompt, so
+/// the model can correct exactly that. The note goes before the trailing
+/// JSON fence when the prompt ends with one.
+pub fn with_retry_feedback(prompt: &str, error: &str) -> String {
+    let note = format!(
+        "## Previous Attempt Rejected\n\nYour previous response was rejected: {}\n\nFix this and output the complete plan again.\n\n",
+        error
+    );
+    match prompt.strip_suffix("```json\n") {
+        Some(head) => format!("{}{}```json\n", head, note),
+        None => format!("{}\n{}", prompt, note),
+    }
+}
+
 /// Build the content for the hunks input file (file-based I/O mode).
 ///
 /// This extracts the hunks section that would normally be embedded in the prompt.
@@ -148,6 +187,9 @@ pub fn build_hunks_file_content(context: &LlmContext) -> String {
     let mut content = String::new();
 
     content.push_str("# Hunks to Reorganize\n\n");
+    if context.compact_diff {
+        content.push_str(COMPACT_DIFF_NOTE);
+    }
 
     for hunk in &context.hunks {
         content.push_str(&format!("## Hunk {} - {}\n", hunk.id, hunk.file_path));
@@ -165,7 +207,7 @@ pub fn build_hunks_file_content(context: &LlmContext) -> String {
                     .unwrap_or("(unknown)");
                 content.push_str(&format!(
                     "  - {} - {}\n",
-                    &sha[..8.min(sha.len())],
+                    short_sha(sha),
                     commit_msg.lines().next().unwrap_or("(no message)")
                 ));
             }
@@ -210,6 +252,7 @@ The JSON file should contain:
     {
       "short_description": "Brief commit message (50 chars or less)",
       "long_description": "Detailed commit message explaining the change",
+      "rationale": "One sentence on why these changes belong together",
       "changes": [
         {"type": "hunk", "id": 0},
         {"type": "hunk", "id": 1}
@@ -227,6 +270,10 @@ Each change in a commit can be one of:
 2. `{"type": "partial", "hunk_id": N, "lines": [1, 2, 3]}` - Include only specific lines from hunk N (1-indexed)
 3. `{"type": "raw", "file_path": "path/to/file", "diff": "+new line\n-old line"}` - Raw diff content
 
+Hunk and partial changes may carry an optional `"confidence"` between 0 and 1
+(e.g. `{"type": "hunk", "id": N, "confidence": 0.4}`) when you are unsure which
+commit they belong to. Omit it when you are sure.
+
 ## Guidelines
 
 0. You should ALWAYS emphasise WHY a change was made, if that information was available.
@@ -249,7 +296,7 @@ Each change in a commit can be one of:
     for commit in &context.source_commits {
         prompt.push_str(&format!(
             "### Commit {}\n```\n{}\n```\n\n",
-            &commit.source_commit.sha[..8.min(commit.source_commit.sha.len())],
+            short_sha(&commit.source_commit.sha),
             commit.source_commit.message.long
         ));
     }
@@ -319,7 +366,7 @@ Please assign the missing hunks to existing commits or create new commits for th
                         .unwrap_or("(unknown)");
                     prompt.push_str(&format!(
                         "  - {} - {}\n",
-                        &sha[..8.min(sha.len())],
+                        short_sha(sha),
                         commit_msg.lines().next().unwrap_or("(no message)")
                     ));
                 }
@@ -349,6 +396,8 @@ Output a JSON object with the assignments:
 }
 ```
 
+Add an optional `"confidence"` between 0 and 1 to an assignment when you are unsure.
+
 Output ONLY the JSON.
 
 ```json
@@ -516,6 +565,7 @@ pub fn build_fix_message_prompt(
     commit: &crate::models::PlannedCommit,
     assessment: &crate::assessment::types::CommitAssessment,
     hunks: &[crate::models::Hunk],
+    redaction: &Redaction,
 ) -> String {
     let mut prompt = String::new();
 
@@ -565,9 +615,9 @@ quality assessment feedback.
             if let Some(hunk) = hunks.iter().find(|h| h.id == *hunk_id) {
                 prompt.push_str(&format!(
                     "### {} (hunk {})\n```diff\n{}\n```\n\n",
-                    hunk.file_path.display(),
+                    redaction.path(&hunk.file_path),
                     hunk_id.0,
-                    format_diff_lines(&hunk.lines)
+                    redaction.hunk_lines(hunk)
                 ));
             }
         }
@@ -606,51 +656,55 @@ Output ONLY the JSON.
 /// Build a prompt for rewording an existing commit's message.
 ///
 /// This is similar to `build_fix_message_prompt` but works with SourceCommit (actual git commits)
-/// rather than PlannedCommit. Used by the `reword` command.
+/// rather than PlannedCommit. Used by the `reword` command and the reword strategy; the latter
+/// has no assessment, in which case the feedback section is omitted.
 pub fn build_reword_prompt(
     commit: &crate::models::SourceCommit,
-    assessment: &crate::assessment::types::CommitAssessment,
+    assessment: Option<&crate::assessment::types::CommitAssessment>,
     diff_content: &str,
 ) -> String {
     let mut prompt = String::new();
 
     prompt.push_str(
-        r#"You are a git commit message editor. Your task is to improve a commit message based on
-quality assessment feedback.
-
-## Current Commit
-
-"#,
+        r#"You are a git commit message editor. Your task is to improve a commit message"#,
     );
+    if assessment.is_some() {
+        prompt.push_str(" based on\nquality assessment feedback.");
+    } else {
+        prompt.push_str(" so it\naccurately describes the changes.");
+    }
+    prompt.push_str("\n\n## Current Commit\n\n");
 
     prompt.push_str(&format!(
         "**SHA**: {}\n\n**Short message**: {}\n\n**Long message**:\n```\n{}\n```\n\n",
-        &commit.sha[..8.min(commit.sha.len())],
+        short_sha(&commit.sha),
         commit.message.short,
         commit.message.long
     ));
 
-    // Show the assessment feedback
-    prompt.push_str("## Assessment Feedback\n\n");
-    prompt.push_str(&format!(
-        "**Overall Score**: {:.1}%\n\n",
-        assessment.overall_score * 100.0
-    ));
-
-    for score in &assessment.criterion_scores {
+    if let Some(assessment) = assessment {
+        // Show the assessment feedback
+        prompt.push_str("## Assessment Feedback\n\n");
         prompt.push_str(&format!(
-            "### {} (Level {}/5)\n\n**Rationale**: {}\n\n",
-            score.criterion_id.name(),
-            score.level,
-            score.rationale
+            "**Overall Score**: {:.1}%\n\n",
+            assessment.overall_score * 100.0
         ));
 
-        if !score.suggestions.is_empty() {
-            prompt.push_str("**Suggestions**:\n");
-            for suggestion in &score.suggestions {
-                prompt.push_str(&format!("- {}\n", suggestion));
+        for score in &assessment.criterion_scores {
+            prompt.push_str(&format!(
+                "### {} (Level {}/5)\n\n**Rationale**: {}\n\n",
+                score.criterion_id.name(),
+                score.level,
+                score.rationale
+            ));
+
+            if !score.suggestions.is_empty() {
+                prompt.push_str("**Suggestions**:\n");
+                for suggestion in &score.suggestions {
+                    prompt.push_str(&format!("- {}\n", suggestion));
+                }
+                prompt.push('\n');
             }
-            prompt.push('\n');
         }
     }
 
@@ -659,16 +713,30 @@ quality assessment feedback.
     prompt.push_str(diff_content);
     prompt.push_str("\n```\n\n");
 
-    prompt.push_str(
-        r#"## Your Task
-
-Rewrite the commit message to address the assessment feedback. The improved message should:
+    prompt.push_str("## Your Task\n\n");
+    if assessment.is_some() {
+        prompt.push_str(
+            "Rewrite the commit message to address the assessment feedback. The improved message should:\n",
+        );
+    } else {
+        prompt.push_str(
+            "Rewrite the commit message to describe the changes above. The improved message should:\n",
+        );
+    }
 
+    prompt.push_str(
+        r#"
 1. Be clear and descriptive (short message ≤ 50 chars)
 2. Explain WHY the change was made, not just WHAT changed
 3. Provide context for reviewers and future maintainers
-4. Address any specific suggestions from the assessment
+"#,
+    );
+    if assessment.is_some() {
+        prompt.push_str("4. Address any specific suggestions from the assessment\n");
+    }
 
+    prompt.push_str(
+        r#"
 Output a JSON object with the improved message:
 
 ```json
@@ -710,10 +778,54 @@ mod tests {
             vec!["abc123".to_string()],
         )];
 
-        let context = build_context(&commits, &hunks);
+        let context = build_context(&commits, &hunks, &Redaction::default(), false);
         assert_eq!(context.source_commits.len(), 1);
         assert_eq!(context.hunks.len(), 1);
         assert_eq!(context.hunks[0].id, 0);
         assert!(context.hunks[0].diff_content.contains("+    println!"));
+
+        let redaction = Redaction::new(&["main.rs".to_string()]).unwrap();
+        let prompt = build_prompt(&build_context(&commits, &hunks, &redaction, true));
+        assert!(!prompt.contains("println!"));
+        assert!(prompt.contains("[redacted: 1 lines in src/main.rs]"));
+    }
+
+    #[test]
+    fn test_compact_diff_shrinks_whitespace_heavy_prompts() {
+        let commits = vec![make_source_commit("abc123", "Reindent")];
+        let mut lines = vec![DiffLine::Context("impl Parser {".to_string())];
+        for i in 0..40 {
+            lines.push(DiffLine::Removed(format!(
+                "  let value_{} = input.next()?;",
+                i
+            )));
+        }
+        for i in 0..40 {
+            lines.push(DiffLine::Added(format!(
+                "        let value_{} = input.next()?;",
+                i
+            )));
+        }
+        let hunks = vec![make_hunk_full(0, "src/parser.rs", lines, vec![])];
+
+        let full_context = build_context(&commits, &hunks, &Redaction::default(), false);
+        let compact_context = build_context(&commits, &hunks, &Redaction::default(), true);
+        let full = build_prompt(&full_context);
+        let compact = build_prompt(&compact_context);
+
+        assert!(compact.contains(COMPACT_DIFF_NOTE));
+        assert!(compact.contains("~        let value_7 = input.next()?;"));
+        assert!(!full.contains(COMPACT_DIFF_NOTE));
+        assert!(compact.len() < full.len());
+        let (full_diff, compact_diff) = (
+            full_context.hunks[0].diff_content.len(),
+            compact_context.hunks[0].diff_content.len(),
+        );
+        assert!(
+            compact_diff * 5 < full_diff * 3,
+            "compact {} vs full {}",
+            compact_diff,
+            full_diff
+        );
     }
 }
@@ -27,6 +27,8 @@ pub struct HunkContext {
 pub struct LlmContext {
     pub source_commits: Vec<CommitContext>,
     pub hunks: Vec<HunkContext>,
+    /// Hunk diffs are word diffs (see [`crate::utils::format_word_diff`])
+    pub compact_diff: bool,
 }
 
 /// A commit planned by the LLM
@@ -35,26 +37,51 @@ pub struct LlmCommit {
     #[serde(flatten)]
     pub description: crate::models::CommitDescription,
     pub changes: Vec<ChangeSpec>,
+    /// One-line explanation of why these changes belong together
+    #[serde(default)]
+    pub rationale: Option<String>,
 }
 
 /// Specification for a change in a commit
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "type")]
+#[serde(tag = "type", deny_unknown_fields)]
 pub enum ChangeSpec {
     /// Use an entire existing hunk
     #[serde(rename = "hunk")]
-    Hunk { id: usize },
+    Hunk {
+        id: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        confidence: Option<f32>,
+    },
 
     /// Use specific lines from a hunk (for splitting)
     /// Lines are 1-indexed and refer to the diff lines (the +/- lines)
     #[serde(rename = "partial")]
-    Partial { hunk_id: usize, lines: Vec<usize> },
+    Partial {
+        hunk_id: usize,
+        lines: Vec<usize>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        confidence: Option<f32>,
+    },
 
     /// Raw diff content (for complex merges or LLM-generated changes)
     #[serde(rename = "raw")]
     Raw { file_path: String, diff: String },
 }
 
+impl ChangeSpec {
+    /// How sure the model was about this placement, from 0 to 1.
+    /// Missing confidence counts as fully confident.
+    pub fn confidence(&self) -> f32 {
+        match self {
+            ChangeSpec::Hunk { confidence, .. } | ChangeSpec::Partial { confidence, .. } => {
+                confidence.unwrap_or(1.0)
+            }
+            ChangeSpec::Raw { .. } => 1.0,
+        }
+    }
+}
+
 /// Response for fixing unassigned hunks
 #[derive(Debug, Clone, Deserialize)]
 pub struct FixUnassignedResponse {
@@ -70,6 +97,8 @@ pub enum HunkAssignment {
     AddToExisting {
         hunk_id: usize,
         commit_description: String,
+        #[serde(default)]
+        confidence: Option<f32>,
     },
     /// Create a new commit for this hunk
     #[serde(rename = "new_commit")]
@@ -77,9 +106,28 @@ pub enum HunkAssignment {
         hunk_id: usize,
         short_description: String,
         long_description: String,
+        #[serde(default)]
+        confidence: Option<f32>,
     },
 }
 
+impl HunkAssignment {
+    pub fn hunk_id(&self) -> usize {
+        match self {
+            HunkAssignment::AddToExisting { hunk_id, .. }
+            | HunkAssignment::NewCommit { hunk_id, .. } => *hunk_id,
+        }
+    }
+
+    /// Same convention as [`ChangeSpec::confidence`].
+    pub fn confidence(&self) -> f32 {
+        match self {
+            HunkAssignment::AddToExisting { confidence, .. }
+            | HunkAssignment::NewCommit { confidence, .. } => confidence.unwrap_or(1.0),
+        }
+    }
+}
+
 /// Response for fixing duplicate hunk assignments
 #[derive(Debug, Clone, Deserialize)]
 pub struct FixDuplicateResponse {
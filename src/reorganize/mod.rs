@@ -1,20 +1,31 @@
 mod absorb;
+mod budget;
 mod by_file;
+mod clean;
+mod comment_hint;
 pub mod hierarchical;
 pub mod llm;
 mod preserve;
+mod reword;
+mod rule_based;
 mod squash;
 
 pub use absorb::Absorb;
+pub use budget::Deadline;
 pub use by_file::GroupByFile;
+pub use clean::{fold_commits, CleanPreserve, Disposition};
+pub use comment_hint::{hint_in_hunk, hint_in_line, strip_hints, CommentHint, DEFAULT_HINT_MARKER};
 pub use hierarchical::{HierarchicalConfig, HierarchicalReorganizer};
 pub use llm::LlmReorganizer;
 pub use preserve::PreserveOriginal;
+pub use reword::RewordOnly;
+pub use rule_based::{BucketRule, RuleBased, RuleConfig, RuleConfigError, RULES_FILE};
 pub use squash::Squash;
 
 use crate::git::GitOps;
 use crate::models::{Hunk, PlannedCommit, SourceCommit};
-use crate::validation::ValidationResult;
+use crate::patch::{self, ParseError, Patch};
+use crate::validation::{apply_deterministic_fixes, ValidationResult};
 
 /// Errors from reorganization
 #[derive(Debug, thiserror::Error)]
@@ -25,6 +36,15 @@ pub enum ReorganizeError {
     Failed(String),
     #[error("Invalid plan: {0}")]
     InvalidPlan(String),
+    #[error("Invalid diff: {0}")]
+    InvalidDiff(#[from] ParseError),
+    #[error(
+        "{count} hunks is more than the llm strategy sends in one prompt (limit {limit}); \
+         a prompt that size usually times out or comes back truncated. \
+         Use --strategy hierarchical, which plans in small batches, \
+         split the prompt with --llm-chunk-size, or raise --llm-max-hunks"
+    )]
+    TooManyHunks { count: usize, limit: usize },
 }
 
 impl From<hierarchical::HierarchicalError> for ReorganizeError {
@@ -73,6 +93,18 @@ pub trait Reorganizer {
         self.plan(source_commits, hunks)
     }
 
+    /// Whether `fix_plan` repairs the plan it is given rather than replanning
+    /// from scratch, which would discard edits to a saved plan.
+    fn fixes_in_place(&self) -> bool {
+        false
+    }
+
+    /// Whether planning calls an LLM, so callers can check the provider is
+    /// usable before doing any git work.
+    fn requires_llm(&self) -> bool {
+        false
+    }
+
     /// Apply the strategy. Returns whether to continue with default execution.
     fn apply(
         &self,
@@ -85,3 +117,159 @@ pub trait Reorganizer {
     /// Human-readable name for this strategy
     fn name(&self) -> &'static str;
 }
+
+/// Strategy-agnostic plan repair: drop repeated hunk assignments (keeping the
+/// first), collect unassigned hunks into a trailing commit, and fix creation
+/// order. Works on any plan, including hand-edited ones.
+pub fn repair_plan(commits: Vec<PlannedCommit>, hunks: &[Hunk]) -> Vec<PlannedCommit> {
+    apply_deterministic_fixes(commits, hunks)
+}
+
+/// Pseudo source commit that hunks from [`plan_from_diff`] are attributed to.
+pub const DIFF_SOURCE_SHA: &str = "diff";
+
+/// A plan built straight from a diff, without a repository.
+#[derive(Debug)]
+pub struct DiffPlan {
+    /// The parsed diff; `file_changes` is needed to apply mode changes,
+    /// binary files and empty new files
+    pub patch: Patch,
+    pub commits: Vec<PlannedCommit>,
+}
+
+/// Parse `diff` and plan it with `reorganizer`, for callers that already
+/// have a diff and don't want to drive git.
+///
+/// Every hunk is attributed to a single pseudo source commit
+/// ([`DIFF_SOURCE_SHA`], message "Apply diff"), so `preserve` yields one
+/// commit.
+///
+/// ```
+/// use git_reabsorb::reorganize::{plan_from_diff, GroupByFile};
+///
+/// let diff = "diff --git a/a.txt b/a.txt
+/// --- a/a.txt
+/// +++ b/a.txt
+/// @@ -1 +1 @@
+/// -old
+/// +new
+/// ";
+/// let plan = plan_from_diff(diff, &GroupByFile).unwrap();
+/// assert_eq!(plan.commits.len(), 1);
+/// assert_eq!(plan.patch.hunks.len(), 1);
+/// ```
+pub fn plan_from_diff(
+    diff: &str,
+    reorganizer: &dyn Reorganizer,
+) -> Result<DiffPlan, ReorganizeError> {
+    let patch = patch::parse(diff, &[DIFF_SOURCE_SHA.to_string()], 0)?;
+    let source = SourceCommit::new(DIFF_SOURCE_SHA, "Apply diff", "Apply diff");
+    let commits = reorganizer.plan(&[source], &patch.hunks)?;
+    Ok(DiffPlan { patch, commits })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CommitDescription, HunkId, PlannedChange, PlannedCommitId};
+    use crate::test_utils::make_hunk_in_file;
+
+    fn commit(id: usize, hunk_ids: &[usize]) -> PlannedCommit {
+        PlannedCommit::from_hunk_ids(
+            PlannedCommitId(id),
+            CommitDescription::short_only(format!("Commit {}", id)),
+            hunk_ids.iter().copied().map(HunkId).collect(),
+        )
+    }
+
+    fn hunk_ids(commit: &PlannedCommit) -> Vec<usize> {
+        commit
+            .changes
+            .iter()
+            .filter_map(|c| match c {
+                PlannedChange::ExistingHunk(id) => Some(id.0),
+                PlannedChange::NewHunk(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn repair_plan_keeps_first_of_duplicate_hunks() {
+        let hunks = vec![make_hunk_in_file(0, "a.rs"), make_hunk_in_file(1, "b.rs")];
+        let repaired = repair_plan(vec![commit(0, &[0, 1]), commit(1, &[1])], &hunks);
+
+        assert_eq!(repaired.len(), 1);
+        assert_eq!(hunk_ids(&repaired[0]), vec![0, 1]);
+    }
+
+    #[test]
+    fn repair_plan_collects_orphans_in_trailing_commit() {
+        let hunks = vec![make_hunk_in_file(0, "a.rs"), make_hunk_in_file(1, "b.rs")];
+        let repaired = repair_plan(vec![commit(0, &[0])], &hunks);
+
+        assert_eq!(repaired.len(), 2);
+        assert_eq!(hunk_ids(&repaired[0]), vec![0]);
+        assert_eq!(hunk_ids(&repaired[1]), vec![1]);
+    }
+
+    const MULTI_FILE_DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,2 +1,3 @@
+ pub mod a;
++pub mod b;
+ pub mod c;
+diff --git a/src/b.rs b/src/b.rs
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/src/b.rs
+@@ -0,0 +1 @@
++pub fn b() {}
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+";
+
+    #[test]
+    fn plan_from_diff_plans_every_hunk() {
+        let plan = plan_from_diff(MULTI_FILE_DIFF, &GroupByFile).unwrap();
+
+        assert_eq!(plan.patch.hunks.len(), 2);
+        assert_eq!(plan.commits.len(), 2);
+        let mut planned: Vec<usize> = plan.commits.iter().flat_map(hunk_ids).collect();
+        planned.sort();
+        assert_eq!(planned, vec![0, 1]);
+
+        // Mode-only and new files come through as file changes
+        let run_sh = plan
+            .patch
+            .file_changes
+            .iter()
+            .find(|fc| fc.file_path.ends_with("run.sh"))
+            .unwrap();
+        assert_eq!(run_sh.new_mode.as_deref(), Some("100755"));
+        assert!(plan
+            .patch
+            .file_changes
+            .iter()
+            .any(|fc| fc.file_path.ends_with("b.rs")));
+    }
+
+    #[test]
+    fn plan_from_diff_preserve_makes_one_commit() {
+        let plan = plan_from_diff(MULTI_FILE_DIFF, &PreserveOriginal).unwrap();
+        assert_eq!(plan.commits.len(), 1);
+        assert_eq!(plan.commits[0].description.short, "Apply diff");
+    }
+
+    #[test]
+    fn plan_from_diff_rejects_bad_hunk_header() {
+        let diff = "diff --git a/a b/a\n--- a/a\n+++ b/a\n@@ nonsense @@\n+x\n";
+        assert!(matches!(
+            plan_from_diff(diff, &Squash),
+            Err(ReorganizeError::InvalidDiff(_))
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+use log::{info, warn};
+
+use crate::llm::{LlmClient, Redaction};
+use crate::models::{Hunk, PlannedCommit, SourceCommit};
+use crate::reorganize::llm::{build_reword_prompt, FixMessageResponse};
+use crate::reorganize::{PreserveOriginal, ReorganizeError, Reorganizer};
+use crate::utils::{extract_json_str, short_sha};
+
+/// Keeps the original commit boundaries but rewrites each message via LLM.
+///
+/// Hunk grouping is delegated to `PreserveOriginal`, so the structure of the
+/// plan is identical; only descriptions change. Commits whose reword fails
+/// keep their original message.
+pub struct RewordOnly {
+    client: Box<dyn LlmClient>,
+    redaction: Redaction,
+}
+
+impl RewordOnly {
+    pub fn new(client: Box<dyn LlmClient>) -> Self {
+        Self {
+            client,
+            redaction: Redaction::default(),
+        }
+    }
+
+    /// Show the files `redaction` matches as placeholders in prompts.
+    pub fn with_redaction(mut self, redaction: Redaction) -> Self {
+        self.redaction = redaction;
+        self
+    }
+}
+
+/// Ask `client` for a new message for `commit` from its diff. Returns whether
+/// the message was replaced; on failure the original is kept.
+pub(super) fn reword_commit(
+    client: &dyn LlmClient,
+    source: &SourceCommit,
+    commit: &mut PlannedCommit,
+    hunks: &[Hunk],
+    redaction: &Redaction,
+) -> bool {
+    let diff_content = commit
+        .changes
+        .iter()
+        .filter_map(|change| change.resolve(hunks))
+        .map(|h| redaction.hunk_patch(h))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let prompt = build_reword_prompt(source, None, &diff_content);
+    let response = match client.complete(&prompt) {
+        Ok(response) => response,
+        Err(e) => {
+            warn!("Failed to reword commit {}: {}", short_sha(&source.sha), e);
+            return false;
+        }
+    };
+
+    match extract_json_str(&response)
+        .and_then(|json| serde_json::from_str::<FixMessageResponse>(json).ok())
+    {
+        Some(fix) if !fix.description.short.trim().is_empty() => {
+            commit.description = fix.description;
+            true
+        }
+        _ => {
+            warn!(
+                "Could not parse reworded message for {}, keeping original",
+                short_sha(&source.sha)
+            );
+            false
+        }
+    }
+}
+
+impl Reorganizer for RewordOnly {
+    fn plan(
+        &self,
+        source_commits: &[SourceCommit],
+        hunks: &[Hunk],
+    ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
+        let mut planned = PreserveOriginal.plan(source_commits, hunks)?;
+
+        let total = planned.len();
+        for (i, commit) in planned.iter_mut().enumerate() {
+            // PreserveOriginal numbers planned commits by source commit index
+            let Some(source) = source_commits.get(commit.id.0) else {
+                continue;
+            };
+            info!("Rewording {}/{}: {}", i + 1, total, source.message.short);
+            reword_commit(self.client.as_ref(), source, commit, hunks, &self.redaction);
+        }
+
+        Ok(planned)
+    }
+
+    fn requires_llm(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> &'static str {
+        "reword"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::test_support::MockLlmClient;
+    use crate::test_utils::make_hunk_with_source;
+
+    #[test]
+    fn test_reword_keeps_structure() {
+        let commits = vec![
+            SourceCommit::new("abc", "wip", "wip"),
+            SourceCommit::new("def", "fix", "fix"),
+        ];
+        let hunks = vec![
+            make_hunk_with_source(0, "a.rs", vec!["abc".to_string()]),
+            make_hunk_with_source(1, "b.rs", vec!["def".to_string()]),
+        ];
+
+        let client = MockLlmClient::new(
+            r#"{"description": {"short": "Add parser", "long": "Add parser\n\nDetails"}}"#,
+        );
+        let planned = RewordOnly::new(Box::new(client))
+            .plan(&commits, &hunks)
+            .unwrap();
+
+        let preserved = PreserveOriginal.plan(&commits, &hunks).unwrap();
+        assert_eq!(planned.len(), preserved.len());
+        for (reworded, original) in planned.iter().zip(&preserved) {
+            assert_eq!(reworded.changes.len(), original.changes.len());
+            assert_eq!(reworded.description.short, "Add parser");
+        }
+    }
+
+    #[test]
+    fn test_reword_falls_back_on_bad_response() {
+        let commits = vec![SourceCommit::new("abc", "wip", "wip")];
+        let hunks = vec![make_hunk_with_source(0, "a.rs", vec!["abc".to_string()])];
+
+        let client = MockLlmClient::new("not json");
+        let planned = RewordOnly::new(Box::new(client))
+            .plan(&commits, &hunks)
+            .unwrap();
+
+        assert_eq!(planned[0].description.short, "wip");
+    }
+}
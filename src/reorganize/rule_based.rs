@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern};
+use serde::Deserialize;
+
+use crate::models::{
+    CommitDescription, Hunk, HunkId, PlannedCommit, PlannedCommitId, SourceCommit,
+};
+use crate::reorganize::{ReorganizeError, Reorganizer};
+
+/// Where the rules strategy looks for its config, relative to the repo root.
+pub const RULES_FILE: &str = ".reabsorb.toml";
+
+const DEFAULT_BUCKET: &str = "other";
+
+/// Errors loading or compiling bucket rules
+#[derive(Debug, thiserror::Error)]
+pub enum RuleConfigError {
+    #[error("Could not read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid rules config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid pattern '{pattern}': {source}")]
+    BadPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+}
+
+/// Ordered glob → bucket rules, as written in `.reabsorb.toml`:
+///
+/// ```toml
+/// default_bucket = "misc"
+///
+/// [[rules]]
+/// pattern = "migrations/**"
+/// bucket = "migrations"
+///
+/// [[rules]]
+/// pattern = "*.md"
+/// bucket = "docs"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuleConfig {
+    /// Bucket for hunks that match no rule
+    #[serde(default = "default_bucket")]
+    pub default_bucket: String,
+    #[serde(default)]
+    pub rules: Vec<BucketRule>,
+}
+
+fn default_bucket() -> String {
+    DEFAULT_BUCKET.to_string()
+}
+
+/// A single rule. Patterns without a `/` match the file name anywhere in the
+/// tree (like `.gitignore`); others match the whole repo-relative path.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BucketRule {
+    pub pattern: String,
+    pub bucket: String,
+}
+
+impl RuleConfig {
+    pub fn from_toml(content: &str) -> Result<Self, RuleConfigError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, RuleConfigError> {
+        let content = fs::read_to_string(path).map_err(|source| RuleConfigError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_toml(&content)
+    }
+}
+
+struct CompiledRule {
+    pattern: Pattern,
+    file_name_only: bool,
+    bucket: String,
+}
+
+impl CompiledRule {
+    fn matches(&self, path: &Path) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        if self.file_name_only {
+            path.file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), options))
+        } else {
+            self.pattern.matches_path_with(path, options)
+        }
+    }
+}
+
+enum RuleSource {
+    File(PathBuf),
+    Config(RuleConfig),
+}
+
+/// Assigns each hunk to the bucket of the first rule matching its file and
+/// makes one commit per bucket, in rule order with the default bucket last.
+/// Deterministic and offline.
+pub struct RuleBased {
+    source: RuleSource,
+}
+
+impl RuleBased {
+    pub fn new(config: RuleConfig) -> Self {
+        Self {
+            source: RuleSource::Config(config),
+        }
+    }
+
+    /// Read rules from `path` when planning, so a missing or broken file
+    /// surfaces as a planning error.
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: RuleSource::File(path.into()),
+        }
+    }
+
+    fn config(&self) -> Result<RuleConfig, RuleConfigError> {
+        match &self.source {
+            RuleSource::File(path) => RuleConfig::load(path),
+            RuleSource::Config(config) => Ok(config.clone()),
+        }
+    }
+}
+
+fn compile(config: &RuleConfig) -> Result<Vec<CompiledRule>, RuleConfigError> {
+    config
+        .rules
+        .iter()
+        .map(|rule| {
+            let pattern =
+                Pattern::new(&rule.pattern).map_err(|source| RuleConfigError::BadPattern {
+                    pattern: rule.pattern.clone(),
+                    source,
+                })?;
+            Ok(CompiledRule {
+                pattern,
+                file_name_only: !rule.pattern.contains('/'),
+                bucket: rule.bucket.clone(),
+            })
+        })
+        .collect()
+}
+
+impl Reorganizer for RuleBased {
+    fn plan(
+        &self,
+        _source_commits: &[SourceCommit],
+        hunks: &[Hunk],
+    ) -> Result<Vec<PlannedCommit>, ReorganizeError> {
+        if hunks.is_empty() {
+            return Err(ReorganizeError::NoHunks);
+        }
+        let config = self
+            .config()
+            .map_err(|e| ReorganizeError::Failed(e.to_string()))?;
+        let rules = compile(&config).map_err(|e| ReorganizeError::Failed(e.to_string()))?;
+
+        // Bucket order: first appearance in the rules, default bucket last
+        let mut order: Vec<&str> = Vec::new();
+        for rule in &rules {
+            if rule.bucket != config.default_bucket && !order.contains(&rule.bucket.as_str()) {
+                order.push(&rule.bucket);
+            }
+        }
+        order.push(&config.default_bucket);
+
+        let mut buckets: HashMap<&str, Vec<&Hunk>> = HashMap::new();
+        for hunk in hunks {
+            let bucket = rules
+                .iter()
+                .find(|rule| rule.matches(&hunk.file_path))
+                .map_or(config.default_bucket.as_str(), |rule| rule.bucket.as_str());
+            buckets.entry(bucket).or_default().push(hunk);
+        }
+
+        let mut planned = Vec::new();
+        for bucket in order {
+            let Some(bucket_hunks) = buckets.remove(bucket) else {
+                continue;
+            };
+            let mut files: Vec<String> = bucket_hunks
+                .iter()
+                .map(|h| h.file_path.display().to_string())
+                .collect();
+            files.sort();
+            files.dedup();
+
+            let short = format!("Update {}", bucket);
+            let long = format!("{}\n\nFiles:\n- {}", short, files.join("\n- "));
+            let hunk_ids: Vec<HunkId> = bucket_hunks.iter().map(|h| h.id).collect();
+            planned.push(PlannedCommit::from_hunk_ids(
+                PlannedCommitId(planned.len()),
+                CommitDescription::new(short, long),
+                hunk_ids,
+            ));
+        }
+
+        Ok(planned)
+    }
+
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PlannedChange;
+    use crate::test_utils::{make_hunk_in_file, make_source_commit};
+
+    const CONFIG: &str = r#"
+default_bucket = "misc"
+
+[[rules]]
+pattern = "migrations/**"
+bucket = "migrations"
+
+[[rules]]
+pattern = "*.md"
+bucket = "docs"
+
+[[rules]]
+pattern = "tests/**"
+bucket = "tests"
+"#;
+
+    fn bucket_of(planned: &[PlannedCommit], hunk: usize) -> String {
+        planned
+            .iter()
+            .find(|c| {
+                c.changes
+                    .iter()
+                    .any(|change| matches!(change, PlannedChange::ExistingHunk(id) if id.0 == hunk))
+            })
+            .map(|c| c.description.short.clone())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let commits = vec![make_source_commit("abc", "Original")];
+        let hunks = vec![
+            make_hunk_in_file(0, "src/main.rs"),
+            make_hunk_in_file(1, "migrations/README.md"),
+            make_hunk_in_file(2, "tests/notes.md"),
+            make_hunk_in_file(3, "tests/it.rs"),
+            make_hunk_in_file(4, "docs/guide.md"),
+        ];
+
+        let config = RuleConfig::from_toml(CONFIG).unwrap();
+        let planned = RuleBased::new(config).plan(&commits, &hunks).unwrap();
+
+        // migrations/** comes before *.md, and *.md before tests/**
+        assert_eq!(bucket_of(&planned, 1), "Update migrations");
+        assert_eq!(bucket_of(&planned, 2), "Update docs");
+        assert_eq!(bucket_of(&planned, 3), "Update tests");
+        assert_eq!(bucket_of(&planned, 4), "Update docs");
+        assert_eq!(bucket_of(&planned, 0), "Update misc");
+
+        let shorts: Vec<&str> = planned
+            .iter()
+            .map(|c| c.description.short.as_str())
+            .collect();
+        assert_eq!(
+            shorts,
+            vec![
+                "Update migrations",
+                "Update docs",
+                "Update tests",
+                "Update misc"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reordering_rules_changes_assignment() {
+        let config = RuleConfig::from_toml(
+            r#"
+[[rules]]
+pattern = "tests/**"
+bucket = "tests"
+
+[[rules]]
+pattern = "*.md"
+bucket = "docs"
+"#,
+        )
+        .unwrap();
+        let hunks = vec![
+            make_hunk_in_file(0, "tests/notes.md"),
+            make_hunk_in_file(1, "src/lib.rs"),
+        ];
+
+        let planned = RuleBased::new(config).plan(&[], &hunks).unwrap();
+        assert_eq!(bucket_of(&planned, 0), "Update tests");
+        assert_eq!(bucket_of(&planned, 1), "Update other");
+        assert_eq!(planned.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_config_fails_planning() {
+        let config = RuleConfig::from_toml(
+            r#"
+[[rules]]
+pattern = "src/***"
+bucket = "src"
+"#,
+        )
+        .unwrap();
+        let result = RuleBased::new(config).plan(&[], &[make_hunk_in_file(0, "a.rs")]);
+        assert!(matches!(result, Err(ReorganizeError::Failed(msg)) if msg.contains("src/***")));
+
+        assert!(RuleConfig::from_toml("rules = 3").is_err());
+        let missing = RuleBased::from_file("/nonexistent/.reabsorb.toml")
+            .plan(&[], &[make_hunk_in_file(0, "a.rs")]);
+        assert!(matches!(missing, Err(ReorganizeError::Failed(_))));
+    }
+}
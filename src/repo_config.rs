@@ -0,0 +1,123 @@
+//! Per-repository defaults read from `.reabsorb.toml`, next to the rules
+//! strategy's buckets:
+//!
+//! ```toml
+//! # Skip commit hooks when applying (the original commits already ran them)
+//! no-verify = true
+//!
+//! [lint]
+//! max-subject-length = 60
+//! # Require a body when a commit adds or removes more than this many lines
+//! require-body-for-large-diffs = 200
+//!
+//! [llm]
+//! # Never send these files' contents to the LLM (adds to --llm-redact)
+//! redact = ["*.env", "secrets/**"]
+//! # Hide their paths too (like --llm-redact-paths)
+//! redact-paths = false
+//!
+//! [verdict]
+//! # Overall assessment score each label starts at; these add to or move
+//! # the default excellent/good/needs-work/poor bands
+//! good = 0.8
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::git::{GitError, GitOps};
+use crate::lint::LintConfig;
+use crate::reorganize::RULES_FILE;
+
+/// Errors loading `.reabsorb.toml`
+#[derive(Debug, thiserror::Error)]
+pub enum RepoConfigError {
+    #[error("Could not read {path}: {source}")]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Invalid .reabsorb.toml: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Invalid .reabsorb.toml: verdict threshold {name} = {value} must be between 0 and 1")]
+    VerdictThreshold { name: String, value: f32 },
+}
+
+/// Settings every key of which has a default, so a missing file or table
+/// behaves like an empty one.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct RepoConfig {
+    /// Default for `--no-verify`
+    pub no_verify: bool,
+    pub lint: LintConfig,
+    pub llm: LlmRepoConfig,
+    /// Assessment verdict thresholds by label
+    pub verdict: BTreeMap<String, f32>,
+}
+
+/// The `[llm]` table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct LlmRepoConfig {
+    /// Globs of files whose contents prompts leave out
+    pub redact: Vec<String>,
+    /// Hide the paths of redacted files too
+    pub redact_paths: bool,
+}
+
+/// `.reabsorb.toml` at the top of `git`'s work tree, so the settings don't
+/// depend on which directory a command runs from.
+pub fn repo_config_path(git: &impl GitOps) -> Result<PathBuf, GitError> {
+    Ok(git.repo_root()?.join(RULES_FILE))
+}
+
+impl RepoConfig {
+    pub fn from_toml(content: &str) -> Result<Self, RepoConfigError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Read `path`, using the defaults if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self, RepoConfigError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Self::from_toml(&content),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(RepoConfigError::Read {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_top_level_defaults_and_the_lint_table() {
+        let config = RepoConfig::from_toml(
+            "default_bucket = \"misc\"\nno-verify = true\n\n[lint]\nmax-subject-length = 50\nrequire-body-for-large-diffs = 100\n",
+        )
+        .unwrap();
+        assert!(config.no_verify);
+        assert_eq!(config.lint.max_subject_length, 50);
+        assert_eq!(config.lint.require_body_for_large_diffs, Some(100));
+
+        assert_eq!(RepoConfig::from_toml("").unwrap(), RepoConfig::default());
+        assert!(RepoConfig::from_toml("[lint]\nmax-subject-length = \"x\"").is_err());
+        assert!(RepoConfig::from_toml("no-verify = 1").is_err());
+        let config =
+            RepoConfig::from_toml("[llm]\nredact = [\"*.env\"]\nredact-paths = true\n").unwrap();
+        assert_eq!(config.llm.redact, vec!["*.env".to_string()]);
+        assert!(config.llm.redact_paths);
+        let config = RepoConfig::from_toml("[verdict]\ngood = 0.8\nneeds-work = 0.6\n").unwrap();
+        assert_eq!(config.verdict.get("needs-work"), Some(&0.6));
+        assert!(RepoConfig::from_toml("[verdict]\ngood = \"high\"").is_err());
+        assert_eq!(
+            RepoConfig::load(Path::new("/nonexistent/.reabsorb.toml")).unwrap(),
+            RepoConfig::default()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+//! The `--stats` summary of a run, counted from its audit events.
+
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::audit::{AuditEvent, AuditSink};
+use crate::utils::format_duration;
+
+/// What a run did and how long it took.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RunSummary {
+    pub wall_time_ms: u64,
+    pub llm_calls: usize,
+    /// LLM calls that failed, so a retry, fallback provider or
+    /// deterministic fix took over
+    pub llm_failures: usize,
+    /// Time spent waiting on the LLM
+    pub llm_time_ms: u64,
+    pub commits_created: usize,
+    /// Hunks applied by the created commits
+    pub hunks_moved: usize,
+    pub validation_issues: usize,
+    /// Whether the rewritten branch matched the original, if it was checked
+    pub integrity_ok: Option<bool>,
+}
+
+impl fmt::Display for RunSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Took {}: {} commit(s) created from {} hunk(s)",
+            format_elapsed(self.wall_time_ms),
+            self.commits_created,
+            self.hunks_moved
+        )?;
+        if self.llm_calls > 0 {
+            write!(
+                f,
+                ", {} LLM call(s) taking {}",
+                self.llm_calls,
+                format_elapsed(self.llm_time_ms)
+            )?;
+            if self.llm_failures > 0 {
+                write!(f, " ({} failed)", self.llm_failures)?;
+            }
+        }
+        if self.validation_issues > 0 {
+            write!(f, ", {} validation issue(s)", self.validation_issues)?;
+        }
+        match self.integrity_ok {
+            Some(true) => write!(f, ", integrity check passed"),
+            Some(false) => write!(f, ", integrity check FAILED"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Tenths of a second below a minute, where whole seconds say little.
+fn format_elapsed(ms: u64) -> String {
+    if ms < 60_000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format_duration(Duration::from_millis(ms))
+    }
+}
+
+/// Counts audit events as they are recorded; give it to the app as an
+/// audit sink. The clock starts when it is created.
+pub struct RunStats {
+    started: Instant,
+    summary: Mutex<RunSummary>,
+}
+
+impl RunStats {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            summary: Mutex::new(RunSummary::default()),
+        }
+    }
+
+    /// The totals so far.
+    pub fn summary(&self) -> RunSummary {
+        let mut summary = self.lock().clone();
+        summary.wall_time_ms = self.started.elapsed().as_millis() as u64;
+        summary
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, RunSummary> {
+        self.summary.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for RunStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AuditSink for RunStats {
+    fn record(&self, event: &AuditEvent) {
+        let mut summary = self.lock();
+        match event {
+            AuditEvent::LlmCall {
+                duration_ms, ok, ..
+            } => {
+                summary.llm_calls += 1;
+                summary.llm_failures += usize::from(!ok);
+                summary.llm_time_ms += duration_ms;
+            }
+            AuditEvent::CommitCreated { hunks, .. } => {
+                summary.commits_created += 1;
+                summary.hunks_moved += hunks;
+            }
+            AuditEvent::ValidationIssue { .. } => summary.validation_issues += 1,
+            AuditEvent::IntegrityChecked { ok, .. } => summary.integrity_ok = Some(*ok),
+            AuditEvent::RangeResolved { .. } | AuditEvent::StrategyChosen { .. } => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_events_into_a_summary() {
+        let stats = RunStats::new();
+        for ok in [true, false] {
+            stats.record(&AuditEvent::LlmCall {
+                prompt_hash: String::new(),
+                prompt_bytes: 10,
+                duration_ms: 1500,
+                ok,
+            });
+        }
+        stats.record(&AuditEvent::CommitCreated {
+            index: 1,
+            source_shas: Vec::new(),
+            sha: "abc".to_string(),
+            hunks: 3,
+        });
+        stats.record(&AuditEvent::IntegrityChecked {
+            ok: true,
+            detail: None,
+        });
+
+        let summary = stats.summary();
+        assert_eq!(summary.llm_calls, 2);
+        assert_eq!(summary.llm_failures, 1);
+        assert_eq!(summary.llm_time_ms, 3000);
+        assert_eq!((summary.commits_created, summary.hunks_moved), (1, 3));
+        assert_eq!(summary.integrity_ok, Some(true));
+
+        let line = RunSummary {
+            wall_time_ms: 4200,
+            ..summary
+        }
+        .to_string();
+        assert_eq!(
+            line,
+            "Took 4.2s: 1 commit(s) created from 3 hunk(s), 2 LLM call(s) taking 3.0s (1 failed), integrity check passed"
+        );
+        assert_eq!(
+            RunSummary::default().to_string(),
+            "Took 0.0s: 0 commit(s) created from 0 hunk(s)"
+        );
+    }
+}
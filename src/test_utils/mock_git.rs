@@ -0,0 +1,827 @@
+//! In-memory `GitOps` for tests that shouldn't need a real repository.
+//!
+//! `MockGit` keeps a linear commit graph, refs and an index of paths. Commits
+//! created through it chain onto HEAD like real ones, so executor and app
+//! code can run end to end. Any method can be made to fail on its next call
+//! with [`MockGit::fail_next`], and every call is recorded for assertions.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::git::{CommitResult, DetectedBase, FileStatuses, GitError, GitOps, PreReabsorbEntry};
+use crate::models::{FileChange, Hunk, HunkId, SourceCommit};
+use crate::patch::PatchContext;
+use crate::utils::short_sha;
+
+/// A commit known to the mock, with the parent it was created on.
+#[derive(Debug, Clone)]
+struct MockCommit {
+    commit: SourceCommit,
+    parent: Option<String>,
+}
+
+#[derive(Default)]
+struct MockState {
+    head: String,
+    commits: HashMap<String, MockCommit>,
+    refs: HashMap<String, String>,
+    hunks: HashMap<String, Vec<Hunk>>,
+    file_statuses: HashMap<String, FileStatuses>,
+    diffs: HashMap<(String, String), String>,
+    pushed: Vec<String>,
+    branch_base: Option<String>,
+    upstreams: HashMap<String, String>,
+    config_paths: HashMap<String, String>,
+    index: HashSet<PathBuf>,
+    operation_in_progress: bool,
+    /// What `get_working_tree_diff` returns
+    working_tree_diff: String,
+    /// Work tree top; a path with no `.reabsorb.toml` unless set
+    root: Option<PathBuf>,
+    /// Staging operations since the last commit
+    staged: usize,
+    /// Failures keyed by method, then by the call number (1-based) they hit
+    failures: HashMap<&'static str, BTreeMap<usize, GitError>>,
+    call_counts: HashMap<&'static str, usize>,
+    calls: Vec<String>,
+    created: usize,
+    history_clock: i64,
+}
+
+/// Programmable in-memory implementation of [`GitOps`].
+pub struct MockGit {
+    state: Mutex<MockState>,
+}
+
+impl MockGit {
+    /// An empty repository. Add history with [`MockGit::with_commit`].
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(MockState::default()),
+        }
+    }
+
+    /// Add `commit` on top of HEAD and move HEAD (and `main`) to it.
+    /// `hunks` are what `read_hunks` returns for it.
+    pub fn with_commit(self, commit: SourceCommit, hunks: Vec<Hunk>) -> Self {
+        {
+            let mut state = self.lock();
+            let parent = Some(state.head.clone()).filter(|h| !h.is_empty());
+            let sha = commit.sha.clone();
+            state.hunks.insert(sha.clone(), hunks);
+            state
+                .commits
+                .insert(sha.clone(), MockCommit { commit, parent });
+            state.refs.insert("main".to_string(), sha.clone());
+            state.head = sha;
+        }
+        self
+    }
+
+    /// Point `name` at `sha`, for `resolve_ref`.
+    pub fn with_ref(self, name: &str, sha: &str) -> Self {
+        self.lock().refs.insert(name.to_string(), sha.to_string());
+        self
+    }
+
+    /// Make `detect_branch_base` and `find_merge_base` report `branch`,
+    /// which must be a ref.
+    pub fn with_branch_base(self, branch: &str) -> Self {
+        self.lock().branch_base = Some(branch.to_string());
+        self
+    }
+
+    /// Make `upstream_of(branch)` report `upstream` (`None` otherwise).
+    pub fn with_upstream(self, branch: &str, upstream: &str) -> Self {
+        self.lock()
+            .upstreams
+            .insert(branch.to_string(), upstream.to_string());
+        self
+    }
+
+    /// What `diff_trees(left, right)` returns (empty otherwise).
+    pub fn with_diff(self, left: &str, right: &str, diff: &str) -> Self {
+        self.lock()
+            .diffs
+            .insert((left.to_string(), right.to_string()), diff.to_string());
+        self
+    }
+
+    /// What `get_file_statuses_in_commit(sha)` returns (empty otherwise).
+    pub fn with_file_statuses(self, sha: &str, statuses: FileStatuses) -> Self {
+        self.lock().file_statuses.insert(sha.to_string(), statuses);
+        self
+    }
+
+    /// Commits reported as already on a remote.
+    pub fn with_pushed(self, shas: &[&str]) -> Self {
+        self.lock().pushed = shas.iter().map(|s| s.to_string()).collect();
+        self
+    }
+
+    /// A path-valued config entry, for `get_config_path`.
+    pub fn with_config_path(self, key: &str, value: &str) -> Self {
+        self.lock()
+            .config_paths
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Mark `path` as present in the index.
+    pub fn with_indexed_file(self, path: &str) -> Self {
+        self.lock().index.insert(PathBuf::from(path));
+        self
+    }
+
+    /// Report `root` as the top of the work tree.
+    pub fn with_root(self, root: &Path) -> Self {
+        self.lock().root = Some(root.to_path_buf());
+        self
+    }
+
+    /// Report a merge or rebase in progress.
+    pub fn with_operation_in_progress(self) -> Self {
+        self.lock().operation_in_progress = true;
+        self
+    }
+
+    /// Report uncommitted changes, as `diff` against HEAD.
+    pub fn with_working_tree_diff(self, diff: &str) -> Self {
+        self.lock().working_tree_diff = diff.to_string();
+        self
+    }
+
+    /// Make the next call to `method` (e.g. `"apply_hunks_to_index"`) that
+    /// isn't already set to fail, fail with `error`.
+    pub fn fail_next(&self, method: &'static str, error: GitError) {
+        let mut state = self.lock();
+        let mut call = state.call_counts.get(method).copied().unwrap_or(0) + 1;
+        let failures = state.failures.entry(method).or_default();
+        while failures.contains_key(&call) {
+            call += 1;
+        }
+        failures.insert(call, error);
+    }
+
+    /// Make the `n`th call to `method` from now (1 is the next one) fail
+    /// with `error`.
+    pub fn fail_call(&self, method: &'static str, n: usize, error: GitError) {
+        let mut state = self.lock();
+        let call = state.call_counts.get(method).copied().unwrap_or(0) + n;
+        state
+            .failures
+            .entry(method)
+            .or_default()
+            .insert(call, error);
+    }
+
+    /// Every method called so far, in order.
+    pub fn calls(&self) -> Vec<String> {
+        self.lock().calls.clone()
+    }
+
+    /// The current HEAD.
+    pub fn head(&self) -> String {
+        self.lock().head.clone()
+    }
+
+    /// The message of a commit known to the mock, including created ones.
+    pub fn message_of(&self, sha: &str) -> Option<String> {
+        self.lock()
+            .commits
+            .get(sha)
+            .map(|c| c.commit.message.long.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a call to `method` and return the failure set for it, if any.
+    fn enter(
+        &self,
+        method: &'static str,
+    ) -> Result<std::sync::MutexGuard<'_, MockState>, GitError> {
+        let mut state = self.lock();
+        state.calls.push(method.to_string());
+        let count = state.call_counts.entry(method).or_default();
+        *count += 1;
+        let call = *count;
+        match state.failures.get_mut(method).and_then(|f| f.remove(&call)) {
+            Some(error) => Err(error),
+            None => Ok(state),
+        }
+    }
+
+    /// Like [`MockGit::enter`], for methods that don't touch the state.
+    fn call(&self, method: &'static str) -> Result<(), GitError> {
+        self.enter(method).map(drop)
+    }
+}
+
+impl Default for MockGit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockState {
+    fn create_ref(&mut self, full_name: String, name: &str, sha: &str) -> Result<(), GitError> {
+        if self.refs.contains_key(&full_name) {
+            return Err(GitError::RefExists(name.to_string()));
+        }
+        self.refs.insert(full_name, sha.to_string());
+        Ok(())
+    }
+
+    fn resolve(&self, rev: &str) -> Result<String, GitError> {
+        if rev == "HEAD" && !self.head.is_empty() {
+            return Ok(self.head.clone());
+        }
+        if let Some(parent) = rev
+            .strip_suffix('^')
+            .and_then(|child| self.commits.get(&self.resolve(child).ok()?))
+            .and_then(|c| c.parent.clone())
+        {
+            return Ok(parent);
+        }
+        if let Some(sha) = self.refs.get(rev) {
+            return Ok(sha.clone());
+        }
+        if self.commits.contains_key(rev) {
+            return Ok(rev.to_string());
+        }
+        Err(GitError::RefNotFound {
+            reference: rev.to_string(),
+            suggestion: None,
+        })
+    }
+
+    /// `sha` and its ancestors, newest first.
+    fn ancestry(&self, sha: &str) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut current = Some(sha.to_string());
+        while let Some(sha) = current {
+            current = self.commits.get(&sha).and_then(|c| c.parent.clone());
+            chain.push(sha);
+        }
+        chain
+    }
+
+    fn stage(&mut self) {
+        self.staged += 1;
+    }
+
+    fn create_commit(&mut self, parent: &str, message: &str) -> String {
+        self.created += 1;
+        // Unique in the first 7 characters, so abbreviations stay distinct
+        let sha = format!("{:07x}{}", self.created, "f".repeat(33));
+        let commit = SourceCommit::new(&sha, message.lines().next().unwrap_or(""), message.trim());
+        self.commits.insert(
+            sha.clone(),
+            MockCommit {
+                commit,
+                parent: Some(parent.to_string()).filter(|p| !p.is_empty()),
+            },
+        );
+        sha
+    }
+}
+
+impl GitOps for MockGit {
+    fn detect_branch_base(&self) -> Result<DetectedBase, GitError> {
+        let state = self.enter("detect_branch_base")?;
+        let branch = state
+            .branch_base
+            .clone()
+            .ok_or_else(|| GitError::NoBranchBase(vec!["main".to_string()]))?;
+        Ok(DetectedBase {
+            sha: state.resolve(&branch)?,
+            branch,
+        })
+    }
+
+    fn find_merge_base(&self, branch: &str) -> Result<String, GitError> {
+        self.enter("find_merge_base")?.resolve(branch)
+    }
+
+    fn get_head(&self) -> Result<String, GitError> {
+        self.enter("get_head")?.resolve("HEAD")
+    }
+
+    fn resolve_ref(&self, ref_name: &str) -> Result<String, GitError> {
+        self.enter("resolve_ref")?.resolve(ref_name)
+    }
+
+    fn abbrev(&self, sha: &str) -> String {
+        short_sha(sha).to_string()
+    }
+
+    fn read_commits(&self, base: &str, head: &str) -> Result<Vec<SourceCommit>, GitError> {
+        let state = self.enter("read_commits")?;
+        let (base, head) = (state.resolve(base)?, state.resolve(head)?);
+        let mut commits: Vec<SourceCommit> = state
+            .ancestry(&head)
+            .into_iter()
+            .take_while(|sha| *sha != base)
+            .filter_map(|sha| state.commits.get(&sha).map(|c| c.commit.clone()))
+            .collect();
+        if commits.is_empty() {
+            return Err(GitError::NoCommitsInRange(format!("{}..{}", base, head)));
+        }
+        commits.reverse();
+        Ok(commits)
+    }
+
+    fn read_commit(&self, rev: &str) -> Result<SourceCommit, GitError> {
+        let state = self.enter("read_commit")?;
+        let sha = state.resolve(rev)?;
+        Ok(state.commits[&sha].commit.clone())
+    }
+
+    fn commits_on_remote(&self, _base: &str, _head: &str) -> Result<Vec<String>, GitError> {
+        Ok(self.enter("commits_on_remote")?.pushed.clone())
+    }
+
+    fn parents_of(&self, sha: &str) -> Result<Vec<String>, GitError> {
+        let state = self.enter("parents_of")?;
+        let sha = state.resolve(sha)?;
+        Ok(state
+            .commits
+            .get(&sha)
+            .and_then(|c| c.parent.clone())
+            .into_iter()
+            .collect())
+    }
+
+    fn is_ancestor(&self, maybe_ancestor: &str, descendant: &str) -> Result<bool, GitError> {
+        let state = self.enter("is_ancestor")?;
+        let ancestor = state.resolve(maybe_ancestor)?;
+        let descendant = state.resolve(descendant)?;
+        Ok(state.ancestry(&descendant).contains(&ancestor))
+    }
+
+    fn count_commits(&self, base: &str, head: &str) -> Result<usize, GitError> {
+        let state = self.enter("count_commits")?;
+        let excluded = state.ancestry(&state.resolve(base)?);
+        let head = state.resolve(head)?;
+        Ok(state
+            .ancestry(&head)
+            .iter()
+            .filter(|sha| !excluded.contains(sha))
+            .count())
+    }
+
+    fn read_hunks(&self, commit_sha: &str, hunk_id_start: usize) -> Result<Vec<Hunk>, GitError> {
+        let state = self.enter("read_hunks")?;
+        let mut hunks = state.hunks.get(commit_sha).cloned().unwrap_or_default();
+        for (i, hunk) in hunks.iter_mut().enumerate() {
+            hunk.id = HunkId(hunk_id_start + i);
+            hunk.likely_source_commits = vec![commit_sha.to_string()];
+        }
+        Ok(hunks)
+    }
+
+    fn get_working_tree_diff(&self) -> Result<String, GitError> {
+        Ok(self
+            .enter("get_working_tree_diff")?
+            .working_tree_diff
+            .clone())
+    }
+
+    fn diff_trees(&self, left: &str, right: &str) -> Result<String, GitError> {
+        let state = self.enter("diff_trees")?;
+        Ok(state
+            .diffs
+            .get(&(left.to_string(), right.to_string()))
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn diff_trees_paths(
+        &self,
+        left: &str,
+        right: &str,
+        paths: &[&Path],
+    ) -> Result<String, GitError> {
+        let state = self.enter("diff_trees_paths")?;
+        let diff = state
+            .diffs
+            .get(&(left.to_string(), right.to_string()))
+            .cloned()
+            .unwrap_or_default();
+        // Keep the per-file sections under one of the paths
+        let mut kept = String::new();
+        let mut keep = false;
+        for line in diff.split_inclusive('\n') {
+            if let Some(rest) = line.strip_prefix("diff --git a/") {
+                let file = Path::new(rest.split(" b/").next().unwrap_or_default());
+                keep = paths.iter().any(|p| file.starts_with(p));
+            }
+            if keep {
+                kept.push_str(line);
+            }
+        }
+        Ok(kept)
+    }
+
+    fn diff_file_in_working_tree(&self, _file_path: &str) -> Result<String, GitError> {
+        self.call("diff_file_in_working_tree")?;
+        Ok(String::new())
+    }
+
+    fn read_blob(&self, _sha: &str, _path: &Path) -> Result<Option<Vec<u8>>, GitError> {
+        self.call("read_blob")?;
+        Ok(None)
+    }
+
+    fn get_file_statuses_in_commit(&self, commit_sha: &str) -> Result<FileStatuses, GitError> {
+        let state = self.enter("get_file_statuses_in_commit")?;
+        Ok(state
+            .file_statuses
+            .get(commit_sha)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn get_file_statuses_for_commits(
+        &self,
+        commit_shas: &[&str],
+    ) -> Result<HashMap<String, FileStatuses>, GitError> {
+        let state = self.enter("get_file_statuses_for_commits")?;
+        Ok(commit_shas
+            .iter()
+            .map(|sha| {
+                let statuses = state.file_statuses.get(*sha).cloned().unwrap_or_default();
+                (sha.to_string(), statuses)
+            })
+            .collect())
+    }
+
+    fn apply_hunk_to_index(&self, hunk: &Hunk) -> Result<(), GitError> {
+        let mut state = self.enter("apply_hunk_to_index")?;
+        state.index.insert(hunk.file_path.clone());
+        state.stage();
+        Ok(())
+    }
+
+    fn reset_to(&self, ref_name: &str) -> Result<(), GitError> {
+        let mut state = self.enter("reset_to")?;
+        state.head = state.resolve(ref_name)?;
+        state.staged = 0;
+        Ok(())
+    }
+
+    fn reset_hard(&self, ref_name: &str) -> Result<(), GitError> {
+        let mut state = self.enter("reset_hard")?;
+        state.head = state.resolve(ref_name)?;
+        state.staged = 0;
+        Ok(())
+    }
+
+    fn apply_hunks_to_index(
+        &self,
+        hunks: &[&Hunk],
+        _patch_context: &PatchContext,
+    ) -> Result<(), GitError> {
+        let mut state = self.enter("apply_hunks_to_index")?;
+        for hunk in hunks {
+            state.index.insert(hunk.file_path.clone());
+        }
+        state.stage();
+        Ok(())
+    }
+
+    fn stage_all(&self) -> Result<(), GitError> {
+        self.enter("stage_all")?.stage();
+        Ok(())
+    }
+
+    fn stage_files(&self, files: &[&Path]) -> Result<(), GitError> {
+        let mut state = self.enter("stage_files")?;
+        state.index.extend(files.iter().map(|f| f.to_path_buf()));
+        state.stage();
+        Ok(())
+    }
+
+    fn stage_deletion(&self, file_path: &Path) -> Result<(), GitError> {
+        let mut state = self.enter("stage_deletion")?;
+        state.index.remove(file_path);
+        state.stage();
+        Ok(())
+    }
+
+    fn set_file_mode(&self, _file_path: &Path, _mode: &str) -> Result<(), GitError> {
+        self.enter("set_file_mode")?.stage();
+        Ok(())
+    }
+
+    fn commit(
+        &self,
+        message: &str,
+        _no_verify: bool,
+        allow_empty: bool,
+    ) -> Result<CommitResult, GitError> {
+        let mut state = self.enter("commit")?;
+        let empty = state.staged == 0;
+        if empty && !allow_empty {
+            return Ok(CommitResult {
+                sha: state.head.clone(),
+                created: false,
+                empty,
+            });
+        }
+        state.staged = 0;
+        let parent = state.head.clone();
+        let sha = state.create_commit(&parent, message);
+        state.head = sha.clone();
+        Ok(CommitResult {
+            sha,
+            created: true,
+            empty,
+        })
+    }
+
+    fn use_index_file(&self, _index_file: Option<&Path>) {
+        self.lock().calls.push("use_index_file".to_string());
+    }
+
+    fn read_tree(&self, tree_ish: &str) -> Result<(), GitError> {
+        let state = self.enter("read_tree")?;
+        state.resolve(tree_ish)?;
+        Ok(())
+    }
+
+    fn write_tree(&self) -> Result<String, GitError> {
+        // Trees are named after a commit: HEAD's while nothing is staged
+        let state = self.enter("write_tree")?;
+        if state.staged == 0 {
+            return Ok(format!("{}^{{tree}}", state.head));
+        }
+        Ok(format!("staged-{}^{{tree}}", state.created + 1))
+    }
+
+    fn tree_of(&self, rev: &str) -> Result<String, GitError> {
+        let state = self.enter("tree_of")?;
+        Ok(format!("{}^{{tree}}", state.resolve(rev)?))
+    }
+
+    fn commit_tree(
+        &self,
+        _tree: &str,
+        parent: &str,
+        message: &str,
+        author: Option<&SourceCommit>,
+    ) -> Result<String, GitError> {
+        let mut state = self.enter("commit_tree")?;
+        state.staged = 0;
+        let sha = state.create_commit(parent, message);
+        if let Some(author) = author {
+            let commit = &mut state.commits.get_mut(&sha).unwrap().commit;
+            commit.author_name = author.author_name.clone();
+            commit.author_email = author.author_email.clone();
+            commit.author_date = author.author_date;
+        }
+        Ok(sha)
+    }
+
+    fn update_head(&self, sha: &str) -> Result<(), GitError> {
+        let mut state = self.enter("update_head")?;
+        state.head = state.resolve(sha)?;
+        Ok(())
+    }
+
+    fn amend_message(&self, message: &str, _no_verify: bool) -> Result<String, GitError> {
+        let mut state = self.enter("amend_message")?;
+        let head = state.resolve("HEAD")?;
+        let parent = state.commits[&head].parent.clone().unwrap_or_default();
+        let sha = state.create_commit(&parent, message);
+        state.head = sha.clone();
+        Ok(sha)
+    }
+
+    fn save_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError> {
+        let mut state = self.enter("save_pre_reabsorb_head")?;
+        let head = state.resolve("HEAD")?;
+        state.refs.insert(ref_name.to_string(), head);
+        Ok(())
+    }
+
+    fn get_pre_reabsorb_head(&self, ref_name: &str) -> Result<String, GitError> {
+        let state = self.enter("get_pre_reabsorb_head")?;
+        state
+            .refs
+            .get(ref_name)
+            .cloned()
+            .ok_or(GitError::NoSavedState)
+    }
+
+    fn has_pre_reabsorb_head(&self, ref_name: &str) -> bool {
+        let mut state = self.lock();
+        state.calls.push("has_pre_reabsorb_head".to_string());
+        state.refs.contains_key(ref_name)
+    }
+
+    fn clear_pre_reabsorb_head(&self, ref_name: &str) -> Result<(), GitError> {
+        self.enter("clear_pre_reabsorb_head")?.refs.remove(ref_name);
+        Ok(())
+    }
+
+    fn record_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+        max_entries: usize,
+    ) -> Result<(), GitError> {
+        let mut state = self.enter("record_pre_reabsorb_history")?;
+        let head = state.resolve("HEAD")?;
+        state.history_clock += 1;
+        let ref_name = format!("{}/{}", history_prefix, state.history_clock);
+        state.refs.insert(ref_name, head);
+
+        let mut entries = history(&state, history_prefix);
+        for stale in entries.drain(..).skip(max_entries) {
+            state.refs.remove(&stale.ref_name);
+        }
+        Ok(())
+    }
+
+    fn list_pre_reabsorb_history(
+        &self,
+        history_prefix: &str,
+    ) -> Result<Vec<PreReabsorbEntry>, GitError> {
+        let state = self.enter("list_pre_reabsorb_history")?;
+        Ok(history(&state, history_prefix))
+    }
+
+    fn create_branch_at(&self, name: &str, sha: &str) -> Result<(), GitError> {
+        self.enter("create_branch_at")?
+            .create_ref(format!("refs/heads/{}", name), name, sha)
+    }
+
+    fn create_tag(&self, name: &str, sha: &str) -> Result<(), GitError> {
+        self.enter("create_tag")?
+            .create_ref(format!("refs/tags/{}", name), name, sha)
+    }
+
+    fn current_branch_name(&self) -> Result<String, GitError> {
+        self.call("current_branch_name")?;
+        Ok("main".to_string())
+    }
+
+    fn upstream_of(&self, branch: &str) -> Result<Option<String>, GitError> {
+        Ok(self.enter("upstream_of")?.upstreams.get(branch).cloned())
+    }
+
+    fn get_config_path(&self, key: &str) -> Result<Option<String>, GitError> {
+        Ok(self
+            .enter("get_config_path")?
+            .config_paths
+            .get(key)
+            .cloned())
+    }
+
+    fn file_in_index(&self, file_path: &Path) -> Result<bool, GitError> {
+        Ok(self.enter("file_in_index")?.index.contains(file_path))
+    }
+
+    fn operation_in_progress(&self) -> Result<bool, GitError> {
+        Ok(self.enter("operation_in_progress")?.operation_in_progress)
+    }
+
+    fn repo_root(&self) -> Result<PathBuf, GitError> {
+        let state = self.enter("repo_root")?;
+        Ok(state
+            .root
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("/nonexistent/mock-repo")))
+    }
+
+    fn run_git_output(&self, args: &[&str]) -> Result<String, GitError> {
+        self.enter("run_git_output")?
+            .calls
+            .push(format!("git {}", args.join(" ")));
+        Ok(String::new())
+    }
+
+    fn apply_binary_files(&self, changes: &[&FileChange]) -> Result<(), GitError> {
+        let mut state = self.enter("apply_binary_files")?;
+        state
+            .index
+            .extend(changes.iter().map(|fc| fc.file_path.clone()));
+        state.stage();
+        Ok(())
+    }
+}
+
+/// Entries under `prefix`, newest first.
+fn history(state: &MockState, prefix: &str) -> Vec<PreReabsorbEntry> {
+    let pattern = format!("{}/", prefix);
+    let mut entries: Vec<PreReabsorbEntry> = state
+        .refs
+        .iter()
+        .filter_map(|(name, sha)| {
+            let saved_at = name.strip_prefix(&pattern)?.parse().ok()?;
+            Some(PreReabsorbEntry {
+                ref_name: name.clone(),
+                sha: sha.clone(),
+                saved_at,
+            })
+        })
+        .collect();
+    entries.sort_by_key(|e| std::cmp::Reverse(e.saved_at));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{make_hunk_in_file, make_source_commit};
+
+    fn two_commit_repo() -> MockGit {
+        MockGit::new()
+            .with_commit(make_source_commit("base", "Initial"), vec![])
+            .with_commit(
+                make_source_commit("aaa", "Add a"),
+                vec![make_hunk_in_file(0, "a.rs")],
+            )
+            .with_commit(
+                make_source_commit("bbb", "Add b"),
+                vec![make_hunk_in_file(0, "b.rs")],
+            )
+    }
+
+    #[test]
+    fn reads_linear_history() {
+        let git = two_commit_repo();
+        let commits = git.read_commits("base", "HEAD").unwrap();
+        let shas: Vec<&str> = commits.iter().map(|c| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["aaa", "bbb"]);
+        assert!(git.is_ancestor("aaa", "bbb").unwrap());
+        assert!(!git.is_ancestor("bbb", "aaa").unwrap());
+        assert!(matches!(
+            git.read_commits("bbb", "bbb"),
+            Err(GitError::NoCommitsInRange(_))
+        ));
+
+        let hunks = git.read_hunks("bbb", 7).unwrap();
+        assert_eq!(hunks[0].id, HunkId(7));
+        assert_eq!(hunks[0].likely_source_commits, vec!["bbb".to_string()]);
+    }
+
+    #[test]
+    fn commits_chain_onto_head_and_need_staged_changes() {
+        let git = two_commit_repo();
+        git.reset_to("base").unwrap();
+        let skipped = git.commit("Empty", false, false).unwrap();
+        assert!(!skipped.created && skipped.empty);
+        assert_eq!(git.head(), "base");
+
+        git.stage_all().unwrap();
+        let first = git.commit("First\n\nBody", false, false).unwrap().sha;
+        git.stage_all().unwrap();
+        let second = git.commit("Second", false, false).unwrap().sha;
+
+        assert_eq!(git.head(), second);
+        assert_ne!(short_sha(&first), short_sha(&second));
+        let commits = git.read_commits("base", "HEAD").unwrap();
+        assert_eq!(commits[0].message.short, "First");
+        assert_eq!(git.message_of(&first).as_deref(), Some("First\n\nBody"));
+    }
+
+    #[test]
+    fn failures_hit_the_chosen_call_once() {
+        let git = two_commit_repo();
+        git.fail_next("get_head", GitError::NotARepository);
+        git.fail_call("get_head", 3, GitError::GitNotFound);
+
+        assert!(matches!(git.get_head(), Err(GitError::NotARepository)));
+        assert_eq!(git.get_head().unwrap(), "bbb");
+        assert!(matches!(git.get_head(), Err(GitError::GitNotFound)));
+        assert_eq!(git.get_head().unwrap(), "bbb");
+        assert_eq!(git.calls().len(), 4);
+    }
+
+    #[test]
+    fn history_keeps_newest_entries() {
+        let git = two_commit_repo();
+        for _ in 0..3 {
+            git.record_pre_reabsorb_history("refs/test/history", 2)
+                .unwrap();
+        }
+        let entries = git.list_pre_reabsorb_history("refs/test/history").unwrap();
+        let saved: Vec<i64> = entries.iter().map(|e| e.saved_at).collect();
+        assert_eq!(saved, vec![3, 2]);
+    }
+
+    #[test]
+    fn diff_trees_paths_keeps_matching_sections() {
+        let diff = "diff --git a/src/a.rs b/src/a.rs\n+a\ndiff --git a/b.rs b/b.rs\n+b\n";
+        let git = two_commit_repo().with_diff("base", "bbb", diff);
+        assert_eq!(
+            git.diff_trees_paths("base", "bbb", &[Path::new("src")])
+                .unwrap(),
+            "diff --git a/src/a.rs b/src/a.rs\n+a\n"
+        );
+    }
+}
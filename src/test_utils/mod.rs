@@ -0,0 +1,149 @@
+//! Shared test utilities for creating test fixtures.
+//!
+//! This module provides helper functions for creating test data
+//! used across multiple test modules, and in-memory stand-ins for the git
+//! and plan storage layers. Downstream crates get it with the `testing`
+//! feature.
+
+use crate::models::{DiffLine, Hunk, HunkId, SourceCommit};
+use crate::plan_store::{PlanFileError, PlanStore, SavedPlan};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+mod mock_git;
+
+pub use mock_git::MockGit;
+
+/// Create a minimal test hunk with default values
+pub fn make_hunk(id: usize) -> Hunk {
+    make_hunk_in_file(id, "test.rs")
+}
+
+/// Create a test hunk in a specific file
+pub fn make_hunk_in_file(id: usize, file: &str) -> Hunk {
+    make_hunk_with_source(id, file, vec![])
+}
+
+/// Create a test hunk with a specific source commit SHA
+pub fn make_hunk_with_source(id: usize, file: &str, source_commits: Vec<String>) -> Hunk {
+    make_hunk_full(
+        id,
+        file,
+        vec![DiffLine::Added("test".to_string())],
+        source_commits,
+    )
+}
+
+/// Create a fully customized test hunk
+pub fn make_hunk_full(
+    id: usize,
+    file: &str,
+    lines: Vec<DiffLine>,
+    source_commits: Vec<String>,
+) -> Hunk {
+    Hunk {
+        id: HunkId(id),
+        file_path: PathBuf::from(file),
+        old_start: 1,
+        old_count: 1,
+        new_start: 1,
+        new_count: 1,
+        lines,
+        likely_source_commits: source_commits,
+        old_missing_newline_at_eof: false,
+        new_missing_newline_at_eof: false,
+    }
+}
+
+/// Create a test source commit with a fixed author and dates
+pub fn make_source_commit(sha: &str, message: &str) -> SourceCommit {
+    SourceCommit::new(sha, message, message)
+        .with_author("Test User", "test@example.com")
+        .with_dates(1_700_000_000, 1_700_000_000)
+}
+
+/// Plan store that keeps the plan in memory.
+#[derive(Default)]
+pub struct MemoryPlanStore {
+    plan: Mutex<Option<SavedPlan>>,
+    archived: Mutex<Vec<SavedPlan>>,
+}
+
+impl MemoryPlanStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store that already holds `plan`.
+    pub fn with_plan(plan: SavedPlan) -> Self {
+        Self {
+            plan: Mutex::new(Some(plan)),
+            archived: Mutex::default(),
+        }
+    }
+
+    /// The stored plan, if any.
+    pub fn plan(&self) -> Option<SavedPlan> {
+        self.plan.lock().unwrap().clone()
+    }
+
+    /// Plans archived so far, oldest first.
+    pub fn archived(&self) -> Vec<SavedPlan> {
+        self.archived.lock().unwrap().clone()
+    }
+}
+
+impl PlanStore for MemoryPlanStore {
+    fn load(&self) -> Result<SavedPlan, PlanFileError> {
+        self.plan().ok_or(PlanFileError::NoPlan)
+    }
+
+    fn save(&self, plan: &SavedPlan) -> Result<(), PlanFileError> {
+        *self.plan.lock().unwrap() = Some(plan.clone());
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<(), PlanFileError> {
+        *self.plan.lock().unwrap() = None;
+        Ok(())
+    }
+
+    fn exists(&self) -> bool {
+        self.plan.lock().unwrap().is_some()
+    }
+
+    fn archive(&self, plan: &SavedPlan) -> Result<PathBuf, PlanFileError> {
+        let mut archived = self.archived.lock().unwrap();
+        archived.push(plan.clone());
+        *self.plan.lock().unwrap() = None;
+        Ok(PathBuf::from(format!("archive/{}.json", archived.len())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_hunk() {
+        let hunk = make_hunk(5);
+        assert_eq!(hunk.id, HunkId(5));
+        assert_eq!(hunk.file_path, PathBuf::from("test.rs"));
+    }
+
+    #[test]
+    fn test_make_hunk_in_file() {
+        let hunk = make_hunk_in_file(3, "src/main.rs");
+        assert_eq!(hunk.id, HunkId(3));
+        assert_eq!(hunk.file_path, PathBuf::from("src/main.rs"));
+    }
+
+    #[test]
+    fn test_make_source_commit() {
+        let commit = make_source_commit("abc123", "Test message");
+        assert_eq!(commit.sha, "abc123");
+        assert_eq!(commit.message.short, "Test message");
+        assert_eq!(commit.author_ident(), "Test User <test@example.com>");
+        assert_eq!(commit.author_date, 1_700_000_000);
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::models::DiffLine;
 
 /// Truncate a SHA to its first 8 characters for display
@@ -5,6 +7,95 @@ pub fn short_sha(sha: &str) -> &str {
     &sha[..8.min(sha.len())]
 }
 
+/// Format a Unix timestamp (seconds) as a local date and time for display
+pub fn format_timestamp(secs: i64) -> String {
+    chrono::DateTime::from_timestamp(secs, 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown time".to_string())
+}
+
+/// Parse a confidence threshold between 0 and 1
+pub fn parse_confidence(input: &str) -> Result<f32, String> {
+    let value: f32 = input
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid confidence '{}': expected a number", input))?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(format!(
+            "invalid confidence '{}': must be between 0 and 1",
+            input
+        ));
+    }
+    Ok(value)
+}
+
+/// Parse a human duration like "90", "90s", "5m", "1h30m" (bare numbers are seconds)
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    if let Ok(secs) = input.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => {
+                return Err(format!(
+                    "invalid duration '{}': unknown unit '{}'",
+                    input, c
+                ))
+            }
+        };
+        let value: u64 = digits.parse().map_err(|_| {
+            format!(
+                "invalid duration '{}': expected a number before '{}'",
+                input, c
+            )
+        })?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(|| format!("invalid duration '{}': too long", input))?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(format!(
+            "invalid duration '{}': missing unit after {}",
+            input, digits
+        ));
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Format a duration compactly for display, e.g. "45s", "5m", "1h 5m"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 && secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else if secs < 3600 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
 /// Format diff lines with standard +/- prefixes for display
 pub fn format_diff_lines(lines: &[DiffLine]) -> String {
     lines
@@ -18,6 +109,122 @@ pub fn format_diff_lines(lines: &[DiffLine]) -> String {
         .join("\n")
 }
 
+/// Longest pair of lines (in words multiplied) compared word by word;
+/// longer pairs are shown as plain -/+ lines.
+const MAX_WORD_DIFF_CELLS: usize = 10_000;
+
+/// Format diff lines compactly, in the style of `git diff --word-diff=plain`.
+///
+/// Each removed line directly followed by an added line is shown once,
+/// prefixed with `~`, with the words that changed marked `[-old-]{+new+}`.
+/// A `~` line without markers differs only in whitespace. Pairs that share
+/// less than half their words, and all other lines, are shown as by
+/// [`format_diff_lines`].
+pub fn format_word_diff(lines: &[DiffLine]) -> String {
+    let mut output = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let removed: Vec<&str> = lines[i..]
+            .iter()
+            .map_while(|line| match line {
+                DiffLine::Removed(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        if removed.is_empty() {
+            output.push(format_diff_lines(&lines[i..=i]));
+            i += 1;
+            continue;
+        }
+        let added: Vec<&str> = lines[i + removed.len()..]
+            .iter()
+            .map_while(|line| match line {
+                DiffLine::Added(s) => Some(s.as_str()),
+                _ => None,
+            })
+            .collect();
+        i += removed.len() + added.len();
+
+        for k in 0..removed.len().max(added.len()) {
+            match (removed.get(k), added.get(k)) {
+                (Some(old), Some(new)) => match word_diff_line(old, new) {
+                    Some(line) => output.push(format!("~{}", line)),
+                    None => {
+                        output.push(format!("-{}", old));
+                        output.push(format!("+{}", new));
+                    }
+                },
+                (Some(old), None) => output.push(format!("-{}", old)),
+                (None, Some(new)) => output.push(format!("+{}", new)),
+                (None, None) => unreachable!(),
+            }
+        }
+    }
+    output.join("\n")
+}
+
+/// `new` with the words it changed from `old` marked, keeping `new`'s
+/// indentation, or `None` when the lines have too little in common.
+fn word_diff_line(old: &str, new: &str) -> Option<String> {
+    let old_words: Vec<&str> = old.split_whitespace().collect();
+    let new_words: Vec<&str> = new.split_whitespace().collect();
+    let (n, m) = (old_words.len(), new_words.len());
+    if n * m > MAX_WORD_DIFF_CELLS {
+        return None;
+    }
+
+    // common[i][j]: longest common subsequence of old_words[i..], new_words[j..]
+    let mut common = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if old_words[i] == new_words[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    if common[0][0] * 2 < n.max(m) {
+        return None;
+    }
+
+    let mut words = Vec::new();
+    let (mut deleted, mut inserted) = (Vec::new(), Vec::new());
+    let flush = |words: &mut Vec<String>, deleted: &mut Vec<&str>, inserted: &mut Vec<&str>| {
+        let mut change = String::new();
+        if !deleted.is_empty() {
+            change.push_str(&format!("[-{}-]", deleted.join(" ")));
+        }
+        if !inserted.is_empty() {
+            change.push_str(&format!("{{+{}+}}", inserted.join(" ")));
+        }
+        if !change.is_empty() {
+            words.push(change);
+        }
+        deleted.clear();
+        inserted.clear();
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_words[i] == new_words[j] {
+            flush(&mut words, &mut deleted, &mut inserted);
+            words.push(new_words[j].to_string());
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && common[i + 1][j] >= common[i][j + 1]) {
+            deleted.push(old_words[i]);
+            i += 1;
+        } else {
+            inserted.push(new_words[j]);
+            j += 1;
+        }
+    }
+    flush(&mut words, &mut deleted, &mut inserted);
+
+    let indent = &new[..new.len() - new.trim_start().len()];
+    Some(format!("{}{}", indent, words.join(" ")))
+}
+
 /// Extract JSON content from an LLM response.
 ///
 /// Handles three formats:
@@ -82,6 +289,37 @@ mod tests {
         assert!(formatted.contains("-old line"));
     }
 
+    #[test]
+    fn test_format_word_diff() {
+        let lines = vec![
+            DiffLine::Context("fn main() {".to_string()),
+            DiffLine::Removed("  let x = 1;".to_string()),
+            DiffLine::Removed("  call(x,   y);".to_string()),
+            DiffLine::Removed("  gone();".to_string()),
+            DiffLine::Added("    let x = 2;".to_string()),
+            DiffLine::Added("    call(x, y);".to_string()),
+            DiffLine::Added("  completely different line".to_string()),
+            DiffLine::Added("  extra();".to_string()),
+            DiffLine::Context("}".to_string()),
+        ];
+
+        assert_eq!(
+            format_word_diff(&lines),
+            [
+                " fn main() {",
+                "~    let x = [-1;-]{+2;+}",
+                "~    call(x, y);",
+                "-  gone();",
+                "+  completely different line",
+                "+  extra();",
+                " }",
+            ]
+            .join("\n")
+        );
+        let plain = vec![DiffLine::Added("new".to_string())];
+        assert_eq!(format_word_diff(&plain), format_diff_lines(&plain));
+    }
+
     #[test]
     fn test_extract_json_str_code_fence() {
         let response = r#"Here's the JSON:
@@ -122,4 +360,37 @@ That's it!"#;
         let response = "Running node v24.8.0 (npm v11.6.0)";
         assert_eq!(extract_json_str(response), None);
     }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("1h30").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("99999999999999999h")
+            .unwrap_err()
+            .contains("too long"));
+        assert!(parse_duration("18446744073709551615s1s").is_err());
+    }
+
+    #[test]
+    fn test_parse_confidence() {
+        assert_eq!(parse_confidence("0.7"), Ok(0.7));
+        assert_eq!(parse_confidence("1"), Ok(1.0));
+        assert!(parse_confidence("1.5").is_err());
+        assert!(parse_confidence("-0.1").is_err());
+        assert!(parse_confidence("high").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+        assert_eq!(format_duration(Duration::from_secs(300)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(330)), "5m 30s");
+        assert_eq!(format_duration(Duration::from_secs(3900)), "1h 5m");
+    }
 }
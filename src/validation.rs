@@ -5,11 +5,17 @@
 
 use std::collections::{HashMap, HashSet};
 
+use serde::{Deserialize, Serialize};
+
 use crate::assessment::types::CommitAssessment;
 use crate::models::{Hunk, HunkId, PlannedChange, PlannedCommit, PlannedCommitId};
 
 /// Issues that can occur in a reorganization plan
-#[derive(Debug, Clone)]
+///
+/// Serialized with a `kind` tag, e.g.
+/// `{"kind": "unassigned_hunks", "hunk_ids": [3, 7]}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ValidationIssue {
     /// A commit has an empty message
     EmptyMessage { commit_id: PlannedCommitId },
@@ -62,6 +68,14 @@ pub enum ValidationIssue {
         commit_b: PlannedCommitId,
     },
 
+    /// A commit modifies a newly-created file before the commit that creates it
+    ModificationBeforeCreation {
+        file_path: std::path::PathBuf,
+        creation_hunk: HunkId,
+        creation_commit: PlannedCommitId,
+        modifying_commit: PlannedCommitId,
+    },
+
     /// A commit failed quality assessment
     FailedAssessment {
         commit_id: PlannedCommitId,
@@ -138,6 +152,21 @@ impl std::fmt::Display for ValidationIssue {
                     commit_b
                 )
             }
+            Self::ModificationBeforeCreation {
+                file_path,
+                creation_hunk,
+                creation_commit,
+                modifying_commit,
+            } => {
+                write!(
+                    f,
+                    "{} modified by {} before it is created by {} (commit {})",
+                    file_path.display(),
+                    modifying_commit,
+                    creation_hunk,
+                    creation_commit
+                )
+            }
             Self::FailedAssessment {
                 commit_id,
                 assessment,
@@ -154,7 +183,7 @@ impl std::fmt::Display for ValidationIssue {
 }
 
 /// Result of validating a plan
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
     pub issues: Vec<ValidationIssue>,
 }
@@ -164,6 +193,15 @@ impl ValidationResult {
         self.issues.is_empty()
     }
 
+    /// Machine-readable report: `{"valid": bool, "issues": [...]}`
+    pub fn to_json(&self) -> String {
+        let report = serde_json::json!({
+            "valid": self.is_valid(),
+            "issues": self.issues,
+        });
+        serde_json::to_string_pretty(&report).unwrap_or_else(|e| format!("Error: {}", e))
+    }
+
     /// Check if there are any fixable issues (unassigned hunks, duplicates, overlapping, failed assessments)
     pub fn has_fixable_issues(&self) -> bool {
         self.issues.iter().any(|issue| {
@@ -238,6 +276,71 @@ impl ValidationResult {
             .collect()
     }
 
+    /// Get files whose creation is ordered after a modification
+    pub fn creation_order_issues(&self) -> Vec<&ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| matches!(issue, ValidationIssue::ModificationBeforeCreation { .. }))
+            .collect()
+    }
+
+    /// One-line summary: counts of duplicate and unassigned hunks with their
+    /// IDs, then a count of any other issues.
+    pub fn summary(&self) -> String {
+        let format_ids = |ids: &[HunkId]| {
+            ids.iter()
+                .map(|id| id.0.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut duplicates: Vec<HunkId> = self
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                ValidationIssue::DuplicateHunkAcrossCommits { hunk_id, .. }
+                | ValidationIssue::DuplicateHunkInCommit { hunk_id, .. } => Some(*hunk_id),
+                _ => None,
+            })
+            .collect();
+        duplicates.sort_by_key(|id| id.0);
+        duplicates.dedup();
+        let mut unassigned = self.unassigned_hunks().unwrap_or_default().to_vec();
+        unassigned.sort_by_key(|id| id.0);
+
+        let mut parts = Vec::new();
+        if !duplicates.is_empty() {
+            parts.push(format!(
+                "{} duplicate hunk(s) [{}]",
+                duplicates.len(),
+                format_ids(&duplicates)
+            ));
+        }
+        if !unassigned.is_empty() {
+            parts.push(format!(
+                "{} unassigned hunk(s) [{}]",
+                unassigned.len(),
+                format_ids(&unassigned)
+            ));
+        }
+        let other = self
+            .issues
+            .iter()
+            .filter(|issue| {
+                !matches!(
+                    issue,
+                    ValidationIssue::DuplicateHunkAcrossCommits { .. }
+                        | ValidationIssue::DuplicateHunkInCommit { .. }
+                        | ValidationIssue::UnassignedHunks { .. }
+                )
+            })
+            .count();
+        if other > 0 {
+            parts.push(format!("{} other issue(s)", other));
+        }
+        parts.join(", ")
+    }
+
     /// Get failed assessment issues
     pub fn failed_assessments(&self) -> Vec<&ValidationIssue> {
         self.issues
@@ -391,9 +494,41 @@ pub fn validate_plan(commits: &[PlannedCommit], hunks: &[Hunk]) -> ValidationRes
         }
     }
 
+    issues.extend(check_creation_order(commits, hunks));
+
     ValidationResult { issues }
 }
 
+/// Whether a hunk creates its file (diffed against an empty old side).
+fn is_creation_hunk(hunk: &Hunk) -> bool {
+    hunk.old_start == 0 && hunk.old_count == 0
+}
+
+/// Check that, for every file created in the range, the commit containing the
+/// creation hunk is applied no later than any other commit touching the file.
+fn check_creation_order(commits: &[PlannedCommit], hunks: &[Hunk]) -> Vec<ValidationIssue> {
+    let mut first_touch: HashMap<&std::path::Path, PlannedCommitId> = HashMap::new();
+    let mut issues = Vec::new();
+
+    for commit in commits {
+        for hunk in commit.changes.iter().filter_map(|c| c.resolve(hunks)) {
+            let first = *first_touch
+                .entry(hunk.file_path.as_path())
+                .or_insert(commit.id);
+            if is_creation_hunk(hunk) && first != commit.id {
+                issues.push(ValidationIssue::ModificationBeforeCreation {
+                    file_path: hunk.file_path.clone(),
+                    creation_hunk: hunk.id,
+                    creation_commit: commit.id,
+                    modifying_commit: first,
+                });
+            }
+        }
+    }
+
+    issues
+}
+
 /// Detect cyclic dependencies using DFS
 fn detect_cycle(commits: &[PlannedCommit]) -> Option<Vec<PlannedCommitId>> {
     let mut visited = HashSet::new();
@@ -500,13 +635,57 @@ pub fn fix_unassigned_hunks(mut commits: Vec<PlannedCommit>, hunks: &[Hunk]) ->
     commits
 }
 
+/// Move file-creation hunks into the first commit that touches their file.
+///
+/// The creation hunk is placed ahead of the other changes to that file so the
+/// file exists in the index before any modification is applied.
+pub fn fix_creation_order(mut commits: Vec<PlannedCommit>, hunks: &[Hunk]) -> Vec<PlannedCommit> {
+    let mut moves: Vec<(usize, usize, usize)> = Vec::new();
+    let mut first_touch: HashMap<&std::path::Path, usize> = HashMap::new();
+
+    for (commit_idx, commit) in commits.iter().enumerate() {
+        for (change_idx, change) in commit.changes.iter().enumerate() {
+            let Some(hunk) = change.resolve(hunks) else {
+                continue;
+            };
+            let first = *first_touch
+                .entry(hunk.file_path.as_path())
+                .or_insert(commit_idx);
+            if is_creation_hunk(hunk) && first != commit_idx {
+                moves.push((commit_idx, change_idx, first));
+            }
+        }
+    }
+
+    // Remove from the back so earlier indices stay valid
+    let mut moved = Vec::new();
+    for &(commit_idx, change_idx, target) in moves.iter().rev() {
+        let change = commits[commit_idx].changes.remove(change_idx);
+        moved.push((target, change));
+    }
+
+    for (target, change) in moved.into_iter().rev() {
+        let file_path = change.resolve(hunks).map(|h| h.file_path.clone());
+        let target_changes = &mut commits[target].changes;
+        let position = target_changes
+            .iter()
+            .position(|c| c.resolve(hunks).map(|h| &h.file_path) == file_path.as_ref())
+            .unwrap_or(target_changes.len());
+        target_changes.insert(position, change);
+    }
+
+    commits.retain(|c| !c.changes.is_empty());
+    commits
+}
+
 /// Apply all deterministic fixes to a plan
 pub fn apply_deterministic_fixes(
     commits: Vec<PlannedCommit>,
     hunks: &[Hunk],
 ) -> Vec<PlannedCommit> {
     let deduped = fix_duplicate_hunks(commits);
-    fix_unassigned_hunks(deduped, hunks)
+    let completed = fix_unassigned_hunks(deduped, hunks);
+    fix_creation_order(completed, hunks)
 }
 
 #[cfg(test)]
@@ -556,6 +735,53 @@ mod tests {
         assert!(result.is_valid());
     }
 
+    #[test]
+    fn test_summary_lists_duplicate_and_unassigned_ids() {
+        let hunks = vec![make_hunk(0), make_hunk(1), make_hunk(2)];
+        let commits = vec![
+            make_commit(0, "First commit", vec![0]),
+            make_commit(1, "", vec![0]),
+        ];
+
+        let result = validate_plan(&commits, &hunks);
+        assert_eq!(
+            result.summary(),
+            "1 duplicate hunk(s) [0], 2 unassigned hunk(s) [1, 2], 2 other issue(s)"
+        );
+    }
+
+    #[test]
+    fn test_json_report_shape_round_trips() {
+        let hunks = vec![make_hunk(0), make_hunk(1)];
+        let commits = vec![make_commit(0, "First commit", vec![0])];
+        let result = validate_plan(&commits, &hunks);
+
+        let json: serde_json::Value = serde_json::from_str(&result.to_json()).unwrap();
+        assert_eq!(json["valid"], false);
+        assert_eq!(
+            json["issues"][0],
+            serde_json::json!({"kind": "unassigned_hunks", "hunk_ids": [1]})
+        );
+
+        let parsed: ValidationResult =
+            serde_json::from_value(serde_json::json!({"issues": json["issues"]})).unwrap();
+        assert_eq!(parsed.unassigned_hunks(), Some(&[HunkId(1)][..]));
+    }
+
+    #[test]
+    fn test_overlap_serializes_path_as_string() {
+        let issue = ValidationIssue::OverlappingHunks {
+            file_path: PathBuf::from("src/lib.rs"),
+            hunk_a: HunkId(0),
+            commit_a: PlannedCommitId(0),
+            hunk_b: HunkId(1),
+            commit_b: PlannedCommitId(1),
+        };
+        let json = serde_json::to_value(&issue).unwrap();
+        assert_eq!(json["kind"], "overlapping_hunks");
+        assert_eq!(json["file_path"], "src/lib.rs");
+    }
+
     #[test]
     fn test_empty_message() {
         let hunks = vec![make_hunk(0)];
@@ -796,4 +1022,79 @@ mod tests {
         // Should be valid after fixes (no duplicates, no unassigned)
         assert!(result.is_valid(), "Issues: {:?}", result.issues);
     }
+
+    fn make_creation_split() -> Vec<Hunk> {
+        let create = Hunk {
+            id: HunkId(0),
+            file_path: PathBuf::from("new.rs"),
+            old_start: 0,
+            old_count: 0,
+            new_start: 1,
+            new_count: 2,
+            lines: vec![DiffLine::Added("a".into()), DiffLine::Added("b".into())],
+            likely_source_commits: vec![],
+            old_missing_newline_at_eof: false,
+            new_missing_newline_at_eof: false,
+        };
+        let modify = Hunk {
+            id: HunkId(1),
+            file_path: PathBuf::from("new.rs"),
+            old_start: 2,
+            old_count: 1,
+            new_start: 2,
+            new_count: 2,
+            lines: vec![DiffLine::Context("b".into()), DiffLine::Added("c".into())],
+            likely_source_commits: vec![],
+            old_missing_newline_at_eof: false,
+            new_missing_newline_at_eof: false,
+        };
+        vec![create, modify]
+    }
+
+    #[test]
+    fn test_modification_before_creation() {
+        let hunks = make_creation_split();
+        let commits = vec![
+            make_commit(0, "Modify", vec![1]),
+            make_commit(1, "Create", vec![0]),
+        ];
+
+        let result = validate_plan(&commits, &hunks);
+        let issues = result.creation_order_issues();
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(
+            issues[0],
+            ValidationIssue::ModificationBeforeCreation {
+                creation_commit: PlannedCommitId(1),
+                modifying_commit: PlannedCommitId(0),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_fix_creation_order() {
+        let mut hunks = make_creation_split();
+        hunks.push(make_hunk(2));
+        let commits = vec![
+            make_commit(0, "Modify", vec![1]),
+            make_commit(1, "Create", vec![0, 2]),
+        ];
+
+        let fixed = fix_creation_order(commits, &hunks);
+
+        assert_eq!(fixed.len(), 2);
+        let ids = |commit: &PlannedCommit| -> Vec<HunkId> {
+            commit
+                .changes
+                .iter()
+                .filter_map(|c| c.resolve(&hunks).map(|h| h.id))
+                .collect()
+        };
+        assert_eq!(ids(&fixed[0]), vec![HunkId(0), HunkId(1)]);
+        assert_eq!(ids(&fixed[1]), vec![HunkId(2)]);
+        assert!(validate_plan(&fixed, &hunks)
+            .creation_order_issues()
+            .is_empty());
+    }
 }
@@ -4,7 +4,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use git_reabsorb::git::{Git, GitOps};
+use git_reabsorb::git::{Git, GitError, GitOps, DEFAULT_REF_PREFIX};
 use git_reabsorb::models::{Hunk, Strategy};
 use git_reabsorb::patch::PatchContext;
 use git_reabsorb::reorganize::{GroupByFile, PreserveOriginal, Reorganizer, Squash};
@@ -793,6 +793,49 @@ fn test_overwrite_pre_reabsorb_head() {
     );
 }
 
+#[test]
+fn test_create_backup_branch_and_tag_never_move_existing_refs() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let first = repo.commit("Initial commit");
+    repo.write_file("src/main.rs", "fn main() {}\n");
+    repo.stage_all();
+    let second = repo.commit("Add main.rs");
+
+    repo.git
+        .create_branch_at("reabsorb/backup/1", &first)
+        .unwrap();
+    repo.git.create_tag("reabsorb/backup/1", &first).unwrap();
+    assert_eq!(
+        repo.git
+            .resolve_ref("refs/heads/reabsorb/backup/1")
+            .unwrap(),
+        first
+    );
+    assert_eq!(
+        repo.git.resolve_ref("refs/tags/reabsorb/backup/1").unwrap(),
+        first
+    );
+
+    assert!(matches!(
+        repo.git.create_branch_at("reabsorb/backup/1", &second),
+        Err(GitError::RefExists(name)) if name == "reabsorb/backup/1"
+    ));
+    assert!(matches!(
+        repo.git.create_tag("reabsorb/backup/1", &second),
+        Err(GitError::RefExists(_))
+    ));
+    assert_eq!(
+        repo.git
+            .resolve_ref("refs/heads/reabsorb/backup/1")
+            .unwrap(),
+        first
+    );
+    assert!(repo.git.create_branch_at("bad..name", &second).is_err());
+}
+
 #[test]
 fn test_diff_trees_matches_identical_commits() {
     let repo = TestRepo::new();
@@ -821,6 +864,53 @@ fn test_diff_trees_detects_changes() {
     assert!(diff.contains("diff --git"));
 }
 
+#[test]
+fn test_diff_trees_paths_limits_output_to_the_paths() {
+    let repo = TestRepo::new();
+
+    repo.write_file("src/lib.rs", "fn a() {}\n");
+    repo.write_file("docs/guide.md", "# Guide\n");
+    repo.write_file("README.md", "# Readme\n");
+    repo.stage_all();
+    let first = repo.commit("init");
+
+    repo.write_file("src/lib.rs", "fn b() {}\n");
+    repo.write_file("docs/guide.md", "# Guide 2\n");
+    repo.write_file("README.md", "# Readme 2\n");
+    repo.stage_all();
+    let second = repo.commit("update");
+
+    let diff = repo
+        .git
+        .diff_trees_paths(&first, &second, &[Path::new("src"), Path::new("README.md")])
+        .unwrap();
+    assert!(diff.contains("diff --git a/src/lib.rs"));
+    assert!(diff.contains("diff --git a/README.md"));
+    assert!(!diff.contains("docs/guide.md"));
+
+    // No paths means nothing to diff, not the whole tree
+    assert_eq!(repo.git.diff_trees_paths(&first, &second, &[]).unwrap(), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_diff_trees_paths_accepts_non_utf8_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let repo = TestRepo::new();
+    let name = Path::new(OsStr::from_bytes(b"notes-\xff.txt"));
+    fs::write(repo.path.join(name), "one\n").unwrap();
+    repo.stage_all();
+    let first = repo.commit("init");
+    fs::write(repo.path.join(name), "two\n").unwrap();
+    repo.stage_all();
+    let second = repo.commit("update");
+
+    let diff = repo.git.diff_trees_paths(&first, &second, &[name]).unwrap();
+    assert!(diff.contains("+two"), "{}", diff);
+}
+
 // ============================================================================
 // Branch Base Tests
 // ============================================================================
@@ -882,6 +972,124 @@ fn test_find_merge_base_with_diverged_branches() {
     assert_eq!(merge_base, initial_commit);
 }
 
+#[test]
+fn test_find_branch_base_tries_candidates_in_order() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let main_head = repo.commit("Initial commit");
+
+    run_git(&repo.path, &["checkout", "-b", "develop"]);
+    repo.write_file("develop.txt", "develop\n");
+    repo.stage_all();
+    let develop_head = repo.commit("Develop work");
+
+    run_git(&repo.path, &["checkout", "-b", "feature"]);
+    repo.write_file("feature.txt", "feature\n");
+    repo.stage_all();
+    repo.commit("Feature work");
+
+    let branches = |names: &[&str]| names.iter().map(|s| s.to_string()).collect();
+    let develop_first =
+        Git::with_work_dir(&repo.path).with_base_branches(branches(&["trunk", "develop", "main"]));
+    assert_eq!(develop_first.find_branch_base().unwrap(), develop_head);
+
+    let main_first =
+        Git::with_work_dir(&repo.path).with_base_branches(branches(&["main", "develop"]));
+    assert_eq!(main_first.find_branch_base().unwrap(), main_head);
+}
+
+#[test]
+fn test_find_branch_base_falls_back_to_upstream() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    run_git(&repo.path, &["branch", "-m", "main", "integration"]);
+
+    run_git(&repo.path, &["checkout", "-b", "feature"]);
+    repo.write_file("feature.txt", "feature\n");
+    repo.stage_all();
+    repo.commit("Feature work");
+
+    // Neither main nor master exists and there's no upstream yet
+    match repo.git.find_branch_base() {
+        Err(GitError::NoBranchBase(tried)) => assert_eq!(tried, vec!["main", "master", "@{u}"]),
+        other => panic!("expected NoBranchBase, got {:?}", other),
+    }
+
+    run_git(&repo.path, &["branch", "--set-upstream-to", "integration"]);
+    assert_eq!(repo.git.find_branch_base().unwrap(), base);
+    // The upstream is reported by name, not as "@{u}"
+    assert_eq!(repo.git.detect_branch_base().unwrap().branch, "integration");
+}
+
+#[test]
+fn test_upstream_of() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    repo.commit("Initial commit");
+    run_git(&repo.path, &["checkout", "-b", "feature"]);
+
+    // No upstream configured is not an error
+    assert_eq!(repo.git.upstream_of("HEAD").unwrap(), None);
+    assert_eq!(repo.git.upstream_of("feature").unwrap(), None);
+
+    run_git(&repo.path, &["branch", "--set-upstream-to", "main"]);
+    assert_eq!(
+        repo.git.upstream_of("HEAD").unwrap(),
+        Some("main".to_string())
+    );
+    assert_eq!(
+        repo.git.upstream_of("feature").unwrap(),
+        Some("main".to_string())
+    );
+    assert_eq!(repo.git.upstream_of("main").unwrap(), None);
+
+    // A detached HEAD has no upstream either
+    run_git(&repo.path, &["checkout", "--detach"]);
+    assert_eq!(repo.git.upstream_of("HEAD").unwrap(), None);
+
+    assert!(repo.git.upstream_of("no-such-branch").is_err());
+}
+
+#[test]
+fn test_resolve_range_reports_origin() {
+    use git_reabsorb::cli::{CommitRange, RangeOrigin};
+
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    run_git(&repo.path, &["checkout", "-b", "feature"]);
+    repo.write_file("feature.txt", "feature\n");
+    repo.stage_all();
+    repo.commit("Feature work");
+
+    let (range, origin) = CommitRange::resolve_with_origin(None, None, &repo.git).unwrap();
+    assert_eq!(range.base, base);
+    assert_eq!(
+        origin,
+        RangeOrigin::AutoDetected {
+            branch: "main".to_string()
+        }
+    );
+    assert_eq!(
+        origin.to_string(),
+        "auto-detected base 'main' (merge-base with HEAD)"
+    );
+
+    let (_, origin) = CommitRange::resolve_with_origin(None, Some("main"), &repo.git).unwrap();
+    assert_eq!(origin, RangeOrigin::BaseTip("main".to_string()));
+
+    let explicit: CommitRange = "main..HEAD".parse().unwrap();
+    let (_, origin) = CommitRange::resolve_with_origin(Some(&explicit), None, &repo.git).unwrap();
+    assert_eq!(origin, RangeOrigin::Explicit);
+}
+
 // ============================================================================
 // New File Detection Tests
 // ============================================================================
@@ -984,6 +1192,58 @@ fn test_get_new_files_in_nested_directories() {
     assert!(new_files.contains(&"src/utils/helpers/string.ts".to_string()));
 }
 
+#[test]
+fn test_get_file_statuses_in_commit() {
+    use git_reabsorb::git::FileStatus;
+
+    let repo = TestRepo::new();
+
+    repo.write_file("keep.txt", "keep\n");
+    repo.write_file("remove.txt", "remove\n");
+    repo.write_file(
+        "old_name.txt",
+        "a file long enough\nto be detected\nas a rename\n",
+    );
+    repo.stage_all();
+    repo.commit("Initial commit");
+
+    repo.write_file("keep.txt", "keep, changed\n");
+    fs::remove_file(repo.path.join("remove.txt")).unwrap();
+    fs::rename(
+        repo.path.join("old_name.txt"),
+        repo.path.join("new_name.txt"),
+    )
+    .unwrap();
+    repo.write_file("added.txt", "added\n");
+    repo.stage_all();
+    let commit_sha = repo.commit("Mixed changes");
+
+    let mut statuses = repo.git.get_file_statuses_in_commit(&commit_sha).unwrap();
+    statuses.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        statuses,
+        vec![
+            ("added.txt".to_string(), FileStatus::Added),
+            ("keep.txt".to_string(), FileStatus::Modified),
+            (
+                "new_name.txt".to_string(),
+                FileStatus::Renamed {
+                    from: "old_name.txt".to_string()
+                }
+            ),
+            ("remove.txt".to_string(), FileStatus::Deleted),
+        ]
+    );
+
+    // The compatibility wrappers report renames as delete + add
+    let changed = repo.git.get_files_changed_in_commit(&commit_sha).unwrap();
+    assert!(changed.contains(&"old_name.txt".to_string()));
+    assert!(changed.contains(&"new_name.txt".to_string()));
+    let mut new_files = repo.git.get_new_files_in_commit(&commit_sha).unwrap();
+    new_files.sort();
+    assert_eq!(new_files, vec!["added.txt", "new_name.txt"]);
+}
+
 // ============================================================================
 // Working Tree Diff Tests
 // ============================================================================
@@ -1140,7 +1400,7 @@ fn helper_two() {
     );
 
     // Commit to verify everything staged correctly
-    let sha = repo.git.commit("Test commit", false).unwrap();
+    let sha = repo.git.commit("Test commit", false, false).unwrap().sha;
     assert!(!sha.is_empty());
 }
 
@@ -1321,7 +1581,7 @@ fn test_commit_without_no_verify() {
     repo.stage_all();
 
     // Commit without no_verify
-    let result = repo.git.commit("Add main.rs", false);
+    let result = repo.git.commit("Add main.rs", false, false);
     assert!(result.is_ok());
 }
 
@@ -1354,19 +1614,45 @@ fn test_commit_with_no_verify() {
     repo.stage_all();
 
     // Without no_verify, commit should fail (hook returns exit 1)
-    let _result_without = repo.git.commit("Should fail", false);
+    let _result_without = repo.git.commit("Should fail", false, false);
 
     // Re-stage if needed (commit failure might unstage)
     repo.stage_all();
 
     // With no_verify, commit should succeed
-    let result_with = repo.git.commit("Should succeed", true);
+    let result_with = repo.git.commit("Should succeed", true, false);
     assert!(
         result_with.is_ok(),
         "Commit with --no-verify should skip hooks"
     );
 }
 
+#[test]
+fn test_commit_with_nothing_staged_is_skipped_unless_allowed() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let head = repo.commit("Initial commit");
+
+    let skipped = repo.git.commit("Nothing", false, false).unwrap();
+    assert!(!skipped.created);
+    assert!(skipped.empty);
+    assert_eq!(skipped.sha, head);
+    assert_eq!(repo.git.get_head().unwrap(), head);
+
+    let allowed = repo.git.commit("Nothing, on purpose", false, true).unwrap();
+    assert!(allowed.created);
+    assert!(allowed.empty);
+    assert_ne!(allowed.sha, head);
+    assert_eq!(repo.git.get_head().unwrap(), allowed.sha);
+
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    let made = repo.git.commit("Add a", false, false).unwrap();
+    assert!(made.created);
+    assert!(!made.empty);
+}
+
 // ============================================================================
 // Apply Hunks to Index Tests
 // ============================================================================
@@ -1436,23 +1722,49 @@ fn test_apply_hunks_from_multiple_files() {
     assert!(result.is_ok());
 
     // Commit to verify
-    let sha = repo.git.commit("Test", false).unwrap();
+    let sha = repo.git.commit("Test", false, false).unwrap().sha;
     assert!(!sha.is_empty());
 }
 
+#[test]
+fn test_apply_hunk_that_no_longer_applies_reports_file() {
+    let repo = TestRepo::new();
+
+    repo.write_file("src/main.rs", "fn main() {\n    // original\n}\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    repo.write_file("src/main.rs", "fn main() {\n    // changed\n}\n");
+    repo.stage_all();
+    repo.commit("Modify");
+
+    repo.git.reset_to(&base).unwrap();
+    let diff = repo.git.get_working_tree_diff().unwrap();
+    let hunks = git_reabsorb::patch::parse(&diff, &[], 0).unwrap().hunks;
+
+    // Change the indexed file so the hunk's preimage is gone
+    repo.write_file("src/main.rs", "fn other() {}\n");
+    repo.stage_all();
+
+    match repo.git.apply_hunk_to_index(&hunks[0]) {
+        Err(GitError::ApplyFailed { file, .. }) => assert_eq!(file, "src/main.rs"),
+        other => panic!("expected ApplyFailed, got {:?}", other),
+    }
+}
+
 // ============================================================================
 // Plan File Tests
 // ============================================================================
 
 use git_reabsorb::models::{
-    CommitDescription, HunkId, PlannedChange, PlannedCommit, PlannedCommitId,
+    CommitDescription, DiffLine, HunkId, PlannedChange, PlannedCommit, PlannedCommitId,
 };
 use git_reabsorb::plan_store::{delete_plan, has_saved_plan, load_plan, save_plan, SavedPlan};
 
 const TEST_REF_NAMESPACE: &str = "test-branch";
 
 fn test_pre_reabsorb_ref() -> String {
-    git_reabsorb::git::pre_reabsorb_ref_for(TEST_REF_NAMESPACE)
+    git_reabsorb::git::pre_reabsorb_ref_for(DEFAULT_REF_PREFIX, TEST_REF_NAMESPACE)
 }
 use std::collections::HashMap;
 
@@ -1742,6 +2054,135 @@ fn test_plan_stores_file_mappings() {
     delete_plan(&namespace).unwrap();
 }
 
+#[test]
+fn test_state_dir_moves_plan_storage_out_of_git_dir() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    repo.commit("Add a");
+    let state_dir = tempfile::tempdir().unwrap();
+
+    let save = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .env("GIT_REABSORB_DIR", state_dir.path())
+        .args(["plan", "--base", &base, "-s", "preserve", "--save-plan"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+    assert!(
+        save.status.success(),
+        "{}",
+        String::from_utf8_lossy(&save.stderr)
+    );
+    assert!(state_dir.path().join("main/plan.json").is_file());
+    assert!(!repo.path.join(".git/reabsorb").exists());
+
+    let check = |state_dir: Option<&Path>| {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"));
+        cmd.current_dir(&repo.path).env_remove("GIT_REABSORB_DIR");
+        if let Some(dir) = state_dir {
+            cmd.arg("--state-dir").arg(dir);
+        }
+        cmd.arg("check")
+            .output()
+            .expect("Failed to run git-reabsorb")
+    };
+    assert!(check(Some(state_dir.path())).status.success());
+    assert!(!check(None).status.success());
+}
+
+#[test]
+fn test_comment_hints_group_hunks_and_can_be_stripped() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.rs", "// reabsorb: Add greeting\nfn a() {}\n");
+    repo.write_file("b.rs", "fn b() {}\n");
+    repo.stage_all();
+    repo.commit("Work");
+    repo.write_file("c.py", "# reabsorb: Add greeting\nprint('hi')\n");
+    repo.stage_all();
+    repo.commit("More work");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--base", &base, "-s", "comment-hint", "--strip-hints"])
+        .args(["--no-editor"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let subjects = run_git(
+        &repo.path,
+        &["log", "--format=%s", &format!("{}..HEAD", base)],
+    );
+    assert_eq!(
+        subjects.lines().collect::<Vec<_>>(),
+        vec!["Add greeting", "Work"]
+    );
+    let files = run_git(&repo.path, &["show", "--name-only", "--format=", "HEAD"]);
+    assert_eq!(files.lines().collect::<Vec<_>>(), vec!["a.rs", "c.py"]);
+
+    // The hints are left out of the commits but kept in the work tree
+    assert_eq!(run_git(&repo.path, &["show", "HEAD:a.rs"]), "fn a() {}\n");
+    assert_eq!(run_git(&repo.path, &["show", "HEAD:c.py"]), "print('hi')\n");
+    assert!(fs::read_to_string(repo.path.join("a.rs"))
+        .unwrap()
+        .starts_with("// reabsorb: Add greeting"));
+}
+
+#[test]
+fn test_keep_plan_archives_applied_plan_for_list() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    repo.commit("Add a");
+
+    let reabsorb = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .args(args)
+            .output()
+            .expect("Failed to run git-reabsorb");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+
+    reabsorb(&["--base", &base, "--no-editor", "--keep-plan"]);
+
+    assert!(!repo.path.join(".git/reabsorb/main/plan.json").exists());
+    let archived: Vec<_> = fs::read_dir(repo.path.join(".git/reabsorb/archive"))
+        .unwrap()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    assert_eq!(archived.len(), 1);
+    let plan: SavedPlan = serde_json::from_str(&fs::read_to_string(&archived[0]).unwrap()).unwrap();
+    let head = run_git(&repo.path, &["rev-parse", "HEAD"]);
+    assert_eq!(
+        plan.commits.last().unwrap().created_sha.as_deref(),
+        Some(head.trim())
+    );
+
+    let listing = reabsorb(&["list", "--archived"]);
+    assert!(listing.contains("Preserve, 1 commit(s)"), "{}", listing);
+    assert!(reabsorb(&["list"]).contains("No saved plans."));
+}
+
 // ============================================================================
 // End-to-End Split Commit Tests
 // ============================================================================
@@ -1840,12 +2281,20 @@ fn helper_two() {
 
     // Apply first group and commit
     repo.git.apply_hunks_to_index(&first_group, &ctx).unwrap();
-    let first_sha = repo.git.commit("First split commit", false).unwrap();
+    let first_sha = repo
+        .git
+        .commit("First split commit", false, false)
+        .unwrap()
+        .sha;
     assert!(!first_sha.is_empty());
 
     // Apply second group and commit
     repo.git.apply_hunks_to_index(&second_group, &ctx).unwrap();
-    let second_sha = repo.git.commit("Second split commit", false).unwrap();
+    let second_sha = repo
+        .git
+        .commit("Second split commit", false, false)
+        .unwrap()
+        .sha;
     assert!(!second_sha.is_empty());
 
     // Verify we have two distinct commits
@@ -1945,12 +2394,20 @@ fn function_b() {
     let func_a_hunks: Vec<&git_reabsorb::models::Hunk> = vec![&hunks[0]];
     let ctx = PatchContext::empty();
     repo.git.apply_hunks_to_index(&func_a_hunks, &ctx).unwrap();
-    let commit_a = repo.git.commit("Implement function_a", false).unwrap();
+    let commit_a = repo
+        .git
+        .commit("Implement function_a", false, false)
+        .unwrap()
+        .sha;
 
     // Commit function_b changes second
     let func_b_hunks: Vec<&git_reabsorb::models::Hunk> = hunks.iter().skip(1).collect();
     repo.git.apply_hunks_to_index(&func_b_hunks, &ctx).unwrap();
-    let commit_b = repo.git.commit("Implement function_b", false).unwrap();
+    let commit_b = repo
+        .git
+        .commit("Implement function_b", false, false)
+        .unwrap()
+        .sha;
 
     // Verify
     assert_ne!(commit_a, commit_b);
@@ -2043,8 +2500,9 @@ impl Calculator {
     repo.git.apply_hunks_to_index(&hunk_refs, &ctx).unwrap();
     let new_sha = repo
         .git
-        .commit("Reorganized: implement both methods", false)
-        .unwrap();
+        .commit("Reorganized: implement both methods", false, false)
+        .unwrap()
+        .sha;
 
     // Verify the new commit exists and file content is correct
     assert!(!new_sha.is_empty());
@@ -2286,7 +2744,7 @@ fn test_squash_with_file_deletions() {
     let hunk_refs: Vec<&git_reabsorb::models::Hunk> = hunks.iter().collect();
     let ctx = PatchContext::empty();
     repo.git.apply_hunks_to_index(&hunk_refs, &ctx).unwrap();
-    repo.git.commit("Squashed", false).unwrap();
+    repo.git.commit("Squashed", false, false).unwrap();
 
     // Verify the deletions were applied
     assert!(
@@ -2480,7 +2938,11 @@ fn test_reorder_commits_creating_new_file_from_modifications() {
     run_git(&repo.path, &["add", "file.txt"]);
 
     // Commit and verify
-    let sha = repo.git.commit("Squashed commit", false).unwrap();
+    let sha = repo
+        .git
+        .commit("Squashed commit", false, false)
+        .unwrap()
+        .sha;
     assert!(!sha.is_empty());
 
     // Verify file content is correct (final state, not with removed content added back)
@@ -2546,50 +3008,131 @@ fn test_resolve_ref_for_head() {
     assert_eq!(resolved_after, second_sha);
 }
 
-/// Test that single-ref range syntax (e.g., "main") correctly implies "main..HEAD"
-/// by verifying that read_commits returns the expected commits.
+/// Test that a mistyped branch name reports a suggestion
 #[test]
-fn test_single_ref_range_implies_head() {
+fn test_resolve_ref_missing_suggests_branch() {
     let repo = TestRepo::new();
 
-    // Create initial commit on main
     repo.write_file("README.md", "# Test\n");
     repo.stage_all();
-    let base = repo.commit("Initial commit");
-
-    // Create two more commits
-    repo.write_file("src/a.rs", "// a\n");
-    repo.stage_all();
-    repo.commit("Add a.rs");
-
-    repo.write_file("src/b.rs", "// b\n");
-    repo.stage_all();
-    repo.commit("Add b.rs");
-
-    // Using base..HEAD should give us 2 commits
-    let commits_explicit = repo.git.read_commits(&base, "HEAD").unwrap();
-    assert_eq!(commits_explicit.len(), 2);
+    repo.commit("Initial commit");
 
-    // Using just the base SHA (single ref) should give the same result
-    // when combined with get_head() - this is what resolve_range does
-    let head = repo.git.get_head().unwrap();
-    let commits_single_ref = repo.git.read_commits(&base, &head).unwrap();
-    assert_eq!(commits_single_ref.len(), 2);
+    match repo.git.resolve_ref("mian") {
+        Err(GitError::RefNotFound {
+            reference,
+            suggestion,
+        }) => {
+            assert_eq!(reference, "mian");
+            assert_eq!(suggestion.as_deref(), Some("main"));
+        }
+        other => panic!("expected RefNotFound, got {:?}", other),
+    }
 
-    // Both should return the same commits
-    assert_eq!(commits_explicit[0].sha, commits_single_ref[0].sha);
-    assert_eq!(commits_explicit[1].sha, commits_single_ref[1].sha);
+    match repo.git.resolve_ref("completely-unrelated-name") {
+        Err(GitError::RefNotFound { suggestion, .. }) => assert_eq!(suggestion, None),
+        other => panic!("expected RefNotFound, got {:?}", other),
+    }
 }
 
-/// Test that using a branch name as the base in a single-ref range works
+/// Test that a name shared by a branch and a tag is reported as ambiguous
 #[test]
-fn test_single_ref_range_with_branch_name() {
+fn test_resolve_ref_ambiguous() {
     let repo = TestRepo::new();
 
-    // Create initial commit on main
     repo.write_file("README.md", "# Test\n");
     repo.stage_all();
-    repo.commit("Initial commit on main");
+    repo.commit("Initial commit");
+    run_git(&repo.path, &["branch", "release"]);
+    run_git(&repo.path, &["tag", "release"]);
+
+    assert!(matches!(
+        repo.git.resolve_ref("release"),
+        Err(GitError::AmbiguousRef(_))
+    ));
+}
+
+/// Test that shorthand and reflog revisions still resolve
+#[test]
+fn test_resolve_ref_at_and_reflog_syntax() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let first_sha = repo.commit("Initial commit");
+
+    repo.write_file("src/main.rs", "fn main() {}\n");
+    repo.stage_all();
+    let second_sha = repo.commit("Add main.rs");
+
+    assert_eq!(repo.git.resolve_ref("@").unwrap(), second_sha);
+    assert_eq!(repo.git.resolve_ref("HEAD@{1}").unwrap(), first_sha);
+}
+
+/// Abbreviations follow core.abbrev, and fall back to truncation for
+/// objects git doesn't know
+#[test]
+fn test_abbrev_respects_core_abbrev() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let sha = repo.commit("Initial commit");
+
+    run_git(&repo.path, &["config", "core.abbrev", "12"]);
+    assert_eq!(repo.git.abbrev(&sha), sha[..12]);
+
+    run_git(&repo.path, &["config", "core.abbrev", "5"]);
+    let short = repo.git.abbrev(&sha);
+    assert!(sha.starts_with(&short));
+    assert!(short.len() >= 5 && short.len() < 12);
+
+    let unknown = "0123456789abcdef0123456789abcdef01234567";
+    assert_eq!(repo.git.abbrev(unknown), "01234567");
+}
+
+/// Test that single-ref range syntax (e.g., "main") correctly implies "main..HEAD"
+/// by verifying that read_commits returns the expected commits.
+#[test]
+fn test_single_ref_range_implies_head() {
+    let repo = TestRepo::new();
+
+    // Create initial commit on main
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    // Create two more commits
+    repo.write_file("src/a.rs", "// a\n");
+    repo.stage_all();
+    repo.commit("Add a.rs");
+
+    repo.write_file("src/b.rs", "// b\n");
+    repo.stage_all();
+    repo.commit("Add b.rs");
+
+    // Using base..HEAD should give us 2 commits
+    let commits_explicit = repo.git.read_commits(&base, "HEAD").unwrap();
+    assert_eq!(commits_explicit.len(), 2);
+
+    // Using just the base SHA (single ref) should give the same result
+    // when combined with get_head() - this is what resolve_range does
+    let head = repo.git.get_head().unwrap();
+    let commits_single_ref = repo.git.read_commits(&base, &head).unwrap();
+    assert_eq!(commits_single_ref.len(), 2);
+
+    // Both should return the same commits
+    assert_eq!(commits_explicit[0].sha, commits_single_ref[0].sha);
+    assert_eq!(commits_explicit[1].sha, commits_single_ref[1].sha);
+}
+
+/// Test that using a branch name as the base in a single-ref range works
+#[test]
+fn test_single_ref_range_with_branch_name() {
+    let repo = TestRepo::new();
+
+    // Create initial commit on main
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    repo.commit("Initial commit on main");
 
     // Create a feature branch
     run_git(&repo.path, &["checkout", "-b", "feature"]);
@@ -2617,3 +3160,2227 @@ fn test_single_ref_range_with_branch_name() {
     assert_eq!(commits[0].message.short, "Add feature.rs");
     assert_eq!(commits[1].message.short, "Add more.rs");
 }
+
+// ============================================================================
+// Plan Execution Tests
+// ============================================================================
+
+use git_reabsorb::app::PlanExecutor;
+use git_reabsorb::editor::SystemEditor;
+use git_reabsorb::plan_store::{FilePlanStore, PlanStore};
+
+/// Reset to `base` and execute `planned` with the real executor, returning the created SHAs.
+fn execute_plan(
+    repo: &TestRepo,
+    base: &str,
+    planned: &[PlannedCommit],
+    hunks: &[Hunk],
+    file_changes: &[git_reabsorb::models::FileChange],
+) -> Vec<String> {
+    let head = repo.git.get_head().unwrap();
+    let store = FilePlanStore::new(format!("exec-{}", uuid()));
+    let mut plan = SavedPlan::new(
+        Strategy::Preserve,
+        base.to_string(),
+        head,
+        planned,
+        hunks,
+        &HashMap::new(),
+        file_changes,
+    );
+
+    repo.git.reset_to(base).unwrap();
+    let editor = SystemEditor::new();
+    let executor = PlanExecutor::new(&repo.git, &editor, &store);
+    let result = executor.execute(hunks, planned, file_changes, true, true, &mut plan);
+    let _ = store.delete();
+    result.unwrap();
+
+    plan.commits
+        .iter()
+        .filter_map(|c| c.created_sha.clone())
+        .collect()
+}
+
+#[test]
+fn test_deleted_file_lands_in_exactly_one_commit() {
+    let repo = TestRepo::new();
+
+    repo.write_file("keep.txt", "keep\n");
+    repo.write_file("gone.txt", "gone\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    fs::remove_file(repo.path.join("gone.txt")).unwrap();
+    repo.write_file("keep.txt", "keep\nmore\n");
+    repo.stage_all();
+    let head = repo.commit("Delete gone.txt and extend keep.txt");
+
+    let diff = repo.git.diff_trees(&base, &head).unwrap();
+    let patch = git_reabsorb::patch::parse(&diff, &[], 0).unwrap();
+    let commits = repo.read_commits(&base, &head);
+    let planned = GroupByFile.plan(&commits, &patch.hunks).unwrap();
+    assert_eq!(planned.len(), 2);
+
+    let created = execute_plan(&repo, &base, &planned, &patch.hunks, &patch.file_changes);
+    assert_eq!(created.len(), 2);
+
+    let deleting: Vec<_> = created
+        .iter()
+        .filter(|sha| {
+            run_git(&repo.path, &["show", "--name-status", "--format=", sha])
+                .contains("D\tgone.txt")
+        })
+        .collect();
+    assert_eq!(deleting.len(), 1, "Deletion should land in one commit");
+
+    assert!(repo.git.diff_trees(&head, "HEAD").unwrap().is_empty());
+    assert!(!repo.git.file_in_index(Path::new("gone.txt")).unwrap());
+}
+
+/// Execute the top and EOF hunks of a file without a trailing newline as
+/// separate commits, in both orders, checking the file after each commit.
+#[test]
+fn test_missing_newline_at_eof_follows_the_last_line() {
+    let lines = |last: &str| {
+        let mut content: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        content.push_str(last);
+        content
+    };
+    let before = lines("last");
+    let after = lines("last changed").replacen("line 2\n", "line two\n", 1);
+
+    for eof_first in [true, false] {
+        let repo = TestRepo::new();
+        repo.write_file("file.txt", &before);
+        repo.stage_all();
+        let base = repo.commit("Initial commit");
+        repo.write_file("file.txt", &after);
+        repo.stage_all();
+        let head = repo.commit("Change the top and the last line");
+
+        let diff = repo.git.diff_trees(&base, &head).unwrap();
+        let patch = git_reabsorb::patch::parse(&diff, &[], 0).unwrap();
+        assert_eq!(patch.hunks.len(), 2);
+        let (top, eof) = (&patch.hunks[0], &patch.hunks[1]);
+        assert!(eof.old_missing_newline_at_eof && eof.new_missing_newline_at_eof);
+
+        let order = if eof_first { [eof, top] } else { [top, eof] };
+        let planned: Vec<PlannedCommit> = order
+            .iter()
+            .enumerate()
+            .map(|(i, hunk)| {
+                PlannedCommit::from_hunk_ids(
+                    PlannedCommitId(i),
+                    CommitDescription::short_only(format!("Part {}", i + 1)),
+                    vec![hunk.id],
+                )
+            })
+            .collect();
+        let created = execute_plan(&repo, &base, &planned, &patch.hunks, &patch.file_changes);
+        assert_eq!(created.len(), 2);
+
+        let middle = run_git(&repo.path, &["show", &format!("{}:file.txt", created[0])]);
+        let expected_middle = if eof_first {
+            lines("last changed")
+        } else {
+            lines("last").replacen("line 2\n", "line two\n", 1)
+        };
+        assert_eq!(middle, expected_middle, "eof_first={}", eof_first);
+        assert!(!middle.ends_with('\n'));
+        assert_eq!(
+            fs::read_to_string(repo.path.join("file.txt")).unwrap(),
+            after
+        );
+        assert!(repo.git.diff_trees(&head, "HEAD").unwrap().is_empty());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_mode_only_change_survives_reabsorb() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    repo.write_file("run.sh", "#!/bin/sh\necho hi\n");
+    repo.write_file("lib.txt", "one\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    let script = repo.path.join("run.sh");
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    repo.write_file("lib.txt", "one\ntwo\n");
+    repo.stage_all();
+    let head = repo.commit("Make run.sh executable and extend lib.txt");
+
+    let diff = repo.git.diff_trees(&base, &head).unwrap();
+    let patch = git_reabsorb::patch::parse(&diff, &[], 0).unwrap();
+    assert_eq!(patch.hunks.len(), 1, "chmod alone should produce no hunk");
+    let commits = repo.read_commits(&base, &head);
+    let planned = GroupByFile.plan(&commits, &patch.hunks).unwrap();
+
+    let created = execute_plan(&repo, &base, &planned, &patch.hunks, &patch.file_changes);
+    assert_eq!(created.len(), 1);
+
+    let tree = run_git(&repo.path, &["ls-tree", "HEAD", "run.sh"]);
+    assert!(
+        tree.starts_with("100755"),
+        "unexpected tree entry: {}",
+        tree
+    );
+    assert!(repo.git.diff_trees(&head, "HEAD").unwrap().is_empty());
+}
+
+#[test]
+fn test_set_file_mode_rejects_unsupported_modes() {
+    let repo = TestRepo::new();
+    repo.write_file("run.sh", "#!/bin/sh\n");
+    repo.stage_all();
+    repo.commit("Initial commit");
+
+    repo.git
+        .set_file_mode(Path::new("run.sh"), "100755")
+        .unwrap();
+    let staged = run_git(&repo.path, &["ls-files", "--stage", "run.sh"]);
+    assert!(staged.starts_with("100755"), "{}", staged);
+
+    assert!(repo
+        .git
+        .set_file_mode(Path::new("run.sh"), "120000")
+        .is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_set_file_mode_accepts_non_utf8_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let repo = TestRepo::new();
+    let name = Path::new(OsStr::from_bytes(b"run-\xff.sh"));
+    fs::write(repo.path.join(name), "#!/bin/sh\n").unwrap();
+    repo.stage_all();
+    repo.commit("Initial commit");
+
+    repo.git.set_file_mode(name, "100755").unwrap();
+    let staged = run_git(&repo.path, &["ls-files", "--stage"]);
+    assert!(staged.starts_with("100755"), "{}", staged);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_staging_accepts_non_utf8_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "hello\n");
+    repo.stage_all();
+    repo.commit("Initial commit");
+
+    let name = Path::new(OsStr::from_bytes(b"notes-\xff.txt"));
+    fs::write(repo.path.join(name), "notes\n").unwrap();
+    repo.git.stage_files(&[name]).unwrap();
+    assert!(repo.git.file_in_index(name).unwrap());
+
+    repo.git.stage_deletion(name).unwrap();
+    assert!(!repo.git.file_in_index(name).unwrap());
+}
+
+#[test]
+fn test_non_adjacent_hunks_of_one_file_split_across_commits() {
+    let repo = TestRepo::new();
+
+    let original: Vec<String> = (1..=60).map(|i| format!("line {}", i)).collect();
+    repo.write_file("file.rs", &(original.join("\n") + "\n"));
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    let mut with_a = original.clone();
+    with_a[2] = "line 3 (A)".to_string();
+    with_a.insert(3, "inserted by A".to_string());
+    let mut with_both = with_a.clone();
+    with_both[52] = "line 52 (B)".to_string();
+    let with_a = with_a.join("\n") + "\n";
+    let with_both = with_both.join("\n") + "\n";
+    let mut with_b = original.clone();
+    with_b[51] = "line 52 (B)".to_string();
+    let with_b = with_b.join("\n") + "\n";
+
+    repo.write_file("file.rs", &with_both);
+    repo.stage_all();
+    let head = repo.commit("Change top and bottom of file.rs");
+
+    let diff = repo.git.diff_trees(&base, &head).unwrap();
+    let patch = git_reabsorb::patch::parse(&diff, &[], 0).unwrap();
+    assert_eq!(patch.hunks.len(), 2, "Expected two separate hunks");
+    let (a, b) = (patch.hunks[0].id, patch.hunks[1].id);
+
+    // Both orders must work: the later hunk first exercises line adjustment
+    for (first, second, first_content) in [(a, b, &with_a), (b, a, &with_b)] {
+        run_git(&repo.path, &["reset", "--hard", &head]);
+        let planned = vec![
+            PlannedCommit::from_hunk_ids(
+                PlannedCommitId(0),
+                CommitDescription::short_only("First hunk"),
+                vec![first],
+            ),
+            PlannedCommit::from_hunk_ids(
+                PlannedCommitId(1),
+                CommitDescription::short_only("Second hunk"),
+                vec![second],
+            ),
+        ];
+
+        let created = execute_plan(&repo, &base, &planned, &patch.hunks, &[]);
+        assert_eq!(created.len(), 2);
+
+        let first_blob = run_git(&repo.path, &["show", &format!("{}:file.rs", created[0])]);
+        assert_eq!(&first_blob, first_content);
+        let second_blob = run_git(&repo.path, &["show", &format!("{}:file.rs", created[1])]);
+        assert_eq!(second_blob, with_both);
+
+        // The working tree keeps the full content throughout
+        assert_eq!(
+            fs::read_to_string(repo.path.join("file.rs")).unwrap(),
+            with_both
+        );
+        assert_eq!(run_git(&repo.path, &["status", "--porcelain"]), "");
+    }
+}
+
+// ============================================================================
+// Blob Reading Tests
+// ============================================================================
+
+#[test]
+fn test_read_blob_at_commit() {
+    let repo = TestRepo::new();
+
+    repo.write_file("src/main.rs", "fn main() {}\n");
+    repo.stage_all();
+    let first = repo.commit("Initial commit");
+
+    repo.write_file("src/main.rs", "fn main() { todo!() }\n");
+    repo.stage_all();
+    let second = repo.commit("Change main");
+
+    let at_first = repo
+        .git
+        .read_blob(&first, Path::new("src/main.rs"))
+        .unwrap();
+    assert_eq!(at_first.as_deref(), Some(&b"fn main() {}\n"[..]));
+    let at_second = repo
+        .git
+        .read_blob(&second, Path::new("src/main.rs"))
+        .unwrap();
+    assert_eq!(at_second.as_deref(), Some(&b"fn main() { todo!() }\n"[..]));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_read_blob_accepts_non_utf8_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let repo = TestRepo::new();
+    let name = Path::new(OsStr::from_bytes(b"notes-\xff.txt"));
+    fs::write(repo.path.join(name), "notes\n").unwrap();
+    repo.stage_all();
+    let commit = repo.commit("Initial commit");
+
+    let blob = repo.git.read_blob(&commit, name).unwrap();
+    assert_eq!(blob.as_deref(), Some(&b"notes\n"[..]));
+}
+
+#[test]
+fn test_read_blob_binary_content() {
+    let repo = TestRepo::new();
+
+    let bytes: Vec<u8> = vec![0x00, 0xff, 0x10, 0x80, 0x00, b'\n'];
+    fs::write(repo.path.join("data.bin"), &bytes).unwrap();
+    repo.stage_all();
+    let sha = repo.commit("Add binary");
+
+    let blob = repo.git.read_blob(&sha, Path::new("data.bin")).unwrap();
+    assert_eq!(blob, Some(bytes));
+}
+
+#[test]
+fn test_read_blob_missing_path() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let sha = repo.commit("Initial commit");
+
+    let blob = repo.git.read_blob(&sha, Path::new("missing.txt")).unwrap();
+    assert!(blob.is_none());
+
+    // An unknown commit is an error, not a missing path
+    assert!(repo
+        .git
+        .read_blob(
+            "0123456789abcdef0123456789abcdef01234567",
+            Path::new("README.md")
+        )
+        .is_err());
+}
+
+// ============================================================================
+// Commit Template Tests
+// ============================================================================
+
+use git_reabsorb::editor::{Editor, EditorError};
+use std::cell::RefCell;
+
+/// Editor that records the buffer it was given and accepts it unchanged.
+#[derive(Default)]
+struct RecordingEditor {
+    buffers: RefCell<Vec<String>>,
+    helps: RefCell<Vec<String>>,
+}
+
+impl Editor for RecordingEditor {
+    fn edit(&self, initial: &str, comment_help: &str) -> Result<String, EditorError> {
+        self.buffers.borrow_mut().push(initial.to_string());
+        self.helps.borrow_mut().push(comment_help.to_string());
+        Ok(initial
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string())
+    }
+}
+
+#[test]
+fn test_commit_template_seeds_editor() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    repo.write_file("README.md", "# Test\n\nMore.\n");
+    repo.stage_all();
+    let head = repo.commit("Extend README");
+
+    let template_path = repo.path.join(".git/commit-template.txt");
+    fs::write(&template_path, "Refs: \n# Reference a ticket above\n").unwrap();
+    run_git(
+        &repo.path,
+        &["config", "commit.template", template_path.to_str().unwrap()],
+    );
+
+    let commits = repo.read_commits(&base, &head);
+    let hunks = repo.read_hunks(&commits);
+    let planned = PreserveOriginal.plan(&commits, &hunks).unwrap();
+    let mut plan = SavedPlan::new(
+        Strategy::Preserve,
+        base.clone(),
+        head.clone(),
+        &planned,
+        &hunks,
+        &HashMap::new(),
+        &[],
+    );
+
+    let editor = RecordingEditor::default();
+    let store = FilePlanStore::new(format!("template-{}", uuid()));
+    repo.git.reset_to(&base).unwrap();
+    let result = PlanExecutor::new(&repo.git, &editor, &store).execute(
+        &hunks,
+        &planned,
+        &[],
+        true,
+        false,
+        &mut plan,
+    );
+    let _ = store.delete();
+    result.unwrap();
+
+    let buffers = editor.buffers.borrow();
+    assert_eq!(buffers.len(), 1);
+    assert!(buffers[0].starts_with("Extend README"));
+    assert!(buffers[0].ends_with("\n\nRefs:"));
+    assert!(editor.helps.borrow()[0].starts_with("Reference a ticket above\n"));
+
+    let message = run_git(&repo.path, &["log", "-1", "--format=%B"]);
+    assert_eq!(message.trim(), "Extend README\n\nRefs:");
+}
+
+#[test]
+fn test_verify_each_names_the_commit_that_diverges() {
+    use git_reabsorb::app::ExecutionError;
+
+    let repo = TestRepo::new();
+    repo.write_file("a.txt", "a\n");
+    repo.write_file("b.txt", "b\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a2\n");
+    repo.stage_all();
+    let first = repo.commit("Change a");
+    repo.write_file("b.txt", "b2\n");
+    repo.stage_all();
+    let head = repo.commit("Change b");
+
+    let commits = repo.read_commits(&base, &head);
+    let hunks = repo.read_hunks(&commits);
+    let planned = PreserveOriginal.plan(&commits, &hunks).unwrap();
+    let run = |expected_head: &str| {
+        let mut plan = SavedPlan::new(
+            Strategy::Preserve,
+            base.clone(),
+            expected_head.to_string(),
+            &planned,
+            &hunks,
+            &HashMap::new(),
+            &[],
+        );
+        let editor = RecordingEditor::default();
+        let store = FilePlanStore::new(format!("verify-each-{}", uuid()));
+        repo.git.reset_to(&base).unwrap();
+        let result = PlanExecutor::new(&repo.git, &editor, &store)
+            .with_verify_each(true)
+            .execute(&hunks, &planned, &[], true, true, &mut plan);
+        let _ = store.delete();
+        result
+    };
+
+    run(&head).unwrap();
+
+    // Claiming the range ends at the first commit makes b.txt's finished
+    // state wrong once the second commit lands
+    let err = run(&first).unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            ExecutionError::TreeMismatch { index: 2, path, expected: "original head", .. }
+                if path == Path::new("b.txt")
+        ),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_verify_each_checks_files_the_commit_is_part_way_through() {
+    use git_reabsorb::app::ExecutionError;
+
+    let lines = |fifth: &str, first: &str, last: &str| {
+        format!("{}\n2\n3\n4\n{}\n6\n7\n8\n9\n{}\n", first, fifth, last)
+    };
+    let repo = TestRepo::new();
+    repo.write_file("a.txt", &lines("5", "1", "10"));
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", &lines("5", "one", "10"));
+    repo.stage_all();
+    repo.commit("Change the top");
+    repo.write_file("a.txt", &lines("5", "one", "ten"));
+    repo.stage_all();
+    let head = repo.commit("Change the bottom");
+    // Same file apart from a line neither hunk touches
+    repo.write_file("a.txt", &lines("five", "1", "10"));
+    repo.stage_all();
+    let other_base = repo.commit("Unrelated");
+
+    let commits = repo.read_commits(&base, &head);
+    let hunks = repo.read_hunks(&commits);
+    let planned = PreserveOriginal.plan(&commits, &hunks).unwrap();
+    let run = |claimed_base: &str| {
+        let mut plan = SavedPlan::new(
+            Strategy::Preserve,
+            claimed_base.to_string(),
+            head.clone(),
+            &planned,
+            &hunks,
+            &HashMap::new(),
+            &[],
+        );
+        let editor = RecordingEditor::default();
+        let store = FilePlanStore::new(format!("verify-partial-{}", uuid()));
+        repo.git.reset_to(&base).unwrap();
+        let result = PlanExecutor::new(&repo.git, &editor, &store)
+            .with_verify_each(true)
+            .execute(&hunks, &planned, &[], true, true, &mut plan);
+        let _ = store.delete();
+        result
+    };
+
+    run(&base).unwrap();
+
+    // Measured against the wrong base, a.txt is off after the first commit
+    let err = run(&other_base).unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            ExecutionError::TreeMismatch {
+                index: 1,
+                expected: "planned intermediate state",
+                ..
+            }
+        ),
+        "{}",
+        err
+    );
+}
+
+// ============================================================================
+// Pre-reabsorb History Tests
+// ============================================================================
+
+use git_reabsorb::git::pre_reabsorb_history_ref_for;
+
+/// Test that history lists recorded heads newest first and prunes old entries
+#[test]
+fn test_pre_reabsorb_history_keeps_newest_entries() {
+    let repo = TestRepo::new();
+    let history = pre_reabsorb_history_ref_for(DEFAULT_REF_PREFIX, "test");
+
+    let mut heads = Vec::new();
+    for i in 0..3 {
+        repo.write_file("file.txt", &format!("version {}\n", i));
+        repo.stage_all();
+        heads.push(repo.commit(&format!("Commit {}", i)));
+        repo.git.record_pre_reabsorb_history(&history, 2).unwrap();
+    }
+
+    let entries = repo.git.list_pre_reabsorb_history(&history).unwrap();
+    let shas: Vec<_> = entries.iter().map(|e| e.sha.as_str()).collect();
+    assert_eq!(shas, vec![heads[2].as_str(), heads[1].as_str()]);
+    assert!(entries[0].saved_at >= entries[1].saved_at);
+
+    // Other namespaces are unaffected
+    let other = pre_reabsorb_history_ref_for(DEFAULT_REF_PREFIX, "other");
+    assert!(repo
+        .git
+        .list_pre_reabsorb_history(&other)
+        .unwrap()
+        .is_empty());
+}
+
+// ============================================================================
+// Author Filter Tests
+// ============================================================================
+
+use git_reabsorb::app::{Planner, StrategyFactory};
+
+/// Test that --author reorganizes only matching commits and keeps the rest as-is
+#[test]
+fn test_author_filter_passes_other_commits_through() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    for (file, author, message) in [
+        ("alice1.txt", "Alice <alice@example.com>", "Alice one"),
+        ("bob.txt", "Bob <bob@example.com>", "Bob's change"),
+        ("alice2.txt", "Alice <alice@example.com>", "Alice two"),
+    ] {
+        repo.write_file(file, "content\n");
+        repo.stage_all();
+        run_git(&repo.path, &["commit", "-m", message, "--author", author]);
+    }
+    let head = repo.git.get_head().unwrap();
+
+    let planner = Planner::new(&repo.git, StrategyFactory::new())
+        .with_author_filter(Some("alice".to_string()));
+    let source_commits = planner.read_source_commits(&base, &head).unwrap();
+    assert_eq!(source_commits[1].author_name, "Bob");
+    assert_eq!(source_commits[1].author_email, "bob@example.com");
+    assert!(source_commits[1].author_date > 0);
+    assert!(source_commits[1].commit_date >= source_commits[1].author_date);
+
+    let file_to_commits = planner.build_file_to_commits_map(&source_commits).unwrap();
+    let diff = repo.git.diff_trees(&base, &head).unwrap();
+    let (hunks, file_changes) = planner
+        .parse_diff_full_with_commit_mapping(&diff, &file_to_commits)
+        .unwrap();
+    let draft = planner
+        .draft_plan(
+            Strategy::Squash,
+            &source_commits,
+            &hunks,
+            &file_to_commits,
+            &file_changes,
+        )
+        .unwrap();
+
+    let files_of = |commit: &git_reabsorb::models::PlannedCommit| {
+        let mut files: Vec<_> = commit
+            .changes
+            .iter()
+            .filter_map(|c| c.resolve(&hunks))
+            .map(|h| h.file_path.to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        files
+    };
+    assert_eq!(draft.planned_commits.len(), 2);
+    assert_eq!(draft.planned_commits[0].description.short, "Bob's change");
+    assert_eq!(files_of(&draft.planned_commits[0]), vec!["bob.txt"]);
+    assert_eq!(
+        files_of(&draft.planned_commits[1]),
+        vec!["alice1.txt", "alice2.txt"]
+    );
+}
+
+// ============================================================================
+// File-to-commits Map Tests
+// ============================================================================
+
+/// Test that the batched file-to-commits map matches walking each commit
+#[test]
+fn test_file_to_commits_map_matches_per_commit_walk() {
+    let repo = TestRepo::new();
+
+    repo.write_file("README.md", "# Test\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    for i in 0..6 {
+        repo.write_file(&format!("file{}.txt", i), "content\n");
+        repo.write_file("shared.txt", &format!("version {}\n", i));
+        repo.stage_all();
+        repo.commit(&format!("Commit {}", i));
+    }
+    run_git(&repo.path, &["mv", "file0.txt", "renamed.txt"]);
+    let head = repo.commit("Rename file0");
+
+    let planner = Planner::new(&repo.git, StrategyFactory::new());
+    let commits = planner.read_source_commits(&base, &head).unwrap();
+
+    let map = planner.build_file_to_commits_map(&commits).unwrap();
+
+    // Same semantics as walking each commit separately, in commit order
+    let all_shas: Vec<String> = commits.iter().map(|c| c.sha.clone()).collect();
+    assert_eq!(map["shared.txt"], all_shas[..6].to_vec());
+    assert_eq!(
+        map["file0.txt"],
+        vec![all_shas[0].clone(), all_shas[6].clone()]
+    );
+    assert_eq!(map["renamed.txt"], vec![all_shas[6].clone()]);
+    for commit in &commits {
+        for file in repo.git.get_files_changed_in_commit(&commit.sha).unwrap() {
+            assert!(map[&file].contains(&commit.sha));
+        }
+    }
+}
+
+// ============================================================================
+// Caching Git Tests
+// ============================================================================
+
+use git_reabsorb::git::CachingGit;
+
+/// Test that repeated diff queries are served from cache until a ref moves
+#[test]
+fn test_caching_git_reuses_diffs_until_commit() {
+    let repo = TestRepo::new();
+
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    let head = repo.commit("Second commit");
+
+    let git = CachingGit::new(Git::with_work_dir(&repo.path));
+    let first = git.diff_trees(&base, "HEAD").unwrap();
+    let hunks = git.read_hunks(&head, 0).unwrap();
+    let files = git.get_files_changed_in_commit(&head).unwrap();
+
+    assert_eq!(git.diff_trees(&base, "HEAD").unwrap(), first);
+    assert_eq!(git.read_hunks(&head, 0).unwrap().len(), hunks.len());
+    assert_eq!(git.get_files_changed_in_commit(&head).unwrap(), files);
+
+    // Committing moves HEAD, so the symbolic diff must be recomputed
+    repo.write_file("file.txt", "three\n");
+    git.stage_all().unwrap();
+    git.commit("Third commit", true, false).unwrap();
+    let after = git.diff_trees(&base, "HEAD").unwrap();
+    assert_ne!(after, first);
+    assert!(after.contains("+three"));
+}
+
+// ============================================================================
+// Repository Discovery Tests
+// ============================================================================
+
+/// A fresh temp directory that is not inside any git repository.
+fn non_repo_dir() -> PathBuf {
+    let path = std::env::temp_dir().join(format!("git-reabsorb-norepo-{}", uuid()));
+    fs::create_dir_all(&path).expect("Failed to create temp dir");
+    path
+}
+
+fn run_reabsorb(dir: &Path, path_env: Option<&str>) -> std::process::Output {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"));
+    cmd.current_dir(dir)
+        .arg("status")
+        .env("GIT_CEILING_DIRECTORIES", dir.parent().unwrap());
+    if let Some(path) = path_env {
+        cmd.env("PATH", path);
+    }
+    cmd.output().expect("Failed to run git-reabsorb")
+}
+
+#[test]
+fn test_discover_outside_repo_is_not_a_repository() {
+    let dir = non_repo_dir();
+    let result = Git::discover(&dir);
+    let _ = fs::remove_dir_all(&dir);
+    assert!(matches!(result, Err(GitError::NotARepository)));
+}
+
+#[test]
+fn test_cli_outside_repo_reports_friendly_error() {
+    let dir = non_repo_dir();
+    let output = run_reabsorb(&dir, None);
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("not a git repository; run inside a repo"),
+        "unexpected stderr: {}",
+        stderr
+    );
+    assert!(!stderr.contains("panicked"));
+}
+
+#[test]
+fn test_repo_option_runs_against_another_directory() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    repo.commit("Add a");
+
+    let elsewhere = non_repo_dir();
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&elsewhere)
+        .env("GIT_CEILING_DIRECTORIES", elsewhere.parent().unwrap())
+        .arg("-C")
+        .arg(&repo.path)
+        .args(["plan", "--base", &base, "-s", "preserve", "--save-plan"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+    let stray = fs::read_dir(&elsewhere).unwrap().count();
+    let _ = fs::remove_dir_all(&elsewhere);
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(repo.path.join(".git/reabsorb/main/plan.json").is_file());
+    assert_eq!(stray, 0);
+}
+
+#[test]
+fn test_cli_without_git_binary_reports_missing_git() {
+    let dir = non_repo_dir();
+    let output = run_reabsorb(&dir, Some(""));
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("git not found"),
+        "unexpected stderr: {}",
+        stderr
+    );
+}
+
+// ============================================================================
+// Pushed History Tests
+// ============================================================================
+
+#[test]
+fn test_commits_on_remote_lists_only_pushed_commits() {
+    let repo = TestRepo::new();
+
+    repo.write_file("file.txt", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let pushed = repo.commit("Pushed commit");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    let head = repo.commit("Local commit");
+
+    assert!(repo.git.commits_on_remote(&base, &head).unwrap().is_empty());
+
+    // Simulate a push of the first commit in range
+    run_git(
+        &repo.path,
+        &["update-ref", "refs/remotes/origin/feature", &pushed],
+    );
+    assert_eq!(
+        repo.git.commits_on_remote(&base, &head).unwrap(),
+        vec![pushed]
+    );
+}
+
+// ============================================================================
+// Absorb Strategy Tests
+// ============================================================================
+
+#[test]
+fn test_absorb_refuses_to_save_a_plan() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    repo.commit("Initial commit");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["plan", "--strategy", "absorb", "--save-plan"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("has no plan to save"),
+        "unexpected stderr: {}",
+        stderr
+    );
+    assert!(!repo.path.join(".git/reabsorb/main/plan.json").exists());
+}
+
+// ============================================================================
+// Hierarchical Config Tests
+// ============================================================================
+
+#[test]
+fn test_print_config_reflects_cli_overrides() {
+    let dir = non_repo_dir();
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&dir)
+        .args(["--print-config", "--cluster-max-size", "7"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("cluster-max-size = 7\n"), "{}", stdout);
+    assert!(stdout.contains("hierarchical-parallel = 8\n"), "{}", stdout);
+}
+
+#[test]
+fn test_hierarchical_parallel_out_of_range_is_rejected() {
+    let dir = non_repo_dir();
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&dir)
+        .args(["--print-config", "--hierarchical-parallel", "0"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--hierarchical-parallel"), "{}", stderr);
+}
+
+#[test]
+fn test_no_llm_hierarchical_plans_offline() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    repo.commit("Initial commit");
+    repo.write_file("src/lib.rs", "pub fn one() {}\n");
+    repo.stage_all();
+    repo.commit("Add lib");
+    repo.write_file("README.md", "base\nmore\n");
+    repo.stage_all();
+    repo.commit("Document lib");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args([
+            "plan",
+            "HEAD~2..HEAD",
+            "-s",
+            "hierarchical",
+            "--no-llm",
+            "--dry-run",
+        ])
+        .env("GIT_REABSORB_CLAUDE_BIN", "/nonexistent/claude")
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_models_lists_what_the_provider_offers() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // No repository needed
+    let dir = tempfile::tempdir().unwrap();
+    let opencode = dir.path().join("opencode");
+    fs::write(
+        &opencode,
+        "#!/bin/sh\n[ \"$1\" = models ] || exit 1\necho lmstudio/qwen\n",
+    )
+    .unwrap();
+    fs::set_permissions(&opencode, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let models = |provider: &str| {
+        Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(dir.path())
+            .args(["--llm-provider", provider, "models"])
+            .env("GIT_REABSORB_OPENCODE_BIN", &opencode)
+            .env("GIT_REABSORB_CLAUDE_BIN", "/nonexistent/claude")
+            .output()
+            .expect("Failed to run git-reabsorb")
+    };
+
+    let output = models("opencode");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "lmstudio/qwen\n");
+
+    let output = models("claude");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("can't list its models"));
+}
+
+// ============================================================================
+// Ancestry and Resume Tests
+// ============================================================================
+
+#[test]
+fn test_is_ancestor_on_linear_and_diverged_history() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let base = repo.commit("Base");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    let ours = repo.commit("Ours");
+
+    run_git(&repo.path, &["checkout", "-q", "-b", "other", &base]);
+    repo.write_file("other.txt", "theirs\n");
+    repo.stage_all();
+    let theirs = repo.commit("Theirs");
+
+    assert!(repo.git.is_ancestor(&base, &ours).unwrap());
+    assert!(repo.git.is_ancestor(&ours, &ours).unwrap());
+    assert!(!repo.git.is_ancestor(&ours, &base).unwrap());
+    assert!(!repo.git.is_ancestor(&ours, &theirs).unwrap());
+    assert!(repo.git.is_ancestor("no-such-ref", &ours).is_err());
+}
+
+#[test]
+fn test_count_commits_matches_range_size() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "0\n");
+    repo.stage_all();
+    let base = repo.commit("Base");
+    for i in 1..=3 {
+        repo.write_file("file.txt", &format!("{}\n", i));
+        repo.stage_all();
+        repo.commit(&format!("Change {}", i));
+    }
+
+    assert_eq!(repo.git.count_commits(&base, "HEAD").unwrap(), 3);
+    assert_eq!(repo.git.count_commits("HEAD~1", "HEAD").unwrap(), 1);
+    assert_eq!(repo.git.count_commits("HEAD", &base).unwrap(), 0);
+    assert!(repo.git.count_commits("no-such-ref", "HEAD").is_err());
+}
+
+/// Save `plan` where the binary looks for the `main` branch's plan.
+fn write_saved_plan(repo: &TestRepo, plan: &SavedPlan) -> PathBuf {
+    let plan_dir = repo.path.join(".git/reabsorb/main");
+    fs::create_dir_all(&plan_dir).unwrap();
+    let path = plan_dir.join("plan.json");
+    fs::write(&path, serde_json::to_string(plan).unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn test_resume_refuses_when_head_lost_created_commits() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    repo.commit("Add a");
+    repo.write_file("b.txt", "b\n");
+    repo.stage_all();
+    let head = repo.commit("Add b");
+
+    let commits = repo.read_commits(&base, &head);
+    let hunks = repo.read_hunks(&commits);
+    let planned: Vec<PlannedCommit> = hunks
+        .iter()
+        .enumerate()
+        .map(|(i, hunk)| {
+            PlannedCommit::new(
+                PlannedCommitId(i),
+                CommitDescription::new(format!("Commit {}", i), "Body"),
+                vec![PlannedChange::ExistingHunk(hunk.id)],
+            )
+        })
+        .collect();
+    let mut plan = SavedPlan::new(
+        Strategy::Preserve,
+        base.clone(),
+        head.clone(),
+        &planned,
+        &hunks,
+        &HashMap::new(),
+        &[],
+    );
+    plan.mark_commit_created(head.clone());
+    let plan_path = write_saved_plan(&repo, &plan);
+
+    // Someone moved HEAD back to base and committed something else
+    run_git(&repo.path, &["reset", "-q", "--hard", &base]);
+    repo.write_file("c.txt", "c\n");
+    repo.stage_all();
+    repo.commit("Unrelated");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["apply", "--resume", "--no-editor"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--force"), "unexpected stderr: {}", stderr);
+    assert!(plan_path.exists());
+}
+
+#[test]
+fn test_resume_from_redoes_created_commits() {
+    let (repo, base) = repo_with_two_additions();
+    let reabsorb = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .args(args)
+            .output()
+            .expect("Failed to run git-reabsorb")
+    };
+
+    // Stops after recording commit 2, leaving the plan behind
+    let output = reabsorb(&[
+        "--base",
+        &base,
+        "-s",
+        "preserve",
+        "--no-editor",
+        "--test-cmd",
+        "test ! -f b.txt",
+    ]);
+    assert!(!output.status.success());
+
+    // Fix commit 2's message in the plan, then redo it
+    let plan_path = repo.path.join(".git/reabsorb/main/plan.json");
+    let plan = fs::read_to_string(&plan_path).unwrap();
+    fs::write(&plan_path, plan.replace("Add b", "Add b properly")).unwrap();
+
+    let output = reabsorb(&["apply", "--resume", "--from", "3", "--no-editor"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("between 1 and 2"), "{}", stderr);
+
+    // A refusal after --from is checked leaves HEAD and the plan alone
+    let head = run_git(&repo.path, &["rev-parse", "HEAD"]);
+    let saved = fs::read_to_string(&plan_path).unwrap();
+    let output = reabsorb(&[
+        "apply",
+        "--resume",
+        "--from",
+        "2",
+        "--no-editor",
+        "--max-commits",
+        "0",
+    ]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--max-commits"), "{}", stderr);
+    assert_eq!(run_git(&repo.path, &["rev-parse", "HEAD"]), head);
+    assert_eq!(fs::read_to_string(&plan_path).unwrap(), saved);
+
+    let output = reabsorb(&["apply", "--resume", "--from", "2", "--no-editor"]);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let subjects = run_git(
+        &repo.path,
+        &["log", "--format=%s", &format!("{}..HEAD", base)],
+    );
+    assert_eq!(subjects, "Add b properly\nAdd a\n");
+    assert!(!plan_path.exists());
+}
+
+#[test]
+fn test_plan_warns_about_commits_with_no_changes_left() {
+    let repo = TestRepo::new();
+    repo.write_file("old.txt", "content\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    run_git(&repo.path, &["mv", "old.txt", "new.txt"]);
+    let renamed = repo.commit("Rename old to new");
+    run_git(&repo.path, &["mv", "new.txt", "old.txt"]);
+    repo.commit("Rename it back");
+    repo.write_file("feature.txt", "feature\n");
+    repo.stage_all();
+    repo.commit("Add feature");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["plan", "--base", &base, "-s", "preserve", "--dry-run"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains(&renamed[..7]) && stderr.contains("was a pure rename"),
+        "unexpected stderr: {}",
+        stderr
+    );
+    assert!(
+        stderr.contains("\"Rename it back\") was a pure rename"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_plan_rejects_base_that_is_not_an_ancestor() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let base = repo.commit("Base");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    repo.commit("Ours");
+
+    run_git(&repo.path, &["checkout", "-q", "-b", "other", &base]);
+    repo.write_file("other.txt", "theirs\n");
+    repo.stage_all();
+    repo.commit("Theirs");
+    run_git(&repo.path, &["checkout", "-q", "main"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["plan", "other..HEAD", "--dry-run"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("not an ancestor"),
+        "unexpected stderr: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_empty_range_is_reported_with_guidance() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    repo.commit("Base");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    repo.commit("Work");
+
+    for args in [
+        &["plan", "--base", "HEAD", "--dry-run"][..],
+        &["plan", "HEAD..HEAD~1", "--dry-run"][..],
+        &["assess", "--base", "HEAD"][..],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .args(args)
+            .output()
+            .expect("Failed to run git-reabsorb");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("No commits in range") && stderr.contains("Check the base"),
+            "unexpected stderr for {:?}: {}",
+            args,
+            stderr
+        );
+    }
+}
+
+#[test]
+fn test_dump_plan_prints_saved_plan_json_without_saving() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    repo.commit("Add a");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["plan", "--base", &base, "--dry-run", "--dump-plan"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let plan: SavedPlan = serde_json::from_slice(&output.stdout).expect("stdout is plan JSON");
+    assert_eq!(plan.base_sha, base);
+    assert_eq!(plan.commits.len(), 1);
+    assert_eq!(plan.commits[0].description.short, "Add a");
+    assert!(plan
+        .working_tree_hunks
+        .iter()
+        .any(|h| h.lines.contains(&DiffLine::Added("a".to_string()))));
+    assert!(!repo.path.join(".git/reabsorb/main/plan.json").exists());
+}
+
+// ============================================================================
+// Plan Repair Tests
+// ============================================================================
+
+/// A two-hunk plan whose second commit repeats the first hunk and drops the
+/// second, as a careless hand edit might.
+fn hand_edited_plan(repo: &TestRepo, strategy: Strategy) -> SavedPlan {
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.write_file("b.txt", "b\n");
+    repo.stage_all();
+    let head = repo.commit("Add a and b");
+
+    let commits = repo.read_commits(&base, &head);
+    let hunks = repo.read_hunks(&commits);
+    let planned = vec![
+        PlannedCommit::new(
+            PlannedCommitId(0),
+            CommitDescription::new("Add a", "Body"),
+            vec![PlannedChange::ExistingHunk(hunks[0].id)],
+        ),
+        PlannedCommit::new(
+            PlannedCommitId(1),
+            CommitDescription::new("Add a again", "Body"),
+            vec![PlannedChange::ExistingHunk(hunks[0].id)],
+        ),
+    ];
+    SavedPlan::new(strategy, base, head, &planned, &hunks, &HashMap::new(), &[])
+}
+
+fn run_repair(repo: &TestRepo, extra_args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .arg("repair")
+        .args(extra_args)
+        .output()
+        .expect("Failed to run git-reabsorb")
+}
+
+#[test]
+fn test_repair_fixes_hand_edited_hierarchical_plan() {
+    let repo = TestRepo::new();
+    let plan = hand_edited_plan(&repo, Strategy::Hierarchical);
+    let path = write_saved_plan(&repo, &plan);
+
+    let output = run_repair(&repo, &["--features", "attempt-validation-fix"]);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let repaired: SavedPlan = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let planned = repaired.to_planned_commits();
+    let validation =
+        git_reabsorb::validation::validate_plan(&planned, &repaired.working_tree_hunks);
+    assert!(validation.is_valid(), "{:?}", validation.issues);
+}
+
+#[test]
+fn test_repair_uses_generic_repair_for_other_strategies() {
+    let repo = TestRepo::new();
+    let plan = hand_edited_plan(&repo, Strategy::Preserve);
+    let path = write_saved_plan(&repo, &plan);
+
+    let output = run_repair(&repo, &[]);
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let repaired: SavedPlan = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(repaired.commits.len(), 2);
+    assert_eq!(repaired.commits[0].description.short, "Add a");
+    let validation = git_reabsorb::validation::validate_plan(
+        &repaired.to_planned_commits(),
+        &repaired.working_tree_hunks,
+    );
+    assert!(validation.is_valid(), "{:?}", validation.issues);
+}
+
+#[test]
+fn test_check_reports_issues_as_json() {
+    let repo = TestRepo::new();
+    let plan = hand_edited_plan(&repo, Strategy::Preserve);
+    write_saved_plan(&repo, &plan);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["check", "--format", "json"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["valid"], false);
+    let kinds: Vec<&str> = report["issues"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|issue| issue["kind"].as_str().unwrap())
+        .collect();
+    assert!(
+        kinds.contains(&"duplicate_hunk_across_commits"),
+        "{:?}",
+        kinds
+    );
+    assert!(kinds.contains(&"unassigned_hunks"), "{:?}", kinds);
+}
+
+// ============================================================================
+// Commit Set Assessment Tests
+// ============================================================================
+
+#[test]
+fn test_read_commit_resolves_revisions() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    repo.commit("First");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    let second = repo.commit("Second\n\nWith a body");
+
+    let commit = repo.git.read_commit("HEAD").unwrap();
+    assert_eq!(commit.sha, second);
+    assert_eq!(commit.message.short, "Second");
+    assert_eq!(
+        repo.git.read_commit("HEAD~1").unwrap().message.short,
+        "First"
+    );
+
+    let err = repo.git.read_commit("deadbeef").unwrap_err();
+    assert!(matches!(err, GitError::RefNotFound { .. }), "{:?}", err);
+}
+
+#[test]
+fn test_assess_commits_conflicts_with_base() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let sha = repo.commit("First");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["assess", "--commits", &sha, "--base", "main"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("cannot be used with"), "{}", stderr);
+}
+
+#[test]
+fn test_assess_dry_run_lists_commits_without_calling_the_llm() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let base = repo.commit("First");
+    repo.write_file("file.txt", "two\nthree\n");
+    repo.stage_all();
+    repo.commit("Second");
+    repo.write_file("other.txt", "new\n");
+    repo.stage_all();
+    repo.commit("Third");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--llm-provider", "claude", "assess", "--base", &base])
+        .args(["--criteria", "atomicity,scope", "--dry-run"])
+        .env("GIT_REABSORB_CLAUDE_BIN", "/nonexistent/claude")
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Would assess 2 commit(s) against 2 criteria"));
+    assert!(stdout.contains("LLM calls: 2"));
+    assert!(stdout.contains("Second (1 hunk(s), +2 -1)"), "{}", stdout);
+    assert!(stdout.contains("Third (1 hunk(s), +1 -0)"), "{}", stdout);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_assess_compare_range_assesses_both_ranges() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let base = repo.commit("First");
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+    let before = repo.commit("Second");
+    repo.write_file("other.txt", "new\n");
+    repo.stage_all();
+    repo.commit("Third");
+
+    let scripts = tempfile::tempdir().unwrap();
+    let claude = scripts.path().join("claude");
+    fs::write(
+        &claude,
+        "#!/bin/sh\ncat > /dev/null\necho '{\"scores\": [{\"criterion\": \"atomicity\", \"level\": 4, \"rationale\": \"ok\", \"evidence\": [], \"suggestions\": []}]}'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&claude, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--llm-provider", "claude", "assess", "--base", &base])
+        .args(["--criteria", "atomicity", "--format", "json"])
+        .args(["--compare-range", &format!("{}..{}", base, before)])
+        .env("GIT_REABSORB_CLAUDE_BIN", &claude)
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let comparison: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(comparison["before"]["head_sha"], before.as_str());
+    assert_eq!(comparison["before"]["commits"], 1);
+    assert_eq!(comparison["after"]["commits"], 2);
+    assert_eq!(comparison["verdict"], "unchanged");
+    assert_eq!(comparison["criteria"][0]["criterion_id"], "atomicity");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_assess_working_assesses_uncommitted_changes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    repo.commit("First");
+
+    let scripts = tempfile::tempdir().unwrap();
+    let claude = scripts.path().join("claude");
+    fs::write(
+        &claude,
+        "#!/bin/sh\ncat > /dev/null\necho '{\"scores\": [{\"criterion\": \"atomicity\", \"level\": 2, \"rationale\": \"too much\", \"evidence\": [], \"suggestions\": []}]}'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&claude, fs::Permissions::from_mode(0o755)).unwrap();
+    let assess = |repo: &TestRepo| {
+        Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .args(["--llm-provider", "claude", "assess", "--working"])
+            .args(["--criteria", "message,atomicity", "--format", "json"])
+            .env("GIT_REABSORB_CLAUDE_BIN", &claude)
+            .output()
+            .expect("Failed to run git-reabsorb")
+    };
+
+    let output = assess(&repo);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No uncommitted changes"));
+
+    repo.write_file("file.txt", "two\n");
+    let output = assess(&repo);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let result: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(result["head_sha"], "worktree");
+    let commits = result["commit_assessments"].as_array().unwrap();
+    assert_eq!(commits.len(), 1);
+    // Without --message there is no message to judge
+    let scores = commits[0]["criterion_scores"].as_array().unwrap();
+    assert_eq!(scores.len(), 1);
+    assert_eq!(scores[0]["criterion_id"], "atomicity");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_llm_redact_keeps_file_contents_out_of_prompts() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.write_file("prod.env", "TOKEN=old\n");
+    repo.stage_all();
+    repo.commit("First");
+    repo.write_file("file.txt", "two\n");
+    repo.write_file("prod.env", "TOKEN=hunter2\n");
+
+    let scripts = tempfile::tempdir().unwrap();
+    let prompt = scripts.path().join("prompt.txt");
+    let claude = scripts.path().join("claude");
+    fs::write(
+        &claude,
+        format!(
+            "#!/bin/sh\ncat > '{}'\necho '{{\"scores\": [{{\"criterion\": \"atomicity\", \"level\": 4, \"rationale\": \"ok\", \"evidence\": [], \"suggestions\": []}}]}}'\n",
+            prompt.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&claude, fs::Permissions::from_mode(0o755)).unwrap();
+    let assess_in = |dir: &Path, extra: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(dir)
+            .args(["--llm-provider", "claude", "assess", "--working"])
+            .args(["--criteria", "atomicity", "--format", "json"])
+            .args(extra)
+            .env("GIT_REABSORB_CLAUDE_BIN", &claude)
+            .output()
+            .expect("Failed to run git-reabsorb");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        fs::read_to_string(&prompt).unwrap()
+    };
+    let assess = |extra: &[&str]| assess_in(&repo.path, extra);
+
+    let sent = assess(&["--llm-redact", "*.env"]);
+    assert!(!sent.contains("hunter2"), "{}", sent);
+    assert!(sent.contains("[redacted: 2 lines in prod.env]"), "{}", sent);
+    assert!(sent.contains("+two"), "{}", sent);
+
+    // The config file's list counts too, and paths can be hidden
+    repo.write_file(".reabsorb.toml", "[llm]\nredact = [\"*.env\"]\n");
+    let sent = assess(&["--llm-redact-paths"]);
+    assert!(!sent.contains("hunter2"), "{}", sent);
+    assert!(!sent.contains("prod.env"), "{}", sent);
+    assert!(sent.contains("+two"), "{}", sent);
+
+    // The config file is found from a subdirectory too
+    let subdir = repo.path.join("sub");
+    fs::create_dir(&subdir).unwrap();
+    let sent = assess_in(&subdir, &[]);
+    assert!(!sent.contains("hunter2"), "{}", sent);
+    assert!(sent.contains("+two"), "{}", sent);
+}
+
+#[test]
+fn test_compare_fail_on_regression_exits_non_zero() {
+    let repo = TestRepo::new();
+    let write = |name: &str, mean: f32| {
+        let path = repo.path.join(name);
+        let assessment = serde_json::json!({
+            "base_sha": "base",
+            "head_sha": name,
+            "assessed_at": "2024-01-01T00:00:00Z",
+            "commit_assessments": [],
+            "aggregate_scores": {
+                "atomicity": {
+                    "criterion_id": "atomicity",
+                    "mean_score": mean,
+                    "min_score": mean,
+                    "max_score": mean,
+                    "std_deviation": 0.0
+                }
+            },
+            "overall_score": mean / 5.0,
+            "range_observations": []
+        });
+        fs::write(&path, assessment.to_string()).unwrap();
+        path
+    };
+    let good = write("good.json", 4.0);
+    let bad = write("bad.json", 2.0);
+
+    let compare = |before: &Path, after: &Path| {
+        Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .arg("compare")
+            .args([before, after])
+            .args(["--format", "json", "--fail-on-regression"])
+            .output()
+            .expect("Failed to run git-reabsorb")
+    };
+
+    let output = compare(&good, &bad);
+    assert!(!output.status.success());
+    let comparison: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(comparison["verdict"], "regressed");
+    assert_eq!(comparison["criteria"][0]["delta"], -2.0);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("atomicity"));
+
+    let output = compare(&bad, &good);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let comparison: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(comparison["verdict"], "improved");
+}
+
+// ============================================================================
+// Binary File Tests
+// ============================================================================
+
+#[test]
+fn test_binary_file_lands_in_its_source_commit() {
+    let repo = TestRepo::new();
+    repo.write_file("src.rs", "fn a() {}\n");
+    repo.write_file("docs.md", "# Docs\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+
+    repo.write_file("src.rs", "fn a() {}\nfn b() {}\n");
+    repo.stage_all();
+    repo.commit("Add b");
+
+    // PNG signature plus a NUL so git treats it as binary
+    let png: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x00\x01";
+    fs::write(repo.path.join("logo.png"), png).unwrap();
+    repo.write_file("docs.md", "# Docs\n\n![logo](logo.png)\n");
+    repo.stage_all();
+    let head = repo.commit("Add logo");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--base", &base, "-s", "preserve", "--no-editor"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let files_in = |rev: &str| run_git(&repo.path, &["show", "--name-only", "--format=", rev]);
+    assert!(!files_in("HEAD~1").contains("logo.png"));
+    assert!(files_in("HEAD").contains("logo.png"));
+    assert!(repo.git.diff_trees(&head, "HEAD").unwrap().is_empty());
+}
+
+// ============================================================================
+// Test Command Tests
+// ============================================================================
+
+/// A repo with a base commit and two commits adding a.txt then b.txt.
+fn repo_with_two_additions() -> (TestRepo, String) {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let base = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    repo.commit("Add a");
+    repo.write_file("b.txt", "b\n");
+    repo.stage_all();
+    repo.commit("Add b");
+    (repo, base)
+}
+
+#[test]
+fn test_test_cmd_stops_at_first_failing_commit() {
+    let (repo, base) = repo_with_two_additions();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--base", &base, "-s", "preserve", "--no-editor"])
+        .args(["--test-cmd", "test ! -f b.txt"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("on commit 2"),
+        "unexpected stderr: {}",
+        stderr
+    );
+    assert_eq!(
+        run_git(&repo.path, &["log", "-1", "--format=%s"]).trim(),
+        "Add b"
+    );
+    assert!(repo.path.join(".git/reabsorb/main/plan.json").exists());
+}
+
+#[test]
+fn test_test_cmd_runs_after_every_commit() {
+    let (repo, base) = repo_with_two_additions();
+    // The command runs in a temporary checkout, so log to an absolute path
+    let log = repo.path.join(".git/tested");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--base", &base, "-s", "preserve", "--no-editor"])
+        .args([
+            "--exec",
+            &format!("git log -1 --format=%s >> {}", log.display()),
+        ])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let tested = fs::read_to_string(&log).unwrap();
+    assert_eq!(tested, "Add a\nAdd b\n");
+    let worktrees = run_git(&repo.path, &["worktree", "list"]);
+    assert_eq!(worktrees.lines().count(), 1, "{}", worktrees);
+}
+
+// ============================================================================
+// Index-free Execution Tests
+// ============================================================================
+
+#[test]
+fn test_index_free_apply_leaves_staged_work_alone() {
+    let (repo, base) = repo_with_two_additions();
+    repo.write_file("staged.txt", "work in progress\n");
+    run_git(&repo.path, &["add", "staged.txt"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args([
+            "--base",
+            &base,
+            "-s",
+            "preserve",
+            "--no-editor",
+            "--index-free",
+        ])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let subjects = run_git(
+        &repo.path,
+        &["log", "--format=%s", &format!("{}..HEAD", base)],
+    );
+    assert_eq!(subjects, "Add b\nAdd a\n");
+    let files = run_git(&repo.path, &["ls-tree", "--name-only", "HEAD"]);
+    assert_eq!(files, "README.md\na.txt\nb.txt\n");
+    // The user's staged file is still staged, and nothing else is
+    let staged = run_git(&repo.path, &["diff", "--cached", "--name-only"]);
+    assert_eq!(staged, "staged.txt\n");
+}
+
+// ============================================================================
+// Amend Tests
+// ============================================================================
+
+#[test]
+fn test_amend_message_keeps_tree_and_staged_changes() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    let original = repo.commit("Typo in mesage");
+    let tree = run_git(&repo.path, &["rev-parse", "HEAD^{tree}"]);
+
+    repo.write_file("file.txt", "two\n");
+    repo.stage_all();
+
+    let amended = repo
+        .git
+        .amend_message("Fix typo in message\n\nWith a body", true)
+        .unwrap();
+
+    assert_ne!(amended, original);
+    assert_eq!(run_git(&repo.path, &["rev-parse", "HEAD^{tree}"]), tree);
+    assert_eq!(
+        repo.git.read_commit("HEAD").unwrap().message.short,
+        "Fix typo in message"
+    );
+    let staged = run_git(&repo.path, &["diff", "--cached", "--name-only"]);
+    assert_eq!(staged.trim(), "file.txt");
+}
+
+#[test]
+fn test_amend_command_sets_message() {
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    repo.commit("Old message");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["amend", "-m", "New message"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        run_git(&repo.path, &["log", "-1", "--format=%s"]).trim(),
+        "New message"
+    );
+    assert_eq!(
+        run_git(&repo.path, &["rev-list", "--count", "HEAD"]).trim(),
+        "1"
+    );
+}
+
+#[test]
+fn test_fold_squashes_range_into_base_and_reset_undoes_it() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "base\n");
+    repo.stage_all();
+    let root = repo.commit("Initial commit");
+    repo.write_file("a.txt", "a\n");
+    repo.stage_all();
+    run_git(
+        &repo.path,
+        &[
+            "commit",
+            "-m",
+            "Add a",
+            "--author",
+            "Someone Else <else@example.com>",
+            "--date",
+            "@1600000000",
+        ],
+    );
+    let target = repo.git.get_head().unwrap();
+    repo.write_file("a.txt", "a fixed\n");
+    repo.stage_all();
+    repo.commit("Fix a");
+    repo.write_file("b.txt", "b\n");
+    repo.stage_all();
+    let original = repo.commit("Add b");
+    let tree = run_git(&repo.path, &["rev-parse", "HEAD^{tree}"]);
+
+    let reabsorb = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .args(args)
+            .output()
+            .expect("Failed to run git-reabsorb");
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    };
+
+    reabsorb(&["fold", &format!("{}..HEAD", target)]);
+
+    assert_eq!(run_git(&repo.path, &["rev-parse", "HEAD^{tree}"]), tree);
+    assert_eq!(run_git(&repo.path, &["rev-parse", "HEAD^"]).trim(), root);
+    assert_eq!(
+        run_git(&repo.path, &["log", "-1", "--format=%s"]).trim(),
+        "Add a"
+    );
+    // The folded commit keeps the authorship of the commit folded into
+    assert_eq!(
+        run_git(&repo.path, &["log", "-1", "--format=%an <%ae> %at"]).trim(),
+        "Someone Else <else@example.com> 1600000000"
+    );
+    assert!(run_git(&repo.path, &["status", "--porcelain"]).is_empty());
+
+    reabsorb(&["reset"]);
+    assert_eq!(run_git(&repo.path, &["rev-parse", "HEAD"]).trim(), original);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reabsorb_editor_env_wins_over_editor() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = TestRepo::new();
+    repo.write_file("file.txt", "one\n");
+    repo.stage_all();
+    repo.commit("Old message");
+
+    let scripts = tempfile::tempdir().unwrap();
+    let script = |name: &str, body: &str| {
+        let path = scripts.path().join(name);
+        fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    };
+    let reabsorb_editor = script("reabsorb-editor", "echo \"From $1\" > \"$2\"");
+    let failing_editor = script("failing-editor", "exit 1");
+    let git_editor = script("git-editor", "echo 'From EDITOR' > \"$1\"");
+
+    let amend = |reabsorb_editor: &str| {
+        Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .env("GIT_REABSORB_EDITOR", reabsorb_editor)
+            .env("EDITOR", &git_editor)
+            .arg("amend")
+            .output()
+            .expect("Failed to run git-reabsorb")
+    };
+    let subject = || run_git(&repo.path, &["log", "-1", "--format=%s"]);
+
+    let output = amend(&format!("{} reabsorb", reabsorb_editor.display()));
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(subject().trim(), "From reabsorb");
+
+    // A failing reabsorb editor is an error, not a reason to use $EDITOR
+    let output = amend(&failing_editor.display().to_string());
+    assert!(!output.status.success());
+    assert_eq!(subject().trim(), "From reabsorb");
+}
+
+// ============================================================================
+// Audit Log Tests
+// ============================================================================
+
+#[test]
+fn test_audit_log_records_run() {
+    let (repo, base) = repo_with_two_additions();
+    let log_path = repo.path.join("audit.jsonl");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--base", &base, "-s", "preserve", "--no-editor"])
+        .arg("--audit-log")
+        .arg(&log_path)
+        .output()
+        .expect("Failed to run git-reabsorb");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let events: Vec<serde_json::Value> = fs::read_to_string(&log_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "range_resolved",
+            "strategy_chosen",
+            "commit_created",
+            "commit_created",
+            "integrity_checked"
+        ]
+    );
+    assert_eq!(events[0]["base"], base.as_str());
+    assert_eq!(events[1]["strategy"], "preserve");
+    assert_eq!(events[2]["index"], 1);
+    assert_eq!(events[2]["source_shas"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        events[3]["sha"],
+        run_git(&repo.path, &["rev-parse", "HEAD"]).trim()
+    );
+    assert_eq!(events[4]["ok"], true);
+}
+
+#[test]
+fn test_stats_summarize_the_run() {
+    let (repo, base) = repo_with_two_additions();
+    let log_path = repo.path.join("audit.jsonl");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args([
+            "--base",
+            &base,
+            "-s",
+            "preserve",
+            "--no-editor",
+            "--stats=json",
+        ])
+        .arg("--audit-log")
+        .arg(&log_path)
+        .output()
+        .expect("Failed to run git-reabsorb");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stats: serde_json::Value = serde_json::from_str(stdout.lines().last().unwrap()).unwrap();
+    assert_eq!(stats["commits_created"], 2);
+    assert_eq!(stats["hunks_moved"], 2);
+    assert_eq!(stats["llm_calls"], 0);
+    assert_eq!(stats["integrity_ok"], true);
+    assert!(stats["wall_time_ms"].is_u64());
+    // The audit log still gets every event
+    assert_eq!(fs::read_to_string(&log_path).unwrap().lines().count(), 5);
+}
+
+// ============================================================================
+// Root Commit Tests
+// ============================================================================
+
+#[test]
+fn test_reabsorb_from_the_root_commit() {
+    let repo = TestRepo::new();
+    repo.write_file("README.md", "hello\n");
+    repo.write_file("src/a.rs", "fn a() {}\n");
+    repo.stage_all();
+    let root = repo.commit("Initial commit");
+    repo.write_file("src/a.rs", "fn a() {}\nfn b() {}\n");
+    repo.write_file("README.md", "hello\nworld\n");
+    repo.stage_all();
+    repo.commit("Add b and docs");
+    let original_tree = run_git(&repo.path, &["rev-parse", "HEAD^{tree}"]);
+
+    let git = Git::with_work_dir(&repo.path);
+    assert!(git.parents_of(&root).unwrap().is_empty());
+    assert_eq!(git.parents_of("HEAD").unwrap(), vec![root.clone()]);
+    assert_eq!(git.read_hunks(&root, 0).unwrap().len(), 2);
+    assert_eq!(git.get_files_changed_in_commit(&root).unwrap().len(), 2);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+        .current_dir(&repo.path)
+        .args(["--root", "-s", "by-file", "--no-editor"])
+        .output()
+        .expect("Failed to run git-reabsorb");
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let subjects = run_git(&repo.path, &["log", "--format=%s", "--reverse"]);
+    assert_eq!(subjects.lines().count(), 2, "{}", subjects);
+    let roots = run_git(&repo.path, &["rev-list", "--max-parents=0", "HEAD"]);
+    assert_eq!(roots.lines().count(), 1);
+    assert_ne!(roots.trim(), root);
+    assert_eq!(
+        run_git(&repo.path, &["rev-parse", "HEAD^{tree}"]),
+        original_tree
+    );
+    assert!(run_git(&repo.path, &["status", "--porcelain"])
+        .trim()
+        .is_empty());
+}
+
+// ============================================================================
+// In-Progress Operation Tests
+// ============================================================================
+
+#[test]
+fn test_refuses_to_run_during_a_conflicted_merge() {
+    let repo = TestRepo::new();
+    repo.write_file("a.txt", "base\n");
+    repo.stage_all();
+    repo.commit("Initial commit");
+    run_git(&repo.path, &["checkout", "-q", "-b", "other"]);
+    repo.write_file("a.txt", "theirs\n");
+    repo.stage_all();
+    repo.commit("Change a on other");
+    run_git(&repo.path, &["checkout", "-q", "-"]);
+    repo.write_file("a.txt", "ours\n");
+    repo.stage_all();
+    repo.commit("Change a here");
+    let head = run_git(&repo.path, &["rev-parse", "HEAD"]);
+
+    let git = Git::with_work_dir(&repo.path);
+    assert!(!git.operation_in_progress().unwrap());
+
+    let merge = Command::new("git")
+        .current_dir(&repo.path)
+        .args(["merge", "-q", "other"])
+        .output()
+        .expect("Failed to run git");
+    assert!(!merge.status.success(), "the merge should conflict");
+    assert!(git.operation_in_progress().unwrap());
+
+    for args in [
+        &["-s", "preserve", "--no-editor"][..],
+        &["plan", "-s", "preserve"],
+    ] {
+        let output = Command::new(env!("CARGO_BIN_EXE_git-reabsorb"))
+            .current_dir(&repo.path)
+            .args(args)
+            .output()
+            .expect("Failed to run git-reabsorb");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("in the middle of a merge/rebase"),
+            "stderr: {}",
+            stderr
+        );
+    }
+    assert_eq!(run_git(&repo.path, &["rev-parse", "HEAD"]), head);
+
+    // Resolving the conflicts isn't enough while MERGE_HEAD remains
+    repo.write_file("a.txt", "merged\n");
+    repo.stage_all();
+    assert!(git.operation_in_progress().unwrap());
+    run_git(&repo.path, &["merge", "--abort"]);
+    assert!(!git.operation_in_progress().unwrap());
+}